@@ -0,0 +1,186 @@
+//! Compares the performance of storages on a few workloads, to help choose a backend empirically.
+//!
+//! Run with `cargo bench --bench storage`. When run without `--bench`, for example as part of `cargo test
+//! --all-targets`, each workload is run once, as a smoke test.
+//!
+//! To compare backends on a workload of your own, invoke `bench_storage!` with the backends of interest, each being
+//! constructed with `Default::default()`, and a workload whose argument is the freshly constructed storage:
+//!
+//! ```ignore
+//! bench_storage! {
+//!     "my workload",
+//!     iterations: 1_000,
+//!     backends: {
+//!         "inline" => NonTrackingRange<u8, u32, 64>,
+//!         "alloc" => AllocStorage<Global>,
+//!     },
+//!     workload: |storage| {
+//!         let mut vec = RawVec::<u32, _>::new(storage);
+//!         vec.push(1);
+//!         vec.len()
+//!     },
+//! }
+//! ```
+
+#![feature(allocator_api)]
+#![feature(bench_black_box)]
+
+use std::{alloc::Global, env, hint, time::Instant};
+
+use storage_poc::{
+    allocator::AllocStorage,
+    collections::RawVec,
+    fallback::{FallbackElement, FallbackRange},
+    inline::{BumpElement, NonTrackingElement, NonTrackingRange, TrackingElement},
+    small::{SingleElement, SingleRange},
+    traits::ElementStorage,
+};
+
+/// Runs a workload against each backend, then prints a comparison table.
+///
+/// The workload is expanded once per backend, hence its argument takes on the type of each backend in turn. Its
+/// result is fed to `black_box`, so as to prevent the optimizer from eliding the work.
+///
+/// The time measured includes the construction of the storage, which is part of the cost of a backend.
+macro_rules! bench_storage {
+    (
+        $title:expr,
+        iterations: $iterations:expr,
+        backends: { $($name:expr => $backend:ty),+ $(,)? },
+        workload: |$storage:ident| $workload:block $(,)?
+    ) => {{
+        let mut report = Report::new($title);
+
+        $(
+            report.record($name, measure($iterations, || {
+                #[allow(unused_mut)]
+                let mut $storage = <$backend>::default();
+                $workload
+            }));
+        )+
+
+        report.print();
+    }};
+}
+
+fn main() {
+    let iterations = if env::args().any(|arg| arg == "--bench") {
+        100_000
+    } else {
+        1
+    };
+
+    bench_storage! {
+        "RawVec: push 48 elements, then sum",
+        iterations: iterations,
+        backends: {
+            "inline" => NonTrackingRange<u8, u32, 64>,
+            "alloc" => AllocStorage<Global>,
+            "fallback" => FallbackRange<NonTrackingRange<u8, u32, 16>, AllocStorage<Global>>,
+            "alternative" => SingleRange<[u32; 16], Global>,
+        },
+        workload: |storage| {
+            let mut vec = RawVec::<u32, _>::new(storage);
+
+            for i in 0..hint::black_box(48) {
+                vec.push(i);
+            }
+
+            vec.iter().map(|i| *i as u64).sum::<u64>()
+        },
+    }
+
+    bench_storage! {
+        "Element: create, read, and destroy 16 elements in turn",
+        iterations: iterations,
+        backends: {
+            "inline" => NonTrackingElement<u64>,
+            "pool" => TrackingElement<u64, 4>,
+            "alloc" => AllocStorage<Global>,
+            "fallback" => FallbackElement<NonTrackingElement<u64>, AllocStorage<Global>>,
+            "alternative" => SingleElement<u64, Global>,
+            "arena" => BumpElement<[u64; 4]>,
+        },
+        workload: |storage| {
+            let mut sum = 0;
+
+            for i in 0..hint::black_box(16u64) {
+                let handle = storage.create(i).unwrap();
+
+                //  Safety:
+                //  -   `handle` is valid, fresh off the press.
+                let pointer = hint::black_box(unsafe { ElementStorage::get(&storage, &handle) });
+
+                //  Safety:
+                //  -   `pointer` points to a valid value.
+                sum += unsafe { *pointer.as_ptr() };
+
+                //  Safety:
+                //  -   `handle` is valid, and not used afterwards.
+                unsafe { storage.destroy(&handle) };
+            }
+
+            sum
+        },
+    }
+}
+
+//
+//  Implementation
+//
+
+//  Measures the average duration of `workload`, in nanoseconds, after a warm-up.
+fn measure<R>(iterations: u32, mut workload: impl FnMut() -> R) -> f64 {
+    for _ in 0..iterations / 10 {
+        hint::black_box(workload());
+    }
+
+    let start = Instant::now();
+
+    for _ in 0..iterations {
+        hint::black_box(workload());
+    }
+
+    start.elapsed().as_secs_f64() * 1e9 / iterations as f64
+}
+
+//  The measurements of a workload, one per backend.
+struct Report {
+    title: &'static str,
+    entries: Vec<(&'static str, f64)>,
+}
+
+impl Report {
+    fn new(title: &'static str) -> Self {
+        Self {
+            title,
+            entries: Vec::new(),
+        }
+    }
+
+    fn record(&mut self, backend: &'static str, nanos: f64) {
+        self.entries.push((backend, nanos));
+    }
+
+    fn print(&self) {
+        let fastest = self
+            .entries
+            .iter()
+            .map(|(_, nanos)| *nanos)
+            .fold(f64::INFINITY, f64::min);
+
+        println!("{}", self.title);
+        println!("    {:<12} {:>12} {:>10}", "backend", "ns/iter", "relative");
+
+        for (backend, nanos) in &self.entries {
+            println!(
+                "    {:<12} {:>12.1} {:>9.2}x",
+                backend,
+                nanos,
+                nanos / fastest
+            );
+        }
+
+        println!();
+    }
+}
@@ -0,0 +1,139 @@
+//! A parser building its syntax tree in a bump storage, reset between inputs.
+//!
+//! The nodes reference each other through handles, which are offsets within the arena, rather than pointers.
+
+use storage_poc::{
+    inline::{BumpElement, BumpElementHandle},
+    traits::ElementStorage,
+};
+
+type Arena = BumpElement<[usize; 64]>;
+
+type Node = BumpElementHandle<Expr>;
+
+//  The syntax tree of a simple arithmetic expression.
+enum Expr {
+    Number(i64),
+    Add(Node, Node),
+    Mul(Node, Node),
+}
+
+//  A recursive-descent parser of `+` and `*` over single digits.
+struct Parser<'a> {
+    input: &'a [u8],
+    arena: &'a mut Arena,
+}
+
+impl<'a> Parser<'a> {
+    fn parse(input: &'a str, arena: &'a mut Arena) -> Option<Node> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            arena,
+        };
+
+        let node = parser.sum()?;
+
+        if parser.input.is_empty() {
+            Some(node)
+        } else {
+            None
+        }
+    }
+
+    fn sum(&mut self) -> Option<Node> {
+        let mut left = self.product()?;
+
+        while self.eat(b'+') {
+            let right = self.product()?;
+            left = self.node(Expr::Add(left, right))?;
+        }
+
+        Some(left)
+    }
+
+    fn product(&mut self) -> Option<Node> {
+        let mut left = self.number()?;
+
+        while self.eat(b'*') {
+            let right = self.number()?;
+            left = self.node(Expr::Mul(left, right))?;
+        }
+
+        Some(left)
+    }
+
+    fn number(&mut self) -> Option<Node> {
+        self.skip_whitespace();
+
+        let (&digit, rest) = self.input.split_first()?;
+
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+
+        self.input = rest;
+        self.node(Expr::Number((digit - b'0') as i64))
+    }
+
+    fn eat(&mut self, expected: u8) -> bool {
+        self.skip_whitespace();
+
+        match self.input.split_first() {
+            Some((&byte, rest)) if byte == expected => {
+                self.input = rest;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some((b' ', rest)) = self.input.split_first() {
+            self.input = rest;
+        }
+    }
+
+    fn node(&mut self, expr: Expr) -> Option<Node> {
+        self.arena.create(expr).ok()
+    }
+}
+
+fn evaluate(arena: &Arena, node: Node) -> i64 {
+    //  Safety:
+    //  -   `node` was allocated by `arena`, which has not been reset since.
+    let expr = unsafe { arena.get(&node).as_ref() };
+
+    match *expr {
+        Expr::Number(n) => n,
+        Expr::Add(left, right) => evaluate(arena, left) + evaluate(arena, right),
+        Expr::Mul(left, right) => evaluate(arena, left) * evaluate(arena, right),
+    }
+}
+
+fn run() {
+    let mut arena = Arena::new();
+
+    for (input, expected) in &[("1 + 2 * 3", 7), ("2 * 3 + 4 * 5", 26), ("9", 9)] {
+        let root = Parser::parse(input, &mut arena).expect("Valid expression");
+
+        assert_eq!(*expected, evaluate(&arena, root));
+
+        println!("{} = {} ({:?})", input, expected, arena);
+
+        //  The syntax tree is no longer necessary, reclaim the whole arena at once.
+        arena.reset();
+    }
+
+    //  An expression too large for the arena is rejected, rather than allocated on the heap.
+    let large = vec!["1"; 64].join(" + ");
+    assert!(Parser::parse(&large, &mut arena).is_none());
+}
+
+fn main() {
+    run();
+}
+
+#[test]
+fn arena_parser() {
+    run();
+}
@@ -0,0 +1,137 @@
+//! An event dispatcher storing its handlers as `SmallBox<dyn Handler>`.
+//!
+//! Small handlers are stored inline, in the box itself, and only larger ones spill over to the heap.
+
+#![feature(allocator_api)]
+
+use std::alloc::Global;
+
+use storage_poc::{collections::RawBox, small::SingleElement};
+
+type SmallBox<T> = RawBox<T, SingleElement<[usize; 2], Global>>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Event {
+    Click(i32, i32),
+    Key(char),
+}
+
+trait Handler {
+    //  Returns whether the event was consumed.
+    fn handle(&mut self, event: Event) -> bool;
+
+    fn summary(&self) -> String;
+}
+
+//  Small enough to be stored inline.
+struct ClickCounter(usize);
+
+impl Handler for ClickCounter {
+    fn handle(&mut self, event: Event) -> bool {
+        if let Event::Click(..) = event {
+            self.0 += 1;
+        }
+        false
+    }
+
+    fn summary(&self) -> String {
+        format!("{} click(s)", self.0)
+    }
+}
+
+//  Too large to be stored inline, and thus spills over to the heap.
+struct KeyLogger {
+    keys: String,
+    stop: char,
+    stopped: bool,
+}
+
+impl Handler for KeyLogger {
+    fn handle(&mut self, event: Event) -> bool {
+        match event {
+            Event::Key(key) if !self.stopped => {
+                self.stopped = key == self.stop;
+                self.keys.push(key);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn summary(&self) -> String {
+        format!("keys {:?}", self.keys)
+    }
+}
+
+#[derive(Default)]
+struct Dispatcher {
+    handlers: Vec<SmallBox<dyn Handler>>,
+}
+
+impl Dispatcher {
+    fn register<H: Handler + 'static>(&mut self, handler: H) {
+        let storage = SingleElement::new(Global);
+
+        let boxed: SmallBox<dyn Handler> = RawBox::new(handler, storage)
+            .map_err(|_| "Global allocation failure")
+            .unwrap()
+            .coerce();
+
+        self.handlers.push(boxed);
+    }
+
+    //  Dispatches the event to each handler in turn, until one consumes it.
+    fn dispatch(&mut self, event: Event) {
+        for handler in &mut self.handlers {
+            if handler.handle(event) {
+                break;
+            }
+        }
+    }
+
+    fn summaries(&self) -> Vec<String> {
+        self.handlers
+            .iter()
+            .map(|handler| handler.summary())
+            .collect()
+    }
+}
+
+fn run() {
+    let mut dispatcher = Dispatcher::default();
+
+    dispatcher.register(ClickCounter(0));
+    dispatcher.register(KeyLogger {
+        keys: String::new(),
+        stop: '!',
+        stopped: false,
+    });
+
+    let events = [
+        Event::Click(1, 2),
+        Event::Key('h'),
+        Event::Key('i'),
+        Event::Key('!'),
+        Event::Click(3, 4),
+        Event::Key('?'),
+    ];
+
+    for event in &events {
+        dispatcher.dispatch(*event);
+    }
+
+    let summaries = dispatcher.summaries();
+
+    assert_eq!(vec!["2 click(s)", "keys \"hi!\""], summaries);
+
+    println!("{:?}", summaries);
+}
+
+fn main() {
+    run();
+}
+
+#[test]
+fn event_dispatcher() {
+    run();
+}
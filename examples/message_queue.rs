@@ -0,0 +1,114 @@
+//! A message queue whose messages live in a fixed pool, without any heap allocation.
+//!
+//! The pool could just as well live in a `static`, as is customary on embedded targets: it is a plain value, with no
+//! pointer to itself.
+
+use core::ptr;
+
+use storage_poc::{
+    inline::{TrackingElement, TrackingElementHandle},
+    traits::ElementStorage,
+};
+
+const CAPACITY: usize = 4;
+
+#[derive(Debug, PartialEq)]
+struct Message {
+    id: u32,
+    payload: [u8; 8],
+}
+
+impl Message {
+    fn new(id: u32) -> Self {
+        Self {
+            id,
+            payload: [id as u8; 8],
+        }
+    }
+}
+
+//  A FIFO queue of messages, whose messages are stored in a fixed pool.
+struct MessageQueue {
+    pool: TrackingElement<Message, CAPACITY>,
+    slots: [Option<TrackingElementHandle<Message>>; CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl MessageQueue {
+    fn new() -> Self {
+        Self {
+            pool: TrackingElement::new(),
+            slots: [None; CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    //  Sends a message, handing it back if the pool is exhausted.
+    fn send(&mut self, message: Message) -> Result<(), Message> {
+        let handle = self.pool.create(message)?;
+
+        self.slots[(self.head + self.len) % CAPACITY] = Some(handle);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    //  Receives the oldest message, if any.
+    fn receive(&mut self) -> Option<Message> {
+        let handle = self.slots[self.head].take()?;
+
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+
+        //  Safety:
+        //  -   `handle` is valid, and the message is moved out prior to deallocating its slot.
+        unsafe {
+            let message = ptr::read(self.pool.get(&handle).as_ptr());
+            self.pool.deallocate(&handle);
+            Some(message)
+        }
+    }
+}
+
+impl Drop for MessageQueue {
+    fn drop(&mut self) {
+        while self.receive().is_some() {}
+    }
+}
+
+fn run() {
+    let mut queue = MessageQueue::new();
+
+    for id in 0..4 {
+        queue.send(Message::new(id)).unwrap();
+    }
+
+    //  The pool is exhausted, apply back-pressure.
+    let rejected = queue.send(Message::new(4)).unwrap_err();
+    assert_eq!(4, rejected.id);
+
+    assert_eq!(Some(Message::new(0)), queue.receive());
+    assert_eq!(Some(Message::new(1)), queue.receive());
+
+    //  Slots are recycled as messages are received.
+    queue.send(Message::new(5)).unwrap();
+
+    let received: Vec<_> = core::iter::from_fn(|| queue.receive())
+        .map(|message| message.id)
+        .collect();
+
+    assert_eq!(vec![2, 3, 5], received);
+
+    println!("Received all messages, in order: {:?}", received);
+}
+
+fn main() {
+    run();
+}
+
+#[test]
+fn message_queue() {
+    run();
+}
@@ -2,6 +2,8 @@
 
 mod alloc_element;
 mod builder;
+mod caching_element;
 
 pub use alloc_element::AllocStorage;
 pub use builder::AllocatorBuilder;
+pub use caching_element::{CacheStats, CachingStorage};
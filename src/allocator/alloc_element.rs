@@ -1,10 +1,10 @@
 //! Simple implementation of `ElementStorage<T>`.
 
 use core::{
-    alloc::{AllocError, Allocator, Layout},
+    alloc::{Allocator, Layout},
     fmt::{self, Debug},
     marker::Unsize,
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
     ptr::NonNull,
 };
 
@@ -12,7 +12,9 @@ use rfc2580::{self, Pointee};
 
 use crate::{
     alternative::Builder,
-    traits::{ElementStorage, RangeStorage},
+    error::{StorageError, StorageResultExt},
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::{AddressStability, ElementStorage, PinSafeStorage, RangeStorage},
     utils,
 };
 
@@ -27,14 +29,21 @@ pub struct AllocStorage<A> {
 
 impl<A> AllocStorage<A> {
     /// Creates an instance of AllocStorage.
-    pub fn new(allocator: A) -> Self {
+    pub const fn new(allocator: A) -> Self {
         Self { allocator }
     }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(&self) -> &A {
+        &self.allocator
+    }
 }
 
 impl<A: Allocator> ElementStorage for AllocStorage<A> {
     type Handle<T: ?Sized + Pointee> = NonNull<T>;
 
+    const ADDRESS_STABILITY: AddressStability = AddressStability::PINNED;
+
     unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
         //  Safety:
         //  -   `element` points to a valid value.
@@ -57,11 +66,39 @@ impl<A: Allocator> ElementStorage for AllocStorage<A> {
         handle.clone()
     }
 
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        handle.cast()
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let layout = Layout::for_value_raw(handle.as_ptr());
+
+        //  The allocator requires the layout of the allocation upon deallocation, hence it must be preserved.
+        if layout != Layout::new::<U>() {
+            return Err(StorageError::new(Layout::new::<U>()).with_context(CONTEXT));
+        }
+
+        Ok(handle.cast())
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        Some(Layout::new::<T>())
+    }
+
     fn allocate<T: ?Sized + Pointee>(
         &mut self,
         meta: T::MetaData,
-    ) -> Result<Self::Handle<T>, AllocError> {
-        let slice = self.allocator.allocate(utils::layout_of::<T>(meta))?;
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let layout = utils::layout_of::<T>(meta);
+        let slice = self.allocator.allocate(layout).context(CONTEXT, layout)?;
 
         let pointer: NonNull<u8> = slice.as_non_null_ptr().cast();
 
@@ -69,13 +106,21 @@ impl<A: Allocator> ElementStorage for AllocStorage<A> {
     }
 }
 
+//  Safety:
+//  -   Elements live in memory obtained from the allocator, which does not move with `self`.
+unsafe impl<A: Allocator> PinSafeStorage for AllocStorage<A> {}
+
 impl<A: Allocator> RangeStorage for AllocStorage<A> {
     type Handle<T> = NonNull<[MaybeUninit<T>]>;
 
     type Capacity = usize;
 
     fn maximum_capacity<T>(&self) -> Self::Capacity {
-        usize::MAX
+        //  The size of an allocation cannot exceed `isize::MAX`.
+        match mem::size_of::<T>() {
+            0 => usize::MAX,
+            size => isize::MAX as usize / size,
+        }
     }
 
     unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
@@ -90,11 +135,15 @@ impl<A: Allocator> RangeStorage for AllocStorage<A> {
         handle.clone()
     }
 
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        Self::dangling_handle()
+    }
+
     unsafe fn try_grow<T>(
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         debug_assert!(handle.len() < new_capacity);
 
         if handle.len() == 0 {
@@ -105,7 +154,10 @@ impl<A: Allocator> RangeStorage for AllocStorage<A> {
         let old_pointer = Self::from_handle(handle.clone());
 
         let new_layout = Self::layout_for::<T>(new_capacity)?;
-        let new_pointer = self.allocator.grow(old_pointer, old_layout, new_layout)?;
+        let new_pointer = self
+            .allocator
+            .grow(old_pointer, old_layout, new_layout)
+            .context(CONTEXT, new_layout)?;
 
         Ok(Self::into_handle(new_pointer, new_capacity))
     }
@@ -114,11 +166,11 @@ impl<A: Allocator> RangeStorage for AllocStorage<A> {
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         debug_assert!(handle.len() > new_capacity);
 
         if handle.len() == 0 {
-            return Err(AllocError);
+            return Err(StorageError::array::<T>(new_capacity).with_context(CONTEXT));
         }
 
         let old_layout = Self::layout_of(handle.clone());
@@ -130,18 +182,47 @@ impl<A: Allocator> RangeStorage for AllocStorage<A> {
         }
 
         let new_layout = Self::layout_for::<T>(new_capacity)?;
-        let new_pointer = self.allocator.shrink(old_pointer, old_layout, new_layout)?;
+        let new_pointer = self
+            .allocator
+            .shrink(old_pointer, old_layout, new_layout)
+            .context(CONTEXT, new_layout)?;
 
         Ok(Self::into_handle(new_pointer, new_capacity))
     }
 
-    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, AllocError> {
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        if handle.is_empty() {
+            return Ok(Self::dangling_handle());
+        }
+
+        let layout = Self::layout_of(*handle);
+
+        let capacity = match mem::size_of::<U>() {
+            0 => return Err(StorageError::new(Layout::new::<U>()).with_context(CONTEXT)),
+            size => layout.size() / size,
+        };
+
+        //  The allocator requires the layout of the allocation upon deallocation, hence it must be preserved.
+        if layout.size() == 0 || Layout::array::<U>(capacity).ok() != Some(layout) {
+            return Err(StorageError::array::<U>(capacity).with_context(CONTEXT));
+        }
+
+        Ok(NonNull::slice_from_raw_parts(
+            handle.as_non_null_ptr().cast(),
+            capacity,
+        ))
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
         if capacity == 0 {
             return Ok(Self::dangling_handle());
         }
 
         let layout = Self::layout_for::<T>(capacity)?;
-        let pointer = self.allocator.allocate(layout)?;
+        let pointer = self.allocator.allocate(layout).context(CONTEXT, layout)?;
         Ok(Self::into_handle(pointer, capacity))
     }
 }
@@ -166,6 +247,15 @@ impl<A> Builder<AllocStorage<A>> for AllocatorBuilder<A> {
     }
 }
 
+//  The allocations are not tracked, see `StatsStorage` to report them.
+impl<A> MemoryUsage for AllocStorage<A> {
+    const TRACKS_ALLOCATIONS: bool = false;
+
+    fn mem_usage(&self) -> MemUsage {
+        MemUsage::with_payload(mem::size_of::<Self>(), 0, 0)
+    }
+}
+
 impl<A: Default> Default for AllocStorage<A> {
     fn default() -> Self {
         let allocator = A::default();
@@ -182,15 +272,19 @@ impl<A> Debug for AllocStorage<A> {
 //
 //  Implementation
 //
+
+const CONTEXT: &str = "AllocStorage";
+
 impl<A: Allocator> AllocStorage<A> {
     fn dangling_handle<T>() -> NonNull<[MaybeUninit<T>]> {
         NonNull::slice_from_raw_parts(NonNull::dangling(), 0)
     }
 
-    fn layout_for<T>(capacity: usize) -> Result<Layout, AllocError> {
+    fn layout_for<T>(capacity: usize) -> Result<Layout, StorageError> {
         debug_assert!(capacity > 0);
 
-        Layout::array::<T>(capacity).map_err(|_| AllocError)
+        Layout::array::<T>(capacity)
+            .map_err(|_| StorageError::array::<T>(capacity).with_context(CONTEXT))
     }
 
     fn layout_of<T>(handle: NonNull<[MaybeUninit<T>]>) -> Layout {
@@ -213,7 +307,9 @@ impl<A: Allocator> AllocStorage<A> {
 #[cfg(test)]
 mod tests {
 
-    use crate::utils::{NonAllocator, SpyAllocator};
+    use core::{fmt::Debug, marker::PhantomPinned, ptr};
+
+    use crate::utils::{ArenaAllocator, NonAllocator, Packet, SpyAllocator};
 
     use super::*;
 
@@ -251,6 +347,32 @@ mod tests {
         storage.create(1u8).unwrap_err();
     }
 
+    #[test]
+    fn create_deterministic() {
+        let allocator = ArenaAllocator::<64>::new();
+
+        let mut storage = AllocStorage::new(&allocator);
+        let h1 = storage.create(1u8).unwrap();
+        let h2 = storage.create(2u32).unwrap();
+
+        assert_eq!(0, allocator.offset_of(h1));
+        assert_eq!(4, allocator.offset_of(h2));
+        assert_eq!(5, allocator.live_bytes());
+
+        unsafe { storage.destroy(&h2) };
+        unsafe { storage.destroy(&h1) };
+
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(2, allocator.deallocated());
+        assert_eq!(0, allocator.live_bytes());
+
+        let h3 = storage.create(3u16).unwrap();
+
+        assert_eq!(0, allocator.offset_of(h3));
+
+        unsafe { storage.destroy(&h3) };
+    }
+
     #[test]
     fn coerce() {
         let allocator = SpyAllocator::default();
@@ -290,6 +412,153 @@ mod tests {
         assert_eq!(1, allocator.deallocated());
     }
 
+    #[test]
+    fn uncoerce() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+        let handle = storage.create([1u8, 2]).unwrap();
+        let handle = unsafe { storage.coerce::<[u8], _>(&handle) };
+        let handle = unsafe { storage.uncoerce::<[u8; 2], _>(&handle) };
+
+        assert_eq!([1, 2], unsafe {
+            *<_ as ElementStorage>::get(&storage, &handle).as_ref()
+        });
+
+        unsafe { storage.destroy(&handle) };
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn custom_dst() {
+        let allocator = ArenaAllocator::<64>::new();
+
+        let mut storage = AllocStorage::new(&allocator);
+        let handle = storage
+            .create(Packet {
+                tag: 1u8,
+                payload: [2u16, 3, 4],
+            })
+            .unwrap();
+        let handle = unsafe { storage.coerce::<Packet<[u16]>, _>(&handle) };
+
+        let packet = unsafe { <_ as ElementStorage>::get(&storage, &handle).as_ref() };
+
+        assert_eq!(1, packet.tag);
+        assert_eq!([2, 3, 4], &packet.payload);
+
+        let meta = rfc2580::into_non_null_parts(NonNull::from(packet)).0;
+        let other = <_ as ElementStorage>::allocate::<Packet<[u16]>>(&mut storage, meta).unwrap();
+
+        assert_eq!(8, allocator.offset_of(other));
+        assert_eq!(16, allocator.live_bytes());
+
+        unsafe { <_ as ElementStorage>::deallocate(&mut storage, &other) };
+        unsafe { storage.destroy(&handle) };
+
+        assert_eq!(0, allocator.live_bytes());
+    }
+
+    #[test]
+    fn custom_dst_trait() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+        let handle = storage
+            .create(Packet {
+                tag: 1u8,
+                payload: "Hello".to_string(),
+            })
+            .unwrap();
+        let handle = unsafe { storage.coerce::<Packet<dyn Debug>, _>(&handle) };
+
+        let packet = unsafe { <_ as ElementStorage>::get(&storage, &handle).as_ref() };
+
+        assert_eq!(1, packet.tag);
+        assert_eq!("\"Hello\"", format!("{:?}", &packet.payload));
+
+        unsafe { storage.destroy(&handle) };
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn create_pinned_with() {
+        struct SelfReferential {
+            value: u32,
+            this: *const SelfReferential,
+            _pinned: PhantomPinned,
+        }
+
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+
+        let (handle, pinned) = storage
+            .create_pinned_with(|| SelfReferential {
+                value: 42,
+                this: ptr::null(),
+                _pinned: PhantomPinned,
+            })
+            .unwrap();
+
+        //  Safety:
+        //  -   The value is not moved out of.
+        let element = unsafe { pinned.get_unchecked_mut() };
+        element.this = element;
+
+        let moved = storage;
+
+        let element = unsafe { ElementStorage::get(&moved, &handle) };
+
+        assert_eq!(element.as_ptr() as *const _, unsafe {
+            element.as_ref().this
+        });
+        assert_eq!(42, unsafe { element.as_ref().value });
+
+        let mut moved = moved;
+        unsafe { moved.destroy(&handle) };
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn create_pinned_with_failure() {
+        let mut storage = AllocStorage::new(NonAllocator);
+
+        let result =
+            storage.create_pinned_with::<u32, _>(|| unreachable!("Not invoked on failure"));
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn recycle() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+        let handle = storage.create(1u32).unwrap();
+
+        //  The layout of the allocation must be preserved, down to its alignment.
+        unsafe { storage.recycle::<_, [u16; 2]>(&handle) }.unwrap_err();
+
+        let recycled = unsafe { storage.recycle::<_, i32>(&handle) }.unwrap();
+
+        let pointer = unsafe { <_ as ElementStorage>::get(&storage, &recycled) };
+
+        unsafe { pointer.as_ptr().write(-1) };
+
+        assert_eq!(-1, unsafe { *pointer.as_ref() });
+        assert_eq!(1, allocator.allocated());
+
+        unsafe { storage.destroy(&recycled) };
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
     // Range tests
 
     #[test]
@@ -317,9 +586,97 @@ mod tests {
         assert_eq!(1, allocator.deallocated());
     }
 
+    #[test]
+    fn dangling() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+        let handle = storage.dangling::<u32>();
+
+        assert_eq!(0, unsafe { RangeStorage::get(&storage, &handle) }.len());
+
+        let handle = unsafe { storage.try_grow(&handle, 2) }.unwrap();
+
+        assert_eq!(2, unsafe { RangeStorage::get(&storage, &handle) }.len());
+        assert_eq!(1, allocator.allocated());
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+        let handle = storage.dangling::<u32>();
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn cast() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+        let handle = <_ as RangeStorage>::allocate::<u8>(&mut storage, 8).unwrap();
+
+        //  The layout of the allocation must be preserved, down to its alignment and size.
+        unsafe { storage.cast::<_, u32>(&handle) }.unwrap_err();
+        unsafe { storage.cast::<_, [u8; 3]>(&handle) }.unwrap_err();
+
+        let packets = unsafe { storage.cast::<_, [u8; 4]>(&handle) }.unwrap();
+
+        assert_eq!(2, packets.len());
+        assert_eq!(handle.as_mut_ptr() as usize, packets.as_mut_ptr() as usize);
+
+        unsafe { <_ as RangeStorage>::deallocate(&mut storage, &packets) };
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn maximum_capacity() {
+        let storage = AllocStorage::new(NonAllocator);
+
+        assert_eq!(
+            isize::MAX as usize,
+            RangeStorage::maximum_capacity::<u8>(&storage)
+        );
+        assert_eq!(
+            isize::MAX as usize / 8,
+            RangeStorage::maximum_capacity::<u64>(&storage)
+        );
+        assert_eq!(usize::MAX, RangeStorage::maximum_capacity::<()>(&storage));
+
+        type Huge = [u8; 1 << 40];
+
+        let maximum = RangeStorage::maximum_capacity::<Huge>(&storage);
+
+        assert_eq!(isize::MAX as usize >> 40, maximum);
+        assert!(Layout::array::<Huge>(maximum).is_ok());
+        assert!(Layout::array::<Huge>(maximum + 1).is_err());
+    }
+
     #[test]
     fn allocate_failure() {
         let mut storage = AllocStorage::new(NonAllocator);
         <_ as RangeStorage>::allocate::<String>(&mut storage, 1).unwrap_err();
     }
+
+    #[test]
+    fn grow_deterministic() {
+        let allocator = ArenaAllocator::<64>::new();
+
+        let mut storage = AllocStorage::new(&allocator);
+        let handle = <_ as RangeStorage>::allocate::<u32>(&mut storage, 2).unwrap();
+
+        assert_eq!(0, allocator.offset_of(handle));
+
+        let handle = unsafe { storage.try_grow(&handle, 4) }.unwrap();
+
+        assert_eq!(8, allocator.offset_of(handle));
+        assert_eq!(16, allocator.live_bytes());
+
+        unsafe { storage.try_grow(&handle, 16) }.unwrap_err();
+
+        unsafe { <_ as RangeStorage>::deallocate(&mut storage, &handle) };
+
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(2, allocator.deallocated());
+        assert_eq!(0, allocator.live_bytes());
+    }
 } // mod tests
@@ -0,0 +1,430 @@
+//! Implementation of `ElementStorage<T>` retaining freed memory blocks in per size class caches.
+
+use core::{
+    alloc::{Allocator, Layout},
+    fmt::{self, Debug},
+    marker::Unsize,
+    ptr::NonNull,
+};
+
+use rfc2580::{self, Pointee};
+
+use crate::{
+    error::{StorageError, StorageResultExt},
+    traits::{AddressStability, ElementStorage, PinSafeStorage},
+    utils,
+};
+
+use super::AllocStorage;
+
+/// Statistics gathered by a `CachingStorage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of allocations served from the cache.
+    pub hits: usize,
+    /// Number of allocations served by the allocator, for lack of a suitable cached block.
+    pub misses: usize,
+    /// Number of deallocations whose block was retained in the cache.
+    pub retained: usize,
+    /// Number of deallocations whose block was returned to the allocator, for lack of room in the cache.
+    pub released: usize,
+}
+
+/// Allocator-based ElementStorage, retaining freed memory blocks in per size class caches.
+///
+/// The size of each allocation is rounded up to the next power of two, and allocations of equal rounded size and
+/// alignment form a size class. A block freed by `deallocate` is retained in the magazine of its size class, if it has
+/// room, and handed out again by a subsequent allocation of the same class rather than returned to the allocator. This
+/// drastically reduces the allocator traffic of node-churning collections, such as linked lists.
+///
+/// Up to `C` size classes are cached at any one time, each retaining up to `D` blocks; a size class is only assigned a
+/// magazine while it has cached blocks. The magazines are searched linearly, and are therefore meant to be small.
+pub struct CachingStorage<A: Allocator, const C: usize = 4, const D: usize = 8> {
+    storage: AllocStorage<A>,
+    magazines: [Magazine<D>; C],
+    stats: CacheStats,
+}
+
+impl<A: Allocator, const C: usize, const D: usize> CachingStorage<A, C, D> {
+    /// Creates an instance of CachingStorage, with empty caches.
+    pub const fn new(allocator: A) -> Self {
+        Self {
+            storage: AllocStorage::new(allocator),
+            magazines: [Magazine::EMPTY; C],
+            stats: CacheStats {
+                hits: 0,
+                misses: 0,
+                retained: 0,
+                released: 0,
+            },
+        }
+    }
+
+    /// Returns the statistics gathered so far.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Returns the number of memory blocks currently retained in the caches.
+    pub fn cached(&self) -> usize {
+        self.magazines.iter().map(|magazine| magazine.len).sum()
+    }
+
+    /// Pre-allocates memory blocks for `count` elements of type `T`, retaining them in the cache of their size class.
+    ///
+    /// At most `D` blocks are retained per size class, any excess `count` is ignored, as is `count` altogether if no
+    /// magazine is available for the size class of `T`. On failure, the blocks allocated so far remain in the cache.
+    pub fn warm_up<T>(&mut self, count: usize) -> Result<(), StorageError> {
+        let class = match Self::class_of(Layout::new::<T>()) {
+            Some(class) => class,
+            None => return Ok(()),
+        };
+
+        for _ in 0..count {
+            let index = match self.magazine_with_room(class) {
+                Some(index) => index,
+                None => break,
+            };
+
+            let pointer = self
+                .storage
+                .allocator()
+                .allocate(class)
+                .context("CachingStorage", class)?;
+
+            self.magazines[index].push(class, pointer.as_non_null_ptr());
+        }
+
+        Ok(())
+    }
+
+    /// Returns all the memory blocks retained in the caches to the allocator.
+    pub fn flush(&mut self) {
+        for magazine in &mut self.magazines {
+            while let Some((pointer, class)) = magazine.pop() {
+                //  Safety:
+                //  -   `pointer` was allocated by `self.storage.allocator()`, with `class`.
+                unsafe { self.storage.allocator().deallocate(pointer, class) };
+            }
+        }
+    }
+}
+
+impl<A: Allocator, const C: usize, const D: usize> ElementStorage for CachingStorage<A, C, D> {
+    type Handle<T: ?Sized + Pointee> = NonNull<T>;
+
+    const ADDRESS_STABILITY: AddressStability = AddressStability::PINNED;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        //  Safety:
+        //  -   `handle` points to a valid value.
+        let layout = Layout::for_value_raw(handle.as_ptr());
+
+        let class = match Self::class_of(layout) {
+            Some(class) => class,
+            None => return self.storage.deallocate(handle),
+        };
+
+        match self.magazine_with_room(class) {
+            Some(index) => {
+                self.magazines[index].push(class, handle.cast());
+                self.stats.retained += 1;
+            }
+            None => {
+                //  Safety:
+                //  -   `handle` was allocated by `self.storage.allocator()`, with `class`.
+                self.storage.allocator().deallocate(handle.cast(), class);
+                self.stats.released += 1;
+            }
+        }
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.storage.get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.storage.coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.storage.uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        //  The layout is preserved, and therefore so is the size class.
+        self.storage.recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        let layout = Layout::new::<T>();
+
+        //  Zero-sized allocations bypass the cache.
+        Some(Self::class_of(layout).unwrap_or(layout))
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let class = match Self::class_of(utils::layout_of::<T>(meta)) {
+            Some(class) => class,
+            None => return self.storage.allocate(meta),
+        };
+
+        let cached = self
+            .magazines
+            .iter_mut()
+            .find(|magazine| magazine.class == Some(class))
+            .and_then(Magazine::pop);
+
+        let pointer = match cached {
+            Some((pointer, _)) => {
+                self.stats.hits += 1;
+                pointer
+            }
+            None => {
+                let pointer = self
+                    .storage
+                    .allocator()
+                    .allocate(class)
+                    .context("CachingStorage", class)?;
+                self.stats.misses += 1;
+                pointer.as_non_null_ptr()
+            }
+        };
+
+        Ok(rfc2580::from_non_null_parts(meta, pointer))
+    }
+}
+
+//  Safety:
+//  -   Elements, and cached blocks, live in memory obtained from the allocator, which does not move with `self`.
+unsafe impl<A: Allocator, const C: usize, const D: usize> PinSafeStorage
+    for CachingStorage<A, C, D>
+{
+}
+
+//  Safety:
+//  -   The cached blocks are exclusively owned by `self`, and only accessed through `&mut self`.
+unsafe impl<A: Allocator + Send, const C: usize, const D: usize> Send for CachingStorage<A, C, D> {}
+
+//  Safety:
+//  -   The cached blocks are exclusively owned by `self`, and only accessed through `&mut self`.
+unsafe impl<A: Allocator + Sync, const C: usize, const D: usize> Sync for CachingStorage<A, C, D> {}
+
+impl<A: Allocator + Default, const C: usize, const D: usize> Default for CachingStorage<A, C, D> {
+    fn default() -> Self {
+        Self::new(A::default())
+    }
+}
+
+impl<A: Allocator, const C: usize, const D: usize> Debug for CachingStorage<A, C, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "CachingStorage{{ cached: {}, stats: {:?} }}",
+            self.cached(),
+            self.stats
+        )
+    }
+}
+
+impl<A: Allocator, const C: usize, const D: usize> Drop for CachingStorage<A, C, D> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+//
+//  Implementation
+//
+
+//  The cached blocks of a single size class.
+struct Magazine<const D: usize> {
+    class: Option<Layout>,
+    len: usize,
+    blocks: [NonNull<u8>; D],
+}
+
+impl<const D: usize> Magazine<D> {
+    const EMPTY: Self = Self {
+        class: None,
+        len: 0,
+        blocks: [NonNull::dangling(); D],
+    };
+
+    fn push(&mut self, class: Layout, block: NonNull<u8>) {
+        debug_assert!(self.len < D);
+        debug_assert!(self.class.map_or(true, |current| current == class));
+
+        self.class = Some(class);
+        self.blocks[self.len] = block;
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(NonNull<u8>, Layout)> {
+        let class = self.class?;
+
+        self.len -= 1;
+        let block = self.blocks[self.len];
+
+        //  An empty magazine is released, for any size class to claim.
+        if self.len == 0 {
+            self.class = None;
+        }
+
+        Some((block, class))
+    }
+}
+
+impl<A: Allocator, const C: usize, const D: usize> CachingStorage<A, C, D> {
+    //  Returns the size class of `layout`, or `None` if allocations of `layout` bypass the caches.
+    //
+    //  Zero-sized allocations are left to the allocator, as are those too large to be rounded up.
+    fn class_of(layout: Layout) -> Option<Layout> {
+        if layout.size() == 0 {
+            return None;
+        }
+
+        let size = layout.size().checked_next_power_of_two()?;
+
+        Layout::from_size_align(size, layout.align()).ok()
+    }
+
+    //  Returns the index of the magazine of `class`, if it has room, or else of an empty magazine, if any.
+    fn magazine_with_room(&self, class: Layout) -> Option<usize> {
+        let index = self
+            .magazines
+            .iter()
+            .position(|magazine| magazine.class == Some(class))
+            .or_else(|| {
+                self.magazines
+                    .iter()
+                    .position(|magazine| magazine.class.is_none())
+            })?;
+
+        if self.magazines[index].len < D {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::collections::RawLinkedList;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    #[test]
+    fn size_classes() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = CachingStorage::<_, 1, 2>::new(allocator.clone());
+
+        let first = storage.create([1u8; 5]).unwrap();
+        let second = storage.create([2u8; 6]).unwrap();
+        let third = storage.create([3u8; 7]).unwrap();
+
+        unsafe {
+            storage.destroy(&first);
+            storage.destroy(&second);
+            storage.destroy(&third);
+        }
+
+        assert_eq!(2, storage.cached());
+        assert_eq!(1, allocator.deallocated());
+
+        //  `[u8; 8]` shares the size class of `[u8; 5]`, whereas `[u8; 9]` does not, and finds no magazine either.
+        let fourth = storage.create([4u8; 8]).unwrap();
+        let fifth = storage.create([5u8; 9]).unwrap();
+
+        unsafe { storage.destroy(&fifth) };
+
+        assert_eq!(
+            CacheStats {
+                hits: 1,
+                misses: 4,
+                retained: 2,
+                released: 2
+            },
+            storage.stats()
+        );
+
+        unsafe { storage.destroy(&fourth) };
+
+        storage.flush();
+
+        assert_eq!(0, storage.cached());
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn warm_up() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = CachingStorage::<_, 1, 2>::new(allocator.clone());
+        storage.warm_up::<u32>(3).unwrap();
+
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(
+            "CachingStorage{ cached: 2, stats: CacheStats { hits: 0, misses: 0, retained: 0, released: 0 } }",
+            format!("{:?}", storage)
+        );
+
+        let first = storage.create(1u32).unwrap();
+        let second = storage.create(2u32).unwrap();
+
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(0, storage.cached());
+
+        unsafe {
+            storage.destroy(&first);
+            storage.destroy(&second);
+        }
+
+        drop(storage);
+
+        assert_eq!(2, allocator.deallocated());
+    }
+
+    #[test]
+    fn warm_up_failure() {
+        let mut storage = CachingStorage::<NonAllocator>::default();
+
+        storage.warm_up::<u32>(1).unwrap_err();
+
+        assert_eq!(0, storage.cached());
+    }
+
+    #[test]
+    fn linked_list_churn() {
+        let allocator = SpyAllocator::default();
+
+        let mut list = RawLinkedList::new(CachingStorage::<_>::new(allocator.clone()));
+
+        for round in 0..10 {
+            list.push(round);
+            list.push(round);
+
+            assert_eq!(Some(round), list.pop());
+            assert_eq!(Some(round), list.pop());
+        }
+
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(0, allocator.deallocated());
+    }
+} // mod tests
@@ -1,7 +1,7 @@
 //! Alternative implementation of `ElementStorage`.
 
 use core::{
-    alloc::AllocError,
+    alloc::Layout,
     fmt::{self, Debug},
     hint,
     marker::Unsize,
@@ -12,7 +12,10 @@ use core::{
 
 use rfc2580::Pointee;
 
-use crate::traits::ElementStorage;
+use crate::{
+    error::StorageError,
+    traits::{AddressStability, ElementStorage, IndexedStorage},
+};
 
 use super::{Builder, Inner};
 
@@ -43,6 +46,9 @@ where
 {
     type Handle<T: ?Sized + Pointee> = SingleElementHandle<F::Handle<T>, S::Handle<T>>;
 
+    //  Switching from one alternative to the other relocates the element.
+    const ADDRESS_STABILITY: AddressStability = AddressStability::NONE;
+
     unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
         match &mut self.0 {
             Inner::First(ref mut first) => first.deallocate(&handle.first),
@@ -74,6 +80,44 @@ where
         }
     }
 
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        match &self.0 {
+            Inner::First(ref first) => SingleElementHandle {
+                first: ManuallyDrop::new(first.uncoerce(&handle.first)),
+            },
+            Inner::Second(ref second) => SingleElementHandle {
+                second: ManuallyDrop::new(second.uncoerce(&handle.second)),
+            },
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        match &mut self.0 {
+            Inner::First(ref mut first) => Ok(SingleElementHandle {
+                first: ManuallyDrop::new(first.recycle(&handle.first)?),
+            }),
+            Inner::Second(ref mut second) => Ok(SingleElementHandle {
+                second: ManuallyDrop::new(second.recycle(&handle.second)?),
+            }),
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        match &self.0 {
+            Inner::First(first) => first.slot_layout::<T>(),
+            Inner::Second(second) => second.slot_layout::<T>(),
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+
     fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
         match &mut self.0 {
             Inner::First(ref mut first) => match first.create(value) {
@@ -107,7 +151,7 @@ where
     fn allocate<T: ?Sized + Pointee>(
         &mut self,
         meta: T::MetaData,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         match &mut self.0 {
             Inner::First(ref mut first) => match first.allocate(meta) {
                 Ok(first) => Ok(SingleElementHandle {
@@ -138,6 +182,24 @@ where
     }
 }
 
+impl<F, S, FB, SB> IndexedStorage for SingleElement<F, S, FB, SB>
+where
+    F: IndexedStorage,
+    S: IndexedStorage,
+    FB: Builder<F>,
+    SB: Builder<S>,
+{
+    const SLOT_COUNT: usize = F::SLOT_COUNT + S::SLOT_COUNT;
+
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize {
+        match &self.0 {
+            Inner::First(ref first) => first.index(&handle.first),
+            Inner::Second(ref second) => F::SLOT_COUNT + second.index(&handle.second),
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+}
+
 impl<F, S, FB, SB> Debug for SingleElement<F, S, FB, SB> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "SingleElement")
@@ -1,7 +1,6 @@
 //! Alternative implementation of `RangeStorage`.
 
 use core::{
-    alloc::AllocError,
     cmp,
     fmt::{self, Debug},
     hint,
@@ -10,7 +9,10 @@ use core::{
     ptr::{self, NonNull},
 };
 
-use crate::traits::{Capacity, RangeStorage};
+use crate::{
+    error::StorageError,
+    traits::{Capacity, RangeStorage},
+};
 
 use super::{Builder, Inner};
 
@@ -45,7 +47,9 @@ where
 
     fn maximum_capacity<T>(&self) -> Self::Capacity {
         match &self.0 {
-            Inner::First(ref first) => into_second::<F, S>(first.maximum_capacity::<T>()),
+            //  Any allocation beyond the capacity of the first storage switches to the second storage, which is yet
+            //  to be built, hence the only known bound is that of its capacity type.
+            Inner::First(_) => S::Capacity::max(),
             Inner::Second(ref second) => second.maximum_capacity::<T>(),
             Inner::Poisoned => panic!("Poisoned"),
         }
@@ -67,37 +71,41 @@ where
         }
     }
 
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        match &self.0 {
+            Inner::First(ref first) => SingleRangeHandle {
+                first: ManuallyDrop::new(first.dangling()),
+            },
+            Inner::Second(ref second) => SingleRangeHandle {
+                second: ManuallyDrop::new(second.dangling()),
+            },
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+
     unsafe fn try_grow<T>(
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         match &mut self.0 {
             Inner::First(ref mut first) => {
-                let grow = into_first::<F, S>(new_capacity)
+                let grow = into_first::<T, F, S>(new_capacity)
                     .and_then(|new_capacity| first.try_grow(&handle.first, new_capacity));
 
                 match grow {
                     Ok(first) => Ok(SingleRangeHandle {
                         first: ManuallyDrop::new(first),
                     }),
-                    Err(_) => {
-                        if let Inner::First(first) = mem::replace(&mut self.0, Inner::Poisoned) {
-                            let (second, result) =
-                                first.transform(|first: &mut F, second: &mut S| {
-                                    let new_handle = second.allocate(new_capacity)?;
-                                    transfer(first.get(&handle.first), second.get(&new_handle));
-                                    Ok(SingleRangeHandle {
-                                        second: ManuallyDrop::new(new_handle),
-                                    })
-                                });
-                            self.0 = Inner::Second(second);
-                            return result;
-                        }
-                        //  Safety:
-                        //  -   self.0 was First before invoking replace, hence replace returns First.
-                        hint::unreachable_unchecked();
-                    }
+                    Err(_) => self
+                        .switch_to_second(|first: &mut F, second: &mut S| {
+                            let new_handle = second.allocate(new_capacity)?;
+                            transfer(first.get(&handle.first), second.get(&new_handle));
+                            Ok(new_handle)
+                        })
+                        .map(|second| SingleRangeHandle {
+                            second: ManuallyDrop::new(second),
+                        }),
                 }
             }
             Inner::Second(ref mut second) => {
@@ -111,14 +119,115 @@ where
         }
     }
 
+    unsafe fn try_grow_with<T, G>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: G,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        G: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        match &mut self.0 {
+            Inner::First(ref mut first) => {
+                //  Allocating through `self` may switch alternatives, invalidating `handle`, hence the new range is
+                //  allocated from the first storage if it fits, and otherwise from the second, switching to it.
+                let within = into_first::<T, F, S>(new_capacity)
+                    .and_then(|new_capacity| first.allocate::<T>(new_capacity));
+
+                if let Ok(new_handle) = within {
+                    let to = first.get(&new_handle);
+                    transfer(first.get(&handle.first), to);
+
+                    if let Err(error) = init(to) {
+                        first.deallocate(&new_handle);
+                        return Err(error);
+                    }
+
+                    first.deallocate(&handle.first);
+
+                    return Ok(SingleRangeHandle {
+                        first: ManuallyDrop::new(new_handle),
+                    });
+                }
+
+                self.switch_to_second(|first: &mut F, second: &mut S| {
+                    let new_handle = second.allocate(new_capacity)?;
+                    let to = second.get(&new_handle);
+                    transfer(first.get(&handle.first), to);
+
+                    if let Err(error) = init(to) {
+                        second.deallocate(&new_handle);
+                        return Err(error);
+                    }
+
+                    first.deallocate(&handle.first);
+                    Ok(new_handle)
+                })
+                .map(|second| SingleRangeHandle {
+                    second: ManuallyDrop::new(second),
+                })
+            }
+            Inner::Second(ref mut second) => second
+                .try_grow_with(&handle.second, new_capacity, init)
+                .map(|second| SingleRangeHandle {
+                    second: ManuallyDrop::new(second),
+                }),
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        match &mut self.0 {
+            Inner::First(ref mut first) => {
+                let grow = into_first::<T, F, S>(new_capacity).and_then(|new_capacity| {
+                    first.try_grow_with_gap(&handle.first, new_capacity, gap_at, gap_len)
+                });
+
+                match grow {
+                    Ok(first) => Ok(SingleRangeHandle {
+                        first: ManuallyDrop::new(first),
+                    }),
+                    Err(_) => self
+                        .switch_to_second(|first: &mut F, second: &mut S| {
+                            let new_handle = second.allocate(new_capacity)?;
+                            transfer_with_gap(
+                                first.get(&handle.first),
+                                second.get(&new_handle),
+                                gap_at,
+                                gap_len,
+                            );
+                            first.deallocate(&handle.first);
+                            Ok(new_handle)
+                        })
+                        .map(|second| SingleRangeHandle {
+                            second: ManuallyDrop::new(second),
+                        }),
+                }
+            }
+            Inner::Second(ref mut second) => second
+                .try_grow_with_gap(&handle.second, new_capacity, gap_at, gap_len)
+                .map(|second| SingleRangeHandle {
+                    second: ManuallyDrop::new(second),
+                }),
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+
     unsafe fn try_shrink<T>(
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         match &mut self.0 {
             Inner::First(ref mut first) => first
-                .try_shrink(&handle.first, into_first::<F, S>(new_capacity)?)
+                .try_shrink(&handle.first, into_first::<T, F, S>(new_capacity)?)
                 .map(|first| SingleRangeHandle {
                     first: ManuallyDrop::new(first),
                 }),
@@ -130,8 +239,9 @@ where
                     Ok(second) => Ok(SingleRangeHandle {
                         second: ManuallyDrop::new(second),
                     }),
-                    Err(_) => {
-                        let new_capacity = into_first::<F, S>(new_capacity)?;
+                    Err(error) => {
+                        let new_capacity =
+                            into_first::<T, F, S>(new_capacity).map_err(|_| error)?;
 
                         if let Inner::Second(second) = mem::replace(&mut self.0, Inner::Poisoned) {
                             let (first, result) =
@@ -155,11 +265,26 @@ where
         }
     }
 
-    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, AllocError> {
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        match &mut self.0 {
+            Inner::First(ref mut first) => Ok(SingleRangeHandle {
+                first: ManuallyDrop::new(first.cast(&handle.first)?),
+            }),
+            Inner::Second(ref mut second) => Ok(SingleRangeHandle {
+                second: ManuallyDrop::new(second.cast(&handle.second)?),
+            }),
+            Inner::Poisoned => panic!("Poisoned"),
+        }
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
         match &mut self.0 {
             Inner::First(ref mut first) => {
                 let handle =
-                    into_first::<F, S>(capacity).and_then(|capacity| first.allocate(capacity));
+                    into_first::<T, F, S>(capacity).and_then(|capacity| first.allocate(capacity));
 
                 match handle {
                     Ok(first) => Ok(SingleRangeHandle {
@@ -219,15 +344,58 @@ impl<F, S> Debug for SingleRangeHandle<F, S> {
 //  Implementation
 //
 
-fn into_first<F: RangeStorage, S: RangeStorage>(
-    capacity: S::Capacity,
-) -> Result<F::Capacity, AllocError> {
-    F::Capacity::from_usize(capacity.into_usize()).ok_or(AllocError)
+impl<F, S, FB, SB> SingleRange<F, S, FB, SB>
+where
+    F: RangeStorage,
+    S: RangeStorage,
+    FB: Builder<F>,
+    SB: Builder<S>,
+{
+    //  Switches to the Second alternative, migrating the range of the First alternative with `migrate`, and returns the
+    //  handle of the migrated range.
+    //
+    //  Should `migrate` fail, switches back to the First alternative, whose state is salvaged by its builder, hence the
+    //  handles it issued remain valid.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `self.0` is First.
+    unsafe fn switch_to_second<T, M>(&mut self, migrate: M) -> Result<S::Handle<T>, StorageError>
+    where
+        M: FnOnce(&mut F, &mut S) -> Result<S::Handle<T>, StorageError>,
+    {
+        let first = match mem::replace(&mut self.0, Inner::Poisoned) {
+            Inner::First(first) => first,
+            //  Safety:
+            //  -   self.0 is First, as per pre-condition.
+            _ => hint::unreachable_unchecked(),
+        };
+
+        let (second, result) = first.transform(migrate);
+
+        match result {
+            Ok(second_handle) => {
+                self.0 = Inner::Second(second);
+
+                Ok(second_handle)
+            }
+            Err(error) => {
+                let (first, _) = second.transform(|_: &mut S, _: &mut F| ());
+                self.0 = Inner::First(first);
+
+                Err(error)
+            }
+        }
+    }
 }
 
-fn into_second<F: RangeStorage, S: RangeStorage>(capacity: F::Capacity) -> S::Capacity {
-    S::Capacity::from_usize(capacity.into_usize())
-        .expect("Second to have a greater capacity type than First")
+fn into_first<T, F: RangeStorage, S: RangeStorage>(
+    capacity: S::Capacity,
+) -> Result<F::Capacity, StorageError> {
+    let capacity = capacity.into_usize();
+
+    F::Capacity::from_usize(capacity)
+        .ok_or_else(|| StorageError::array::<T>(capacity).with_context("SingleRange"))
 }
 
 unsafe fn transfer<T>(from: NonNull<[MaybeUninit<T>]>, mut to: NonNull<[MaybeUninit<T>]>) {
@@ -240,3 +408,20 @@ unsafe fn transfer<T>(from: NonNull<[MaybeUninit<T>]>, mut to: NonNull<[MaybeUni
         cmp::min(from.len(), to.len()),
     );
 }
+
+//  Copies `from` into `to`, leaving a gap of `gap_len` elements at `gap_at`, see `RangeStorage::try_grow_with_gap`.
+unsafe fn transfer_with_gap<T>(
+    from: NonNull<[MaybeUninit<T>]>,
+    to: NonNull<[MaybeUninit<T>]>,
+    gap_at: usize,
+    gap_len: usize,
+) {
+    debug_assert!(gap_at <= from.len());
+    debug_assert!(from.len() + gap_len <= to.len());
+
+    let tail = from.len() - gap_at;
+    let (from, to) = (from.as_mut_ptr(), to.as_mut_ptr());
+
+    ptr::copy_nonoverlapping(from, to, gap_at);
+    ptr::copy_nonoverlapping(from.add(gap_at), to.add(gap_at + gap_len), tail);
+}
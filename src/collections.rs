@@ -1,9 +1,64 @@
 //! Proof of Concept implementations of some collections, to demonstrate the use of Storages.
 
+mod eviction;
+mod insert_error;
+mod range_writer;
+mod raw_arena;
 mod raw_box;
+mod raw_btree_map;
+mod raw_btree_set;
+mod raw_bytes_pool;
+mod raw_cache;
+mod raw_cursor;
+mod raw_delay_queue;
+mod raw_dense_slot_map;
+mod raw_doubly_linked_list;
+mod raw_flight_recorder;
+mod raw_graph;
+mod raw_hash_map;
+mod raw_hash_set;
 mod raw_linked_list;
+mod raw_pool;
+mod raw_sorted_vec;
+mod raw_string;
+mod raw_trie;
 mod raw_vec;
+mod raw_vec_deque;
 
+pub use eviction::{DropOldest, EvictionStrategy, RejectNewest};
+pub use insert_error::{ExtendError, InsertError};
+pub use range_writer::RangeWriter;
+pub use raw_arena::{Idx, RawArena};
 pub use raw_box::RawBox;
-pub use raw_linked_list::{RawLinkedList, RawLinkedListNodeStorage};
-pub use raw_vec::RawVec;
+pub use raw_btree_map::{RawBTreeMap, RawBTreeMapNode, RawBTreeMapNodeStorage};
+pub use raw_btree_set::RawBTreeSet;
+pub use raw_bytes_pool::{RawBytesBlock, RawBytesPool, StorageBytes, StorageBytesMut};
+pub use raw_cache::RawCache;
+pub use raw_cursor::RawCursor;
+pub use raw_delay_queue::{DelayQueueEntry, DelayQueueKey, RawDelayQueue};
+pub use raw_dense_slot_map::{
+    RawDenseSlot, RawDenseSlotMap, RawDenseSlotMapKey, RawDenseSlotStorage,
+};
+pub use raw_doubly_linked_list::{
+    RawDoublyLinkedList, RawDoublyLinkedListNode, RawDoublyLinkedListNodeStorage,
+};
+pub use raw_flight_recorder::{FlightRecord, RawFlightRecorder};
+pub use raw_graph::{RawGraph, RawGraphNode, RawGraphNodeKey};
+pub use raw_hash_map::{FnvHasher, RawHashMap, RawHashMapBucketStorage};
+pub use raw_hash_set::RawHashSet;
+pub use raw_linked_list::{
+    RawLinkedList, RawLinkedListCursor, RawLinkedListCursorMut, RawLinkedListIntoIter,
+    RawLinkedListIter, RawLinkedListIterMut, RawLinkedListNode, RawLinkedListNodeStorage,
+};
+pub use raw_pool::{PoolGuard, RawPool, RawPoolSlotStorage, Reusable};
+pub use raw_sorted_vec::RawSortedVec;
+pub use raw_string::{FromUtf8Error, RawString};
+pub use raw_trie::{RawTrie, RawTrieNode};
+pub use raw_vec::{FrozenVec, RawVec};
+pub use raw_vec_deque::RawVecDeque;
+
+#[cfg(any(test, feature = "alloc"))]
+pub use raw_string::SmallString;
+
+#[cfg(feature = "serde")]
+pub use raw_vec::RawVecSeed;
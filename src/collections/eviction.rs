@@ -0,0 +1,37 @@
+//! The strategies deciding which element to evict when pushing onto a full bounded collection.
+
+/// A strategy deciding which element to evict, if any, to make room for a new element in a full collection.
+///
+/// Closures of the form `FnMut(&[T], &T) -> Option<usize>` are strategies.
+pub trait EvictionStrategy<T> {
+    /// Returns the index of the element of `elements` to evict to make room for `value`, or `None` to reject `value`.
+    ///
+    /// `elements` is never empty, and the index must be within its bounds.
+    fn victim(&mut self, elements: &[T], value: &T) -> Option<usize>;
+}
+
+/// Evicts the oldest element, that is the front element.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DropOldest;
+
+impl<T> EvictionStrategy<T> for DropOldest {
+    fn victim(&mut self, _: &[T], _: &T) -> Option<usize> {
+        Some(0)
+    }
+}
+
+/// Rejects the newest element, leaving the collection unchanged.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RejectNewest;
+
+impl<T> EvictionStrategy<T> for RejectNewest {
+    fn victim(&mut self, _: &[T], _: &T) -> Option<usize> {
+        None
+    }
+}
+
+impl<T, F: FnMut(&[T], &T) -> Option<usize>> EvictionStrategy<T> for F {
+    fn victim(&mut self, elements: &[T], value: &T) -> Option<usize> {
+        self(elements, value)
+    }
+}
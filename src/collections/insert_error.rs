@@ -0,0 +1,128 @@
+//! The errors returned by the fallible inserting methods of the collections.
+
+use core::{
+    alloc::AllocError,
+    fmt::{self, Debug, Display},
+};
+
+use crate::error::StorageError;
+
+/// The error returned by the fallible inserting methods of the collections, giving back the value which could not be
+/// inserted along with the cause of the failure.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct InsertError<T> {
+    value: T,
+    error: StorageError,
+}
+
+impl<T> InsertError<T> {
+    /// Creates an instance.
+    pub fn new(value: T, error: StorageError) -> Self {
+        Self { value, error }
+    }
+
+    /// Returns a reference to the value which could not be inserted.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the value which could not be inserted.
+    pub fn into_value(self) -> T {
+        self.value
+    }
+
+    /// Returns the cause of the failure.
+    pub fn error(&self) -> StorageError {
+        self.error
+    }
+}
+
+//  `T` is not required to implement `Debug`, so that any `Result<_, InsertError<T>>` may be unwrapped.
+impl<T> Debug for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "InsertError{{ error: {:?} }}", self.error)
+    }
+}
+
+impl<T> Display for InsertError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl<T> From<InsertError<T>> for StorageError {
+    fn from(error: InsertError<T>) -> Self {
+        error.error
+    }
+}
+
+impl<T> From<InsertError<T>> for AllocError {
+    fn from(error: InsertError<T>) -> Self {
+        error.error.into()
+    }
+}
+
+/// The error returned by the fallible extending methods of the collections, reporting how many elements were inserted
+/// prior to the failure, along with the element which could not be inserted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ExtendError<T> {
+    inserted: usize,
+    error: InsertError<T>,
+}
+
+impl<T> ExtendError<T> {
+    /// Creates an instance.
+    pub fn new(inserted: usize, error: InsertError<T>) -> Self {
+        Self { inserted, error }
+    }
+
+    /// Returns the number of elements inserted prior to the failure.
+    pub fn inserted(&self) -> usize {
+        self.inserted
+    }
+
+    /// Returns a reference to the value which could not be inserted.
+    pub fn value(&self) -> &T {
+        self.error.value()
+    }
+
+    /// Returns the value which could not be inserted.
+    pub fn into_value(self) -> T {
+        self.error.into_value()
+    }
+
+    /// Returns the cause of the failure.
+    pub fn error(&self) -> StorageError {
+        self.error.error()
+    }
+}
+
+//  `T` is not required to implement `Debug`, so that any `Result<_, ExtendError<T>>` may be unwrapped.
+impl<T> Debug for ExtendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "ExtendError{{ inserted: {}, error: {:?} }}",
+            self.inserted,
+            self.error.error()
+        )
+    }
+}
+
+impl<T> Display for ExtendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{} after {} elements", self.error, self.inserted)
+    }
+}
+
+impl<T> From<ExtendError<T>> for StorageError {
+    fn from(error: ExtendError<T>) -> Self {
+        error.error()
+    }
+}
+
+impl<T> From<ExtendError<T>> for AllocError {
+    fn from(error: ExtendError<T>) -> Self {
+        error.error().into()
+    }
+}
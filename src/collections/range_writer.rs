@@ -0,0 +1,276 @@
+//! Streaming builder of ranges, appending elements into a range handle.
+
+use core::{
+    cmp,
+    fmt::{self, Debug},
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+use crate::{
+    error::StorageError,
+    traits::{Capacity, RangeStorage},
+};
+
+use super::InsertError;
+
+/// A writer appending elements into a range of a borrowed storage, with amortized growth.
+///
+/// Unlike `RawVec`, the writer only borrows the storage, and resolves the handle once per call, rather than once per
+/// element. The pointer cannot be cached across calls, as the handles of inline ranges hold the elements, and move
+/// along with the writer. Once done, `finish` hands out the handle and the number of elements written.
+///
+/// On drop, without `finish`, the elements written are destroyed and the range is deallocated.
+pub struct RangeWriter<'a, T, S: RangeStorage> {
+    len: usize,
+    handle: S::Handle<T>,
+    storage: &'a mut S,
+}
+
+impl<'a, T, S: RangeStorage> RangeWriter<'a, T, S> {
+    /// Creates a new instance, starting from a range requiring no allocation.
+    pub fn new(storage: &'a mut S) -> Self {
+        let handle = storage.dangling();
+
+        Self {
+            len: 0,
+            handle,
+            storage,
+        }
+    }
+
+    /// Attempts to create a new instance, with capacity for at least `capacity` elements.
+    pub fn try_with_capacity(storage: &'a mut S, capacity: usize) -> Result<Self, StorageError> {
+        let capacity = S::Capacity::from_usize(capacity)
+            .ok_or_else(|| StorageError::array::<T>(capacity).with_context(CONTEXT))?;
+
+        let handle = storage.allocate(capacity)?;
+
+        Ok(Self {
+            len: 0,
+            handle,
+            storage,
+        })
+    }
+
+    /// Returns whether no element was written yet, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of elements which can be written without growing.
+    pub fn capacity(&self) -> usize {
+        self.range().len()
+    }
+
+    /// Attempts to append `value`.
+    ///
+    /// On failure, returns `value`.
+    pub fn try_push(&mut self, value: T) -> Result<(), InsertError<T>> {
+        if let Err(error) = self.try_reserve(1) {
+            return Err(InsertError::new(value, error));
+        }
+
+        let len = self.len;
+
+        //  Safety:
+        //  -   `len < self.capacity()`, as it was reserved.
+        unsafe { self.range().get_unchecked_mut(len).as_mut().write(value) };
+
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Attempts to append clones of the elements of `source`.
+    ///
+    /// On failure, nothing is appended.
+    pub fn try_extend_from_slice(&mut self, source: &[T]) -> Result<(), StorageError>
+    where
+        T: Clone,
+    {
+        self.try_reserve(source.len())?;
+
+        let range = self.range();
+
+        for value in source {
+            //  Safety:
+            //  -   `self.len < self.capacity()`, as it was reserved.
+            //  -   `range` is valid, as `self` is borrowed mutably, and thus neither moved nor grown.
+            unsafe {
+                range
+                    .get_unchecked_mut(self.len)
+                    .as_mut()
+                    .write(value.clone())
+            };
+
+            self.len += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Attempts to reserve capacity for at least `additional` more elements.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), StorageError> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or_else(|| StorageError::array::<T>(usize::MAX).with_context(CONTEXT))?;
+        let capacity = self.capacity();
+
+        if required <= capacity {
+            return Ok(());
+        }
+
+        let maximum = self.storage.maximum_capacity::<T>().into_usize();
+
+        if required > maximum {
+            return Err(StorageError::array::<T>(required).with_context(CONTEXT));
+        }
+
+        let new_capacity = cmp::min(cmp::max(required, capacity * 2), maximum);
+        let new_capacity = S::Capacity::from_usize(new_capacity)
+            .ok_or_else(|| StorageError::array::<T>(new_capacity).with_context(CONTEXT))?;
+
+        //  Safety:
+        //  -   `self.handle` is valid.
+        self.handle = unsafe { self.storage.try_grow(&self.handle, new_capacity)? };
+
+        Ok(())
+    }
+
+    /// Finishes writing, returning the handle to the range and the number of elements written.
+    ///
+    /// The first `len` elements of the range are initialized; it is up to the caller to destroy them, and deallocate
+    /// the range.
+    pub fn finish(self) -> (S::Handle<T>, usize) {
+        let len = self.len;
+
+        //  Safety:
+        //  -   `self.handle` is valid.
+        //  -   The immediate `forget` avoids double-frees.
+        let handle = unsafe { ptr::read(&self.handle) };
+        mem::forget(self);
+
+        (handle, len)
+    }
+
+    //  Internal: returns a pointer to the range, only valid as long as `self` is neither moved nor grown.
+    fn range(&self) -> NonNull<[MaybeUninit<T>]> {
+        //  Safety:
+        //  -   `self.handle` is valid.
+        unsafe { self.storage.get(&self.handle) }
+    }
+}
+
+impl<'a, T, S: RangeStorage> Debug for RangeWriter<'a, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "RangeWriter{{ len: {}, capacity: {} }}",
+            self.len,
+            self.capacity()
+        )
+    }
+}
+
+impl<'a, T, S: RangeStorage> Drop for RangeWriter<'a, T, S> {
+    fn drop(&mut self) {
+        //  Safety:
+        //  -   The range is valid, and its first `self.len` elements are initialized.
+        unsafe {
+            let written = self.range().get_unchecked_mut(..self.len).as_mut();
+            ptr::drop_in_place(MaybeUninit::slice_assume_init_mut(written));
+        }
+
+        //  Safety:
+        //  -   `self.handle` is valid.
+        unsafe { self.storage.deallocate(&self.handle) };
+    }
+}
+
+//
+//  Implementation
+//
+
+const CONTEXT: &str = "RangeWriter";
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::RawVec;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn inline() {
+        let mut storage = NonTrackingRange::<u8, u32, 2>::default();
+
+        let mut writer = RangeWriter::new(&mut storage);
+
+        writer.try_extend_from_slice(&[1, 2, 3]).unwrap();
+        writer.try_push(4).unwrap();
+
+        assert_eq!(Err(5), writer.try_push(5).map_err(InsertError::into_value));
+        assert_eq!(
+            "RangeWriter{ len: 4, capacity: 4 }",
+            format!("{:?}", writer)
+        );
+
+        let (handle, len) = writer.finish();
+
+        let vec = unsafe { RawVec::<u16, _>::from_raw_parts(handle, len, storage) };
+
+        assert_eq!([1, 2, 3, 4], *vec);
+    }
+
+    #[test]
+    fn growth() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+
+        let mut writer = RangeWriter::try_with_capacity(&mut storage, 2).unwrap();
+
+        for i in 0..10 {
+            writer.try_push(i.to_string()).unwrap();
+        }
+
+        assert_eq!(10, writer.len());
+        assert!(writer.capacity() >= 10);
+
+        let (handle, len) = writer.finish();
+
+        let vec = unsafe { RawVec::from_raw_parts(handle, len, storage) };
+
+        assert_eq!("9", vec[9]);
+
+        drop(vec);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn drop_unfinished() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = AllocStorage::new(allocator.clone());
+
+        let mut writer = RangeWriter::new(&mut storage);
+
+        writer.try_push("Hello".to_string()).unwrap();
+        writer.try_push("World".to_string()).unwrap();
+
+        drop(writer);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
@@ -0,0 +1,244 @@
+//! Proof-of-Concept implementation of a typed Arena parameterized by a Storage.
+
+use core::{
+    cmp::Ordering,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+use crate::{defaults::DefaultRangeStorage, traits::RangeStorage};
+
+use super::{InsertError, RawVec};
+
+/// A PoC Arena, handing out typed indices to its values.
+///
+/// Values are only ever appended, and dropped along with the arena, hence an index remains valid for the lifetime of
+/// the arena which issued it. This is typically used for trees and graphs, such as ASTs, whose nodes refer to one
+/// another by index rather than by reference.
+///
+/// The values are stored contiguously, in a single range.
+pub struct RawArena<T, S: RangeStorage = DefaultRangeStorage> {
+    values: RawVec<T, S>,
+}
+
+/// The typed index of a value of a `RawArena`.
+///
+/// Indices are only meaningful for the arena which issued them; using an index issued by another arena either panics,
+/// or refers to an unrelated value.
+pub struct Idx<T> {
+    index: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, S: RangeStorage> RawArena<T, S> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self {
+            values: RawVec::new(storage),
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the number of values in `self`.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Attempts to append `value`, returning its index.
+    ///
+    /// On failure, returns `value`.
+    pub fn try_alloc(&mut self, value: T) -> Result<Idx<T>, InsertError<T>> {
+        let index = self.values.len();
+
+        self.values.try_push(value)?;
+
+        Ok(Idx::new(index))
+    }
+
+    /// Appends `value`, returning its index.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate `value`.
+    pub fn alloc(&mut self, value: T) -> Idx<T> {
+        self.try_alloc(value).expect("Sufficient capacity")
+    }
+
+    /// Returns a reference to the value at `index`, if any.
+    pub fn get(&self, index: Idx<T>) -> Option<&T> {
+        self.values.get(index.index)
+    }
+
+    /// Returns a mutable reference to the value at `index`, if any.
+    pub fn get_mut(&mut self, index: Idx<T>) -> Option<&mut T> {
+        self.values.get_mut(index.index)
+    }
+
+    /// Returns an iterator over the indices and values of `self`, in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (Idx<T>, &T)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (Idx::new(index), value))
+    }
+
+    /// Returns the values of `self`, as a slice, in insertion order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.values
+    }
+}
+
+impl<T, S: RangeStorage> Index<Idx<T>> for RawArena<T, S> {
+    type Output = T;
+
+    fn index(&self, index: Idx<T>) -> &T {
+        &self.values[index.index]
+    }
+}
+
+impl<T, S: RangeStorage> IndexMut<Idx<T>> for RawArena<T, S> {
+    fn index_mut(&mut self, index: Idx<T>) -> &mut T {
+        &mut self.values[index.index]
+    }
+}
+
+impl<T: Debug, S: RangeStorage> Debug for RawArena<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", self.as_slice())
+    }
+}
+
+impl<T, S: Default + RangeStorage> Default for RawArena<T, S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<T> Idx<T> {
+    /// Returns the position of the value, in insertion order.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    fn new(index: usize) -> Self {
+        Self {
+            index,
+            _marker: PhantomData,
+        }
+    }
+}
+
+//  The traits are implemented manually, so as not to require that `T` implements them.
+
+impl<T> Clone for Idx<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Idx<T> {}
+
+impl<T> Debug for Idx<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "Idx({})", self.index)
+    }
+}
+
+impl<T> Eq for Idx<T> {}
+
+impl<T> Hash for Idx<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state)
+    }
+}
+
+impl<T> Ord for Idx<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<T> PartialEq for Idx<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> PartialOrd for Idx<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    enum Expr {
+        Literal(i64),
+        Add(Idx<Expr>, Idx<Expr>),
+        Mul(Idx<Expr>, Idx<Expr>),
+    }
+
+    fn evaluate<S: RangeStorage>(arena: &RawArena<Expr, S>, expr: Idx<Expr>) -> i64 {
+        match arena[expr] {
+            Expr::Literal(value) => value,
+            Expr::Add(left, right) => evaluate(arena, left) + evaluate(arena, right),
+            Expr::Mul(left, right) => evaluate(arena, left) * evaluate(arena, right),
+        }
+    }
+
+    #[test]
+    fn ast() {
+        let mut arena = RawArena::new(AllocStorage::new(SpyAllocator::default()));
+
+        //  (1 + 2) * 4
+        let one = arena.alloc(Expr::Literal(1));
+        let two = arena.alloc(Expr::Literal(2));
+        let sum = arena.alloc(Expr::Add(one, two));
+        let four = arena.alloc(Expr::Literal(4));
+        let product = arena.alloc(Expr::Mul(sum, four));
+
+        assert_eq!(12, evaluate(&arena, product));
+
+        arena[four] = Expr::Literal(5);
+
+        assert_eq!(15, evaluate(&arena, product));
+        assert_eq!(5, arena.len());
+    }
+
+    #[test]
+    fn indices() {
+        let mut arena = RawArena::<u8, NonTrackingRange<u8, u8, 2>>::default();
+
+        let first = arena.try_alloc(1).unwrap();
+        let second = arena.try_alloc(2).unwrap();
+
+        assert_eq!(3, arena.try_alloc(3).unwrap_err().into_value());
+
+        assert!(first < second);
+        assert_eq!(1, second.index());
+        assert_eq!("Idx(0)", format!("{:?}", first));
+        assert_eq!("[1, 2]", format!("{:?}", arena));
+
+        *arena.get_mut(first).unwrap() = 3;
+
+        let pairs: Vec<_> = arena
+            .iter()
+            .map(|(index, value)| (index.index(), *value))
+            .collect();
+
+        assert_eq!(vec![(0, 3), (1, 2)], pairs);
+    }
+} // mod tests
@@ -2,6 +2,7 @@
 
 use core::{
     alloc::Layout,
+    any::Any,
     fmt::{self, Debug},
     marker::Unsize,
     mem::{self, ManuallyDrop},
@@ -11,10 +12,10 @@ use core::{
 
 use rfc2580::{self, Pointee};
 
-use crate::traits::ElementStorage;
+use crate::{defaults::DefaultElementStorage, traits::ElementStorage};
 
 /// A PoC Box.
-pub struct RawBox<T: ?Sized + Pointee, S: ElementStorage> {
+pub struct RawBox<T: ?Sized + Pointee, S: ElementStorage = DefaultElementStorage> {
     storage: ManuallyDrop<S>,
     handle: S::Handle<T>,
 }
@@ -30,9 +31,36 @@ impl<T: Pointee, S: ElementStorage> RawBox<T, S> {
             Err(value) => Err((value, storage)),
         }
     }
+
+    /// Creates an instance of Self, containing `value` stored in `storage`.
+    ///
+    /// #   Panics
+    ///
+    /// If `storage` cannot accomodate `value`.
+    pub fn new_in(value: T, storage: S) -> Self {
+        Self::new(value, storage).unwrap_or_else(|_| panic!("Sufficient capacity"))
+    }
 }
 
 impl<T: ?Sized + Pointee, S: ElementStorage> RawBox<T, S> {
+    /// Creates an instance of Self, containing `value` stored in `storage` as a `T`, typically a trait object.
+    ///
+    /// The metadata of `T` is captured by `coerce`, as in `|value| value as &dyn Trait`, rather than by unsizing, see
+    /// `ElementStorage::create_dyn`.
+    pub fn new_dyn<V: Pointee>(
+        value: V,
+        mut storage: S,
+        coerce: fn(&V) -> &T,
+    ) -> Result<Self, (V, S)> {
+        match storage.create_dyn(value, coerce) {
+            Ok(handle) => Ok(RawBox {
+                storage: ManuallyDrop::new(storage),
+                handle,
+            }),
+            Err(value) => Err((value, storage)),
+        }
+    }
+
     /// Coerces to another Box.
     ///
     /// A poor's man CoerceUnsized implementation, for now.
@@ -55,6 +83,43 @@ impl<T: ?Sized + Pointee, S: ElementStorage> RawBox<T, S> {
         }
     }
 
+    /// Decomposes `self` into its handle and storage.
+    ///
+    /// The value is neither destroyed nor deallocated, it is up to the caller to do so.
+    pub fn into_raw_parts(mut self) -> (S::Handle<T>, S) {
+        //  Safety:
+        //  -   `self.handle` and `self.storage` are valid.
+        //  -   The immediate `forget` avoids double-frees.
+        let handle = unsafe { ptr::read(&self.handle as *const _) };
+        let storage = unsafe { ManuallyDrop::take(&mut self.storage) };
+        mem::forget(self);
+
+        (handle, storage)
+    }
+
+    /// Destroys the value, and returns the storage for reuse.
+    pub fn into_storage(self) -> S {
+        let (handle, mut storage) = self.into_raw_parts();
+
+        //  Safety:
+        //  -   `handle` is valid, and points to a valid value, as per constructor's invariants.
+        unsafe { storage.destroy(&handle) };
+
+        storage
+    }
+
+    /// Creates an instance from its handle and storage.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, was issued by `storage`, and points to a valid value.
+    pub unsafe fn from_raw_parts(handle: S::Handle<T>, storage: S) -> Self {
+        RawBox {
+            storage: ManuallyDrop::new(storage),
+            handle,
+        }
+    }
+
     /// Switch to another storage, if possible.
     pub fn try_in<NS: ElementStorage>(
         this: Self,
@@ -133,6 +198,24 @@ impl<T: ?Sized + Pointee, S: ElementStorage> DerefMut for RawBox<T, S> {
     }
 }
 
+impl<S: ElementStorage> RawBox<dyn Any, S> {
+    /// Attempts to downcast the Box to a concrete type.
+    pub fn downcast<T: Any + Pointee>(self) -> Result<RawBox<T, S>, Self> {
+        //  Safety:
+        //  -   `self.handle` is valid, and issued by `self.storage`.
+        let handle = match unsafe { self.storage.downcast::<T>(&self.handle) } {
+            Some(handle) => handle,
+            None => return Err(self),
+        };
+
+        let (_, storage) = self.into_raw_parts();
+
+        //  Safety:
+        //  -   `handle` points to the valid value of `self`, of type `T`.
+        Ok(unsafe { RawBox::from_raw_parts(handle, storage) })
+    }
+}
+
 impl<T: ?Sized + Pointee, S: ElementStorage> Drop for RawBox<T, S> {
     fn drop(&mut self) {
         //  Safety:
@@ -190,6 +273,25 @@ mod test_inline {
 
         assert_eq!("RawBox{ [1, 2, 3] }", format!("{:?}", boxed));
     }
+
+    #[test]
+    fn trait_storage_without_unsize() {
+        let storage = NonTrackingElement::<[u8; 4]>::new();
+        let boxed = RawBox::<dyn Debug, _>::new_dyn([1u8, 2, 3], storage, |value| value).unwrap();
+
+        assert_eq!("RawBox{ [1, 2, 3] }", format!("{:?}", boxed));
+    }
+
+    #[test]
+    fn downcast() {
+        let storage = NonTrackingElement::<u32>::new();
+        let boxed: RawBox<dyn Any, _> = RawBox::new(42u32, storage).unwrap().coerce();
+
+        let boxed = boxed.downcast::<u16>().unwrap_err();
+        let boxed = boxed.downcast::<u32>().unwrap();
+
+        assert_eq!(42, *boxed);
+    }
 } // mod test_inline
 
 #[cfg(test)]
@@ -308,6 +410,25 @@ mod test_small {
         let storage = SingleElement::<[u8; 2], _>::new(NonAllocator);
         RawBox::new([1u8, 2, 3], storage).unwrap_err();
     }
+
+    #[test]
+    fn into_storage() {
+        let allocator = SpyAllocator::default();
+
+        let storage = SingleElement::<u8, _>::new(allocator.clone());
+        let boxed = RawBox::new(1u32, storage).unwrap();
+
+        assert_eq!(1, allocator.allocated());
+
+        let storage = boxed.into_storage();
+
+        assert_eq!(1, allocator.deallocated());
+
+        let boxed = RawBox::new(2u32, storage).unwrap();
+
+        assert_eq!(2u32, *boxed);
+        assert_eq!(2, allocator.allocated());
+    }
 } // mod test_small
 
 #[cfg(test)]
@@ -441,3 +562,93 @@ mod test_allocator {
         assert_eq!(1, allocator.deallocated());
     }
 } // mod test_allocator
+
+#[cfg(test)]
+mod test_composite {
+
+    use crate::allocator::{AllocStorage, AllocatorBuilder};
+    use crate::alternative::{self, DefaultBuilder};
+    use crate::fallback::FallbackElement;
+    use crate::inline::{NonTrackingElement, TrackingElement};
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    type Fallback<A> = FallbackElement<TrackingElement<u32, 1>, AllocStorage<A>>;
+
+    type Alternative<A> = alternative::SingleElement<
+        NonTrackingElement<u32>,
+        AllocStorage<A>,
+        DefaultBuilder,
+        AllocatorBuilder<A>,
+    >;
+
+    #[test]
+    fn fallback_inline() {
+        let storage: Fallback<_> =
+            FallbackElement::new(TrackingElement::default(), AllocStorage::new(NonAllocator));
+        let mut boxed = RawBox::new_in(1u32, storage);
+
+        *boxed += 1;
+
+        assert_eq!(2u32, *boxed);
+    }
+
+    #[test]
+    fn fallback_allocated() {
+        let allocator = SpyAllocator::default();
+
+        let storage: Fallback<_> = FallbackElement::new(
+            TrackingElement::default(),
+            AllocStorage::new(allocator.clone()),
+        );
+        let boxed: RawBox<[u32], _> = RawBox::new_in([1u32, 2, 3], storage).coerce();
+
+        assert_eq!([1u32, 2, 3], *boxed);
+        assert_eq!(1, allocator.allocated());
+
+        drop(boxed);
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn alternative_inline() {
+        let storage: Alternative<_> = alternative::SingleElement::first(
+            NonTrackingElement::default(),
+            AllocatorBuilder(NonAllocator),
+        );
+        let boxed: RawBox<dyn Debug, _> = RawBox::new_in(1u32, storage).coerce();
+
+        assert_eq!("RawBox{ 1 }", format!("{:?}", boxed));
+    }
+
+    #[test]
+    fn alternative_allocated() {
+        let allocator = SpyAllocator::default();
+
+        let storage: Alternative<_> = alternative::SingleElement::first(
+            NonTrackingElement::default(),
+            AllocatorBuilder(allocator.clone()),
+        );
+        let mut boxed = RawBox::new_in([1u32, 2, 3], storage);
+
+        boxed[2] = 4;
+
+        assert_eq!([1u32, 2, 4], *boxed);
+        assert_eq!(1, allocator.allocated());
+
+        drop(boxed);
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    #[should_panic]
+    fn new_in_failure() {
+        let storage: Fallback<_> =
+            FallbackElement::new(TrackingElement::default(), AllocStorage::new(NonAllocator));
+
+        RawBox::new_in(1u64, storage);
+    }
+} // mod test_composite
@@ -0,0 +1,1058 @@
+//! Proof-of-Concept implementation of a BTreeMap parameterized by a Storage.
+
+use core::{
+    cmp::Ordering,
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::{self, MaybeUninit},
+    ops::{Bound, RangeBounds},
+    ptr,
+};
+
+use crate::{defaults::DefaultElementStorage, error::StorageError, traits::ElementStorage};
+
+use super::InsertError;
+
+/// A PoC BTreeMap.
+///
+/// Each node holds up to `2 * B - 1` entries, and the links to its up to `2 * B` children, each child being referred
+/// to by a handle of the storage. Nodes are split on insertion, and merged on removal, as they fill up and empty.
+///
+/// The nodes may be sized with `RawBTreeMapNodeStorage`, and the handles of the storage must be `Copy`.
+pub struct RawBTreeMap<K, V, S: ElementStorage = DefaultElementStorage> {
+    root: Option<NodeHandle<K, V, S>>,
+    len: usize,
+    storage: S,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<K, V, S: ElementStorage> RawBTreeMap<K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self {
+            root: None,
+            len: 0,
+            storage,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of entries in `self`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Clears `self`, destroying all entries.
+    pub fn clear(&mut self) {
+        if let Some(root) = self.root.take() {
+            destroy(&mut self.storage, root);
+        }
+
+        self.len = 0;
+    }
+}
+
+impl<K: Ord, V, S: ElementStorage> RawBTreeMap<K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    /// Returns a reference to the value associated to `key`, if any.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let (node, index) = self.find(key)?;
+
+        //  Safety:
+        //  -   `index` is within the entries of `node`.
+        Some(unsafe { (*node).values[index].assume_init_ref() })
+    }
+
+    /// Returns a mutable reference to the value associated to `key`, if any.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (node, index) = self.find(key)?;
+
+        //  Safety:
+        //  -   `index` is within the entries of `node`.
+        Some(unsafe { (*node).values[index].assume_init_mut() })
+    }
+
+    /// Returns whether `self` contains `key`, or not.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Returns an iterator over the entries, in increasing order of keys, or decreasing order once reversed.
+    ///
+    /// Each step descends from the root, rather than maintaining a stack of nodes, and is thus `O(log N)`.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator + '_ {
+        Iter {
+            range: Range::new(self, Bound::Unbounded, Bound::Unbounded),
+            remaining: self.len,
+        }
+    }
+
+    /// Returns an iterator over the entries whose keys are within `range`, in increasing order of keys, or decreasing
+    /// order once reversed.
+    ///
+    /// Each step descends from the root, as per `iter`.
+    pub fn range<R: RangeBounds<K>>(
+        &self,
+        range: R,
+    ) -> impl DoubleEndedIterator<Item = (&K, &V)> + '_ {
+        Range::new(self, range.start_bound(), range.end_bound())
+    }
+
+    /// Attempts to insert `value` associated to `key`, returning the previous value associated to `key`, if any.
+    ///
+    /// On failure, the entries of `self` are left unchanged -- though its nodes may have been split -- and `key` and
+    /// `value` are returned.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, InsertError<(K, V)>> {
+        if let Some(current) = self.get_mut(&key) {
+            return Ok(Some(mem::replace(current, value)));
+        }
+
+        let mut handle = match self.root {
+            Some(root) => root,
+            None => match self.create() {
+                Ok(root) => {
+                    self.root = Some(root);
+                    root
+                }
+                Err(error) => return Err(InsertError::new((key, value), error)),
+            },
+        };
+
+        //  Safety:
+        //  -   `handle` is valid.
+        if unsafe { (*self.node(&handle)).len } == CAPACITY {
+            handle = match self.grow_root(handle) {
+                Ok(root) => root,
+                Err(error) => return Err(InsertError::new((key, value), error)),
+            };
+        }
+
+        //  Invariant: the node of `handle` is not full, hence splitting its children always succeeds in finding room.
+        loop {
+            //  Safety:
+            //  -   `handle` is valid.
+            let node = unsafe { &mut *self.node(&handle) };
+
+            let mut index = match node.search(&key) {
+                Ok(_) => unreachable!("Keys are unique"),
+                Err(index) => index,
+            };
+
+            let child = match node.children[index] {
+                Some(child) => child,
+                None => {
+                    node.insert_entry(index, key, value);
+                    self.len += 1;
+
+                    return Ok(None);
+                }
+            };
+
+            //  Safety:
+            //  -   `child` is valid.
+            if unsafe { (*self.node(&child)).len } == CAPACITY {
+                if let Err(error) = self.split_child(&handle, index) {
+                    return Err(InsertError::new((key, value), error));
+                }
+
+                //  Safety:
+                //  -   `handle` is valid, and its node may only be accessed through pointers re-acquired after the
+                //      split, as the storage may have relocated it.
+                let node = unsafe { &*self.node(&handle) };
+
+                //  Safety:
+                //  -   `index` is within the entries of `node`, as the median of the child was moved there.
+                if unsafe { node.keys[index].assume_init_ref() } < &key {
+                    index += 1;
+                }
+
+                handle = node.children[index].expect("Internal node");
+            } else {
+                handle = child;
+            }
+        }
+    }
+
+    /// Inserts `value` associated to `key`, returning the previous value associated to `key`, if any.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new entry.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.try_insert(key, value).expect("Sufficient capacity")
+    }
+
+    /// Removes `key`, returning the value associated to it, if any.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let root = self.root?;
+
+        //  Safety:
+        //  -   `root` is valid.
+        let result = unsafe { self.remove_from(root, Target::Key(key)) };
+
+        //  Safety:
+        //  -   `root` is valid.
+        let node = unsafe { &mut *self.node(&root) };
+
+        //  An emptied root is replaced by its only child, if any, and the tree shrinks by one level.
+        if node.len == 0 {
+            self.root = node.children[0].take();
+
+            //  Safety:
+            //  -   `root` is valid, has no entry, and is no longer reachable.
+            unsafe { self.storage.deallocate(&root) };
+        }
+
+        let (_, value) = result?;
+        self.len -= 1;
+
+        Some(value)
+    }
+}
+
+impl<K: Debug + Ord, V: Debug, S: ElementStorage> Debug for RawBTreeMap<K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S: Default + ElementStorage> Default for RawBTreeMap<K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<K, V, S: ElementStorage> Drop for RawBTreeMap<K, V, S> {
+    fn drop(&mut self) {
+        if let Some(root) = self.root.take() {
+            destroy(&mut self.storage, root);
+        }
+    }
+}
+
+/// A node of a `RawBTreeMap`, exposed as its handles appear in the bounds of the map.
+pub struct RawBTreeMapNode<K, V, S: ElementStorage> {
+    len: usize,
+    keys: [MaybeUninit<K>; CAPACITY],
+    values: [MaybeUninit<V>; CAPACITY],
+    children: [Option<S::Handle<Self>>; CAPACITY + 1],
+}
+
+/// A PoC BTreeMap storage helper.
+///
+/// Reserves enough space for storing a map node containing `K` and `V`, for a handle of size similar to `H`.
+pub struct RawBTreeMapNodeStorage<K, V, H>(
+    [MaybeUninit<K>; CAPACITY],
+    [MaybeUninit<V>; CAPACITY],
+    (usize, [Option<H>; CAPACITY + 1]),
+);
+
+//
+//  Implementation
+//
+
+//  The minimum degree of the tree: each node but the root has at least `B - 1` entries, and at most `2 * B - 1`.
+const B: usize = 6;
+
+const CAPACITY: usize = 2 * B - 1;
+
+type NodeHandle<K, V, S> = <S as ElementStorage>::Handle<RawBTreeMapNode<K, V, S>>;
+
+//  The entry to remove from a sub-tree.
+#[derive(Clone, Copy)]
+enum Target<'a, K> {
+    Key(&'a K),
+    First,
+    Last,
+}
+
+//  An entry of the map.
+type Entry<'a, K, V> = (&'a K, &'a V);
+
+//  The remaining entries of a range, from `front` to `back`, both included.
+struct Range<'a, K, V, S: ElementStorage> {
+    map: &'a RawBTreeMap<K, V, S>,
+    front: Option<Entry<'a, K, V>>,
+    back: Option<Entry<'a, K, V>>,
+}
+
+struct Iter<'a, K, V, S: ElementStorage> {
+    range: Range<'a, K, V, S>,
+    remaining: usize,
+}
+
+//  Internal: destroys the sub-tree rooted at `handle`.
+//
+//  This does not require `NodeHandle<K, V, S>: Copy`, as `Drop` cannot, hence the handles are moved out of the nodes.
+fn destroy<K, V, S: ElementStorage>(storage: &mut S, handle: NodeHandle<K, V, S>) {
+    //  Safety:
+    //  -   `handle` is valid, as all handles reachable from the root.
+    let node = unsafe { &mut *storage.get(&handle).as_ptr() };
+
+    for index in 0..=node.len {
+        if let Some(child) = node.children[index].take() {
+            destroy(storage, child);
+        }
+    }
+
+    for index in 0..node.len {
+        //  Safety:
+        //  -   `index` is within the entries of `node`.
+        unsafe {
+            node.keys[index].assume_init_drop();
+            node.values[index].assume_init_drop();
+        }
+    }
+
+    //  Safety:
+    //  -   `handle` is valid, and its entries were dropped above.
+    unsafe { storage.deallocate(&handle) };
+}
+
+impl<K, V, S: ElementStorage> RawBTreeMapNode<K, V, S> {
+    fn is_leaf(&self) -> bool {
+        self.children[0].is_none()
+    }
+
+    fn key(&self, index: usize) -> &K {
+        debug_assert!(index < self.len);
+
+        //  Safety:
+        //  -   `index` is within the entries.
+        unsafe { self.keys[index].assume_init_ref() }
+    }
+
+    //  Internal: returns `Ok` with the index of `key`, or `Err` with the index of the child whose sub-tree would
+    //  contain it.
+    fn search(&self, key: &K) -> Result<usize, usize>
+    where
+        K: Ord,
+    {
+        for index in 0..self.len {
+            match key.cmp(self.key(index)) {
+                Ordering::Less => return Err(index),
+                Ordering::Equal => return Ok(index),
+                Ordering::Greater => (),
+            }
+        }
+
+        Err(self.len)
+    }
+
+    fn insert_entry(&mut self, index: usize, key: K, value: V) {
+        debug_assert!(index <= self.len && self.len < CAPACITY);
+
+        self.keys[index..=self.len].rotate_right(1);
+        self.values[index..=self.len].rotate_right(1);
+
+        self.keys[index].write(key);
+        self.values[index].write(value);
+
+        self.len += 1;
+    }
+
+    fn remove_entry(&mut self, index: usize) -> (K, V) {
+        debug_assert!(index < self.len);
+
+        //  Safety:
+        //  -   `index` is within the entries, and is overwritten, or excluded, below.
+        let entry = unsafe {
+            (
+                self.keys[index].assume_init_read(),
+                self.values[index].assume_init_read(),
+            )
+        };
+
+        self.keys[index..self.len].rotate_left(1);
+        self.values[index..self.len].rotate_left(1);
+
+        self.len -= 1;
+
+        entry
+    }
+
+    fn insert_child(&mut self, index: usize, child: Option<S::Handle<Self>>) {
+        debug_assert!(self.children[CAPACITY].is_none());
+
+        self.children[index..].rotate_right(1);
+        self.children[index] = child;
+    }
+
+    fn remove_child(&mut self, index: usize) -> Option<S::Handle<Self>> {
+        let child = self.children[index].take();
+        self.children[index..].rotate_left(1);
+
+        child
+    }
+}
+
+impl<K, V, S: ElementStorage> RawBTreeMap<K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    //  Internal: returns a pointer to the node of `handle`.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn node(&self, handle: &NodeHandle<K, V, S>) -> *mut RawBTreeMapNode<K, V, S> {
+        self.storage.get(handle).as_ptr()
+    }
+
+    //  Internal: creates a new, empty, node.
+    fn create(&mut self) -> Result<NodeHandle<K, V, S>, StorageError> {
+        let node = RawBTreeMapNode {
+            len: 0,
+            keys: MaybeUninit::uninit_array(),
+            values: MaybeUninit::uninit_array(),
+            children: [None; CAPACITY + 1],
+        };
+
+        self.storage.try_create(node).map_err(|(_, error)| error)
+    }
+
+    //  Internal: returns the node, and index, of `key`, if any.
+    fn find(&self, key: &K) -> Option<(*mut RawBTreeMapNode<K, V, S>, usize)>
+    where
+        K: Ord,
+    {
+        let mut handle = self.root?;
+
+        loop {
+            //  Safety:
+            //  -   `handle` is valid.
+            let node = unsafe { self.node(&handle) };
+
+            //  Safety:
+            //  -   `node` is valid.
+            match unsafe { (*node).search(key) } {
+                Ok(index) => return Some((node, index)),
+                Err(index) => handle = unsafe { (*node).children[index]? },
+            }
+        }
+    }
+
+    //  Internal: returns the entry with the smallest key within `bound`, taken as a lower bound, if any.
+    fn successor(&self, bound: Bound<&K>) -> Option<(&K, &V)>
+    where
+        K: Ord,
+    {
+        //  The entry found in the deepest node is the smallest.
+        self.descend(
+            |node| {
+                (0..node.len)
+                    .find(|&index| match bound {
+                        Bound::Included(bound) => node.key(index) >= bound,
+                        Bound::Excluded(bound) => node.key(index) > bound,
+                        Bound::Unbounded => true,
+                    })
+                    .unwrap_or(node.len)
+            },
+            |index, node| if index < node.len { Some(index) } else { None },
+        )
+    }
+
+    //  Internal: returns the entry with the largest key within `bound`, taken as an upper bound, if any.
+    fn predecessor(&self, bound: Bound<&K>) -> Option<(&K, &V)>
+    where
+        K: Ord,
+    {
+        //  The entry found in the deepest node is the largest.
+        self.descend(
+            |node| {
+                (0..node.len)
+                    .find(|&index| match bound {
+                        Bound::Included(bound) => node.key(index) > bound,
+                        Bound::Excluded(bound) => node.key(index) >= bound,
+                        Bound::Unbounded => false,
+                    })
+                    .unwrap_or(node.len)
+            },
+            |index, _| index.checked_sub(1),
+        )
+    }
+
+    //  Internal: descends from the root, into the child at the index selected by `child` in each node, and returns the
+    //  entry selected by `entry` in the deepest node in which it selects one.
+    fn descend<C, E>(&self, child: C, entry: E) -> Option<(&K, &V)>
+    where
+        C: Fn(&RawBTreeMapNode<K, V, S>) -> usize,
+        E: Fn(usize, &RawBTreeMapNode<K, V, S>) -> Option<usize>,
+    {
+        let mut result = None;
+        let mut handle = self.root;
+
+        while let Some(current) = handle {
+            //  Safety:
+            //  -   `current` is valid, and `self` is borrowed for the lifetime of the result.
+            let node = unsafe { &*self.node(&current) };
+
+            let index = child(node);
+
+            if let Some(index) = entry(index, node) {
+                //  Safety:
+                //  -   `index` is within the entries of `node`.
+                result = Some((node.key(index), unsafe {
+                    node.values[index].assume_init_ref()
+                }));
+            }
+
+            handle = node.children[index];
+        }
+
+        result
+    }
+
+    //  Internal: pushes a new root above the full `root`, and splits the latter, returning the new root.
+    //
+    //  On failure, the tree is left unchanged.
+    fn grow_root(
+        &mut self,
+        root: NodeHandle<K, V, S>,
+    ) -> Result<NodeHandle<K, V, S>, StorageError> {
+        let handle = self.create()?;
+
+        //  Safety:
+        //  -   `handle` is valid.
+        unsafe { (*self.node(&handle)).children[0] = Some(root) };
+
+        if let Err(error) = self.split_child(&handle, 0) {
+            //  Safety:
+            //  -   `handle` is valid, has no entry, and is not reachable.
+            unsafe { self.storage.deallocate(&handle) };
+            return Err(error);
+        }
+
+        self.root = Some(handle);
+
+        Ok(handle)
+    }
+
+    //  Internal: splits the full child at `index` of the node of `handle`, which is not full, in two around its median
+    //  entry, which is moved into the node of `handle`.
+    //
+    //  On failure, the tree is left unchanged.
+    fn split_child(
+        &mut self,
+        handle: &NodeHandle<K, V, S>,
+        index: usize,
+    ) -> Result<(), StorageError> {
+        let right = self.create()?;
+
+        //  Safety:
+        //  -   `handle`, its child, and `right` are valid, and distinct.
+        //  -   The pointers are acquired after the creation of `right`, in case the storage relocated the nodes.
+        let (parent, left, right_node) = unsafe {
+            let parent = &mut *self.node(handle);
+            let left = parent.children[index].expect("Internal node");
+
+            (parent, &mut *self.node(&left), &mut *self.node(&right))
+        };
+
+        debug_assert!(left.len == CAPACITY && parent.len < CAPACITY);
+
+        //  Safety:
+        //  -   The upper `B - 1` entries of `left` are moved to `right_node`, and excluded from `left` below.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                left.keys.as_ptr().add(B),
+                right_node.keys.as_mut_ptr(),
+                B - 1,
+            );
+            ptr::copy_nonoverlapping(
+                left.values.as_ptr().add(B),
+                right_node.values.as_mut_ptr(),
+                B - 1,
+            );
+        }
+
+        for (to, from) in right_node.children[..B]
+            .iter_mut()
+            .zip(&mut left.children[B..])
+        {
+            *to = from.take();
+        }
+
+        right_node.len = B - 1;
+        left.len = B;
+
+        let (key, value) = left.remove_entry(B - 1);
+
+        parent.insert_entry(index, key, value);
+        parent.insert_child(index + 1, Some(right));
+
+        Ok(())
+    }
+
+    //  Internal: merges the child at `index + 1` of `parent`, and the entry at `index`, into the child at `index`.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that both children have `B - 1` entries.
+    unsafe fn merge_children(&mut self, parent: &mut RawBTreeMapNode<K, V, S>, index: usize) {
+        let (left, right) = (
+            parent.children[index].expect("Internal node"),
+            parent.remove_child(index + 1).expect("Internal node"),
+        );
+
+        let (key, value) = parent.remove_entry(index);
+
+        let (left, right_node) = (&mut *self.node(&left), &mut *self.node(&right));
+
+        debug_assert!(left.len == B - 1 && right_node.len == B - 1);
+
+        left.insert_entry(B - 1, key, value);
+
+        ptr::copy_nonoverlapping(
+            right_node.keys.as_ptr(),
+            left.keys.as_mut_ptr().add(B),
+            B - 1,
+        );
+        ptr::copy_nonoverlapping(
+            right_node.values.as_ptr(),
+            left.values.as_mut_ptr().add(B),
+            B - 1,
+        );
+
+        for (to, from) in left.children[B..]
+            .iter_mut()
+            .zip(&mut right_node.children[..B])
+        {
+            *to = from.take();
+        }
+
+        left.len = CAPACITY;
+
+        //  Safety:
+        //  -   The entries and children of `right` were moved to `left`.
+        self.storage.deallocate(&right);
+    }
+
+    //  Internal: ensures that the child at `index` of `parent` has at least `B` entries, borrowing from a sibling, or
+    //  merging with it, returning the index of the child which now covers the former child.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `parent` is an internal node, with at least `B` entries unless it is the root.
+    unsafe fn fill_child(&mut self, parent: &mut RawBTreeMapNode<K, V, S>, index: usize) -> usize {
+        let child = &mut *self.node(&parent.children[index].expect("Internal node"));
+
+        if child.len >= B {
+            return index;
+        }
+
+        if index > 0 {
+            let left = &mut *self.node(&parent.children[index - 1].expect("Internal node"));
+
+            if left.len >= B {
+                let (key, value) = left.remove_entry(left.len - 1);
+                let grandchild = left.children[left.len + 1].take();
+
+                let (key, value) = (
+                    mem::replace(parent.keys[index - 1].assume_init_mut(), key),
+                    mem::replace(parent.values[index - 1].assume_init_mut(), value),
+                );
+
+                child.insert_entry(0, key, value);
+                child.insert_child(0, grandchild);
+
+                return index;
+            }
+        }
+
+        if index < parent.len {
+            let right = &mut *self.node(&parent.children[index + 1].expect("Internal node"));
+
+            if right.len >= B {
+                let (key, value) = right.remove_entry(0);
+                let grandchild = right.remove_child(0);
+
+                let (key, value) = (
+                    mem::replace(parent.keys[index].assume_init_mut(), key),
+                    mem::replace(parent.values[index].assume_init_mut(), value),
+                );
+
+                child.insert_entry(child.len, key, value);
+                child.children[child.len] = grandchild;
+
+                return index;
+            }
+
+            self.merge_children(parent, index);
+
+            return index;
+        }
+
+        self.merge_children(parent, index - 1);
+
+        index - 1
+    }
+
+    //  Internal: removes `target` from the sub-tree rooted at `handle`, if present.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid, and that its node has at least `B` entries unless it is the root.
+    unsafe fn remove_from(
+        &mut self,
+        handle: NodeHandle<K, V, S>,
+        target: Target<'_, K>,
+    ) -> Option<(K, V)>
+    where
+        K: Ord,
+    {
+        let node = &mut *self.node(&handle);
+
+        let found = match target {
+            Target::Key(key) => node.search(key),
+            Target::First if node.is_leaf() => Ok(0),
+            Target::First => Err(0),
+            Target::Last if node.is_leaf() => Ok(node.len - 1),
+            Target::Last => Err(node.len),
+        };
+
+        match (found, node.is_leaf()) {
+            (Ok(index), true) => Some(node.remove_entry(index)),
+            (Err(_), true) => None,
+            (Ok(index), false) => {
+                let (left, right) = (
+                    node.children[index].expect("Internal node"),
+                    node.children[index + 1].expect("Internal node"),
+                );
+
+                //  The entry is replaced by its predecessor, or successor, if either child can spare an entry, or
+                //  else both children are merged around it, and it is removed from the merged child.
+                let replacement = if (*self.node(&left)).len >= B {
+                    self.remove_from(left, Target::Last)
+                } else if (*self.node(&right)).len >= B {
+                    self.remove_from(right, Target::First)
+                } else {
+                    self.merge_children(node, index);
+
+                    return self.remove_from(left, target);
+                };
+
+                let (key, value) = replacement.expect("Non-empty child");
+
+                Some((
+                    mem::replace(node.keys[index].assume_init_mut(), key),
+                    mem::replace(node.values[index].assume_init_mut(), value),
+                ))
+            }
+            (Err(index), false) => {
+                let index = self.fill_child(node, index);
+                let child = node.children[index].expect("Internal node");
+
+                self.remove_from(child, target)
+            }
+        }
+    }
+}
+
+impl<'a, K: Ord, V, S: ElementStorage> Range<'a, K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    fn new(map: &'a RawBTreeMap<K, V, S>, start: Bound<&K>, end: Bound<&K>) -> Self {
+        let (front, back) = (map.successor(start), map.predecessor(end));
+
+        let empty = match (front, back) {
+            (Some((front, _)), Some((back, _))) => front > back,
+            _ => true,
+        };
+
+        if empty {
+            Self {
+                map,
+                front: None,
+                back: None,
+            }
+        } else {
+            Self { map, front, back }
+        }
+    }
+
+    //  Internal: removes `entry`, either the front or the back, from the range, which ends once they meet.
+    fn advance(&mut self, entry: Entry<'a, K, V>, forward: bool) -> Entry<'a, K, V> {
+        let (key, _) = entry;
+
+        if self.front.map(|(front, _)| front) == self.back.map(|(back, _)| back) {
+            self.front = None;
+            self.back = None;
+        } else if forward {
+            self.front = self.map.successor(Bound::Excluded(key));
+        } else {
+            self.back = self.map.predecessor(Bound::Excluded(key));
+        }
+
+        entry
+    }
+}
+
+impl<'a, K: Ord, V, S: ElementStorage> Iterator for Range<'a, K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let front = self.front?;
+
+        Some(self.advance(front, true))
+    }
+}
+
+impl<'a, K: Ord, V, S: ElementStorage> DoubleEndedIterator for Range<'a, K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let back = self.back?;
+
+        Some(self.advance(back, false))
+    }
+}
+
+impl<'a, K: Ord, V, S: ElementStorage> Iterator for Iter<'a, K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.range.next()?;
+        self.remaining -= 1;
+
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, K: Ord, V, S: ElementStorage> DoubleEndedIterator for Iter<'a, K, V, S>
+where
+    NodeHandle<K, V, S>: Copy,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.range.next_back()?;
+        self.remaining -= 1;
+
+        Some(result)
+    }
+}
+
+impl<'a, K: Ord, V, S: ElementStorage> ExactSizeIterator for Iter<'a, K, V, S> where
+    NodeHandle<K, V, S>: Copy
+{
+}
+
+#[cfg(test)]
+mod test_inline {
+
+    use crate::inline::TrackingElement;
+
+    use super::*;
+
+    type NodeStorage = RawBTreeMapNodeStorage<u16, u16, usize>;
+    type Map<const N: usize> = RawBTreeMap<u16, u16, TrackingElement<NodeStorage, N>>;
+
+    #[test]
+    fn smoke_test() {
+        let mut map = Map::<4>::default();
+
+        assert_eq!(None, map.insert(2, 20));
+        assert_eq!(None, map.insert(1, 10));
+        assert_eq!(Some(20), map.insert(2, 21));
+
+        assert_eq!(2, map.len());
+        assert_eq!(Some(&10), map.get(&1));
+        assert!(!map.contains_key(&3));
+
+        *map.get_mut(&1).unwrap() = 11;
+
+        assert_eq!("{1: 11, 2: 21}", format!("{:?}", map));
+
+        assert_eq!(Some(11), map.remove(&1));
+        assert_eq!(None, map.remove(&1));
+        assert_eq!(Some(21), map.remove(&2));
+
+        assert!(map.is_empty());
+        assert!(map.root.is_none());
+    }
+
+    #[test]
+    fn try_insert_full() {
+        let mut map = Map::<1>::default();
+
+        for key in 0..(CAPACITY as u16) {
+            map.insert(key, key);
+        }
+
+        //  Splitting the root requires 2 more nodes.
+        let error = map.try_insert(100, 100).unwrap_err();
+
+        assert_eq!((100, 100), error.into_value());
+        assert_eq!(CAPACITY, map.len());
+        assert_eq!(CAPACITY, map.iter().count());
+
+        //  Existing keys need no room.
+        assert_eq!(Some(0), map.try_insert(0, 1).unwrap());
+    }
+} // mod test_inline
+
+#[cfg(test)]
+mod test_allocator {
+
+    use crate::allocator::AllocStorage;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type Map = RawBTreeMap<u32, String, AllocStorage<SpyAllocator>>;
+
+    //  Verifies the invariants of the sub-tree rooted at `handle`, returning its depth and number of entries.
+    fn check(
+        map: &Map,
+        handle: NodeHandle<u32, String, AllocStorage<SpyAllocator>>,
+        is_root: bool,
+    ) -> (usize, usize) {
+        let node = unsafe { &*map.node(&handle) };
+
+        assert!(node.len <= CAPACITY);
+        assert!(is_root || node.len >= B - 1);
+
+        for index in 1..node.len {
+            assert!(node.key(index - 1) < node.key(index));
+        }
+
+        if node.is_leaf() {
+            assert!(node.children.iter().all(Option::is_none));
+            return (1, node.len);
+        }
+
+        let mut result = (0, node.len);
+
+        for child in &node.children[..=node.len] {
+            let (depth, len) = check(map, child.expect("Internal node"), false);
+
+            assert!(result.0 == 0 || result.0 == depth + 1);
+
+            result = (depth + 1, result.1 + len);
+        }
+
+        assert!(node.children[node.len + 1..].iter().all(Option::is_none));
+
+        result
+    }
+
+    fn check_map(map: &Map) {
+        let len = map.root.map(|root| check(map, root, true).1).unwrap_or(0);
+
+        assert_eq!(map.len(), len);
+    }
+
+    #[test]
+    fn split_and_merge() {
+        let allocator = SpyAllocator::default();
+        let mut map = Map::new(AllocStorage::new(allocator.clone()));
+
+        //  A permutation of 0..1000, so as to split nodes in all positions.
+        let keys: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+
+        for &key in &keys {
+            assert_eq!(None, map.insert(key, key.to_string()));
+        }
+
+        check_map(&map);
+
+        assert_eq!(1000, map.len());
+        assert!(map.iter().map(|(key, _)| *key).eq(0..1000));
+        assert!(map.iter().all(|(key, value)| key.to_string() == *value));
+
+        for &key in keys.iter().filter(|key| *key % 3 != 0) {
+            assert_eq!(Some(key.to_string()), map.remove(&key));
+            assert_eq!(None, map.get(&key));
+        }
+
+        check_map(&map);
+
+        assert_eq!(334, map.len());
+        assert!(map.iter().map(|(key, _)| *key).eq((0..1000).step_by(3)));
+
+        for key in (0..1000).step_by(3) {
+            assert_eq!(Some(key.to_string()), map.remove(&key));
+        }
+
+        assert!(map.is_empty());
+        assert!(map.root.is_none());
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn range() {
+        let mut map = Map::new(AllocStorage::new(SpyAllocator::default()));
+
+        for key in (0..200).step_by(2) {
+            map.insert(key, key.to_string());
+        }
+
+        let keys = |range: &mut dyn Iterator<Item = (&u32, &String)>| {
+            range.map(|(key, _)| *key).collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![10, 12, 14], keys(&mut map.range(9..15)));
+        assert_eq!(vec![10, 12, 14, 16], keys(&mut map.range(10..=16)));
+        assert_eq!(vec![194, 196, 198], keys(&mut map.range(193..)));
+        assert_eq!(vec![4, 2, 0], keys(&mut map.range(..5).rev()));
+        assert_eq!(Vec::<u32>::new(), keys(&mut map.range(11..12)));
+        assert_eq!(Vec::<u32>::new(), keys(&mut map.range(300..)));
+
+        let mut iter = map.iter();
+
+        assert_eq!(Some(&0), iter.next().map(|(key, _)| key));
+        assert_eq!(Some(&198), iter.next_back().map(|(key, _)| key));
+        assert_eq!(98, iter.len());
+        assert!(iter.map(|(key, _)| *key).eq((2..198).step_by(2)));
+    }
+
+    #[test]
+    fn drop_entries() {
+        let allocator = SpyAllocator::default();
+        let mut map = Map::new(AllocStorage::new(allocator.clone()));
+
+        for key in 0..100 {
+            map.insert(key, key.to_string());
+        }
+
+        map.clear();
+
+        assert!(map.is_empty());
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+
+        for key in 0..100 {
+            map.insert(key, key.to_string());
+        }
+
+        drop(map);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod test_allocator
@@ -0,0 +1,182 @@
+//! Proof-of-Concept implementation of a BTreeSet parameterized by a Storage.
+
+use core::{
+    fmt::{self, Debug},
+    ops::RangeBounds,
+};
+
+use crate::{defaults::DefaultElementStorage, traits::ElementStorage};
+
+use super::{InsertError, RawBTreeMap, RawBTreeMapNode};
+
+/// A PoC BTreeSet, a `RawBTreeMap` of `T` to `()`.
+///
+/// The nodes may be sized with `RawBTreeMapNodeStorage<T, (), H>`.
+pub struct RawBTreeSet<T, S: ElementStorage = DefaultElementStorage> {
+    map: RawBTreeMap<T, (), S>,
+}
+
+impl<T, S: ElementStorage> RawBTreeSet<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self {
+            map: RawBTreeMap::new(storage),
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the number of elements in `self`.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Clears `self`, destroying all elements.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl<T: Ord, S: ElementStorage> RawBTreeSet<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    /// Returns an iterator over the elements, in increasing order, or decreasing order once reversed.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        self.map.iter().map(|(element, _)| element)
+    }
+
+    /// Returns an iterator over the elements within `range`, in increasing order, or decreasing order once reversed.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.map.range(range).map(|(element, _)| element)
+    }
+
+    /// Returns whether `self` contains `value`, or not.
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Attempts to insert `value`, returning whether it was newly inserted.
+    ///
+    /// An element equal to `value` is left in place, and `value` is dropped.
+    ///
+    /// On failure, the elements of `self` are left unchanged, and `value` is returned.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, InsertError<T>> {
+        if self.map.contains_key(&value) {
+            return Ok(false);
+        }
+
+        match self.map.try_insert(value, ()) {
+            Ok(_) => Ok(true),
+            Err(error) => {
+                let cause = error.error();
+                Err(InsertError::new(error.into_value().0, cause))
+            }
+        }
+    }
+
+    /// Inserts `value`, returning whether it was newly inserted.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.try_insert(value).expect("Sufficient capacity")
+    }
+
+    /// Removes `value`, returning whether it was present.
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.map.remove(value).is_some()
+    }
+}
+
+impl<T: Debug + Ord, S: ElementStorage> Debug for RawBTreeSet<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S: Default + ElementStorage> Default for RawBTreeSet<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+//
+//  Implementation
+//
+
+type NodeHandle<T, S> = <S as ElementStorage>::Handle<RawBTreeMapNode<T, (), S>>;
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::RawBTreeMapNodeStorage;
+    use crate::inline::TrackingElement;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn inline() {
+        type Set = RawBTreeSet<u16, TrackingElement<RawBTreeMapNodeStorage<u16, (), usize>, 1>>;
+
+        let mut set = Set::default();
+
+        assert!(set.insert(3));
+        assert!(!set.insert(3));
+        assert!(set.insert(1));
+        assert!(set.insert(2));
+
+        assert!(set.contains(&2));
+        assert!(set.remove(&2));
+        assert!(!set.remove(&2));
+        assert!(!set.contains(&2));
+
+        assert_eq!("{1, 3}", format!("{:?}", set));
+    }
+
+    #[test]
+    fn allocator() {
+        let allocator = SpyAllocator::default();
+        let mut set = RawBTreeSet::<String, _>::new(AllocStorage::new(allocator.clone()));
+
+        for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+            set.insert(word.to_string());
+        }
+
+        assert_eq!(8, set.len());
+
+        assert!(set
+            .iter()
+            .eq(["brown", "dog", "fox", "jumps", "lazy", "over", "quick", "the"].iter()));
+
+        let (from, to) = ("f".to_string(), "p".to_string());
+
+        assert!(set
+            .range(from..to)
+            .rev()
+            .eq(["over", "lazy", "jumps", "fox"].iter()));
+
+        set.clear();
+
+        assert!(set.is_empty());
+
+        drop(set);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
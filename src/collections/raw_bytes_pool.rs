@@ -0,0 +1,475 @@
+//! Proof-of-Concept implementation of a pool of byte buffers, handing out reference-counted slices.
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    fmt::{self, Debug},
+    mem,
+    ops::{Deref, DerefMut, Range},
+    slice,
+};
+
+use crate::{
+    error::StorageError,
+    traits::{Capacity, RangeStorage},
+};
+
+/// A PoC pool of byte blocks, of `B` bytes each, from which cheaply cloneable slices are carved.
+///
+/// A block is checked out as a `StorageBytesMut`, filled -- for example by receiving a packet -- then frozen into a
+/// `StorageBytes`, which may be cloned, and sliced, without copying. The reference count of each block lives in the
+/// block itself, within the storage, hence neither checking out, nor cloning, allocates.
+///
+/// Checking out and cloning only require shared access to the pool, whilst growing the pool requires exclusive access.
+/// This guarantees that no block is relocated by the storage whilst referenced.
+pub struct RawBytesPool<S: RangeStorage, const B: usize> {
+    free: Cell<usize>,
+    available: Cell<usize>,
+    data: S::Handle<RawBytesBlock<B>>,
+    storage: S,
+}
+
+impl<S: RangeStorage, const B: usize> RawBytesPool<S, B> {
+    /// Creates a new instance, whose blocks are those available without allocation.
+    ///
+    /// This is meant for inline storages; storages requiring allocation should use `try_reserve` afterwards.
+    pub fn new(storage: S) -> Self {
+        let data = storage.dangling();
+
+        let result = Self {
+            free: Cell::new(NIL),
+            available: Cell::new(0),
+            data,
+            storage,
+        };
+
+        result.initialize(0);
+
+        result
+    }
+
+    /// Returns the size of each block.
+    pub fn block_size(&self) -> usize {
+        B
+    }
+
+    /// Returns the number of blocks currently available for checkout.
+    pub fn available(&self) -> usize {
+        self.available.get()
+    }
+
+    /// Returns the number of blocks owned by the pool, whether available or referenced.
+    pub fn capacity(&self) -> usize {
+        //  Safety:
+        //  -   `self.data` is valid.
+        unsafe { self.storage.get(&self.data) }.len()
+    }
+
+    /// Attempts to grow the pool by at least `additional` blocks.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), StorageError> {
+        let capacity = self.capacity();
+        let required = capacity
+            .checked_add(additional)
+            .and_then(S::Capacity::from_usize)
+            .ok_or_else(|| {
+                StorageError::array::<RawBytesBlock<B>>(capacity.saturating_add(additional))
+                    .with_context("RawBytesPool")
+            })?;
+
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   `&mut self` guarantees that no block is referenced, hence the blocks may be relocated.
+        self.data = unsafe { self.storage.try_grow(&self.data, required)? };
+
+        self.initialize(capacity);
+
+        Ok(())
+    }
+
+    /// Checks out a block, if any is available.
+    ///
+    /// The content of the block is left as is, from its previous use, if any.
+    pub fn checkout(&self) -> Option<StorageBytesMut<'_, S, B>> {
+        let index = self.free.get();
+
+        if index == NIL {
+            return None;
+        }
+
+        let block = self.block(index);
+
+        self.free.set(block.next.get());
+        self.available.set(self.available.get() - 1);
+
+        block.refs.set(1);
+
+        Some(StorageBytesMut { pool: self, index })
+    }
+
+    /// Checks out a block, and copies `bytes` into it, if any is available.
+    ///
+    /// #   Panics
+    ///
+    /// If `bytes` is larger than a block.
+    pub fn copy_from_slice(&self, bytes: &[u8]) -> Option<StorageBytes<'_, S, B>> {
+        assert!(
+            bytes.len() <= B,
+            "{} bytes exceed block size {}",
+            bytes.len(),
+            B
+        );
+
+        let mut block = self.checkout()?;
+        block[..bytes.len()].copy_from_slice(bytes);
+
+        Some(block.freeze(bytes.len()))
+    }
+}
+
+impl<S: RangeStorage, const B: usize> Debug for RawBytesPool<S, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "RawBytesPool{{ available: {}, capacity: {} }}",
+            self.available(),
+            self.capacity()
+        )
+    }
+}
+
+impl<S: Default + RangeStorage, const B: usize> Default for RawBytesPool<S, B> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: RangeStorage, const B: usize> Drop for RawBytesPool<S, B> {
+    fn drop(&mut self) {
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   The blocks have no drop glue.
+        unsafe { self.storage.deallocate(&self.data) };
+    }
+}
+
+/// An exclusively owned, mutable, block checked out of a `RawBytesPool`.
+///
+/// Dereferences to the whole block, until frozen into a `StorageBytes` of the bytes actually filled.
+pub struct StorageBytesMut<'a, S: RangeStorage, const B: usize> {
+    pool: &'a RawBytesPool<S, B>,
+    index: usize,
+}
+
+impl<'a, S: RangeStorage, const B: usize> StorageBytesMut<'a, S, B> {
+    /// Freezes the first `len` bytes of the block into a shareable slice.
+    ///
+    /// #   Panics
+    ///
+    /// If `len` exceeds the size of the block.
+    pub fn freeze(self, len: usize) -> StorageBytes<'a, S, B> {
+        assert!(len <= B, "{} bytes exceed block size {}", len, B);
+
+        let (pool, index) = (self.pool, self.index);
+
+        //  The reference is transferred to the slice.
+        mem::forget(self);
+
+        StorageBytes {
+            pool,
+            index,
+            range: 0..len,
+        }
+    }
+}
+
+impl<'a, S: RangeStorage, const B: usize> Deref for StorageBytesMut<'a, S, B> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        //  Safety:
+        //  -   The block is exclusively owned by `self`.
+        unsafe { slice::from_raw_parts(self.pool.bytes(self.index), B) }
+    }
+}
+
+impl<'a, S: RangeStorage, const B: usize> DerefMut for StorageBytesMut<'a, S, B> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        //  Safety:
+        //  -   The block is exclusively owned by `self`.
+        unsafe { slice::from_raw_parts_mut(self.pool.bytes(self.index), B) }
+    }
+}
+
+impl<'a, S: RangeStorage, const B: usize> Drop for StorageBytesMut<'a, S, B> {
+    fn drop(&mut self) {
+        self.pool.release(self.index);
+    }
+}
+
+/// A cheaply cloneable, immutable, slice of a block of a `RawBytesPool`.
+///
+/// The block returns to the pool once the last slice referencing it is dropped.
+pub struct StorageBytes<'a, S: RangeStorage, const B: usize> {
+    pool: &'a RawBytesPool<S, B>,
+    index: usize,
+    range: Range<usize>,
+}
+
+impl<'a, S: RangeStorage, const B: usize> StorageBytes<'a, S, B> {
+    /// Returns a sub-slice of `self`, sharing its block.
+    ///
+    /// #   Panics
+    ///
+    /// If `range` is out of the bounds of `self`.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "{:?} out of bounds {}",
+            range,
+            self.len()
+        );
+
+        let mut result = self.clone();
+        result.range = (self.range.start + range.start)..(self.range.start + range.end);
+
+        result
+    }
+
+    /// Splits `self` in two at `at`, returning the bytes before `at` and leaving those after in `self`.
+    ///
+    /// #   Panics
+    ///
+    /// If `at` is out of the bounds of `self`.
+    pub fn split_to(&mut self, at: usize) -> Self {
+        let head = self.slice(0..at);
+        self.range.start += at;
+
+        head
+    }
+
+    /// Returns the number of slices referencing the block of `self`, including `self`.
+    pub fn ref_count(&self) -> usize {
+        self.pool.block(self.index).refs.get()
+    }
+}
+
+impl<'a, S: RangeStorage, const B: usize> Clone for StorageBytes<'a, S, B> {
+    fn clone(&self) -> Self {
+        let refs = &self.pool.block(self.index).refs;
+        refs.set(refs.get().checked_add(1).expect("Reference count overflow"));
+
+        Self {
+            pool: self.pool,
+            index: self.index,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl<'a, S: RangeStorage, const B: usize> Debug for StorageBytes<'a, S, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", &**self)
+    }
+}
+
+impl<'a, S: RangeStorage, const B: usize> Deref for StorageBytes<'a, S, B> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        //  Safety:
+        //  -   `self.range` is within the block, which is immutable whilst shared.
+        unsafe {
+            let bytes = self.pool.bytes(self.index).add(self.range.start);
+            slice::from_raw_parts(bytes, self.range.len())
+        }
+    }
+}
+
+impl<'a, S: RangeStorage, const B: usize> Drop for StorageBytes<'a, S, B> {
+    fn drop(&mut self) {
+        self.pool.release(self.index);
+    }
+}
+
+/// A block of a `RawBytesPool`, exposed so as to size the storage.
+pub struct RawBytesBlock<const B: usize> {
+    refs: Cell<usize>,
+    next: Cell<usize>,
+    bytes: UnsafeCell<[u8; B]>,
+}
+
+//
+//  Implementation
+//
+
+const NIL: usize = usize::MAX;
+
+impl<S: RangeStorage, const B: usize> RawBytesPool<S, B> {
+    fn block(&self, index: usize) -> &RawBytesBlock<B> {
+        //  Safety:
+        //  -   `self.data` is valid.
+        let blocks = unsafe { self.storage.get(&self.data) };
+
+        debug_assert!(index < blocks.len());
+
+        //  Safety:
+        //  -   `index` is within bounds.
+        //  -   All blocks are initialized, by invariant.
+        unsafe { &*(blocks.as_mut_ptr().add(index) as *const RawBytesBlock<B>) }
+    }
+
+    fn bytes(&self, index: usize) -> *mut u8 {
+        self.block(index).bytes.get() as *mut u8
+    }
+
+    //  Initializes the blocks from `initialized` onwards, and pushes them onto the free list.
+    fn initialize(&self, initialized: usize) {
+        //  Safety:
+        //  -   `self.data` is valid.
+        let blocks = unsafe { self.storage.get(&self.data) };
+
+        for index in (initialized..blocks.len()).rev() {
+            let block = RawBytesBlock {
+                refs: Cell::new(0),
+                next: Cell::new(self.free.get()),
+                bytes: UnsafeCell::new([0; B]),
+            };
+
+            //  Safety:
+            //  -   `index` is within bounds, and the block is uninitialized.
+            unsafe { (blocks.as_mut_ptr().add(index) as *mut RawBytesBlock<B>).write(block) };
+
+            self.free.set(index);
+        }
+
+        self.available
+            .set(self.available.get() + blocks.len() - initialized);
+    }
+
+    fn release(&self, index: usize) {
+        let block = self.block(index);
+        let refs = block.refs.get() - 1;
+
+        block.refs.set(refs);
+
+        if refs == 0 {
+            block.next.set(self.free.get());
+
+            self.free.set(index);
+            self.available.set(self.available.get() + 1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type Inline = NonTrackingRange<u8, RawBytesBlock<16>, 2>;
+
+    #[test]
+    fn inline() {
+        let pool = RawBytesPool::<Inline, 16>::default();
+
+        assert_eq!(2, pool.available());
+        assert_eq!(16, pool.block_size());
+
+        let mut block = pool.checkout().unwrap();
+        block[..5].copy_from_slice(b"Hello");
+
+        let hello = block.freeze(5);
+        let other = pool.copy_from_slice(b"World").unwrap();
+
+        assert!(pool.checkout().is_none());
+        assert_eq!(b"Hello", &*hello);
+        assert_eq!(b"World", &*other);
+
+        drop(other);
+
+        assert_eq!(1, pool.available());
+        assert_eq!(
+            "RawBytesPool{ available: 1, capacity: 2 }",
+            format!("{:?}", pool)
+        );
+    }
+
+    #[test]
+    fn shared_slices() {
+        let pool = RawBytesPool::<Inline, 16>::default();
+
+        //  A packet, with its 2 bytes header.
+        let mut payload = pool.copy_from_slice(b"\x00\x05Hello").unwrap();
+        let header = payload.split_to(2);
+
+        assert_eq!(b"\x00\x05", &*header);
+        assert_eq!(b"Hello", &*payload);
+        assert_eq!(2, payload.ref_count());
+
+        let hell = payload.slice(0..4);
+        let copy = hell.clone();
+
+        assert_eq!(b"Hell", &*copy);
+        assert_eq!(4, hell.ref_count());
+
+        drop(header);
+        drop(payload);
+        drop(hell);
+
+        //  The block is still referenced by `copy`.
+        assert_eq!(1, pool.available());
+        assert_eq!(1, copy.ref_count());
+
+        drop(copy);
+
+        assert_eq!(2, pool.available());
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_out_of_bounds() {
+        let pool = RawBytesPool::<Inline, 16>::default();
+
+        let bytes = pool.copy_from_slice(b"Hello").unwrap();
+
+        bytes.slice(3..6);
+    }
+
+    #[test]
+    fn allocator() {
+        let allocator = SpyAllocator::default();
+
+        let mut pool = RawBytesPool::<_, 64>::new(AllocStorage::new(allocator.clone()));
+
+        assert!(pool.checkout().is_none());
+
+        pool.try_reserve(3).unwrap();
+
+        assert_eq!(3, pool.available());
+
+        let packets: Vec<_> = (0..3u8)
+            .map(|i| pool.copy_from_slice(&[i; 10]).unwrap())
+            .collect();
+
+        let allocated = allocator.allocated();
+
+        //  Cloning, slicing, and recycling never allocate.
+        for _ in 0..10 {
+            let clones: Vec<_> = packets.iter().map(|packet| packet.slice(1..3)).collect();
+
+            assert_eq!([2u8; 2], *clones[2]);
+        }
+
+        drop(packets);
+
+        assert_eq!(3, pool.available());
+        assert_eq!(allocated, allocator.allocated());
+
+        drop(pool);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
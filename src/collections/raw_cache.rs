@@ -0,0 +1,425 @@
+//! Proof-of-Concept implementation of a segmented-LRU Cache parameterized by a Storage.
+
+use core::{
+    fmt::{self, Debug},
+    iter,
+};
+
+use crate::{defaults::DefaultRangeStorage, error::StorageError, traits::RangeStorage};
+
+use super::RawVec;
+
+/// A PoC Cache, memoizing up to a fixed number of entries, and evicting according to a segmented-LRU policy.
+///
+/// The entries are split in 2 segments:
+///
+/// -   The probation segment, in which new entries are inserted.
+/// -   The protected segment, to which entries are promoted upon being accessed again, and which holds at most 4/5th of
+///     the capacity, demoting its least recently used entry back into probation as necessary.
+///
+/// Once the pool of slots is full, inserting evicts the least recently used entry in probation, if any, and otherwise
+/// the least recently used entry in protection. Hence a scan of one-off keys only ever churns the probation segment,
+/// leaving the frequently accessed entries in place.
+///
+/// Exhaustion of the pool is thus handled by policy, rather than by error: the capacity is fixed at construction, and
+/// never grows thereafter.
+///
+/// Look-ups are linear in the number of entries, as befits a small memoization table.
+pub struct RawCache<K, V, S: RangeStorage = DefaultRangeStorage> {
+    entries: RawVec<CacheEntry<K, V>, S>,
+    capacity: usize,
+    probation: Segment,
+    protected: Segment,
+    evictions: u64,
+}
+
+impl<K: Eq, V, S: RangeStorage> RawCache<K, V, S> {
+    /// Creates a new, empty, instance, whose capacity is that available without allocation.
+    ///
+    /// This is meant for inline storages; storages requiring allocation should use `with_capacity` instead.
+    pub fn new(storage: S) -> Self {
+        let entries = RawVec::new(storage);
+        let capacity = entries.capacity();
+
+        Self::from_entries(entries, capacity)
+    }
+
+    /// Attempts to create a new, empty, instance able to hold `capacity` entries.
+    pub fn with_capacity(storage: S, capacity: usize) -> Result<Self, StorageError> {
+        let mut entries = RawVec::new(storage);
+        entries.try_reserve(capacity)?;
+
+        Ok(Self::from_entries(entries, capacity))
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of entries in `self`.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns the number of entries `self` holds before evicting.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of entries evicted since the creation of `self`.
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+
+    /// Returns a reference to the value associated to `key`, if any, without affecting its recency.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let index = self.find(key)?;
+
+        Some(&self.entries[index].value)
+    }
+
+    /// Returns a reference to the value associated to `key`, if any, promoting it to the protected segment.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let index = self.find(key)?;
+
+        self.promote(index);
+
+        Some(&self.entries[index].value)
+    }
+
+    /// Returns a mutable reference to the value associated to `key`, inserting the result of `f` if none.
+    ///
+    /// An existing entry is promoted to the protected segment, whilst a new entry is inserted in probation, evicting
+    /// the least recently used entry if `self` is full.
+    ///
+    /// #   Panics
+    ///
+    /// If the capacity of `self` is 0.
+    pub fn get_or_insert_with<F: FnOnce() -> V>(&mut self, key: K, f: F) -> &mut V {
+        let index = match self.find(&key) {
+            Some(index) => {
+                self.promote(index);
+                index
+            }
+            None => self.insert_new(key, f()),
+        };
+
+        &mut self.entries[index].value
+    }
+
+    /// Removes the entry associated to `key`, if any, and returns its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.find(key)?;
+        let last = self.entries.len() - 1;
+
+        self.unlink(index);
+
+        //  The last entry is about to be moved into `index`, hence its neighbours are patched beforehand.
+        if index != last {
+            let (prev, next, protected) = self.links(last);
+
+            match prev {
+                NIL => self.segment_mut(protected).head = index,
+                _ => self.entries[prev].next = index,
+            }
+
+            match next {
+                NIL => self.segment_mut(protected).tail = index,
+                _ => self.entries[next].prev = index,
+            }
+        }
+
+        Some(self.entries.swap_remove(index).value)
+    }
+
+    /// Clears `self`, destroying all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.probation = Segment::EMPTY;
+        self.protected = Segment::EMPTY;
+    }
+}
+
+impl<K: Debug, V: Debug, S: RangeStorage> Debug for RawCache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let entries = &self.entries;
+        let walk = |head: usize| {
+            iter::successors(entries.get(head), move |entry: &&CacheEntry<K, V>| {
+                entries.get(entry.next)
+            })
+            .map(|entry| (&entry.key, &entry.value))
+        };
+
+        write!(f, "RawCache{{ protected: ")?;
+        f.debug_map().entries(walk(self.protected.head)).finish()?;
+        write!(f, ", probation: ")?;
+        f.debug_map().entries(walk(self.probation.head)).finish()?;
+        write!(f, " }}")
+    }
+}
+
+impl<K: Eq, V, S: Default + RangeStorage> Default for RawCache<K, V, S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+//
+//  Implementation
+//
+
+const NIL: usize = usize::MAX;
+
+struct CacheEntry<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+    protected: bool,
+}
+
+//  A doubly-linked list of entries, from the most recently used at `head` to the least recently used at `tail`.
+#[derive(Clone, Copy)]
+struct Segment {
+    head: usize,
+    tail: usize,
+    len: usize,
+}
+
+impl Segment {
+    const EMPTY: Segment = Segment {
+        head: NIL,
+        tail: NIL,
+        len: 0,
+    };
+}
+
+impl<K: Eq, V, S: RangeStorage> RawCache<K, V, S> {
+    fn from_entries(entries: RawVec<CacheEntry<K, V>, S>, capacity: usize) -> Self {
+        Self {
+            entries,
+            capacity,
+            probation: Segment::EMPTY,
+            protected: Segment::EMPTY,
+            evictions: 0,
+        }
+    }
+
+    fn find(&self, key: &K) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.key == *key)
+    }
+
+    fn protected_capacity(&self) -> usize {
+        self.capacity - self.capacity / 5
+    }
+
+    fn segment_mut(&mut self, protected: bool) -> &mut Segment {
+        if protected {
+            &mut self.protected
+        } else {
+            &mut self.probation
+        }
+    }
+
+    fn links(&self, index: usize) -> (usize, usize, bool) {
+        let entry = &self.entries[index];
+
+        (entry.prev, entry.next, entry.protected)
+    }
+
+    //  Inserts a new entry in probation, evicting if necessary, and returns its index.
+    fn insert_new(&mut self, key: K, value: V) -> usize {
+        assert!(self.capacity > 0, "Sufficient capacity");
+
+        let entry = CacheEntry {
+            key,
+            value,
+            prev: NIL,
+            next: NIL,
+            protected: false,
+        };
+
+        let index = if self.entries.len() < self.capacity {
+            //  The capacity was reserved at construction, hence this never allocates.
+            self.entries.push(entry);
+            self.entries.len() - 1
+        } else {
+            let victim = match self.probation.tail {
+                NIL => self.protected.tail,
+                tail => tail,
+            };
+
+            self.unlink(victim);
+            self.entries[victim] = entry;
+            self.evictions += 1;
+
+            victim
+        };
+
+        self.push_front(index, false);
+
+        index
+    }
+
+    //  Moves the entry at `index` to the front of the protected segment, demoting the least recently used protected
+    //  entry if the segment overflows.
+    fn promote(&mut self, index: usize) {
+        self.unlink(index);
+        self.push_front(index, true);
+
+        if self.protected.len > self.protected_capacity() {
+            let demoted = self.protected.tail;
+
+            self.unlink(demoted);
+            self.push_front(demoted, false);
+        }
+    }
+
+    fn unlink(&mut self, index: usize) {
+        let (prev, next, protected) = self.links(index);
+
+        match prev {
+            NIL => self.segment_mut(protected).head = next,
+            _ => self.entries[prev].next = next,
+        }
+
+        match next {
+            NIL => self.segment_mut(protected).tail = prev,
+            _ => self.entries[next].prev = prev,
+        }
+
+        self.segment_mut(protected).len -= 1;
+    }
+
+    fn push_front(&mut self, index: usize, protected: bool) {
+        let head = self.segment_mut(protected).head;
+
+        let entry = &mut self.entries[index];
+        entry.prev = NIL;
+        entry.next = head;
+        entry.protected = protected;
+
+        match head {
+            NIL => self.segment_mut(protected).tail = index,
+            _ => self.entries[head].prev = index,
+        }
+
+        let segment = self.segment_mut(protected);
+        segment.head = index;
+        segment.len += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn memoize() {
+        let allocator = SpyAllocator::default();
+        let storage = AllocStorage::new(allocator.clone());
+
+        let mut cache = RawCache::<u32, String, _>::with_capacity(storage, 2).unwrap();
+        let mut computed = 0;
+
+        for key in [1, 2, 1, 2, 1] {
+            cache.get_or_insert_with(key, || {
+                computed += 1;
+                key.to_string()
+            });
+        }
+
+        assert_eq!(2, computed);
+        assert_eq!(Some(&"1".to_string()), cache.peek(&1));
+        assert_eq!(0, cache.evictions());
+
+        drop(cache);
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn scan_resistance() {
+        let mut cache =
+            RawCache::<u32, u32, _>::with_capacity(AllocStorage::new(SpyAllocator::default()), 5)
+                .unwrap();
+
+        //  Hot keys, accessed twice, are protected.
+        for key in [1, 2, 1, 2] {
+            cache.get_or_insert_with(key, || key * 10);
+        }
+
+        //  A scan of one-off keys only churns probation.
+        for key in 100..110 {
+            cache.get_or_insert_with(key, || key * 10);
+        }
+
+        assert_eq!(5, cache.len());
+        assert_eq!(7, cache.evictions());
+        assert_eq!(Some(&10), cache.get(&1));
+        assert_eq!(Some(&20), cache.get(&2));
+        assert_eq!(None, cache.peek(&106));
+        assert_eq!(
+            "RawCache{ protected: {2: 20, 1: 10}, probation: {109: 1090, 108: 1080, 107: 1070} }",
+            format!("{:?}", cache)
+        );
+    }
+
+    #[test]
+    fn demotion() {
+        let mut cache = RawCache::<u8, u8, NonTrackingRange<u8, [usize; 3], 5>>::default();
+
+        assert_eq!(5, cache.capacity());
+
+        //  The protected segment holds at most 4 entries, hence promoting a 5th demotes the least recently used.
+        for key in [1, 2, 3, 4, 5, 1, 2, 3, 4, 5] {
+            cache.get_or_insert_with(key, || key);
+        }
+
+        assert_eq!(
+            "RawCache{ protected: {5: 5, 4: 4, 3: 3, 2: 2}, probation: {1: 1} }",
+            format!("{:?}", cache)
+        );
+
+        cache.get_or_insert_with(6, || 6);
+
+        assert_eq!(None, cache.peek(&1));
+        assert_eq!(1, cache.evictions());
+    }
+
+    #[test]
+    fn remove() {
+        let mut cache = RawCache::<u8, u8, NonTrackingRange<u8, [usize; 3], 4>>::default();
+
+        for key in [1, 2, 3, 4, 1] {
+            cache.get_or_insert_with(key, || key);
+        }
+
+        assert_eq!(Some(2), cache.remove(&2));
+        assert_eq!(None, cache.remove(&2));
+
+        //  The last entry, 4, was moved into the slot of 2, and must remain reachable.
+        assert_eq!(
+            "RawCache{ protected: {1: 1}, probation: {4: 4, 3: 3} }",
+            format!("{:?}", cache)
+        );
+
+        cache.get_or_insert_with(5, || 5);
+        cache.get_or_insert_with(6, || 6);
+
+        assert_eq!(4, cache.len());
+        assert_eq!(None, cache.peek(&3));
+        assert_eq!(Some(&4), cache.peek(&4));
+
+        cache.clear();
+
+        assert!(cache.is_empty());
+    }
+} // mod tests
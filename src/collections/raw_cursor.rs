@@ -0,0 +1,217 @@
+//! A read cursor over the bytes of a `RawVec`.
+
+use core::{
+    cmp,
+    fmt::{self, Debug},
+};
+
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::traits::RangeStorage;
+
+use super::RawVec;
+
+/// A read cursor over the bytes of a `RawVec`, tracking the position of the next byte to read.
+///
+/// This allows parsers to operate directly over buffers owned by a storage, inline or otherwise. The reading API is
+/// available in `no_std` environments, and `io::Read` and `io::BufRead` are implemented with the `std` feature.
+pub struct RawCursor<S: RangeStorage> {
+    position: usize,
+    buffer: RawVec<u8, S>,
+}
+
+impl<S: RangeStorage> RawCursor<S> {
+    /// Creates an instance, positioned at the first byte of `buffer`.
+    pub fn new(buffer: RawVec<u8, S>) -> Self {
+        Self {
+            position: 0,
+            buffer,
+        }
+    }
+
+    /// Returns the position of the next byte to read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Sets the position of the next byte to read.
+    ///
+    /// A position past the end of the buffer is clamped to the end.
+    pub fn set_position(&mut self, position: usize) {
+        self.position = cmp::min(position, self.buffer.len());
+    }
+
+    /// Returns the bytes yet to be read.
+    pub fn remaining(&self) -> &[u8] {
+        //  `self.position <= self.buffer.len()` by invariant.
+        &self.buffer[self.position..]
+    }
+
+    /// Returns whether all bytes have been read.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining().is_empty()
+    }
+
+    /// Marks the next `n` bytes as read.
+    ///
+    /// Consuming more bytes than remain consumes all remaining bytes.
+    pub fn consume(&mut self, n: usize) {
+        self.set_position(self.position.saturating_add(n));
+    }
+
+    /// Reads as many bytes as possible into `buffer`, returning the number of bytes read.
+    pub fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let remaining = self.remaining();
+        let read = cmp::min(remaining.len(), buffer.len());
+
+        buffer[..read].copy_from_slice(&remaining[..read]);
+
+        self.position += read;
+
+        read
+    }
+
+    /// Reads exactly `buffer.len()` bytes into `buffer`.
+    ///
+    /// On failure, nothing is read, and the number of remaining bytes is returned.
+    pub fn read_exact(&mut self, buffer: &mut [u8]) -> Result<(), usize> {
+        let remaining = self.remaining().len();
+
+        if remaining < buffer.len() {
+            return Err(remaining);
+        }
+
+        self.read(buffer);
+
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying buffer.
+    pub fn get_ref(&self) -> &RawVec<u8, S> {
+        &self.buffer
+    }
+
+    /// Returns the underlying buffer.
+    pub fn into_inner(self) -> RawVec<u8, S> {
+        self.buffer
+    }
+}
+
+impl<S: RangeStorage> Debug for RawCursor<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "RawCursor{{ position: {}, len: {} }}",
+            self.position,
+            self.buffer.len()
+        )
+    }
+}
+
+impl<S: RangeStorage> From<RawVec<u8, S>> for RawCursor<S> {
+    fn from(buffer: RawVec<u8, S>) -> Self {
+        Self::new(buffer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: RangeStorage> io::Read for RawCursor<S> {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        Ok(RawCursor::read(self, buffer))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: RangeStorage> io::BufRead for RawCursor<S> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.remaining())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        RawCursor::consume(self, amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    fn inline_cursor(bytes: &[u8]) -> RawCursor<NonTrackingRange<u8, u8, 16>> {
+        let mut buffer = RawVec::default();
+
+        for byte in bytes {
+            buffer.push(*byte);
+        }
+
+        RawCursor::new(buffer)
+    }
+
+    #[test]
+    fn read_inline() {
+        let mut cursor = inline_cursor(b"Hello, World!");
+        let mut buffer = [0u8; 5];
+
+        assert_eq!(5, cursor.read(&mut buffer));
+        assert_eq!(b"Hello", &buffer);
+
+        cursor.consume(2);
+
+        assert_eq!(7, cursor.position());
+        assert_eq!(b"World!", cursor.remaining());
+
+        assert_eq!(5, cursor.read(&mut buffer));
+        assert_eq!(1, cursor.read(&mut buffer));
+        assert_eq!(0, cursor.read(&mut buffer));
+        assert!(cursor.is_exhausted());
+    }
+
+    #[test]
+    fn read_exact_allocator() {
+        let allocator = SpyAllocator::default();
+
+        let mut buffer = RawVec::new(AllocStorage::new(allocator.clone()));
+        buffer.push(1u8);
+        buffer.push(2u8);
+        buffer.push(3u8);
+
+        let mut cursor = RawCursor::from(buffer);
+        let mut header = [0u8; 2];
+
+        cursor.read_exact(&mut header).unwrap();
+        assert_eq!([1, 2], header);
+
+        assert_eq!(Err(1), cursor.read_exact(&mut header));
+        assert_eq!(2, cursor.position());
+
+        cursor.set_position(42);
+        assert_eq!(3, cursor.position());
+
+        drop(cursor);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_traits() {
+        use std::io::{BufRead, Read};
+
+        let mut cursor = inline_cursor(b"GET /\r\nHost: a\r\n");
+
+        let mut line = String::new();
+        cursor.read_line(&mut line).unwrap();
+
+        assert_eq!("GET /\r\n", line);
+
+        let mut rest = Vec::new();
+        cursor.read_to_end(&mut rest).unwrap();
+
+        assert_eq!(b"Host: a\r\n", &*rest);
+    }
+} // mod tests
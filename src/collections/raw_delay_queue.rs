@@ -0,0 +1,304 @@
+//! Proof-of-Concept implementation of a DelayQueue parameterized by Storages.
+
+use core::fmt::{self, Debug};
+
+use crate::traits::{ElementStorage, RangeStorage};
+
+use super::{InsertError, RawVec};
+
+/// A PoC DelayQueue, yielding values once their deadline has expired.
+///
+/// The entries live in an element storage, typically a pool, whilst the binary heap ordering them by deadline only
+/// holds their handles, in a range storage. Entries with equal deadlines expire in insertion order.
+///
+/// The deadline is any ordered type: an `Instant`, a tick count, etc...
+pub struct RawDelayQueue<D: Ord + Copy, T, E: ElementStorage, R: RangeStorage> {
+    next_id: u64,
+    entries: E,
+    heap: RawVec<E::Handle<DelayQueueEntry<D, T>>, R>,
+}
+
+/// The key of an entry of a `RawDelayQueue`, used to remove it prior to its expiration.
+///
+/// Keys are never reused by a given queue, hence a stale key is harmless.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DelayQueueKey(u64);
+
+/// An entry of a `RawDelayQueue`, exposed so as to size the element storage.
+pub struct DelayQueueEntry<D, T> {
+    deadline: D,
+    id: u64,
+    value: T,
+}
+
+impl<D: Ord + Copy, T, E: ElementStorage, R: RangeStorage> RawDelayQueue<D, T, E, R> {
+    /// Creates a new instance, with its entries stored in `entries` and its heap stored in `heap`.
+    pub fn new(entries: E, heap: R) -> Self {
+        Self {
+            next_id: 0,
+            entries,
+            heap: RawVec::new(heap),
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns the number of entries in `self`.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Returns the earliest deadline, if any.
+    pub fn peek_deadline(&self) -> Option<D> {
+        self.heap.first().map(|handle| self.entry(handle).deadline)
+    }
+
+    /// Attempts to insert `value`, to expire at `deadline`.
+    ///
+    /// On failure, returns `value`.
+    pub fn try_insert(&mut self, deadline: D, value: T) -> Result<DelayQueueKey, InsertError<T>> {
+        if let Err(error) = self.heap.try_reserve(1) {
+            return Err(InsertError::new(value, error));
+        }
+
+        let id = self.next_id;
+
+        let entry = DelayQueueEntry {
+            deadline,
+            id,
+            value,
+        };
+
+        let handle = self
+            .entries
+            .try_create(entry)
+            .map_err(|(entry, error)| InsertError::new(entry.value, error))?;
+
+        if let Err(error) = self.heap.try_push(handle) {
+            let (error, handle) = (error.error(), error.into_value());
+
+            //  Safety:
+            //  -   `handle` is valid, fresh off the press.
+            let entry = unsafe { self.read(&handle) };
+            return Err(InsertError::new(entry.value, error));
+        }
+
+        self.next_id += 1;
+        self.sift_up(self.heap.len() - 1);
+
+        Ok(DelayQueueKey(id))
+    }
+
+    /// Removes the entry associated to `key`, if still present, and returns its value.
+    ///
+    /// The entry is searched for linearly, as is appropriate for the small queues backed by inline storages.
+    pub fn remove(&mut self, key: DelayQueueKey) -> Option<T> {
+        let position = self
+            .heap
+            .iter()
+            .position(|handle| self.entry(handle).id == key.0)?;
+
+        Some(self.remove_at(position))
+    }
+
+    /// Removes the entry with the earliest deadline, if it is no later than `now`, and returns its value.
+    pub fn poll_expired(&mut self, now: D) -> Option<T> {
+        if self.peek_deadline()? > now {
+            return None;
+        }
+
+        Some(self.remove_at(0))
+    }
+
+    /// Clears `self`, destroying all entries.
+    pub fn clear(&mut self) {
+        while let Some(handle) = self.heap.pop() {
+            //  Safety:
+            //  -   `handle` is valid, and no longer referenced by the heap.
+            unsafe { self.entries.destroy(&handle) };
+        }
+    }
+
+    //  Internal: reads the entry, deallocating it.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid, and is no longer used afterwards.
+    unsafe fn read(&mut self, handle: &E::Handle<DelayQueueEntry<D, T>>) -> DelayQueueEntry<D, T> {
+        let entry = self.entries.get(handle).as_ptr().read();
+        self.entries.deallocate(handle);
+        entry
+    }
+
+    fn entry(&self, handle: &E::Handle<DelayQueueEntry<D, T>>) -> &DelayQueueEntry<D, T> {
+        //  Safety:
+        //  -   All handles within the heap are valid.
+        unsafe { self.entries.get(handle).as_ref() }
+    }
+
+    //  Returns whether the entry at position `a` expires before the one at position `b`.
+    fn precedes(&self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.entry(&self.heap[a]), self.entry(&self.heap[b]));
+
+        (a.deadline, a.id) < (b.deadline, b.id)
+    }
+
+    fn remove_at(&mut self, position: usize) -> T {
+        let handle = self.heap.swap_remove(position);
+
+        if position < self.heap.len() {
+            self.sift_down(position);
+            self.sift_up(position);
+        }
+
+        //  Safety:
+        //  -   `handle` is valid, and no longer referenced by the heap.
+        unsafe { self.read(&handle) }.value
+    }
+
+    fn sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+
+            if !self.precedes(position, parent) {
+                break;
+            }
+
+            self.heap.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        let len = self.heap.len();
+
+        loop {
+            let (left, right) = (2 * position + 1, 2 * position + 2);
+
+            let mut earliest = position;
+
+            if left < len && self.precedes(left, earliest) {
+                earliest = left;
+            }
+
+            if right < len && self.precedes(right, earliest) {
+                earliest = right;
+            }
+
+            if earliest == position {
+                break;
+            }
+
+            self.heap.swap(position, earliest);
+            position = earliest;
+        }
+    }
+}
+
+impl<D: Ord + Copy, T, E: ElementStorage, R: RangeStorage> Debug for RawDelayQueue<D, T, E, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "RawDelayQueue{{ len: {} }}", self.len())
+    }
+}
+
+impl<D: Ord + Copy, T, E: Default + ElementStorage, R: Default + RangeStorage> Default
+    for RawDelayQueue<D, T, E, R>
+{
+    fn default() -> Self {
+        Self::new(E::default(), R::default())
+    }
+}
+
+impl<D: Ord + Copy, T, E: ElementStorage, R: RangeStorage> Drop for RawDelayQueue<D, T, E, R> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::{NonTrackingRange, TrackingElement};
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type Entry = DelayQueueEntry<u32, u32>;
+    type Queue = RawDelayQueue<u32, u32, TrackingElement<Entry, 4>, NonTrackingRange<u8, usize, 4>>;
+
+    #[test]
+    fn poll_expired() {
+        let mut queue = Queue::default();
+
+        queue.try_insert(30, 3).unwrap();
+        queue.try_insert(10, 1).unwrap();
+        queue.try_insert(20, 2).unwrap();
+        queue.try_insert(10, 4).unwrap();
+
+        assert_eq!(
+            Err(5),
+            queue.try_insert(0, 5).map_err(InsertError::into_value)
+        );
+        assert_eq!(Some(10), queue.peek_deadline());
+
+        assert_eq!(None, queue.poll_expired(5));
+        assert_eq!(Some(1), queue.poll_expired(15));
+        assert_eq!(Some(4), queue.poll_expired(15));
+        assert_eq!(None, queue.poll_expired(15));
+        assert_eq!(Some(2), queue.poll_expired(40));
+        assert_eq!(Some(3), queue.poll_expired(40));
+        assert_eq!(None, queue.poll_expired(40));
+
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn remove() {
+        let mut queue = Queue::default();
+
+        let keys: [DelayQueueKey; 4] = [
+            queue.try_insert(40, 4).unwrap(),
+            queue.try_insert(10, 1).unwrap(),
+            queue.try_insert(30, 3).unwrap(),
+            queue.try_insert(20, 2).unwrap(),
+        ];
+
+        assert_eq!(Some(1), queue.remove(keys[1]));
+        assert_eq!(None, queue.remove(keys[1]));
+        assert_eq!(Some(3), queue.remove(keys[2]));
+
+        assert_eq!(Some(20), queue.peek_deadline());
+        assert_eq!(2, queue.len());
+
+        let key = queue.try_insert(5, 5).unwrap();
+
+        assert_ne!(keys[1], key);
+        assert_eq!(Some(5), queue.poll_expired(5));
+        assert_eq!(Some(2), queue.poll_expired(50));
+        assert_eq!(Some(4), queue.poll_expired(50));
+    }
+
+    #[test]
+    fn drop_entries() {
+        let allocator = SpyAllocator::default();
+
+        let mut queue = RawDelayQueue::<u32, String, _, _>::new(
+            AllocStorage::new(allocator.clone()),
+            AllocStorage::new(allocator.clone()),
+        );
+
+        for deadline in 0..4 {
+            queue.try_insert(deadline, deadline.to_string()).unwrap();
+        }
+
+        assert_eq!(Some("0".to_string()), queue.poll_expired(0));
+
+        drop(queue);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
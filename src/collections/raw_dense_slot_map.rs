@@ -0,0 +1,393 @@
+//! Proof-of-Concept implementation of a dense SlotMap parameterized by Storages.
+
+use core::fmt::{self, Debug};
+
+use crate::traits::{ElementStorage, RangeStorage};
+
+use super::{InsertError, RawLinkedListNodeStorage, RawVec};
+
+/// A PoC dense SlotMap, associating stable keys to values which are kept contiguous in memory.
+///
+/// The index table is a set of slots in an element storage, typically a pool, each slot recording the position of its
+/// value. The values themselves are stored densely, alongside a back-reference to their slot, in a range storage,
+/// hence iteration is as fast as iterating over a slice.
+///
+/// Slots are recycled, but never released until the map is dropped, and each slot carries a generation, hence a
+/// stale key is detected rather than aliasing a newer value.
+///
+/// Keys are only meaningful for the map which issued them; using a key issued by another map is undefined behavior.
+pub struct RawDenseSlotMap<T, E: ElementStorage, R: RangeStorage> {
+    free: Option<E::Handle<RawDenseSlot<E>>>,
+    slots: E,
+    values: RawVec<RawDenseEntry<T, E::Handle<RawDenseSlot<E>>>, R>,
+}
+
+/// The key of a value of a `RawDenseSlotMap`.
+pub struct RawDenseSlotMapKey<E: ElementStorage> {
+    handle: E::Handle<RawDenseSlot<E>>,
+    generation: u32,
+}
+
+/// A slot of the index table of a `RawDenseSlotMap`.
+pub struct RawDenseSlot<E: ElementStorage> {
+    generation: u32,
+    index: usize,
+    next_free: Option<E::Handle<Self>>,
+}
+
+/// A PoC dense SlotMap storage helper.
+///
+/// Reserves enough space for storing a slot of the index table, for a handle of size similar to `H`; a slot has the
+/// same layout as a list node.
+pub type RawDenseSlotStorage<H> = RawLinkedListNodeStorage<(u32, usize), H>;
+
+impl<T, E: ElementStorage, R: RangeStorage> RawDenseSlotMap<T, E, R>
+where
+    E::Handle<RawDenseSlot<E>>: Copy,
+{
+    /// Creates a new instance, with its index table stored in `slots` and its values stored in `values`.
+    pub fn new(slots: E, values: R) -> Self {
+        Self {
+            free: None,
+            slots,
+            values: RawVec::new(values),
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the number of values in `self`.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Attempts to insert `value`, returning its key.
+    ///
+    /// On failure, returns `value`.
+    pub fn try_insert(&mut self, value: T) -> Result<RawDenseSlotMapKey<E>, InsertError<T>> {
+        if let Err(error) = self.values.try_reserve(1) {
+            return Err(InsertError::new(value, error));
+        }
+
+        let index = self.values.len();
+
+        let handle = match self.free {
+            Some(handle) => {
+                //  Safety:
+                //  -   `handle` is valid, as all slots are live until `self` is dropped.
+                let slot = unsafe { self.slot_mut(handle) };
+
+                slot.index = index;
+                self.free = slot.next_free.take();
+
+                handle
+            }
+            None => {
+                let slot = RawDenseSlot {
+                    generation: 0,
+                    index,
+                    next_free: None,
+                };
+
+                match self.slots.try_create(slot) {
+                    Ok(handle) => handle,
+                    Err((_, error)) => return Err(InsertError::new(value, error)),
+                }
+            }
+        };
+
+        let entry = RawDenseEntry {
+            value,
+            slot: handle,
+        };
+
+        if self.values.try_push(entry).is_err() {
+            unreachable!("Capacity was reserved above");
+        }
+
+        //  Safety:
+        //  -   `handle` is valid, as all slots are live until `self` is dropped.
+        let generation = unsafe { self.slot_mut(handle) }.generation;
+
+        Ok(RawDenseSlotMapKey { handle, generation })
+    }
+
+    /// Returns whether `key` is associated to a value.
+    pub fn contains_key(&self, key: &RawDenseSlotMapKey<E>) -> bool {
+        self.index_of(key).is_some()
+    }
+
+    /// Returns a reference to the value associated to `key`, if any.
+    pub fn get(&self, key: &RawDenseSlotMapKey<E>) -> Option<&T> {
+        let index = self.index_of(key)?;
+
+        Some(&self.values[index].value)
+    }
+
+    /// Returns a mutable reference to the value associated to `key`, if any.
+    pub fn get_mut(&mut self, key: &RawDenseSlotMapKey<E>) -> Option<&mut T> {
+        let index = self.index_of(key)?;
+
+        Some(&mut self.values[index].value)
+    }
+
+    /// Removes the value associated to `key`, if any, and returns it.
+    ///
+    /// The last value is moved into the position of the removed one, hence the order of values is not preserved.
+    pub fn remove(&mut self, key: &RawDenseSlotMapKey<E>) -> Option<T> {
+        let index = self.index_of(key)?;
+
+        let entry = self.values.swap_remove(index);
+
+        if let Some(moved) = self.values.get(index) {
+            let moved = moved.slot;
+
+            //  Safety:
+            //  -   `moved` is valid, as all slots are live until `self` is dropped.
+            unsafe { self.slot_mut(moved) }.index = index;
+        }
+
+        let free = self.free.take();
+
+        //  Safety:
+        //  -   `entry.slot` is valid, as all slots are live until `self` is dropped.
+        let slot = unsafe { self.slot_mut(entry.slot) };
+
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.next_free = free;
+
+        self.free = Some(entry.slot);
+
+        Some(entry.value)
+    }
+
+    /// Removes all values, keeping the slots for reuse.
+    pub fn clear(&mut self) {
+        while let Some(entry) = self.values.pop() {
+            let free = self.free.take();
+
+            //  Safety:
+            //  -   `entry.slot` is valid, as all slots are live until `self` is dropped.
+            let slot = unsafe { self.slot_mut(entry.slot) };
+
+            slot.generation = slot.generation.wrapping_add(1);
+            slot.next_free = free;
+
+            self.free = Some(entry.slot);
+        }
+    }
+
+    /// Returns an iterator over the keys and values, in storage order.
+    pub fn iter(&self) -> impl Iterator<Item = (RawDenseSlotMapKey<E>, &T)> + '_ {
+        self.values.iter().map(move |entry| {
+            //  Safety:
+            //  -   `entry.slot` is valid, as all slots are live until `self` is dropped.
+            let generation = unsafe { self.slots.get(&entry.slot).as_ref() }.generation;
+
+            let key = RawDenseSlotMapKey {
+                handle: entry.slot,
+                generation,
+            };
+
+            (key, &entry.value)
+        })
+    }
+
+    /// Returns an iterator over the values, in storage order.
+    pub fn values(&self) -> impl Iterator<Item = &T> + '_ {
+        self.values.iter().map(|entry| &entry.value)
+    }
+
+    /// Returns an iterator over the mutable values, in storage order.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.values.iter_mut().map(|entry| &mut entry.value)
+    }
+
+    fn index_of(&self, key: &RawDenseSlotMapKey<E>) -> Option<usize> {
+        //  Safety:
+        //  -   `key.handle` is valid, as all slots are live until `self` is dropped.
+        let slot = unsafe { self.slots.get(&key.handle).as_ref() };
+
+        if slot.generation == key.generation {
+            Some(slot.index)
+        } else {
+            None
+        }
+    }
+
+    //  Internal: returns a mutable reference to the slot.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn slot_mut(&mut self, handle: E::Handle<RawDenseSlot<E>>) -> &mut RawDenseSlot<E> {
+        self.slots.get(&handle).as_mut()
+    }
+}
+
+impl<T: Debug, E: ElementStorage, R: RangeStorage> Debug for RawDenseSlotMap<T, E, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list()
+            .entries(self.values.iter().map(|entry| &entry.value))
+            .finish()
+    }
+}
+
+impl<T, E: Default + ElementStorage, R: Default + RangeStorage> Default
+    for RawDenseSlotMap<T, E, R>
+{
+    fn default() -> Self {
+        Self {
+            free: None,
+            slots: E::default(),
+            values: RawVec::default(),
+        }
+    }
+}
+
+impl<T, E: ElementStorage, R: RangeStorage> Drop for RawDenseSlotMap<T, E, R> {
+    fn drop(&mut self) {
+        while let Some(entry) = self.values.pop() {
+            //  Safety:
+            //  -   `entry.slot` is valid, and no longer referenced.
+            unsafe { self.slots.destroy(&entry.slot) };
+        }
+
+        let mut next = self.free.take();
+
+        while let Some(handle) = next {
+            //  Safety:
+            //  -   `handle` is valid, as all handles in the free list.
+            unsafe {
+                next = self.slots.get(&handle).as_mut().next_free.take();
+                self.slots.destroy(&handle);
+            }
+        }
+    }
+}
+
+impl<E: ElementStorage> Clone for RawDenseSlotMapKey<E>
+where
+    E::Handle<RawDenseSlot<E>>: Copy,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E: ElementStorage> Copy for RawDenseSlotMapKey<E> where E::Handle<RawDenseSlot<E>>: Copy {}
+
+impl<E: ElementStorage> Debug for RawDenseSlotMapKey<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "RawDenseSlotMapKey{{ generation: {} }}", self.generation)
+    }
+}
+
+//
+//  Implementation
+//
+
+struct RawDenseEntry<T, H> {
+    value: T,
+    slot: H,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::{NonTrackingRange, TrackingElement};
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type SlotStorage = RawDenseSlotStorage<usize>;
+    type Map =
+        RawDenseSlotMap<u32, TrackingElement<SlotStorage, 4>, NonTrackingRange<u8, [usize; 2], 3>>;
+
+    #[test]
+    fn insert_remove() {
+        let mut map = Map::default();
+
+        let one = map.try_insert(1).unwrap();
+        let two = map.try_insert(2).unwrap();
+        let three = map.try_insert(3).unwrap();
+
+        assert_eq!(
+            Err(4),
+            map.try_insert(4)
+                .map(|_| ())
+                .map_err(InsertError::into_value)
+        );
+        assert_eq!(Some(&2), map.get(&two));
+
+        assert_eq!(Some(1), map.remove(&one));
+        assert_eq!(None, map.remove(&one));
+        assert_eq!(None, map.get(&one));
+
+        //  The last value was moved into the hole, yet remains reachable.
+        assert_eq!([3, 2], *map.values().copied().collect::<Vec<_>>());
+        assert_eq!(Some(&3), map.get(&three));
+
+        //  The slot of `one` is reused, yet `one` remains stale.
+        let four = map.try_insert(4).unwrap();
+
+        assert!(!map.contains_key(&one));
+        assert_eq!(Some(&4), map.get(&four));
+
+        *map.get_mut(&four).unwrap() += 1;
+
+        for value in map.values_mut() {
+            *value *= 10;
+        }
+
+        assert_eq!("[30, 20, 50]", format!("{:?}", map));
+
+        let keys: Vec<_> = map
+            .iter()
+            .map(|(key, value)| (*value, map.get(&key).copied()))
+            .collect();
+
+        assert_eq!(vec![(30, Some(30)), (20, Some(20)), (50, Some(50))], keys);
+    }
+
+    #[test]
+    fn clear() {
+        let mut map = Map::default();
+
+        let keys = [map.try_insert(1).unwrap(), map.try_insert(2).unwrap()];
+
+        map.clear();
+
+        assert!(map.is_empty());
+        assert!(keys.iter().all(|key| !map.contains_key(key)));
+
+        map.try_insert(3).unwrap();
+        map.try_insert(4).unwrap();
+        map.try_insert(5).unwrap();
+
+        assert_eq!(3, map.len());
+    }
+
+    #[test]
+    fn drop_values() {
+        let allocator = SpyAllocator::default();
+
+        let mut map = RawDenseSlotMap::<String, _, _>::new(
+            AllocStorage::new(allocator.clone()),
+            AllocStorage::new(allocator.clone()),
+        );
+
+        let hello = map.try_insert("Hello".to_string()).unwrap();
+        map.try_insert("World".to_string()).unwrap();
+
+        assert_eq!(Some("Hello".to_string()), map.remove(&hello));
+
+        drop(map);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
@@ -0,0 +1,469 @@
+//! Proof-of-Concept implementation of a doubly-linked List parameterized by a Storage.
+
+use core::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ptr,
+};
+
+use crate::{defaults::DefaultElementStorage, traits::ElementStorage};
+
+use super::InsertError;
+
+/// A PoC doubly-linked List.
+///
+/// Each node links to both its predecessor and its successor, hence each node is referred to by 2 handles, and the
+/// handles of the storage must be `Copy`.
+pub struct RawDoublyLinkedList<T, S: ElementStorage = DefaultElementStorage> {
+    head: Option<NodeHandle<T, S>>,
+    tail: Option<NodeHandle<T, S>>,
+    len: usize,
+    storage: S,
+    _marker: PhantomData<T>,
+}
+
+impl<T, S: ElementStorage> RawDoublyLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    /// Creates a new instance from `storage`.
+    pub fn new(storage: S) -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            storage,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns whether the list is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Clears all the elements from the list, leading to an empty list.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Returns a reference to the front element of the list, if any.
+    pub fn front(&self) -> Option<&T> {
+        //  Safety:
+        //  -   `self.head` is valid, if any.
+        self.head
+            .map(|head| unsafe { &(*self.node(&head)).element })
+    }
+
+    /// Returns a mutable reference to the front element of the list, if any.
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        //  Safety:
+        //  -   `self.head` is valid, if any.
+        self.head
+            .map(|head| unsafe { &mut (*self.node(&head)).element })
+    }
+
+    /// Returns a reference to the back element of the list, if any.
+    pub fn back(&self) -> Option<&T> {
+        //  Safety:
+        //  -   `self.tail` is valid, if any.
+        self.tail
+            .map(|tail| unsafe { &(*self.node(&tail)).element })
+    }
+
+    /// Returns a mutable reference to the back element of the list, if any.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        //  Safety:
+        //  -   `self.tail` is valid, if any.
+        self.tail
+            .map(|tail| unsafe { &mut (*self.node(&tail)).element })
+    }
+
+    /// Attempts to push a new element to the front of the list.
+    ///
+    /// On failure, `self` is left unchanged, and `value` is returned.
+    pub fn try_push_front(&mut self, value: T) -> Result<(), InsertError<T>> {
+        let handle = self.create(None, self.head, value)?;
+
+        match self.head {
+            //  Safety:
+            //  -   `head` is valid.
+            Some(head) => unsafe { (*self.node(&head)).prev = Some(handle) },
+            None => self.tail = Some(handle),
+        }
+
+        self.head = Some(handle);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Pushes a new element to the front of the list.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn push_front(&mut self, value: T) {
+        self.try_push_front(value).expect("Sufficient capacity");
+    }
+
+    /// Attempts to push a new element to the back of the list.
+    ///
+    /// On failure, `self` is left unchanged, and `value` is returned.
+    pub fn try_push_back(&mut self, value: T) -> Result<(), InsertError<T>> {
+        let handle = self.create(self.tail, None, value)?;
+
+        match self.tail {
+            //  Safety:
+            //  -   `tail` is valid.
+            Some(tail) => unsafe { (*self.node(&tail)).next = Some(handle) },
+            None => self.head = Some(handle),
+        }
+
+        self.tail = Some(handle);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Pushes a new element to the back of the list.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn push_back(&mut self, value: T) {
+        self.try_push_back(value).expect("Sufficient capacity");
+    }
+
+    /// Pops the front element of the list, if any.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+
+        //  Safety:
+        //  -   `head` is valid, and unlinked below.
+        let node = unsafe { self.take(&head) };
+
+        match node.next {
+            //  Safety:
+            //  -   `next` is valid.
+            Some(next) => unsafe { (*self.node(&next)).prev = None },
+            None => self.tail = None,
+        }
+
+        self.head = node.next;
+
+        Some(node.element)
+    }
+
+    /// Pops the back element of the list, if any.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+
+        //  Safety:
+        //  -   `tail` is valid, and unlinked below.
+        let node = unsafe { self.take(&tail) };
+
+        match node.prev {
+            //  Safety:
+            //  -   `prev` is valid.
+            Some(prev) => unsafe { (*self.node(&prev)).next = None },
+            None => self.head = None,
+        }
+
+        self.tail = node.prev;
+
+        Some(node.element)
+    }
+
+    /// Returns an iterator over the elements of the list, from front to back, or from back to front once reversed.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        Iter {
+            list: self,
+            front: self.head,
+            back: self.tail,
+            remaining: self.len,
+        }
+    }
+}
+
+impl<T: Debug, S: ElementStorage> Debug for RawDoublyLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S: Default + ElementStorage> Default for RawDoublyLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<T, S: ElementStorage> Drop for RawDoublyLinkedList<T, S> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+
+        while let Some(handle) = next {
+            //  Safety:
+            //  -   `handle` is valid, as all handles reachable from the head.
+            unsafe {
+                next = (*self.storage.get(&handle).as_ptr()).next.take();
+                self.storage.destroy(&handle);
+            }
+        }
+    }
+}
+
+/// A node of a `RawDoublyLinkedList`, exposed as its handles appear in the bounds of the list.
+pub struct RawDoublyLinkedListNode<T, S: ElementStorage> {
+    prev: Option<S::Handle<Self>>,
+    next: Option<S::Handle<Self>>,
+    element: T,
+}
+
+/// A PoC doubly-linked List storage helper.
+///
+/// Reserves enough space for storing a list node containing `T`, for a handle of size similar to `H`.
+pub struct RawDoublyLinkedListNodeStorage<T, H>(Option<H>, Option<H>, MaybeUninit<T>);
+
+//
+//  Implementation
+//
+
+type NodeHandle<T, S> = <S as ElementStorage>::Handle<RawDoublyLinkedListNode<T, S>>;
+
+struct Iter<'a, T, S: ElementStorage> {
+    list: &'a RawDoublyLinkedList<T, S>,
+    front: Option<NodeHandle<T, S>>,
+    back: Option<NodeHandle<T, S>>,
+    remaining: usize,
+}
+
+impl<T, S: ElementStorage> RawDoublyLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    //  Internal: returns a pointer to the node of `handle`.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn node(&self, handle: &NodeHandle<T, S>) -> *mut RawDoublyLinkedListNode<T, S> {
+        self.storage.get(handle).as_ptr()
+    }
+
+    //  Internal: creates a new node, without linking it.
+    fn create(
+        &mut self,
+        prev: Option<NodeHandle<T, S>>,
+        next: Option<NodeHandle<T, S>>,
+        element: T,
+    ) -> Result<NodeHandle<T, S>, InsertError<T>> {
+        let node = RawDoublyLinkedListNode {
+            prev,
+            next,
+            element,
+        };
+
+        self.storage
+            .try_create(node)
+            .map_err(|(node, error)| InsertError::new(node.element, error))
+    }
+
+    //  Internal: moves the node of `handle` out of the storage, and deallocates it.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    //  -   Assumes that the caller unlinks the node.
+    unsafe fn take(&mut self, handle: &NodeHandle<T, S>) -> RawDoublyLinkedListNode<T, S> {
+        let node = ptr::read(self.node(handle));
+        self.storage.deallocate(handle);
+
+        self.len -= 1;
+
+        node
+    }
+}
+
+impl<'a, T, S: ElementStorage> Iterator for Iter<'a, T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        //  Safety:
+        //  -   `self.front` is valid, as `self.remaining` is non-zero.
+        let node = unsafe { &*self.list.node(&self.front?) };
+
+        self.front = node.next;
+        self.remaining -= 1;
+
+        Some(&node.element)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, S: ElementStorage> DoubleEndedIterator for Iter<'a, T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        //  Safety:
+        //  -   `self.back` is valid, as `self.remaining` is non-zero.
+        let node = unsafe { &*self.list.node(&self.back?) };
+
+        self.back = node.prev;
+        self.remaining -= 1;
+
+        Some(&node.element)
+    }
+}
+
+impl<'a, T, S: ElementStorage> ExactSizeIterator for Iter<'a, T, S> where NodeHandle<T, S>: Copy {}
+
+#[cfg(test)]
+mod test_inline {
+
+    use crate::inline::TrackingElement;
+
+    use super::*;
+
+    type NodeStorage = RawDoublyLinkedListNodeStorage<u8, usize>;
+    type List<const N: usize> = RawDoublyLinkedList<u8, TrackingElement<NodeStorage, N>>;
+
+    #[test]
+    fn smoke_test() {
+        let mut list = List::<4>::default();
+
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+
+        assert_eq!(3, list.len());
+        assert_eq!(Some(&1), list.front());
+        assert_eq!(Some(&3), list.back());
+        assert_eq!("[1, 2, 3]", format!("{:?}", list));
+
+        *list.back_mut().unwrap() = 4;
+        *list.front_mut().unwrap() = 0;
+
+        assert_eq!(Some(4), list.pop_back());
+        assert_eq!(Some(0), list.pop_front());
+        assert_eq!(Some(2), list.pop_back());
+        assert_eq!(None, list.pop_back());
+        assert_eq!(None, list.pop_front());
+
+        assert!(list.is_empty());
+        assert_eq!(None, list.front());
+        assert_eq!(None, list.back());
+    }
+
+    #[test]
+    fn reverse_iteration() {
+        let mut list = List::<4>::default();
+
+        for i in 1..=4 {
+            list.push_back(i);
+        }
+
+        assert_eq!(
+            vec![4, 3, 2, 1],
+            list.iter().rev().copied().collect::<Vec<_>>()
+        );
+
+        //  Iterating from both ends meets in the middle.
+        let mut iter = list.iter();
+
+        assert_eq!(4, iter.len());
+        assert_eq!(Some(&1), iter.next());
+        assert_eq!(Some(&4), iter.next_back());
+        assert_eq!(Some(&2), iter.next());
+        assert_eq!(Some(&3), iter.next_back());
+        assert_eq!(None, iter.next());
+        assert_eq!(None, iter.next_back());
+    }
+
+    #[test]
+    fn reuse_slots() {
+        let mut list = List::<2>::default();
+
+        //  Popping from either end releases the slot, for either end to reuse.
+        for i in 0..8 {
+            list.push_back(i);
+            list.push_front(i);
+
+            assert_eq!(i, list.try_push_back(i).unwrap_err().into_value());
+            assert_eq!(Some(i), list.pop_front());
+            assert_eq!(Some(i), list.pop_back());
+        }
+
+        assert!(list.is_empty());
+    }
+} // mod test_inline
+
+#[cfg(test)]
+mod test_allocator {
+
+    use crate::allocator::AllocStorage;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    #[test]
+    fn smoke_test() {
+        type List = RawDoublyLinkedList<String, AllocStorage<SpyAllocator>>;
+
+        let allocator = SpyAllocator::default();
+        let mut list = List::new(AllocStorage::new(allocator.clone()));
+
+        list.push_back("World".to_string());
+        list.push_front("Hello".to_string());
+        list.push_back("!".to_string());
+
+        assert_eq!(3, allocator.allocated());
+        assert_eq!(Some("!".to_string()), list.pop_back());
+        assert_eq!(1, allocator.deallocated());
+        assert_eq!("[\"Hello\", \"World\"]", format!("{:?}", list));
+
+        drop(list);
+
+        assert_eq!(3, allocator.deallocated());
+    }
+
+    #[test]
+    fn allocation_failure() {
+        type List = RawDoublyLinkedList<&'static str, AllocStorage<NonAllocator>>;
+
+        let mut list = List::default();
+
+        assert_eq!(
+            "Caramba",
+            list.try_push_back("Caramba").unwrap_err().into_value()
+        );
+        assert!(list.is_empty());
+    }
+} // mod test_allocator
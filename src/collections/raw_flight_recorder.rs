@@ -0,0 +1,349 @@
+//! Proof-of-Concept implementation of a FlightRecorder parameterized by a Storage.
+
+use core::{
+    fmt::{self, Debug},
+    mem::MaybeUninit,
+    ptr,
+};
+
+use crate::{
+    defaults::DefaultRangeStorage,
+    error::StorageError,
+    traits::{Capacity, RangeExt, RangeStorage},
+};
+
+/// A PoC FlightRecorder, a fixed-capacity log of timestamped records overwriting its oldest record once full.
+///
+/// This is typically meant for crash diagnostics on embedded targets: the recorder is constructed in a `const`
+/// context, so that it may live in a `static`, and the last few events leading up to the crash are available in a
+/// snapshot.
+///
+/// By default, the range of the recorder is that requiring no allocation, acquired upon the first record, hence its
+/// capacity is that of an inline storage. Storages requiring allocation should use `with_capacity` instead.
+///
+/// The timestamp is any type: an `Instant`, a tick count, etc... It defaults to a tick count.
+pub struct RawFlightRecorder<T, S: RangeStorage = DefaultRangeStorage, D = u64> {
+    head: usize,
+    len: usize,
+    range: Option<S::Handle<FlightRecord<D, T>>>,
+    storage: S,
+}
+
+/// A record of a `RawFlightRecorder`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlightRecord<D, T> {
+    timestamp: D,
+    value: T,
+}
+
+impl<T, S: RangeStorage, D> RawFlightRecorder<T, S, D> {
+    /// Creates a new, empty, instance.
+    ///
+    /// No range is acquired until the first record, hence this is suitable for the initialization of a `static`.
+    pub const fn new(storage: S) -> Self {
+        Self {
+            head: 0,
+            len: 0,
+            range: None,
+            storage,
+        }
+    }
+
+    /// Attempts to create a new, empty, instance able to hold at least `capacity` records.
+    pub fn with_capacity(mut storage: S, capacity: usize) -> Result<Self, StorageError> {
+        let capacity = S::Capacity::from_usize(capacity).ok_or_else(|| {
+            StorageError::array::<FlightRecord<D, T>>(capacity).with_context("RawFlightRecorder")
+        })?;
+        let range = storage.allocate(capacity)?;
+
+        Ok(Self {
+            head: 0,
+            len: 0,
+            range: Some(range),
+            storage,
+        })
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of records in `self`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of records `self` holds before overwriting the oldest.
+    pub fn capacity(&self) -> usize {
+        //  Safety:
+        //  -   `range` is valid, whether acquired or dangling.
+        match &self.range {
+            Some(range) => unsafe { self.storage.get(range) }.len(),
+            None => unsafe {
+                self.storage
+                    .get(&self.storage.dangling::<FlightRecord<D, T>>())
+            }
+            .len(),
+        }
+    }
+
+    /// Records `value`, at `timestamp`, overwriting the oldest record if `self` is full.
+    ///
+    /// If the capacity of `self` is 0, `value` is dropped immediately.
+    pub fn record(&mut self, timestamp: D, value: T) {
+        let storage = &self.storage;
+        let range = self.range.get_or_insert_with(|| storage.dangling());
+
+        //  Safety:
+        //  -   `range` is valid.
+        let slots = unsafe { self.storage.get(range) };
+        let capacity = slots.len();
+
+        if capacity == 0 {
+            return;
+        }
+
+        let record = FlightRecord { timestamp, value };
+
+        if self.len < capacity {
+            let index = (self.head + self.len) % capacity;
+
+            //  Safety:
+            //  -   `index < capacity`.
+            //  -   The slot at `index` is past the last record, hence uninitialized.
+            unsafe { (*slots.as_mut_ptr().add(index)).write(record) };
+
+            self.len += 1;
+            return;
+        }
+
+        //  Safety:
+        //  -   `self.head < capacity`.
+        //  -   The slot at `self.head` holds the oldest record, since `self` is full.
+        let oldest = unsafe { &mut *slots.as_mut_ptr().add(self.head) };
+
+        //  Safety:
+        //  -   `oldest` is initialized.
+        unsafe { oldest.assume_init_drop() };
+        oldest.write(record);
+
+        self.head = (self.head + 1) % capacity;
+    }
+
+    /// Returns the most recent record, if any.
+    pub fn latest(&self) -> Option<&FlightRecord<D, T>> {
+        let (older, newer) = self.as_slices();
+
+        newer.last().or_else(|| older.last())
+    }
+
+    /// Returns an iterator over a snapshot of the records, from the oldest to the most recent.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &FlightRecord<D, T>> + '_ {
+        let (older, newer) = self.as_slices();
+
+        older.iter().chain(newer)
+    }
+
+    /// Returns the records, as two slices, the first holding the oldest records and the second the most recent ones.
+    ///
+    /// The second slice is empty unless the records wrap around the end of the range.
+    #[allow(clippy::type_complexity)]
+    pub fn as_slices(&self) -> (&[FlightRecord<D, T>], &[FlightRecord<D, T>]) {
+        let range = match &self.range {
+            Some(range) => range,
+            None => return (&[], &[]),
+        };
+
+        let (older, newer) = self.split(range);
+
+        //  Safety:
+        //  -   `range` is valid.
+        //  -   `self.head..self.head + older` and `0..newer` are within bounds, and hold the initialized records.
+        //  -   Mutable references to the records only ever exist while `self` is mutably borrowed.
+        unsafe {
+            let older = self.storage.slice_ref(range, self.head, older);
+            let newer = self.storage.slice_ref(range, 0, newer);
+
+            (
+                MaybeUninit::slice_assume_init_ref(older),
+                MaybeUninit::slice_assume_init_ref(newer),
+            )
+        }
+    }
+
+    /// Clears `self`, destroying all records.
+    pub fn clear(&mut self) {
+        let range = match &self.range {
+            Some(range) => range,
+            None => return,
+        };
+
+        let (older, newer) = self.split(range);
+        let head = self.head;
+
+        self.head = 0;
+        self.len = 0;
+
+        //  Safety:
+        //  -   `range` is valid.
+        //  -   `head..head + older` and `0..newer` are within bounds, and hold the initialized records, which are no
+        //      longer accessible through `self`.
+        unsafe {
+            let older = self.storage.slice(range, head, older);
+            let newer = self.storage.slice(range, 0, newer);
+
+            ptr::drop_in_place(older.as_ptr() as *mut [FlightRecord<D, T>]);
+            ptr::drop_in_place(newer.as_ptr() as *mut [FlightRecord<D, T>]);
+        }
+    }
+}
+
+impl<T: Debug, S: RangeStorage, D: Debug> Debug for RawFlightRecorder<T, S, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S: Default + RangeStorage, D> Default for RawFlightRecorder<T, S, D> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<T, S: RangeStorage, D> Drop for RawFlightRecorder<T, S, D> {
+    fn drop(&mut self) {
+        self.clear();
+
+        if let Some(range) = self.range.take() {
+            //  Safety:
+            //  -   `range` is valid, and no longer used.
+            unsafe { self.storage.deallocate(&range) };
+        }
+    }
+}
+
+impl<D, T> FlightRecord<D, T> {
+    /// Returns the timestamp of the record.
+    pub fn timestamp(&self) -> &D {
+        &self.timestamp
+    }
+
+    /// Returns the value of the record.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns the timestamp and value of the record.
+    pub fn into_parts(self) -> (D, T) {
+        (self.timestamp, self.value)
+    }
+}
+
+//
+//  Implementation
+//
+
+impl<T, S: RangeStorage, D> RawFlightRecorder<T, S, D> {
+    //  Returns the number of records in `self.head..`, and in `0..self.head`, respectively.
+    fn split(&self, range: &S::Handle<FlightRecord<D, T>>) -> (usize, usize) {
+        //  Safety:
+        //  -   `range` is valid.
+        let capacity = unsafe { self.storage.get(range) }.len();
+
+        let older = self.len.min(capacity - self.head);
+
+        (older, self.len - older)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Mutex;
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type InlineRecorder = RawFlightRecorder<u8, NonTrackingRange<u8, u64, 4>, u32>;
+
+    static RECORDER: Mutex<InlineRecorder> =
+        Mutex::new(RawFlightRecorder::new(NonTrackingRange::new()));
+
+    #[test]
+    fn static_recorder() {
+        let mut recorder = RECORDER.lock().unwrap();
+
+        assert!(recorder.is_empty());
+        assert_eq!(4, recorder.capacity());
+
+        for tick in 0..6 {
+            recorder.record(tick, tick as u8 * 10);
+        }
+
+        assert_eq!(4, recorder.len());
+        assert_eq!(
+            Some(&FlightRecord {
+                timestamp: 5,
+                value: 50
+            }),
+            recorder.latest()
+        );
+
+        let snapshot: Vec<_> = recorder
+            .iter()
+            .map(|record| (*record.timestamp(), *record.value()))
+            .collect();
+
+        assert_eq!(vec![(2, 20), (3, 30), (4, 40), (5, 50)], snapshot);
+
+        let (older, newer) = recorder.as_slices();
+
+        assert_eq!((2, 2), (older.len(), newer.len()));
+
+        recorder.clear();
+
+        assert!(recorder.is_empty());
+        assert_eq!(None, recorder.latest());
+    }
+
+    #[test]
+    fn overwrite_drops_oldest() {
+        let allocator = SpyAllocator::default();
+        let storage = AllocStorage::new(allocator.clone());
+
+        let mut recorder = RawFlightRecorder::<String, _>::with_capacity(storage, 2).unwrap();
+
+        recorder.record(1, "Hello".to_string());
+        recorder.record(2, "World".to_string());
+        recorder.record(3, "Again".to_string());
+
+        assert_eq!(
+            "[FlightRecord { timestamp: 2, value: \"World\" }, FlightRecord { timestamp: 3, value: \"Again\" }]",
+            format!("{:?}", recorder)
+        );
+
+        let latest = recorder.iter().next_back().cloned().unwrap();
+
+        assert_eq!((3, "Again".to_string()), latest.into_parts());
+
+        drop(recorder);
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut recorder =
+            RawFlightRecorder::<u8, _>::new(AllocStorage::new(SpyAllocator::default()));
+
+        recorder.record(1, 1);
+
+        assert_eq!(0, recorder.capacity());
+        assert!(recorder.is_empty());
+    }
+} // mod tests
@@ -0,0 +1,577 @@
+//! Proof-of-Concept implementation of a directed Graph parameterized by Storages.
+
+use core::{
+    alloc::Layout,
+    fmt::{self, Debug},
+    iter, ptr,
+};
+
+use crate::{error::StorageError, traits::ElementStorage};
+
+use super::InsertError;
+
+/// A PoC directed Graph, with values `V` on its nodes and weights `W` on its edges.
+///
+/// The nodes are stored in one element storage, and the edges in another, each node heading the singly-linked list
+/// of its outgoing edges, and each edge referring to its target node by handle.
+///
+/// Node slots are recycled, but never released until the graph is dropped, and each slot carries a generation, hence
+/// a stale key is detected rather than aliasing a newer node. Keys are only meaningful for the graph which issued
+/// them; using a key issued by another graph is undefined behavior.
+///
+/// Multiple edges may link the same pair of nodes.
+pub struct RawGraph<V, W, N: ElementStorage, E: ElementStorage> {
+    head: Option<NodeHandle<V, W, N, E>>,
+    free: Option<NodeHandle<V, W, N, E>>,
+    node_count: usize,
+    edge_count: usize,
+    nodes: N,
+    edges: E,
+}
+
+/// The key of a node of a `RawGraph`.
+pub struct RawGraphNodeKey<V, W, N: ElementStorage, E: ElementStorage> {
+    handle: NodeHandle<V, W, N, E>,
+    generation: u32,
+}
+
+/// A node of a `RawGraph`, exposed so as to size the node storage.
+pub struct RawGraphNode<V, W, N: ElementStorage, E: ElementStorage> {
+    generation: u32,
+    value: Option<V>,
+    edges: Option<EdgeHandle<V, W, N, E>>,
+    prev: Option<NodeHandle<V, W, N, E>>,
+    next: Option<NodeHandle<V, W, N, E>>,
+}
+
+impl<V, W, N: ElementStorage, E: ElementStorage> RawGraph<V, W, N, E>
+where
+    NodeHandle<V, W, N, E>: Copy,
+{
+    /// Creates a new instance, with its nodes stored in `nodes` and its edges stored in `edges`.
+    pub fn new(nodes: N, edges: E) -> Self {
+        Self {
+            head: None,
+            free: None,
+            node_count: 0,
+            edge_count: 0,
+            nodes,
+            edges,
+        }
+    }
+
+    /// Returns the number of nodes.
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Returns the number of edges.
+    pub fn edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    /// Attempts to add a node with `value`, returning its key.
+    ///
+    /// On failure, returns `value`.
+    pub fn try_add_node(
+        &mut self,
+        value: V,
+    ) -> Result<RawGraphNodeKey<V, W, N, E>, InsertError<V>> {
+        let handle = match self.free {
+            Some(handle) => {
+                //  Safety:
+                //  -   `handle` is valid, as all node slots are live until `self` is dropped.
+                let node = unsafe { &mut *self.node_ptr(&handle) };
+
+                self.free = node.next.take();
+                node.value = Some(value);
+
+                handle
+            }
+            None => {
+                let node = RawGraphNode {
+                    generation: 0,
+                    value: Some(value),
+                    edges: None,
+                    prev: None,
+                    next: None,
+                };
+
+                self.nodes.try_create(node).map_err(|(node, error)| {
+                    InsertError::new(node.value.expect("Value was just set"), error)
+                })?
+            }
+        };
+
+        //  Safety:
+        //  -   `handle` and `self.head` are valid, as all node slots are live until `self` is dropped.
+        unsafe {
+            if let Some(head) = self.head {
+                (*self.node_ptr(&head)).prev = Some(handle);
+            }
+
+            let node = &mut *self.node_ptr(&handle);
+            node.next = self.head;
+
+            self.head = Some(handle);
+            self.node_count += 1;
+
+            Ok(RawGraphNodeKey {
+                handle,
+                generation: node.generation,
+            })
+        }
+    }
+
+    /// Removes the node associated to `key`, if any, along with all its incoming and outgoing edges, and returns its
+    /// value.
+    ///
+    /// This is linear in the number of nodes and edges of the graph, as incoming edges are not indexed.
+    pub fn remove_node(&mut self, key: &RawGraphNodeKey<V, W, N, E>) -> Option<V> {
+        let handle = self.live(key)?;
+
+        //  Safety:
+        //  -   `handle`, and all handles reachable from `self.head`, are valid, as all node slots are live until
+        //      `self` is dropped.
+        unsafe {
+            let target = self.node_ptr(&handle);
+
+            let mut edge = (*target).edges.take();
+
+            while let Some(current) = edge {
+                edge = self.edges.get(&current).as_mut().next.take();
+
+                self.edges.destroy(&current);
+                self.edge_count -= 1;
+            }
+
+            let mut cursor = self.head;
+
+            while let Some(current) = cursor {
+                self.unlink_edges(&current, target, true);
+                cursor = (*self.node_ptr(&current)).next;
+            }
+
+            let node = &mut *target;
+
+            match node.prev {
+                Some(prev) => (*self.node_ptr(&prev)).next = node.next,
+                None => self.head = node.next,
+            }
+
+            if let Some(next) = node.next {
+                (*self.node_ptr(&next)).prev = node.prev;
+            }
+
+            node.generation = node.generation.wrapping_add(1);
+            node.prev = None;
+            node.next = self.free;
+
+            self.free = Some(handle);
+            self.node_count -= 1;
+
+            node.value.take()
+        }
+    }
+
+    /// Returns a reference to the value of the node associated to `key`, if any.
+    pub fn node(&self, key: &RawGraphNodeKey<V, W, N, E>) -> Option<&V> {
+        let handle = self.live(key)?;
+
+        //  Safety:
+        //  -   `handle` is valid, as all node slots are live until `self` is dropped.
+        unsafe { (*self.node_ptr(&handle)).value.as_ref() }
+    }
+
+    /// Returns a mutable reference to the value of the node associated to `key`, if any.
+    pub fn node_mut(&mut self, key: &RawGraphNodeKey<V, W, N, E>) -> Option<&mut V> {
+        let handle = self.live(key)?;
+
+        //  Safety:
+        //  -   `handle` is valid, as all node slots are live until `self` is dropped.
+        unsafe { (*self.node_ptr(&handle)).value.as_mut() }
+    }
+
+    /// Attempts to add an edge from `from` to `to`, with `weight`.
+    ///
+    /// On failure, including if either key is stale, returns `weight`; a stale key is reported in the context of
+    /// `RawGraph`, with the layout of the edge which was not created.
+    pub fn try_add_edge(
+        &mut self,
+        from: &RawGraphNodeKey<V, W, N, E>,
+        to: &RawGraphNodeKey<V, W, N, E>,
+        weight: W,
+    ) -> Result<(), InsertError<W>> {
+        let (from, to) = match (self.live(from), self.live(to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => {
+                let layout = Layout::new::<RawGraphEdge<W, NodeHandle<V, W, N, E>, E>>();
+                let error = StorageError::new(layout).with_context("RawGraph");
+
+                return Err(InsertError::new(weight, error));
+            }
+        };
+
+        let edge = RawGraphEdge {
+            target: to,
+            weight,
+            next: None,
+        };
+
+        let handle = self
+            .edges
+            .try_create(edge)
+            .map_err(|(edge, error)| InsertError::new(edge.weight, error))?;
+
+        //  Safety:
+        //  -   `from` is valid, as all node slots are live until `self` is dropped.
+        //  -   `handle` is valid, fresh off the press.
+        unsafe {
+            let node = &mut *self.node_ptr(&from);
+
+            self.edges.get(&handle).as_mut().next = node.edges.take();
+            node.edges = Some(handle);
+        }
+
+        self.edge_count += 1;
+
+        Ok(())
+    }
+
+    /// Removes the most recently added edge from `from` to `to`, if any, and returns its weight.
+    pub fn remove_edge(
+        &mut self,
+        from: &RawGraphNodeKey<V, W, N, E>,
+        to: &RawGraphNodeKey<V, W, N, E>,
+    ) -> Option<W> {
+        let (from, to) = (self.live(from)?, self.live(to)?);
+
+        //  Safety:
+        //  -   `from` and `to` are valid, as all node slots are live until `self` is dropped.
+        unsafe { self.unlink_edges(&from, self.node_ptr(&to), false) }
+    }
+
+    /// Returns an iterator over the nodes, and their values, from the most recently added.
+    pub fn nodes(&self) -> impl Iterator<Item = (RawGraphNodeKey<V, W, N, E>, &V)> + '_ {
+        iter::successors(self.head, move |handle| {
+            //  Safety:
+            //  -   `handle` is valid, as all node slots are live until `self` is dropped.
+            unsafe { (*self.node_ptr(handle)).next }
+        })
+        .map(move |handle| {
+            //  Safety:
+            //  -   `handle` is valid, as all node slots are live until `self` is dropped.
+            let node = unsafe { &*self.node_ptr(&handle) };
+            let value = node.value.as_ref().expect("Live nodes have a value");
+
+            let key = RawGraphNodeKey {
+                handle,
+                generation: node.generation,
+            };
+
+            (key, value)
+        })
+    }
+
+    /// Returns an iterator over the targets, and weights, of the outgoing edges of `key`, from the most recently added.
+    ///
+    /// If `key` is stale, the iterator is empty.
+    pub fn neighbors(
+        &self,
+        key: &RawGraphNodeKey<V, W, N, E>,
+    ) -> impl Iterator<Item = (RawGraphNodeKey<V, W, N, E>, &W)> + '_ {
+        //  Safety:
+        //  -   `handle` is valid, as all node slots are live until `self` is dropped.
+        let first = self
+            .live(key)
+            .and_then(|handle| unsafe { (*self.node_ptr(&handle)).edges.as_ref() });
+
+        iter::successors(first, move |handle| {
+            //  Safety:
+            //  -   `handle` is valid, as all edges reachable from a live node are.
+            unsafe { self.edges.get(*handle).as_ref().next.as_ref() }
+        })
+        .map(move |handle| {
+            //  Safety:
+            //  -   `handle` is valid, as all edges reachable from a live node are.
+            //  -   `edge.target` is valid, as all node slots are live until `self` is dropped.
+            unsafe {
+                let edge = self.edges.get(handle).as_ref();
+                let generation = (*self.node_ptr(&edge.target)).generation;
+
+                let key = RawGraphNodeKey {
+                    handle: edge.target,
+                    generation,
+                };
+
+                (key, &edge.weight)
+            }
+        })
+    }
+
+    //  Returns the handle of the node associated to `key`, if it is live.
+    fn live(&self, key: &RawGraphNodeKey<V, W, N, E>) -> Option<NodeHandle<V, W, N, E>> {
+        //  Safety:
+        //  -   `key.handle` is valid, as all node slots are live until `self` is dropped.
+        let node = unsafe { &*self.node_ptr(&key.handle) };
+
+        if node.generation == key.generation {
+            Some(key.handle)
+        } else {
+            None
+        }
+    }
+
+    //  Internal: returns a pointer to the node.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn node_ptr(&self, handle: &NodeHandle<V, W, N, E>) -> *mut RawGraphNode<V, W, N, E> {
+        self.nodes.get(handle).as_ptr()
+    }
+
+    //  Internal: unlinks, and destroys, the edges from `from` to `target`, returning the weight of the first one.
+    //
+    //  If `all` is false, stops after the first edge.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `from` is valid.
+    //  -   Assumes that `target` points to a live node slot.
+    unsafe fn unlink_edges(
+        &mut self,
+        from: &NodeHandle<V, W, N, E>,
+        target: *mut RawGraphNode<V, W, N, E>,
+        all: bool,
+    ) -> Option<W> {
+        let mut link: *mut Option<EdgeHandle<V, W, N, E>> = &mut (*self.node_ptr(from)).edges;
+
+        while let Some(handle) = &*link {
+            let edge = self.edges.get(handle).as_ptr();
+
+            if self.node_ptr(&(*edge).target) != target {
+                link = &mut (*edge).next;
+                continue;
+            }
+
+            let handle = (*link).take().expect("Link was just matched");
+            let edge = ptr::read(edge);
+
+            self.edges.deallocate(&handle);
+            self.edge_count -= 1;
+
+            *link = edge.next;
+
+            if !all {
+                return Some(edge.weight);
+            }
+        }
+
+        None
+    }
+}
+
+impl<V, W, N: ElementStorage, E: ElementStorage> Debug for RawGraph<V, W, N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "RawGraph{{ nodes: {}, edges: {} }}",
+            self.node_count, self.edge_count
+        )
+    }
+}
+
+impl<V, W, N: Default + ElementStorage, E: Default + ElementStorage> Default
+    for RawGraph<V, W, N, E>
+{
+    fn default() -> Self {
+        Self {
+            head: None,
+            free: None,
+            node_count: 0,
+            edge_count: 0,
+            nodes: N::default(),
+            edges: E::default(),
+        }
+    }
+}
+
+impl<V, W, N: ElementStorage, E: ElementStorage> Drop for RawGraph<V, W, N, E> {
+    fn drop(&mut self) {
+        let mut cursor = self.head.take();
+
+        while let Some(handle) = cursor {
+            //  Safety:
+            //  -   `handle`, and all edges reachable from it, are valid.
+            unsafe {
+                let node = self.nodes.get(&handle).as_mut();
+
+                let mut edge = node.edges.take();
+
+                while let Some(current) = edge {
+                    edge = self.edges.get(&current).as_mut().next.take();
+                    self.edges.destroy(&current);
+                }
+
+                cursor = node.next.take();
+                self.nodes.destroy(&handle);
+            }
+        }
+
+        let mut cursor = self.free.take();
+
+        while let Some(handle) = cursor {
+            //  Safety:
+            //  -   `handle` is valid, as all handles in the free list.
+            unsafe {
+                cursor = self.nodes.get(&handle).as_mut().next.take();
+                self.nodes.destroy(&handle);
+            }
+        }
+    }
+}
+
+impl<V, W, N: ElementStorage, E: ElementStorage> Clone for RawGraphNodeKey<V, W, N, E>
+where
+    NodeHandle<V, W, N, E>: Copy,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V, W, N: ElementStorage, E: ElementStorage> Copy for RawGraphNodeKey<V, W, N, E> where
+    NodeHandle<V, W, N, E>: Copy
+{
+}
+
+impl<V, W, N: ElementStorage, E: ElementStorage> Debug for RawGraphNodeKey<V, W, N, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "RawGraphNodeKey{{ generation: {} }}", self.generation)
+    }
+}
+
+//
+//  Implementation
+//
+
+type NodeHandle<V, W, N, E> = <N as ElementStorage>::Handle<RawGraphNode<V, W, N, E>>;
+
+type EdgeHandle<V, W, N, E> =
+    <E as ElementStorage>::Handle<RawGraphEdge<W, NodeHandle<V, W, N, E>, E>>;
+
+struct RawGraphEdge<W, H, E: ElementStorage> {
+    target: H,
+    weight: W,
+    next: Option<E::Handle<Self>>,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::TrackingElement;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type Graph =
+        RawGraph<char, u32, TrackingElement<[usize; 8], 4>, TrackingElement<[usize; 4], 4>>;
+
+    fn neighbors(
+        graph: &Graph,
+        key: &RawGraphNodeKey<
+            char,
+            u32,
+            TrackingElement<[usize; 8], 4>,
+            TrackingElement<[usize; 4], 4>,
+        >,
+    ) -> Vec<(char, u32)> {
+        graph
+            .neighbors(key)
+            .map(|(target, weight)| (*graph.node(&target).unwrap(), *weight))
+            .collect()
+    }
+
+    #[test]
+    fn add_remove() {
+        let mut graph = Graph::default();
+
+        let a = graph.try_add_node('a').unwrap();
+        let b = graph.try_add_node('b').unwrap();
+        let c = graph.try_add_node('c').unwrap();
+
+        graph.try_add_edge(&a, &b, 1).unwrap();
+        graph.try_add_edge(&a, &c, 2).unwrap();
+        graph.try_add_edge(&b, &c, 3).unwrap();
+        graph.try_add_edge(&c, &a, 4).unwrap();
+
+        assert_eq!(
+            Err(5),
+            graph
+                .try_add_edge(&c, &b, 5)
+                .map_err(InsertError::into_value)
+        );
+        assert_eq!("RawGraph{ nodes: 3, edges: 4 }", format!("{:?}", graph));
+
+        assert_eq!(vec![('c', 2), ('b', 1)], neighbors(&graph, &a));
+
+        assert_eq!(Some(2), graph.remove_edge(&a, &c));
+        assert_eq!(None, graph.remove_edge(&a, &c));
+        assert_eq!(vec![('b', 1)], neighbors(&graph, &a));
+
+        //  Removes `b -> c` and `c -> a` alongside.
+        assert_eq!(Some('c'), graph.remove_node(&c));
+        assert_eq!(None, graph.remove_node(&c));
+        assert_eq!(None, graph.node(&c));
+        assert_eq!(
+            Err(6),
+            graph
+                .try_add_edge(&a, &c, 6)
+                .map_err(InsertError::into_value)
+        );
+
+        assert_eq!(2, graph.node_count());
+        assert_eq!(1, graph.edge_count());
+        assert!(neighbors(&graph, &b).is_empty());
+
+        //  The slot of `c` is reused, yet `c` remains stale.
+        let d = graph.try_add_node('d').unwrap();
+
+        assert_eq!(None, graph.node(&c));
+
+        *graph.node_mut(&d).unwrap() = 'D';
+
+        let nodes: Vec<_> = graph.nodes().map(|(_, value)| *value).collect();
+
+        assert_eq!(vec!['D', 'b', 'a'], nodes);
+    }
+
+    #[test]
+    fn drop_values() {
+        let allocator = SpyAllocator::default();
+
+        let mut graph = RawGraph::<String, String, _, _>::new(
+            AllocStorage::new(allocator.clone()),
+            AllocStorage::new(allocator.clone()),
+        );
+
+        let a = graph.try_add_node("a".to_string()).unwrap();
+        let b = graph.try_add_node("b".to_string()).unwrap();
+        let c = graph.try_add_node("c".to_string()).unwrap();
+
+        graph.try_add_edge(&a, &b, "ab".to_string()).unwrap();
+        graph.try_add_edge(&b, &a, "ba".to_string()).unwrap();
+        graph.try_add_edge(&b, &c, "bc".to_string()).unwrap();
+
+        assert_eq!(Some("b".to_string()), graph.remove_node(&b));
+        assert_eq!(0, graph.edge_count());
+
+        graph.try_add_edge(&c, &a, "ca".to_string()).unwrap();
+
+        drop(graph);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
@@ -0,0 +1,597 @@
+//! Proof-of-Concept implementation of a HashMap parameterized by a Storage.
+
+use core::{
+    cmp,
+    fmt::{self, Debug},
+    hash::{BuildHasher, BuildHasherDefault, Hash, Hasher},
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+use crate::{
+    defaults::DefaultRangeStorage,
+    error::StorageError,
+    traits::{Capacity, RangeExt, RangeStorage},
+};
+
+use super::InsertError;
+
+/// A PoC HashMap, using open-addressing with linear probing.
+///
+/// The buckets live in a single range of the storage. Growing the range rehashes the entries in place, hence a
+/// storage able to grow in place never holds 2 copies of the buckets.
+///
+/// The map grows once more than 7/8th of its buckets are occupied, whether by entries or by the tombstones of removed
+/// entries. A storage unable to grow may nonetheless be filled to its last bucket.
+pub struct RawHashMap<
+    K,
+    V,
+    S: RangeStorage = DefaultRangeStorage,
+    H = BuildHasherDefault<FnvHasher>,
+> {
+    len: usize,
+    deleted: usize,
+    data: S::Handle<Bucket<K, V>>,
+    storage: S,
+    hasher: H,
+}
+
+impl<K, V, S: RangeStorage, H: Default> RawHashMap<K, V, S, H> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self::with_hasher(storage, H::default())
+    }
+}
+
+impl<K, V, S: RangeStorage, H> RawHashMap<K, V, S, H> {
+    /// Creates a new, empty, instance, hashing its keys with `hasher`.
+    pub fn with_hasher(storage: S, hasher: H) -> Self {
+        let data = storage.dangling();
+
+        let mut result = Self {
+            len: 0,
+            deleted: 0,
+            data,
+            storage,
+            hasher,
+        };
+
+        //  Inline storages may provide buckets from the get go.
+        result.initialize(0);
+
+        result
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of entries in `self`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of buckets of `self`, that is the maximum number of entries it holds without growing.
+    pub fn capacity(&self) -> usize {
+        //  Safety:
+        //  -   `self.data` is valid.
+        unsafe { self.storage.get(&self.data) }.len()
+    }
+
+    /// Returns an iterator over the entries, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.buckets().iter().filter_map(|bucket| match bucket {
+            Bucket::Full(key, value) => Some((key, value)),
+            _ => None,
+        })
+    }
+
+    /// Clears `self`, destroying all entries.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.deleted = 0;
+
+        for bucket in self.buckets_mut() {
+            *bucket = Bucket::Empty;
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, S: RangeStorage, H: BuildHasher> RawHashMap<K, V, S, H> {
+    /// Attempts to reserve buckets for at least `additional` more entries, without exceeding the load factor.
+    ///
+    /// Tombstones are purged first, growing only if still necessary.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), StorageError> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or_else(|| oversized::<K, V>(usize::MAX))?;
+        let capacity = self.capacity();
+
+        if required + self.deleted <= max_load(capacity) {
+            return Ok(());
+        }
+
+        if required <= max_load(capacity) {
+            self.rehash(capacity);
+            return Ok(());
+        }
+
+        let buckets = required
+            .checked_mul(8)
+            .ok_or_else(|| oversized::<K, V>(usize::MAX))?
+            / 7
+            + 1;
+        let maximum = self.storage.maximum_capacity::<Bucket<K, V>>().into_usize();
+
+        if buckets > maximum {
+            return Err(oversized::<K, V>(buckets));
+        }
+
+        let new_capacity = cmp::min(cmp::max(buckets, capacity * 2), maximum);
+        let new_capacity =
+            S::Capacity::from_usize(new_capacity).ok_or_else(|| oversized::<K, V>(new_capacity))?;
+
+        //  Safety:
+        //  -   `self.data` is a valid handle pointing to valid data.
+        self.data = unsafe { self.storage.try_grow(&self.data, new_capacity)? };
+
+        self.rehash(capacity);
+
+        Ok(())
+    }
+
+    /// Returns a reference to the value associated to `key`, if any.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let index = self.find(key).ok()?;
+
+        match &self.buckets()[index] {
+            Bucket::Full(_, value) => Some(value),
+            _ => unreachable!("Found bucket is full"),
+        }
+    }
+
+    /// Returns a mutable reference to the value associated to `key`, if any.
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let index = self.find(key).ok()?;
+
+        match &mut self.buckets_mut()[index] {
+            Bucket::Full(_, value) => Some(value),
+            _ => unreachable!("Found bucket is full"),
+        }
+    }
+
+    /// Returns whether an entry is associated to `key`, or not.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_ok()
+    }
+
+    /// Attempts to associate `value` to `key`, returning the previously associated value, if any.
+    ///
+    /// On failure, `self` is left unchanged, and `key` and `value` are returned.
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<Option<V>, InsertError<(K, V)>> {
+        if let Some(previous) = self.get_mut(&key) {
+            return Ok(Some(mem::replace(previous, value)));
+        }
+
+        //  Growing is opportunistic, a storage unable to grow may still have a free bucket.
+        let reserved = self.try_reserve(1);
+
+        let index = match (self.find(&key), reserved) {
+            (Err(Some(index)), _) => index,
+            (_, Err(error)) => return Err(InsertError::new((key, value), error)),
+            (_, Ok(())) => unreachable!("Reserved a free bucket"),
+        };
+
+        if let Bucket::Deleted =
+            mem::replace(&mut self.buckets_mut()[index], Bucket::Full(key, value))
+        {
+            self.deleted -= 1;
+        }
+
+        self.len += 1;
+
+        Ok(None)
+    }
+
+    /// Associates `value` to `key`, returning the previously associated value, if any.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new entry.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.try_insert(key, value).expect("Sufficient capacity")
+    }
+
+    /// Removes the entry associated to `key`, if any, and returns its value.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.find(key).ok()?;
+
+        self.len -= 1;
+        self.deleted += 1;
+
+        match mem::replace(&mut self.buckets_mut()[index], Bucket::Deleted) {
+            Bucket::Full(_, value) => Some(value),
+            _ => unreachable!("Found bucket is full"),
+        }
+    }
+}
+
+impl<K: Debug, V: Debug, S: RangeStorage, H> Debug for RawHashMap<K, V, S, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S: Default + RangeStorage, H: Default> Default for RawHashMap<K, V, S, H> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<K, V, S: RangeStorage, H> Drop for RawHashMap<K, V, S, H> {
+    fn drop(&mut self) {
+        let buckets: *mut [Bucket<K, V>] = self.buckets_mut();
+
+        //  Safety:
+        //  -   `buckets` are all initialized, and no longer accessible.
+        //  -   `self.data` is valid.
+        unsafe {
+            ptr::drop_in_place(buckets);
+            self.storage.deallocate(&self.data);
+        }
+    }
+}
+
+/// A PoC HashMap storage helper.
+///
+/// Reserves enough space for storing a bucket of a map from `K` to `V`.
+pub struct RawHashMapBucketStorage<K, V>(MaybeUninit<Bucket<K, V>>);
+
+/// The 64-bits FNV-1a hash function, the default hasher of `RawHashMap`.
+///
+/// The function is fast on short keys, and requires neither state nor randomness, hence is available without `std`.
+/// It is however trivially susceptible to collision attacks, and maps exposed to untrusted keys should use a keyed
+/// hasher instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+//
+//  Implementation
+//
+
+enum Bucket<K, V> {
+    Empty,
+    Deleted,
+    Full(K, V),
+    //  An entry yet to be moved to its position for the current capacity, only present during `rehash`.
+    Displaced(K, V),
+}
+
+//  Returns the maximum number of occupied buckets, amongst `capacity`.
+fn max_load(capacity: usize) -> usize {
+    capacity - capacity / 8
+}
+
+//  Returns the error reported when `buckets` buckets exceed what the map, or its storage, can hold.
+fn oversized<K, V>(buckets: usize) -> StorageError {
+    StorageError::array::<Bucket<K, V>>(buckets).with_context("RawHashMap")
+}
+
+impl<K, V, S: RangeStorage, H> RawHashMap<K, V, S, H> {
+    fn buckets(&self) -> &[Bucket<K, V>] {
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   All buckets are initialized, by invariant.
+        //  -   Mutable references to the buckets only ever exist while `self` is mutably borrowed.
+        unsafe {
+            let buckets = self.storage.slice_ref(&self.data, 0, self.capacity());
+            MaybeUninit::slice_assume_init_ref(buckets)
+        }
+    }
+
+    fn buckets_mut(&mut self) -> &mut [Bucket<K, V>] {
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   All buckets are initialized, by invariant.
+        //  -   `&mut self` guarantees that no other reference to the buckets is alive.
+        unsafe {
+            let buckets = self.storage.slice_mut(&self.data, 0, self.capacity());
+            MaybeUninit::slice_assume_init_mut(buckets)
+        }
+    }
+
+    //  Initializes the buckets from `initialized` onwards, as empty.
+    fn initialize(&mut self, initialized: usize) {
+        let capacity = self.capacity();
+
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   `initialized..capacity` is within bounds.
+        let buckets = unsafe {
+            self.storage
+                .slice_mut(&self.data, initialized, capacity - initialized)
+        };
+
+        for bucket in buckets {
+            bucket.write(Bucket::Empty);
+        }
+    }
+}
+
+impl<K: Eq + Hash, V, S: RangeStorage, H: BuildHasher> RawHashMap<K, V, S, H> {
+    fn hash(&self, key: &K) -> usize {
+        let mut hasher = self.hasher.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish() as usize
+    }
+
+    //  Returns the index of the bucket of `key`, if any, or otherwise the index of the first free bucket it may be
+    //  inserted in, if any.
+    fn find(&self, key: &K) -> Result<usize, Option<usize>> {
+        let buckets = self.buckets();
+        let capacity = buckets.len();
+
+        if capacity == 0 {
+            return Err(None);
+        }
+
+        //  Reduced first, lest the probe sequence overflow.
+        let start = self.hash(key) % capacity;
+        let mut free = None;
+
+        for offset in 0..capacity {
+            let index = (start + offset) % capacity;
+
+            match &buckets[index] {
+                Bucket::Empty => return Err(free.or(Some(index))),
+                Bucket::Deleted => free = free.or(Some(index)),
+                Bucket::Full(candidate, _) if candidate == key => return Ok(index),
+                Bucket::Full(..) | Bucket::Displaced(..) => (),
+            }
+        }
+
+        Err(free)
+    }
+
+    //  Moves every entry to its position for the current capacity, purging the tombstones.
+    //
+    //  The buckets from `initialized` onwards are uninitialized, and initialized as empty first.
+    fn rehash(&mut self, initialized: usize) {
+        self.initialize(initialized);
+        self.deleted = 0;
+
+        for bucket in self.buckets_mut() {
+            *bucket = match mem::replace(bucket, Bucket::Empty) {
+                Bucket::Full(key, value) => Bucket::Displaced(key, value),
+                _ => Bucket::Empty,
+            };
+        }
+
+        let capacity = self.capacity();
+
+        for index in 0..capacity {
+            if !matches!(self.buckets()[index], Bucket::Displaced(..)) {
+                continue;
+            }
+
+            let (mut key, mut value) =
+                match mem::replace(&mut self.buckets_mut()[index], Bucket::Empty) {
+                    Bucket::Displaced(key, value) => (key, value),
+                    _ => unreachable!("Checked bucket is displaced"),
+                };
+
+            //  Place the entry in the first bucket which is either empty, or displaced; in the latter case, the
+            //  displaced entry is picked up in turn. Each step turns a displaced bucket into a full one, hence this
+            //  terminates.
+            loop {
+                let start = self.hash(&key) % capacity;
+                let buckets = self.buckets_mut();
+
+                let target = (0..capacity)
+                    .map(|offset| (start + offset) % capacity)
+                    .find(|index| !matches!(buckets[*index], Bucket::Full(..)))
+                    .expect("Fewer entries than buckets");
+
+                match mem::replace(&mut buckets[target], Bucket::Full(key, value)) {
+                    Bucket::Displaced(k, v) => {
+                        key = k;
+                        value = v;
+                    }
+                    _ => break,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_inline {
+
+    use crate::inline::NonTrackingRange;
+
+    use super::*;
+
+    type Map = RawHashMap<u32, u32, NonTrackingRange<u8, RawHashMapBucketStorage<u32, u32>, 4>>;
+
+    #[test]
+    fn smoke_test() {
+        let mut map = Map::default();
+
+        assert_eq!(4, map.capacity());
+        assert_eq!(None, map.get(&1));
+
+        assert_eq!(None, map.insert(1, 10));
+        assert_eq!(None, map.insert(2, 20));
+        assert_eq!(Some(10), map.insert(1, 11));
+
+        assert_eq!(2, map.len());
+        assert_eq!(Some(&11), map.get(&1));
+        assert_eq!(Some(&20), map.get(&2));
+        assert!(!map.contains_key(&3));
+
+        *map.get_mut(&2).unwrap() = 21;
+
+        assert_eq!(Some(21), map.remove(&2));
+        assert_eq!(None, map.remove(&2));
+        assert_eq!(1, map.len());
+    }
+
+    #[test]
+    fn fill_to_capacity() {
+        let mut map = Map::default();
+
+        //  The load factor is exceeded, but the storage cannot grow, hence the last bucket is used nonetheless.
+        for i in 0..4 {
+            map.insert(i, i * 10);
+        }
+
+        let error = map.try_insert(4, 40).unwrap_err();
+
+        assert_eq!((4, 40), error.into_value());
+        assert_eq!(Some(20), map.insert(2, 21));
+
+        let mut entries: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+        entries.sort();
+
+        assert_eq!(vec![(0, 0), (1, 10), (2, 21), (3, 30)], entries);
+    }
+
+    #[test]
+    fn tombstones() {
+        let mut map = Map::default();
+
+        //  Churning through keys leaves tombstones behind, which are purged in place.
+        for i in 0..32 {
+            map.insert(i, i);
+            map.insert(i + 100, i);
+
+            assert_eq!(Some(i), map.remove(&i));
+            assert_eq!(Some(i), map.remove(&(i + 100)));
+        }
+
+        assert!(map.is_empty());
+
+        for i in 0..4 {
+            map.insert(i, i);
+        }
+
+        assert_eq!(4, map.len());
+    }
+} // mod test_inline
+
+#[cfg(test)]
+mod test_allocator {
+
+    use crate::allocator::AllocStorage;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    //  Hashes a `u64` to itself.
+    #[derive(Default)]
+    struct IdentityHasher(u64);
+
+    impl Hasher for IdentityHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {
+            unimplemented!("Only u64 keys are supported")
+        }
+
+        fn write_u64(&mut self, value: u64) {
+            self.0 = value;
+        }
+    }
+
+    #[test]
+    fn hash_near_max() {
+        type Map =
+            RawHashMap<u64, u64, AllocStorage<SpyAllocator>, BuildHasherDefault<IdentityHasher>>;
+
+        let mut map = Map::new(AllocStorage::default());
+
+        //  Colliding keys, whose hashes are near `u64::MAX`, through growth and rehashing.
+        for i in 0..32 {
+            let key = u64::MAX - 4 * i;
+
+            assert_eq!(None, map.insert(key, i));
+        }
+
+        for i in 0..32 {
+            assert_eq!(Some(&i), map.get(&(u64::MAX - 4 * i)));
+        }
+    }
+
+    #[test]
+    fn grow() {
+        let allocator = SpyAllocator::default();
+        let mut map = RawHashMap::<String, usize, _>::new(AllocStorage::new(allocator.clone()));
+
+        assert_eq!(0, map.capacity());
+
+        for i in 0..100 {
+            assert_eq!(None, map.insert(i.to_string(), i));
+        }
+
+        assert_eq!(100, map.len());
+        assert!(100 <= max_load(map.capacity()));
+
+        for i in 0..100 {
+            assert_eq!(Some(&i), map.get(&i.to_string()));
+        }
+
+        for i in (0..100).step_by(2) {
+            assert_eq!(Some(i), map.remove(&i.to_string()));
+        }
+
+        assert_eq!(None, map.get(&"42".to_string()));
+        assert_eq!(Some(&43), map.get(&"43".to_string()));
+
+        drop(map);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn debug() {
+        let mut map = RawHashMap::<&str, u32, _>::new(AllocStorage::new(SpyAllocator::default()));
+
+        map.insert("one", 1);
+
+        assert_eq!("{\"one\": 1}", format!("{:?}", map));
+
+        map.clear();
+
+        assert_eq!("{}", format!("{:?}", map));
+    }
+
+    #[test]
+    fn allocation_failure() {
+        let mut map = RawHashMap::<u32, u32, _>::new(AllocStorage::new(NonAllocator));
+
+        assert_eq!((1, 2), map.try_insert(1, 2).unwrap_err().into_value());
+        assert!(map.is_empty());
+    }
+} // mod test_allocator
@@ -0,0 +1,177 @@
+//! Proof-of-Concept implementation of a HashSet parameterized by a Storage.
+
+use core::{
+    fmt::{self, Debug},
+    hash::{BuildHasher, BuildHasherDefault, Hash},
+};
+
+use crate::{defaults::DefaultRangeStorage, error::StorageError, traits::RangeStorage};
+
+use super::{FnvHasher, InsertError, RawHashMap};
+
+/// A PoC HashSet, a `RawHashMap` of `T` to `()`.
+///
+/// The buckets may be sized with `RawHashMapBucketStorage<T, ()>`.
+pub struct RawHashSet<T, S: RangeStorage = DefaultRangeStorage, H = BuildHasherDefault<FnvHasher>> {
+    map: RawHashMap<T, (), S, H>,
+}
+
+impl<T, S: RangeStorage, H: Default> RawHashSet<T, S, H> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self {
+            map: RawHashMap::new(storage),
+        }
+    }
+}
+
+impl<T, S: RangeStorage, H> RawHashSet<T, S, H> {
+    /// Creates a new, empty, instance, hashing its elements with `hasher`.
+    pub fn with_hasher(storage: S, hasher: H) -> Self {
+        Self {
+            map: RawHashMap::with_hasher(storage, hasher),
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns the number of elements in `self`.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns the number of buckets of `self`, that is the maximum number of elements it holds without growing.
+    pub fn capacity(&self) -> usize {
+        self.map.capacity()
+    }
+
+    /// Returns an iterator over the elements, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.map.iter().map(|(element, _)| element)
+    }
+
+    /// Clears `self`, destroying all elements.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+impl<T: Eq + Hash, S: RangeStorage, H: BuildHasher> RawHashSet<T, S, H> {
+    /// Attempts to reserve buckets for at least `additional` more elements, see `RawHashMap::try_reserve`.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), StorageError> {
+        self.map.try_reserve(additional)
+    }
+
+    /// Returns whether `self` contains `value`, or not.
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    /// Attempts to insert `value`, returning whether it was newly inserted.
+    ///
+    /// An element equal to `value` is left in place, and `value` is dropped.
+    ///
+    /// On failure, `self` is left unchanged, and `value` is returned.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, InsertError<T>> {
+        if self.map.contains_key(&value) {
+            return Ok(false);
+        }
+
+        match self.map.try_insert(value, ()) {
+            Ok(_) => Ok(true),
+            Err(error) => {
+                let cause = error.error();
+                Err(InsertError::new(error.into_value().0, cause))
+            }
+        }
+    }
+
+    /// Inserts `value`, returning whether it was newly inserted.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn insert(&mut self, value: T) -> bool {
+        self.try_insert(value).expect("Sufficient capacity")
+    }
+
+    /// Removes `value`, returning whether it was present.
+    pub fn remove(&mut self, value: &T) -> bool {
+        self.map.remove(value).is_some()
+    }
+}
+
+impl<T: Debug, S: RangeStorage, H> Debug for RawHashSet<T, S, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S: Default + RangeStorage, H: Default> Default for RawHashSet<T, S, H> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::RawHashMapBucketStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn inline() {
+        type Set = RawHashSet<u16, NonTrackingRange<u8, RawHashMapBucketStorage<u16, ()>, 2>>;
+
+        let mut set = Set::default();
+
+        assert!(set.insert(1));
+        assert!(!set.insert(1));
+        assert!(set.insert(2));
+
+        assert_eq!(3, set.try_insert(3).unwrap_err().into_value());
+
+        assert!(set.contains(&2));
+        assert!(set.remove(&2));
+        assert!(!set.remove(&2));
+        assert!(!set.contains(&2));
+
+        assert_eq!("{1}", format!("{:?}", set));
+    }
+
+    #[test]
+    fn allocator() {
+        let allocator = SpyAllocator::default();
+        let mut set = RawHashSet::<String, _>::new(AllocStorage::new(allocator.clone()));
+
+        for word in "the quick brown fox jumps over the lazy dog".split(' ') {
+            set.insert(word.to_string());
+        }
+
+        assert_eq!(8, set.len());
+        assert!(set.contains(&"fox".to_string()));
+
+        let mut words: Vec<_> = set.iter().cloned().collect();
+        words.sort();
+
+        assert_eq!(
+            ["brown", "dog", "fox", "jumps", "lazy", "over", "quick", "the"],
+            &words[..]
+        );
+
+        set.clear();
+
+        assert!(set.is_empty());
+
+        drop(set);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
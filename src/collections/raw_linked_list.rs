@@ -1,38 +1,85 @@
 //! Proof-of-Concept implementation of a LinkedList parameterized by a Storage.
 
 use core::{
+    alloc::Layout,
     fmt::{self, Debug},
+    hash::{Hash, Hasher},
     marker::PhantomData,
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
     ptr,
 };
 
 use rfc2580::Pointee;
 
-use crate::traits::ElementStorage;
+use crate::{
+    defaults::DefaultElementStorage,
+    handle_map::HandleMap,
+    inline::{FreeListOrder, NonTrackingRange, TrackingElement},
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::ElementStorage,
+};
+
+use super::{ExtendError, InsertError};
 
 /// A PoC LinkedList.
-pub struct RawLinkedList<T: Pointee, S: ElementStorage> {
+///
+/// The list keeps track of both its front and back nodes, hence the back node is referred to by 2 handles, and the
+/// handles of the storage must be `Copy` in order to insert elements at the back.
+pub struct RawLinkedList<T: Pointee, S: ElementStorage = DefaultElementStorage> {
     next: Option<S::Handle<RawLinkedListNode<T, S>>>,
+    //  The handle of the back node, if known; `None` if the list is empty, or if the handle could not be duplicated.
+    tail: Option<S::Handle<RawLinkedListNode<T, S>>>,
+    len: usize,
     storage: S,
     _marker: PhantomData<T>,
 }
 
 impl<T: Pointee, S: ElementStorage> RawLinkedList<T, S> {
+    /// The layout of a node, that is of an element and the link to the next node.
+    pub const NODE_LAYOUT: Layout = Layout::new::<RawLinkedListNode<T, S>>();
+
+    /// The layout of a link to a node, as stored in the list itself, and in each node.
+    pub const LINK_LAYOUT: Layout = Layout::new::<Option<S::Handle<RawLinkedListNode<T, S>>>>();
+
     /// Creates a new instance from `storage`.
     pub fn new(storage: S) -> Self {
         Self {
             next: None,
+            tail: None,
+            len: 0,
             storage,
             _marker: PhantomData,
         }
     }
 
+    /// Returns whether the list is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements in the list, in `O(1)`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     /// Clears all the elements from the list, leading to an empty list.
     pub fn clear(&mut self) {
         while let Some(_) = self.pop() {}
     }
 
+    /// Destroys all the elements of the list, and returns the storage for reuse.
+    pub fn into_storage(mut self) -> S {
+        self.clear();
+
+        //  Safety:
+        //  -   `self.storage` is valid.
+        //  -   The immediate `forget` avoids double-frees, and the list is empty, hence nothing leaks.
+        let storage = unsafe { ptr::read(&self.storage) };
+        mem::forget(self);
+
+        storage
+    }
+
     /// Returns a reference to the front element of the list, if any.
     pub fn front(&self) -> Option<&T> {
         unsafe {
@@ -51,17 +98,69 @@ impl<T: Pointee, S: ElementStorage> RawLinkedList<T, S> {
         }
     }
 
-    /// Pushes a new element to the front of the list.
-    pub fn push(&mut self, value: T) -> Result<(), T> {
+    /// Returns a reference to the back element of the list, if any.
+    ///
+    /// This is `O(1)` if the back node is known, and `O(n)` otherwise, see `try_push_back`.
+    pub fn back(&self) -> Option<&T> {
+        unsafe {
+            let pointer = self.back_node()?;
+            let node = &*pointer;
+            Some(&node.element)
+        }
+    }
+
+    /// Returns a mutable reference to the back element of the list, if any.
+    ///
+    /// This is `O(1)` if the back node is known, and `O(n)` otherwise, see `try_push_back`.
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        unsafe {
+            let pointer = self.back_node()?;
+            let node = &mut *pointer;
+            Some(&mut node.element)
+        }
+    }
+
+    /// Attempts to push a new element to the front of the list.
+    ///
+    /// On failure, `self` is left unchanged, and `value` is returned.
+    pub fn try_push(&mut self, value: T) -> Result<(), InsertError<T>> {
         let node = RawLinkedListNode {
             next: self.next.take(),
             element: value,
         };
-        let handle = self.storage.create(node).map_err(|node| node.element)?;
 
-        self.next = Some(handle);
+        match self.storage.try_create(node) {
+            Ok(handle) => {
+                //  Pushing into an empty list leaves the back node unknown, as its handle cannot be duplicated.
+                self.next = Some(handle);
+                self.len += 1;
+                Ok(())
+            }
+            Err((node, error)) => {
+                self.next = node.next;
+                Err(InsertError::new(node.element, error))
+            }
+        }
+    }
 
-        Ok(())
+    /// Pushes a new element to the front of the list.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn push(&mut self, value: T) {
+        self.try_push(value).expect("Sufficient capacity");
+    }
+
+    /// Returns the layout of the memory slot holding each node, as reported by the storage, if known.
+    pub fn slot_layout(&self) -> Option<Layout> {
+        self.storage.slot_layout::<RawLinkedListNode<T, S>>()
+    }
+
+    /// Returns the memory overhead of each element, that is the size of its slot beyond that of the element, if known.
+    pub fn overhead_per_element(&self) -> Option<usize> {
+        self.slot_layout()
+            .map(|layout| layout.size() - mem::size_of::<T>())
     }
 
     /// Pops the front element of the list, if any, and returns it if it succeeded.
@@ -78,37 +177,237 @@ impl<T: Pointee, S: ElementStorage> RawLinkedList<T, S> {
             self.storage.deallocate(&handle);
 
             self.next = node.next;
+            self.len -= 1;
+
+            if self.next.is_none() {
+                self.tail = None;
+            }
+
             node.element
         })
     }
-}
 
-impl<T: Debug + Pointee, S: ElementStorage> Debug for RawLinkedList<T, S> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "[")?;
+    /// Retains only the elements for which `f` returns `true`, unlinking and destroying the others, from front to back.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let storage = &mut self.storage;
+        let mut link: *mut Option<_> = &mut self.next;
 
-        let mut next = self.next.as_ref();
-        if let Some(handle) = next {
-            unsafe {
-                let element = self.storage.get(&handle);
-                let node = element.as_ref();
+        //  Safety:
+        //  -   `link` points either to `self.next`, or to the `next` field of a live node.
+        //  -   Deallocating a node does not relocate the others.
+        while let Some(handle) = unsafe { &*link } {
+            let node = unsafe { storage.get(handle).as_ptr() };
 
-                write!(f, "{:?}", &node.element)?;
-                next = node.next.as_ref();
+            if f(unsafe { &(*node).element }) {
+                link = unsafe { &mut (*node).next };
+                continue;
             }
-        }
 
-        while let Some(handle) = next {
-            unsafe {
-                let element = self.storage.get(&handle);
-                let node = element.as_ref();
+            //  Unlinked first, and forgotten as the back node if need be, so that the list remains consistent should the
+            //  destructor panic.
+            let removed = unsafe { mem::replace(&mut *link, (*node).next.take()) };
 
-                write!(f, ", {:?}", &node.element)?;
-                next = node.next.as_ref();
+            if unsafe { (*link).is_none() } {
+                self.tail = None;
             }
+
+            if let Some(handle) = removed {
+                self.len -= 1;
+                unsafe { storage.destroy(&handle) };
+            }
+        }
+    }
+
+    /// Returns an iterator over the elements of the list, from front to back.
+    pub fn iter(&self) -> RawLinkedListIter<'_, T, S> {
+        RawLinkedListIter {
+            storage: &self.storage,
+            next: self.next.as_ref(),
+        }
+    }
+
+    /// Returns an iterator over mutable references to the elements of the list, from front to back.
+    pub fn iter_mut(&mut self) -> RawLinkedListIterMut<'_, T, S> {
+        RawLinkedListIterMut {
+            storage: &self.storage,
+            next: self.next.as_ref(),
+        }
+    }
+
+    /// Returns a cursor positioned on the front element of the list, or on the "ghost" position if the list is empty.
+    pub fn cursor_front(&self) -> RawLinkedListCursor<'_, T, S> {
+        RawLinkedListCursor {
+            list: self,
+            current: self.next.as_ref(),
+            index: 0,
+        }
+    }
+}
+
+impl<T: Pointee, S: ElementStorage> RawLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    /// Returns a mutable cursor positioned on the front element of the list, or on the "ghost" position if the list
+    /// is empty.
+    pub fn cursor_front_mut(&mut self) -> RawLinkedListCursorMut<'_, T, S> {
+        RawLinkedListCursorMut {
+            list: self,
+            prev: None,
+            index: 0,
+        }
+    }
+
+    /// Attempts to push a new element to the back of the list, in `O(1)`.
+    ///
+    /// The back node is looked up from the front, in `O(n)`, if unknown, that is after pushing to the front of an empty
+    /// list, or after `retain` removed the back element.
+    ///
+    /// On failure, `self` is left unchanged, and `value` is returned.
+    pub fn try_push_back(&mut self, value: T) -> Result<(), InsertError<T>> {
+        let node = RawLinkedListNode {
+            next: None,
+            element: value,
+        };
+
+        let handle = self
+            .storage
+            .try_create(node)
+            .map_err(|(node, error)| InsertError::new(node.element, error))?;
+
+        match self.tail_handle() {
+            //  Safety:
+            //  -   `tail` is valid, as the back node of the list; it is resolved after the allocation, which may have
+            //      relocated the nodes.
+            Some(tail) => unsafe { (*self.storage.get(&tail).as_ptr()).next = Some(handle) },
+            None => self.next = Some(handle),
+        }
+
+        self.tail = Some(handle);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Pushes a new element to the back of the list, in `O(1)`.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn push_back(&mut self, value: T) {
+        self.try_push_back(value).expect("Sufficient capacity");
+    }
+
+    /// Attempts to create a new list from `storage`, containing the elements of `iter`, in order.
+    ///
+    /// On failure, the list is destroyed, and the error reports how many elements were inserted before the storage
+    /// filled up.
+    pub fn from_iter_in<I: IntoIterator<Item = T>>(
+        iter: I,
+        storage: S,
+    ) -> Result<Self, ExtendError<T>> {
+        let mut list = Self::new(storage);
+
+        list.try_extend(iter)?;
+
+        Ok(list)
+    }
+
+    /// Attempts to append the elements of `iter` at the back of the list, in order, and returns how many were
+    /// appended.
+    ///
+    /// On failure, the elements appended so far are kept, and the error reports how many there are, along with the
+    /// element which could not be appended. The remaining elements are left in `iter`, if passed by reference.
+    pub fn try_extend<I: IntoIterator<Item = T>>(
+        &mut self,
+        iter: I,
+    ) -> Result<usize, ExtendError<T>> {
+        let mut inserted = 0;
+
+        for value in iter {
+            self.try_push_back(value)
+                .map_err(|error| ExtendError::new(inserted, error))?;
+
+            inserted += 1;
+        }
+
+        Ok(inserted)
+    }
+}
+
+impl<T: Clone + Pointee, S: ElementStorage> RawLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    /// Attempts to clone the list, allocating fresh nodes from `storage`, which may be a clone of a shared storage.
+    ///
+    /// On failure, the partial clone is destroyed, and the error reports how many elements were cloned before the
+    /// storage filled up.
+    pub fn try_clone_in(&self, storage: S) -> Result<Self, ExtendError<T>> {
+        Self::from_iter_in(self.iter().cloned(), storage)
+    }
+
+    /// Attempts to clone the list, allocating fresh nodes from a default-constructed storage.
+    ///
+    /// On failure, the partial clone is destroyed, and the error reports how many elements were cloned before the
+    /// storage filled up.
+    pub fn try_clone(&self) -> Result<Self, ExtendError<T>>
+    where
+        S: Default,
+    {
+        self.try_clone_in(S::default())
+    }
+}
+
+impl<T: Pointee, S, const N: usize, O: FreeListOrder> RawLinkedList<T, TrackingElement<S, N, O>> {
+    /// Compacts the nodes of the list to the front of the storage, patching the links as necessary.
+    pub fn compact(&mut self) {
+        let mut relocated = HandleMap::<NonTrackingRange<usize, usize, N>>::default();
+
+        //  Safety:
+        //  -   Nodes are never pinned.
+        //  -   All handles are patched below, before any further use.
+        unsafe { self.storage.compact_into(&mut relocated) }.expect("Sufficient capacity");
+
+        let storage = &self.storage;
+        let mut link: *mut Option<_> = &mut self.next;
+
+        //  Safety:
+        //  -   `link` points either to `self.next`, or to the `next` field of a live node.
+        //  -   Each handle is patched prior to being used.
+        while let Some(handle) = unsafe { &mut *link } {
+            *handle = relocated.remap(handle);
+
+            let node = unsafe { storage.get(handle) };
+            link = unsafe { &mut (*node.as_ptr()).next };
         }
 
-        write!(f, "]")
+        self.tail = self.tail.map(|tail| relocated.remap(&tail));
+    }
+}
+
+impl<T: Clone + Pointee, S: Default + ElementStorage> Clone for RawLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    //  Panics if the storage cannot accomodate all the elements, use `try_clone` to recover.
+    fn clone(&self) -> Self {
+        self.try_clone().expect("Sufficient capacity")
+    }
+}
+
+impl<T: Debug + Pointee, S: ElementStorage> Debug for RawLinkedList<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Pointee, S: ElementStorage + MemoryUsage> MemoryUsage for RawLinkedList<T, S> {
+    fn mem_usage(&self) -> MemUsage {
+        let storage = self.storage.mem_usage();
+        let inline = storage.inline_bytes + mem::size_of::<Self>() - mem::size_of::<S>();
+
+        MemUsage::with_payload(inline, storage.heap_bytes, self.len * mem::size_of::<T>())
     }
 }
 
@@ -124,83 +423,1290 @@ impl<T: Pointee, S: ElementStorage> Drop for RawLinkedList<T, S> {
     }
 }
 
-/// A PoC LinkedList storage helper.
+impl<T: Pointee, S: ElementStorage> Extend<T> for RawLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    //  Panics if the storage cannot accomodate all the elements, use `try_extend` to recover.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.try_extend(iter).expect("Sufficient capacity");
+    }
+}
+
+impl<T: Eq + Pointee, S: ElementStorage> Eq for RawLinkedList<T, S> {}
+
+impl<T: Hash + Pointee, S: ElementStorage> Hash for RawLinkedList<T, S> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+
+        for element in self {
+            element.hash(state);
+        }
+    }
+}
+
+impl<T, U, S, OS> PartialEq<RawLinkedList<U, OS>> for RawLinkedList<T, S>
+where
+    T: PartialEq<U> + Pointee,
+    U: Pointee,
+    S: ElementStorage,
+    OS: ElementStorage,
+{
+    fn eq(&self, other: &RawLinkedList<U, OS>) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Pointee, S: ElementStorage> IntoIterator for RawLinkedList<T, S> {
+    type Item = T;
+    type IntoIter = RawLinkedListIntoIter<T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        RawLinkedListIntoIter { list: self }
+    }
+}
+
+impl<'a, T: Pointee, S: ElementStorage> IntoIterator for &'a RawLinkedList<T, S> {
+    type Item = &'a T;
+    type IntoIter = RawLinkedListIter<'a, T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T: Pointee, S: ElementStorage> IntoIterator for &'a mut RawLinkedList<T, S> {
+    type Item = &'a mut T;
+    type IntoIter = RawLinkedListIterMut<'a, T, S>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An iterator over the elements of a `RawLinkedList`, see `RawLinkedList::iter`.
+pub struct RawLinkedListIter<'a, T: Pointee, S: ElementStorage> {
+    storage: &'a S,
+    next: Option<&'a S::Handle<RawLinkedListNode<T, S>>>,
+}
+
+impl<'a, T: Pointee + 'a, S: ElementStorage> Iterator for RawLinkedListIter<'a, T, S> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        //  Safety:
+        //  -   `self.next` is valid, as all handles reachable from the list, which is borrowed for `'a`.
+        let node = unsafe { &*self.storage.get(self.next?).as_ptr() };
+
+        self.next = node.next.as_ref();
+
+        Some(&node.element)
+    }
+}
+
+/// An iterator over mutable references to the elements of a `RawLinkedList`, see `RawLinkedList::iter_mut`.
+pub struct RawLinkedListIterMut<'a, T: Pointee, S: ElementStorage> {
+    storage: &'a S,
+    next: Option<&'a S::Handle<RawLinkedListNode<T, S>>>,
+}
+
+impl<'a, T: Pointee + 'a, S: ElementStorage> Iterator for RawLinkedListIterMut<'a, T, S> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        //  Safety:
+        //  -   `self.next` is valid, as all handles reachable from the list, which is mutably borrowed for `'a`.
+        //  -   Each node is visited once, hence the references to its element are unique.
+        let node = unsafe { self.storage.get(self.next?).as_ptr() };
+
+        //  Safety:
+        //  -   `node` is valid, and its link and element are disjoint.
+        unsafe {
+            self.next = (*node).next.as_ref();
+
+            Some(&mut (*node).element)
+        }
+    }
+}
+
+/// An iterator moving the elements out of a `RawLinkedList`, from front to back.
+pub struct RawLinkedListIntoIter<T: Pointee, S: ElementStorage> {
+    list: RawLinkedList<T, S>,
+}
+
+impl<T: Pointee, S: ElementStorage> Iterator for RawLinkedListIntoIter<T, S> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.list.pop()
+    }
+}
+
+/// A cursor over the elements of a `RawLinkedList`, see `RawLinkedList::cursor_front`.
 ///
-/// Reserves enough space for storing a list node containing `T`, for a handle of size similar to `H`.
-pub struct RawLinkedListNodeStorage<T, H>(Option<H>, MaybeUninit<T>);
+/// Past the back element of the list lies a "ghost" position, pointing to no element; moving past it wraps around to
+/// the front of the list.
+pub struct RawLinkedListCursor<'a, T: Pointee, S: ElementStorage> {
+    list: &'a RawLinkedList<T, S>,
+    current: Option<&'a NodeHandle<T, S>>,
+    index: usize,
+}
 
-//
-//  Implementation
-//
+impl<'a, T: Pointee, S: ElementStorage> RawLinkedListCursor<'a, T, S> {
+    /// Returns the index of the current element, or `None` if on the "ghost" position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
 
-struct RawLinkedListNode<T, S: ElementStorage> {
-    next: Option<S::Handle<Self>>,
-    element: T,
+    /// Returns a reference to the current element, or `None` if on the "ghost" position.
+    pub fn current(&self) -> Option<&'a T> {
+        self.current.map(|handle| &self.node(handle).element)
+    }
+
+    /// Returns a reference to the element following the current one, or to the front element if on the "ghost"
+    /// position.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        let next = match self.current {
+            Some(handle) => self.node(handle).next.as_ref(),
+            None => self.list.next.as_ref(),
+        };
+
+        next.map(|handle| &self.node(handle).element)
+    }
+
+    /// Moves the cursor to the next element, or to the "ghost" position if on the back element.
+    ///
+    /// If on the "ghost" position, moves to the front element instead.
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(handle) => {
+                self.current = self.node(handle).next.as_ref();
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.next.as_ref();
+                self.index = 0;
+            }
+        }
+    }
 }
 
-#[cfg(test)]
-mod test_inline {
+/// A cursor over the elements of a `RawLinkedList`, able to edit the list, see `RawLinkedList::cursor_front_mut`.
+///
+/// Past the back element of the list lies a "ghost" position, pointing to no element; moving past it wraps around to
+/// the front of the list.
+///
+/// The cursor remembers the node preceding its position by handle, rather than by pointer, as inserting an element
+/// may relocate the existing nodes within the storage.
+pub struct RawLinkedListCursorMut<'a, T: Pointee, S: ElementStorage> {
+    list: &'a mut RawLinkedList<T, S>,
+    //  The node preceding the current position, or `None` if the current position is the front of the list.
+    prev: Option<NodeHandle<T, S>>,
+    //  The index of the current position; the "ghost" position is at the length of the list.
+    index: usize,
+}
 
-    use crate::inline::TrackingElement;
+impl<'a, T: Pointee, S: ElementStorage> RawLinkedListCursorMut<'a, T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    /// Returns the index of the current element, or `None` if on the "ghost" position.
+    pub fn index(&self) -> Option<usize> {
+        self.current_handle().map(|_| self.index)
+    }
 
-    use super::*;
+    /// Returns a mutable reference to the current element, or `None` if on the "ghost" position.
+    pub fn current(&mut self) -> Option<&mut T> {
+        let handle = self.current_handle()?;
 
-    #[test]
-    fn smoke_test() {
-        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
-        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+        //  Safety:
+        //  -   `handle` is valid, as reachable from the list.
+        unsafe { Some(&mut (*self.node(&handle)).element) }
+    }
 
-        let mut list = List::default();
+    /// Returns a mutable reference to the element following the current one, or to the front element if on the
+    /// "ghost" position.
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        //  Safety:
+        //  -   The link following the current position is valid, as are the handles reachable from the list.
+        unsafe {
+            let handle = (*self.next_link())?;
+
+            Some(&mut (*self.node(&handle)).element)
+        }
+    }
 
-        list.push(1).unwrap();
-        list.push(2).unwrap();
+    /// Moves the cursor to the next element, or to the "ghost" position if on the back element.
+    ///
+    /// If on the "ghost" position, moves to the front element instead.
+    pub fn move_next(&mut self) {
+        match self.current_handle() {
+            Some(handle) => {
+                self.prev = Some(handle);
+                self.index += 1;
+            }
+            None => {
+                self.prev = None;
+                self.index = 0;
+            }
+        }
+    }
 
-        assert_eq!(Some(&2), list.front());
+    /// Attempts to insert a new element after the current one, or at the front of the list if on the "ghost"
+    /// position.
+    ///
+    /// The cursor remains on the current element, or on the "ghost" position.
+    ///
+    /// On failure, the list is left unchanged, and `value` is returned.
+    pub fn try_insert_after(&mut self, value: T) -> Result<(), InsertError<T>> {
+        let handle = self.create(value)?;
+        let ghost = self.current_handle().is_none();
 
-        *list.front_mut().unwrap() = 3;
+        //  Safety:
+        //  -   The link is derived after the allocation, which may have relocated the nodes.
+        //  -   `handle` is valid, and not linked yet.
+        unsafe {
+            let link = self.next_link();
 
-        assert_eq!(Some(3), list.pop());
-        assert_eq!(Some(&1), list.front());
+            (*self.node(&handle)).next = (*link).take();
+            *link = Some(handle);
+
+            self.link_tail(handle);
+        }
+
+        self.list.len += 1;
+
+        if ghost {
+            //  If the list was empty, the "ghost" position now follows the new element.
+            if self.prev.is_none() {
+                self.prev = Some(handle);
+            }
+
+            self.index += 1;
+        }
+
+        Ok(())
     }
-} // mod test_inline
 
-#[cfg(test)]
-mod test_allocator {
+    /// Inserts a new element after the current one, or at the front of the list if on the "ghost" position.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn insert_after(&mut self, value: T) {
+        self.try_insert_after(value).expect("Sufficient capacity");
+    }
 
-    use crate::allocator::AllocStorage;
-    use crate::utils::{NonAllocator, SpyAllocator};
+    /// Attempts to insert a new element before the current one, or at the back of the list if on the "ghost"
+    /// position.
+    ///
+    /// The cursor remains on the current element, or on the "ghost" position.
+    ///
+    /// On failure, the list is left unchanged, and `value` is returned.
+    pub fn try_insert_before(&mut self, value: T) -> Result<(), InsertError<T>> {
+        let handle = self.create(value)?;
 
-    use super::*;
+        //  Safety:
+        //  -   The link is derived after the allocation, which may have relocated the nodes.
+        //  -   `handle` is valid, and not linked yet.
+        unsafe {
+            let link = self.link();
 
-    #[test]
-    fn smoke_test() {
-        type List = RawLinkedList<String, AllocStorage<SpyAllocator>>;
+            (*self.node(&handle)).next = (*link).take();
+            *link = Some(handle);
 
-        let allocator = SpyAllocator::default();
-        let mut list = List::new(AllocStorage::new(allocator.clone()));
+            self.link_tail(handle);
+        }
 
-        list.push("Hello".to_string()).unwrap();
-        list.push("World".to_string()).unwrap();
+        self.list.len += 1;
 
-        assert_eq!(2, allocator.allocated());
-        assert_eq!(0, allocator.deallocated());
+        self.prev = Some(handle);
+        self.index += 1;
 
-        assert_eq!(Some(&"World".to_string()), list.front());
+        Ok(())
+    }
 
-        *list.front_mut().unwrap() = "All".to_string();
+    /// Inserts a new element before the current one, or at the back of the list if on the "ghost" position.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn insert_before(&mut self, value: T) {
+        self.try_insert_before(value).expect("Sufficient capacity");
+    }
 
-        assert_eq!(Some("All".to_string()), list.pop());
-        assert_eq!(Some(&"Hello".to_string()), list.front());
-        assert_eq!(2, allocator.allocated());
-        assert_eq!(1, allocator.deallocated());
+    /// Removes the current element from the list, and returns it, or returns `None` if on the "ghost" position.
+    ///
+    /// The cursor moves to the next element, or to the "ghost" position if the removed element was the back one.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let handle = self.current_handle()?;
+
+        //  Safety:
+        //  -   `handle` is valid, and is unlinked immediately after being moved out of the storage.
+        unsafe {
+            let node = ptr::read(self.node(&handle));
+            self.list.storage.deallocate(&handle);
+
+            if node.next.is_none() {
+                self.list.tail = self.prev;
+            }
+
+            *self.link() = node.next;
+            self.list.len -= 1;
+
+            Some(node.element)
+        }
     }
 
-    #[test]
-    fn allocation_failure() {
-        type List = RawLinkedList<&'static str, AllocStorage<NonAllocator>>;
+    /// Moves all the elements of `other` after the current element, or at the front of the list if on the "ghost"
+    /// position, in `O(1)`.
+    ///
+    /// The cursor remains on the current element, or on the "ghost" position.
+    ///
+    /// #   Safety
+    ///
+    /// The nodes of `other` are relinked, rather than moved, hence:
+    ///
+    /// -   The storage of `self` must be able to resolve, and deallocate, the handles of `other`, such as when both
+    ///     lists use clones of the same shared storage, for example `Rc<RefCell<S>>`.
+    /// -   The storage of `other` must not deallocate those nodes when dropped.
+    pub unsafe fn splice_after(&mut self, mut other: RawLinkedList<T, S>) {
+        let last = other.tail_handle();
 
-        let mut list = List::default();
+        let (first, last) = match (other.next.take(), last) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+
+        let count = mem::replace(&mut other.len, 0);
+        other.tail = None;
+
+        let ghost = self.current_handle().is_none();
+        let link = self.next_link();
+
+        (*self.node(&last)).next = (*link).take();
+        *link = Some(first);
 
-        list.push("Caramba").unwrap_err();
+        self.link_tail(last);
+        self.list.len += count;
+
+        if ghost {
+            //  If the list was empty, the "ghost" position now follows the spliced elements.
+            if self.prev.is_none() {
+                self.prev = Some(last);
+            }
+
+            self.index += count;
+        }
+    }
+
+    /// Splits the list after the current element, returning a new list, backed by `storage`, containing all the
+    /// following elements.
+    ///
+    /// If on the "ghost" position, the whole list is moved out, leaving an empty list.
+    ///
+    /// #   Safety
+    ///
+    /// The nodes are relinked, rather than moved, hence `storage` must be able to resolve, and deallocate, the handles
+    /// of the storage of `self`, such as when both are clones of the same shared storage, for example
+    /// `Rc<RefCell<S>>`.
+    pub unsafe fn split_after(&mut self, storage: S) -> RawLinkedList<T, S> {
+        let mut list = RawLinkedList::new(storage);
+
+        let kept = match self.current_handle() {
+            Some(_) => self.index + 1,
+            None => {
+                self.prev = None;
+                self.index = 0;
+                0
+            }
+        };
+
+        list.next = (*self.next_link()).take();
+
+        if list.next.is_some() {
+            list.tail = self.list.tail;
+            list.len = self.list.len - kept;
+
+            self.list.tail = self.current_handle();
+            self.list.len = kept;
+        }
+
+        list
+    }
+}
+
+/// A PoC LinkedList storage helper.
+///
+/// Reserves enough space for storing a list node containing `T`, for a handle of size similar to `H`.
+pub struct RawLinkedListNodeStorage<T, H>(Option<H>, MaybeUninit<T>);
+
+//
+//  Implementation
+//
+
+/// A node of a `RawLinkedList`, exposed as its handles appear in the bounds of the cursors.
+pub struct RawLinkedListNode<T, S: ElementStorage> {
+    next: Option<S::Handle<Self>>,
+    element: T,
+}
+
+type NodeHandle<T, S> = <S as ElementStorage>::Handle<RawLinkedListNode<T, S>>;
+
+impl<T: Pointee, S: ElementStorage> RawLinkedList<T, S> {
+    //  Internal: returns a pointer to the back node, if any, looking it up from the front if unknown.
+    fn back_node(&self) -> Option<*mut RawLinkedListNode<T, S>> {
+        let mut handle = self.tail.as_ref().or(self.next.as_ref())?;
+
+        loop {
+            //  Safety:
+            //  -   `handle` is valid, as all handles reachable from the list.
+            let node = unsafe { self.storage.get(handle).as_ptr() };
+
+            match unsafe { &(*node).next } {
+                Some(next) => handle = next,
+                None => return Some(node),
+            }
+        }
+    }
+}
+
+impl<T: Pointee, S: ElementStorage> RawLinkedList<T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    //  Internal: returns the handle of the back node, if any, looking it up from the front, and recording it, if
+    //  unknown.
+    fn tail_handle(&mut self) -> Option<NodeHandle<T, S>> {
+        if self.tail.is_none() {
+            let mut handle = self.next?;
+
+            //  Safety:
+            //  -   `handle` is valid, as all handles reachable from the list.
+            while let Some(next) = unsafe { (*self.storage.get(&handle).as_ptr()).next } {
+                handle = next;
+            }
+
+            self.tail = Some(handle);
+        }
+
+        self.tail
+    }
+}
+
+impl<'a, T: Pointee, S: ElementStorage> RawLinkedListCursor<'a, T, S> {
+    //  Internal: returns a reference to the node of `handle`.
+    fn node(&self, handle: &'a NodeHandle<T, S>) -> &'a RawLinkedListNode<T, S> {
+        //  Safety:
+        //  -   `handle` is valid, as all handles reachable from the list, which is borrowed for `'a`.
+        unsafe { &*self.list.storage.get(handle).as_ptr() }
+    }
+}
+
+impl<'a, T: Pointee, S: ElementStorage> RawLinkedListCursorMut<'a, T, S>
+where
+    NodeHandle<T, S>: Copy,
+{
+    //  Internal: returns a pointer to the node of `handle`.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn node(&self, handle: &NodeHandle<T, S>) -> *mut RawLinkedListNode<T, S> {
+        self.list.storage.get(handle).as_ptr()
+    }
+
+    //  Internal: returns the handle of the current element, if any.
+    fn current_handle(&self) -> Option<NodeHandle<T, S>> {
+        match &self.prev {
+            //  Safety:
+            //  -   `prev` is valid, as reachable from the list.
+            Some(prev) => unsafe { (*self.node(prev)).next },
+            None => self.list.next,
+        }
+    }
+
+    //  Internal: returns a pointer to the link to the current position.
+    //
+    //  The pointer is invalidated by any allocation or deallocation.
+    fn link(&mut self) -> *mut Option<NodeHandle<T, S>> {
+        match self.prev {
+            //  Safety:
+            //  -   `prev` is valid, as reachable from the list.
+            Some(prev) => unsafe { &mut (*self.node(&prev)).next },
+            None => &mut self.list.next,
+        }
+    }
+
+    //  Internal: returns a pointer to the link following the current element, or to the front link if on the "ghost"
+    //  position.
+    //
+    //  The pointer is invalidated by any allocation or deallocation.
+    fn next_link(&mut self) -> *mut Option<NodeHandle<T, S>> {
+        match self.current_handle() {
+            //  Safety:
+            //  -   `current` is valid, as reachable from the list.
+            Some(current) => unsafe { &mut (*self.node(&current)).next },
+            None => &mut self.list.next,
+        }
+    }
+
+    //  Internal: records `handle` as the back node of the list, if it was linked last.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn link_tail(&mut self, handle: NodeHandle<T, S>) {
+        if (*self.node(&handle)).next.is_none() {
+            self.list.tail = Some(handle);
+        }
+    }
+
+    //  Internal: creates a new node, without linking it.
+    fn create(&mut self, element: T) -> Result<NodeHandle<T, S>, InsertError<T>> {
+        let node = RawLinkedListNode {
+            next: None,
+            element,
+        };
+
+        self.list
+            .storage
+            .try_create(node)
+            .map_err(|(node, error)| InsertError::new(node.element, error))
+    }
+}
+
+#[cfg(test)]
+mod test_inline {
+
+    use core::cell::RefCell;
+
+    use alloc::rc::Rc;
+
+    use crate::collections::FnvHasher;
+    use crate::inline::TrackingElement;
+
+    use super::*;
+
+    #[test]
+    fn smoke_test() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::default();
+
+        list.try_push(1).unwrap();
+        list.try_push(2).unwrap();
+
+        assert_eq!(Some(&2), list.front());
+
+        *list.front_mut().unwrap() = 3;
+
+        assert_eq!(Some(3), list.pop());
+        assert_eq!(Some(&1), list.front());
+    }
+
+    #[test]
+    fn compact() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::default();
+
+        list.try_push(1).unwrap();
+        list.try_push(2).unwrap();
+        list.try_push(3).unwrap();
+
+        list.compact();
+
+        assert_eq!("[3, 2, 1]", format!("{:?}", list));
+
+        assert_eq!(Some(3), list.pop());
+        list.try_push(4).unwrap();
+
+        assert_eq!("[4, 2, 1]", format!("{:?}", list));
+    }
+
+    #[test]
+    fn iterators() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::default();
+
+        list.push(1);
+        list.push(2);
+        list.push(3);
+
+        assert!(list.iter().eq(&[3, 2, 1]));
+
+        for element in &mut list {
+            *element *= 10;
+        }
+
+        assert_eq!(60, (&list).into_iter().copied().sum::<u8>());
+        assert_eq!(vec![30, 20, 10], list.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn cursor() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::default();
+
+        list.push(3);
+        list.push(2);
+        list.push(1);
+
+        let mut cursor = list.cursor_front();
+
+        assert_eq!(
+            (Some(0), Some(&1), Some(&2)),
+            (cursor.index(), cursor.current(), cursor.peek_next())
+        );
+
+        cursor.move_next();
+        cursor.move_next();
+
+        assert_eq!(
+            (Some(2), Some(&3), None),
+            (cursor.index(), cursor.current(), cursor.peek_next())
+        );
+
+        cursor.move_next();
+
+        assert_eq!(
+            (None, None, Some(&1)),
+            (cursor.index(), cursor.current(), cursor.peek_next())
+        );
+
+        cursor.move_next();
+
+        assert_eq!((Some(0), Some(&1)), (cursor.index(), cursor.current()));
+    }
+
+    #[test]
+    fn cursor_mut() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 8>>;
+
+        let mut list = List::default();
+
+        list.push(3);
+        list.push(2);
+        list.push(1);
+
+        let mut cursor = list.cursor_front_mut();
+
+        cursor.insert_after(10);
+        cursor.move_next();
+
+        assert_eq!((Some(1), Some(&mut 10)), (cursor.index(), cursor.current()));
+
+        cursor.insert_before(5);
+
+        assert_eq!((Some(2), Some(&mut 10)), (cursor.index(), cursor.current()));
+        assert_eq!(Some(10), cursor.remove_current());
+        assert_eq!((Some(2), Some(&mut 2)), (cursor.index(), cursor.current()));
+
+        cursor.move_next();
+        cursor.move_next();
+
+        assert_eq!((None, Some(&mut 1)), (cursor.index(), cursor.peek_next()));
+
+        //  On the "ghost" position, inserting before appends, and inserting after prepends.
+        cursor.insert_before(4);
+        cursor.insert_after(0);
+        cursor.move_next();
+
+        assert_eq!((Some(0), Some(&mut 0)), (cursor.index(), cursor.current()));
+        assert_eq!("[0, 1, 5, 2, 3, 4]", format!("{:?}", list));
+    }
+
+    #[test]
+    fn cursor_mut_empty() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 2>>;
+
+        let mut list = List::default();
+
+        let mut cursor = list.cursor_front_mut();
+
+        assert_eq!(None, cursor.remove_current());
+
+        cursor.insert_after(1);
+        cursor.insert_after(2);
+
+        assert_eq!((None, None), (cursor.index(), cursor.current()));
+
+        let error = cursor.try_insert_before(3).unwrap_err();
+
+        assert_eq!(3, error.into_value());
+
+        cursor.move_next();
+
+        assert_eq!(Some(2), cursor.remove_current());
+        assert_eq!(Some(1), cursor.remove_current());
+        assert_eq!((None, None), (cursor.index(), cursor.current()));
+        assert_eq!("[]", format!("{:?}", list));
+    }
+
+    #[test]
+    fn extend() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::from_iter_in(vec![1, 2], TrackingElement::new()).unwrap();
+
+        list.extend(Some(3));
+
+        let mut iter = vec![4, 5, 6].into_iter();
+        let error = list.try_extend(&mut iter).unwrap_err();
+
+        assert_eq!((1, 5), (error.inserted(), error.into_value()));
+        assert_eq!(vec![6], iter.collect::<Vec<_>>());
+        assert_eq!("[1, 2, 3, 4]", format!("{:?}", list));
+
+        assert_eq!(
+            Ok(0),
+            list.try_extend(None).map_err(|error| error.inserted())
+        );
+
+        let error = List::from_iter_in(0..6, TrackingElement::new()).unwrap_err();
+
+        assert_eq!((4, 4), (error.inserted(), error.into_value()));
+    }
+
+    #[test]
+    fn retain() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 6>>;
+
+        let mut list = List::default();
+
+        for i in 1..=6 {
+            list.push(i);
+        }
+
+        list.retain(|element| element % 2 == 0);
+
+        assert_eq!("[6, 4, 2]", format!("{:?}", list));
+        assert_eq!((3, Some(&2)), (list.len(), list.back()));
+
+        //  The slots of the removed nodes are available again.
+        for i in 7..=9 {
+            list.push(i);
+        }
+
+        list.retain(|element| *element > 4);
+
+        assert_eq!("[9, 8, 7, 6]", format!("{:?}", list));
+        assert_eq!(Some(&6), list.back());
+
+        list.retain(|_| false);
+
+        assert_eq!(None, list.back());
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn clone() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let list = List::from_iter_in(1..=3, TrackingElement::new()).unwrap();
+        let mut clone = list.clone();
+
+        *clone.front_mut().unwrap() = 0;
+        clone.push_back(4);
+
+        assert_eq!("[1, 2, 3]", format!("{:?}", list));
+        assert_eq!("[0, 2, 3, 4]", format!("{:?}", clone));
+        assert_eq!(4, clone.len());
+    }
+
+    #[test]
+    fn try_clone_in_shared() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type Shared = Rc<RefCell<TrackingElement<NodeStorage, 4>>>;
+        type List = RawLinkedList<u8, Shared>;
+
+        let storage = Shared::default();
+        let list = List::from_iter_in(1..=3, storage.clone()).unwrap();
+
+        //  Only a single slot remains in the shared storage.
+        let error = list.try_clone_in(storage.clone()).unwrap_err();
+
+        assert_eq!((1, 2), (error.inserted(), error.into_value()));
+
+        //  The partial clone was destroyed, releasing its slot.
+        let error = list.try_clone_in(storage).unwrap_err();
+
+        assert_eq!(1, error.inserted());
+        assert_eq!("[1, 2, 3]", format!("{:?}", list));
+    }
+
+    #[test]
+    fn eq_hash() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        fn hash(list: &List) -> u64 {
+            let mut hasher = FnvHasher::default();
+            list.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let list = List::from_iter_in(1..=3, TrackingElement::new()).unwrap();
+        let mut other = List::from_iter_in(1..=2, TrackingElement::new()).unwrap();
+
+        assert_ne!(list, other);
+
+        other.push_back(3);
+
+        assert_eq!(list, other);
+        assert_eq!(hash(&list), hash(&other));
+
+        *other.back_mut().unwrap() = 4;
+
+        assert_ne!(list, other);
+        assert_ne!(hash(&list), hash(&other));
+    }
+
+    #[test]
+    fn retain_panic() {
+        use std::panic::{self, AssertUnwindSafe};
+
+        //  Panics when dropped, if odd.
+        struct Picky(u8);
+
+        impl Drop for Picky {
+            fn drop(&mut self) {
+                if self.0 % 2 == 1 && !std::thread::panicking() {
+                    panic!("Odd");
+                }
+            }
+        }
+
+        type NodeStorage = RawLinkedListNodeStorage<Picky, usize>;
+        type List = RawLinkedList<Picky, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::default();
+
+        list.push_back(Picky(2));
+        list.push_back(Picky(3));
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| list.retain(|e| e.0 == 2)));
+
+        assert!(result.is_err());
+
+        //  The back node was unlinked, and forgotten as such, before its destructor panicked.
+        assert_eq!((1, Some(2)), (list.len(), list.back().map(|e| e.0)));
+
+        list.push_back(Picky(4));
+
+        assert_eq!(vec![2, 4], list.iter().map(|e| e.0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn push_back() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::default();
+
+        assert_eq!(None, list.back());
+
+        list.push(2);
+        list.push_back(3);
+        list.push(1);
+
+        assert_eq!((Some(&1), Some(&3)), (list.front(), list.back()));
+
+        *list.back_mut().unwrap() = 4;
+        list.push_back(5);
+
+        assert_eq!("[1, 2, 4, 5]", format!("{:?}", list));
+        assert_eq!(4, list.len());
+        assert_eq!(6, list.try_push_back(6).unwrap_err().into_value());
+
+        //  Used as a FIFO queue.
+        assert_eq!(Some(1), list.pop());
+        list.push_back(6);
+        assert_eq!(Some(2), list.pop());
+
+        assert_eq!("[4, 5, 6]", format!("{:?}", list));
+
+        list.clear();
+
+        assert_eq!(None, list.back());
+
+        list.push_back(7);
+
+        assert_eq!((Some(&7), Some(&7)), (list.front(), list.back()));
+    }
+
+    #[test]
+    fn cursor_mut_back() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 4>>;
+
+        let mut list = List::default();
+
+        list.push(1);
+
+        {
+            let mut cursor = list.cursor_front_mut();
+
+            cursor.insert_after(2);
+
+            cursor.move_next();
+            assert_eq!(Some(2), cursor.remove_current());
+        }
+
+        assert_eq!((1, Some(&1)), (list.len(), list.back()));
+
+        {
+            let mut cursor = list.cursor_front_mut();
+
+            cursor.move_next();
+            cursor.insert_before(3);
+        }
+
+        assert_eq!((2, Some(&3)), (list.len(), list.back()));
+
+        {
+            let mut cursor = list.cursor_front_mut();
+
+            assert_eq!(Some(1), cursor.remove_current());
+            assert_eq!(Some(3), cursor.remove_current());
+        }
+
+        assert_eq!(None, list.back());
+
+        list.push_back(4);
+
+        assert_eq!("[4]", format!("{:?}", list));
+        assert_eq!(1, list.len());
+    }
+
+    #[test]
+    fn try_push_full() {
+        type NodeStorage = RawLinkedListNodeStorage<u8, usize>;
+        type List = RawLinkedList<u8, TrackingElement<NodeStorage, 2>>;
+
+        let mut list = List::default();
+
+        list.push(1);
+        list.push(2);
+
+        let error = list.try_push(3).unwrap_err();
+
+        assert_eq!(3, error.into_value());
+        assert_eq!("[2, 1]", format!("{:?}", list));
+    }
+} // mod test_inline
+
+#[cfg(test)]
+mod test_allocator {
+
+    use core::cell::RefCell;
+
+    use alloc::rc::Rc;
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::TrackingElement;
+    use crate::small::SingleElement;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    #[test]
+    fn non_copy_handles() {
+        //  The inline alternative is too small for any node, hence all nodes are allocated.
+        type List = RawLinkedList<u32, SingleElement<u8, SpyAllocator>>;
+
+        let allocator = SpyAllocator::default();
+        let mut list = List::new(SingleElement::new(allocator.clone()));
+
+        list.push(1);
+        list.try_push(2).unwrap();
+        list.push(3);
+
+        assert_eq!(3, allocator.allocated());
+        assert_eq!((Some(&3), Some(&1)), (list.front(), list.back()));
+
+        list.retain(|e| e % 2 == 1);
+
+        assert_eq!(vec![3, 1], list.iter().copied().collect::<Vec<_>>());
+
+        list.retain(|e| *e != 1);
+
+        assert_eq!((1, Some(&3)), (list.len(), list.back()));
+
+        drop(list);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn smoke_test() {
+        type List = RawLinkedList<String, AllocStorage<SpyAllocator>>;
+
+        let allocator = SpyAllocator::default();
+        let mut list = List::new(AllocStorage::new(allocator.clone()));
+
+        list.try_push("Hello".to_string()).unwrap();
+        list.try_push("World".to_string()).unwrap();
+
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(0, allocator.deallocated());
+
+        assert_eq!(Some(&"World".to_string()), list.front());
+
+        *list.front_mut().unwrap() = "All".to_string();
+
+        assert_eq!(Some("All".to_string()), list.pop());
+        assert_eq!(Some(&"Hello".to_string()), list.front());
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn into_storage() {
+        type List = RawLinkedList<String, AllocStorage<SpyAllocator>>;
+
+        let allocator = SpyAllocator::default();
+        let mut list = List::new(AllocStorage::new(allocator.clone()));
+
+        list.try_push("Hello".to_string()).unwrap();
+        list.try_push("World".to_string()).unwrap();
+
+        let mut list = List::new(list.into_storage());
+
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(2, allocator.deallocated());
+
+        list.try_push("Again".to_string()).unwrap();
+
+        assert_eq!("[\"Again\"]", format!("{:?}", list));
+    }
+
+    #[test]
+    fn into_iter() {
+        type List = RawLinkedList<String, AllocStorage<SpyAllocator>>;
+
+        let allocator = SpyAllocator::default();
+        let mut list = List::new(AllocStorage::new(allocator.clone()));
+
+        list.push("World".to_string());
+        list.push("Hello".to_string());
+
+        let mut iter = list.into_iter();
+
+        assert_eq!(Some("Hello".to_string()), iter.next());
+
+        //  The remaining elements are destroyed along with the iterator.
+        drop(iter);
+
+        assert_eq!(2, allocator.deallocated());
+    }
+
+    #[test]
+    fn eq_across_storages() {
+        type NodeStorage = RawLinkedListNodeStorage<&'static str, usize>;
+        type Inline = RawLinkedList<&'static str, TrackingElement<NodeStorage, 2>>;
+
+        let mut list = RawLinkedList::<String, _>::new(AllocStorage::new(SpyAllocator::default()));
+
+        list.push("World".to_string());
+        list.push("Hello".to_string());
+
+        let mut inline =
+            Inline::from_iter_in(vec!["Hello", "World"], TrackingElement::new()).unwrap();
+
+        assert!(list == inline);
+
+        inline.pop();
+
+        assert!(list != inline);
+    }
+
+    #[test]
+    fn splice_and_split() {
+        type Shared = Rc<RefCell<AllocStorage<SpyAllocator>>>;
+        type List = RawLinkedList<String, Shared>;
+
+        let allocator = SpyAllocator::default();
+        let storage = Shared::new(RefCell::new(AllocStorage::new(allocator.clone())));
+
+        let mut list = List::new(storage.clone());
+        let mut other = List::new(storage.clone());
+
+        list.push("3".to_string());
+        list.push("1".to_string());
+        other.push("2b".to_string());
+        other.push("2a".to_string());
+
+        let mut cursor = list.cursor_front_mut();
+
+        //  Safety:
+        //  -   Both lists share the same storage.
+        unsafe { cursor.splice_after(other) };
+
+        cursor.move_next();
+        cursor.move_next();
+
+        assert_eq!(
+            (Some(2), Some(&mut "2b".to_string())),
+            (cursor.index(), cursor.current())
+        );
+
+        //  Safety:
+        //  -   Both lists share the same storage.
+        let tail = unsafe { cursor.split_after(storage.clone()) };
+
+        assert_eq!("[\"1\", \"2a\", \"2b\"]", format!("{:?}", list));
+        assert_eq!("[\"3\"]", format!("{:?}", tail));
+        assert_eq!(0, allocator.deallocated());
+        assert_eq!(Some(&"2b".to_string()), list.back());
+        assert_eq!(Some(&"3".to_string()), tail.back());
+        assert_eq!((3, 1), (list.len(), tail.len()));
+
+        drop(list);
+        drop(tail);
+
+        assert_eq!(4, allocator.allocated());
+        assert_eq!(4, allocator.deallocated());
+    }
+
+    #[test]
+    fn retain() {
+        type List = RawLinkedList<String, AllocStorage<SpyAllocator>>;
+
+        let allocator = SpyAllocator::default();
+        let mut list = List::new(AllocStorage::new(allocator.clone()));
+
+        for word in ["World", "Hello", "Goodbye"] {
+            list.push(word.to_string());
+        }
+
+        list.retain(|element| element != "Hello");
+
+        assert_eq!("[\"Goodbye\", \"World\"]", format!("{:?}", list));
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn allocation_failure() {
+        type List = RawLinkedList<&'static str, AllocStorage<NonAllocator>>;
+
+        let mut list = List::default();
+
+        list.try_push("Caramba").unwrap_err();
     }
 } // mod test_allocator
+
+#[cfg(test)]
+mod test_layout {
+
+    use alloc::alloc::Global;
+
+    use crate::allocator::{AllocStorage, CachingStorage};
+    use crate::fallback::FallbackElement;
+    use crate::inline::TrackingElement;
+
+    use super::*;
+
+    const WORD: usize = mem::size_of::<usize>();
+
+    type NodeStorage = RawLinkedListNodeStorage<usize, usize>;
+    type Inline = TrackingElement<NodeStorage, 4>;
+
+    type AllocList = RawLinkedList<usize, AllocStorage<Global>>;
+    type CachingList = RawLinkedList<[usize; 2], CachingStorage<Global>>;
+    type InlineList = RawLinkedList<usize, Inline>;
+    type FallbackList = RawLinkedList<usize, FallbackElement<Inline, AllocStorage<Global>>>;
+
+    //  Pointer-based handles benefit from the niche of `NonNull`.
+    const _: () = assert!(AllocList::LINK_LAYOUT.size() == WORD);
+    const _: () = assert!(AllocList::NODE_LAYOUT.size() == 2 * WORD);
+    const _: () = assert!(CachingList::LINK_LAYOUT.size() == WORD);
+    const _: () = assert!(CachingList::NODE_LAYOUT.size() == 3 * WORD);
+
+    //  Index-based handles do not, and the node storage helper must account for it.
+    const _: () = assert!(InlineList::LINK_LAYOUT.size() == 2 * WORD);
+    const _: () = assert!(InlineList::NODE_LAYOUT.size() == 3 * WORD);
+    const _: () = assert!(InlineList::NODE_LAYOUT.size() <= mem::size_of::<NodeStorage>());
+    const _: () = assert!(mem::size_of::<Inline>() == 14 * WORD);
+
+    //  The discriminant of the handle is stored alongside the larger of both handles, and doubles as the niche of the
+    //  link.
+    const _: () = assert!(FallbackList::LINK_LAYOUT.size() == 2 * WORD);
+
+    #[test]
+    fn alloc_overhead() {
+        let list = AllocList::default();
+
+        assert_eq!(Some(AllocList::NODE_LAYOUT), list.slot_layout());
+        assert_eq!(Some(WORD), list.overhead_per_element());
+    }
+
+    #[test]
+    fn caching_overhead() {
+        let list = CachingList::default();
+
+        //  The node is rounded up to the next power of 2.
+        assert_eq!(
+            Some(4 * WORD),
+            list.slot_layout().map(|layout| layout.size())
+        );
+        assert_eq!(Some(2 * WORD), list.overhead_per_element());
+    }
+
+    #[test]
+    fn inline_overhead() {
+        let list = InlineList::default();
+
+        assert_eq!(
+            Some(3 * WORD),
+            list.slot_layout().map(|layout| layout.size())
+        );
+        assert_eq!(Some(2 * WORD), list.overhead_per_element());
+
+        //  Nodes too large for the slots cannot be stored at all.
+        let list = RawLinkedList::<[usize; 4], Inline>::default();
+
+        assert_eq!(None, list.slot_layout());
+    }
+
+    #[test]
+    fn inline_mem_usage() {
+        let mut list = InlineList::default();
+
+        list.push(1);
+        list.push(2);
+
+        let usage = list.mem_usage();
+
+        assert_eq!(mem::size_of::<InlineList>(), usage.inline_bytes);
+        assert_eq!(0, usage.heap_bytes);
+        assert_eq!(2 * WORD, usage.payload_bytes());
+    }
+
+    #[test]
+    fn fallback_overhead() {
+        let list = FallbackList::new(FallbackElement::new(
+            Inline::default(),
+            AllocStorage::default(),
+        ));
+
+        //  The slots of the first storage are preferred.
+        assert_eq!(
+            Some(3 * WORD),
+            list.slot_layout().map(|layout| layout.size())
+        );
+        assert_eq!(Some(2 * WORD), list.overhead_per_element());
+    }
+} // mod test_layout
@@ -0,0 +1,347 @@
+//! Proof-of-Concept implementation of an object Pool parameterized by a Storage.
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    fmt::{self, Debug},
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+use crate::traits::ElementStorage;
+
+/// An object which may be reset, so as to be reused.
+pub trait Reusable {
+    /// Resets `self`, prior to its return to the pool.
+    fn reset(&mut self);
+}
+
+/// A PoC object Pool, handing out objects via RAII guards.
+///
+/// The objects are constructed by the factory when filling the pool, with `try_reserve`, and are then checked out
+/// and returned -- after being reset -- over and over again, without further allocation.
+///
+/// Filling the pool requires exclusive access, whilst checking objects out only requires shared access. This
+/// guarantees that no object is relocated by the storage whilst checked out.
+pub struct RawPool<T: Reusable, S: ElementStorage, F: FnMut() -> T> {
+    idle: Cell<Option<S::Handle<RawPoolSlot<T, S>>>>,
+    available: Cell<usize>,
+    total: usize,
+    storage: S,
+    factory: F,
+}
+
+impl<T: Reusable, S: ElementStorage, F: FnMut() -> T> RawPool<T, S, F> {
+    /// Creates a new, empty, instance, constructing its objects with `factory`.
+    pub fn new(storage: S, factory: F) -> Self {
+        Self {
+            idle: Cell::new(None),
+            available: Cell::new(0),
+            total: 0,
+            storage,
+            factory,
+        }
+    }
+
+    /// Returns the number of objects currently available for checkout.
+    pub fn available(&self) -> usize {
+        self.available.get()
+    }
+
+    /// Returns the number of objects owned by the pool, whether available or checked out.
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Attempts to construct `additional` more objects.
+    ///
+    /// On failure, the objects constructed so far are kept.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), T> {
+        for _ in 0..additional {
+            let slot = RawPoolSlot {
+                next: Cell::new(None),
+                value: UnsafeCell::new((self.factory)()),
+            };
+
+            let handle = self
+                .storage
+                .create(slot)
+                .map_err(|slot| slot.value.into_inner())?;
+
+            self.total += 1;
+
+            //  Safety:
+            //  -   `handle` is valid, fresh off the press.
+            unsafe { self.release(handle) };
+        }
+
+        Ok(())
+    }
+
+    /// Checks out an object, if any is available.
+    ///
+    /// The object is reset, and returned to the pool, when the guard is dropped.
+    pub fn checkout(&self) -> Option<PoolGuard<'_, T, S, F>> {
+        let handle = self.idle.take()?;
+
+        //  Safety:
+        //  -   `handle` is valid, as all handles in the idle list.
+        let slot = unsafe { self.slot(&handle) };
+
+        self.idle.set(slot.next.take());
+        self.available.set(self.available.get() - 1);
+
+        Some(PoolGuard {
+            pool: self,
+            handle: Some(handle),
+        })
+    }
+
+    //  Internal: pushes the slot back onto the idle list.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid, and no longer referenced by any guard.
+    unsafe fn release(&self, handle: S::Handle<RawPoolSlot<T, S>>) {
+        self.slot(&handle).next.set(self.idle.take());
+
+        self.idle.set(Some(handle));
+        self.available.set(self.available.get() + 1);
+    }
+}
+
+impl<T: Reusable, S: ElementStorage, F: FnMut() -> T> Debug for RawPool<T, S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "RawPool{{ available: {}, total: {} }}",
+            self.available(),
+            self.total()
+        )
+    }
+}
+
+impl<T: Reusable, S: Default + ElementStorage, F: FnMut() -> T> RawPool<T, S, F> {
+    /// Creates a new, empty, instance, with a default storage.
+    pub fn with_factory(factory: F) -> Self {
+        Self::new(S::default(), factory)
+    }
+}
+
+impl<T: Reusable, S: ElementStorage, F: FnMut() -> T> Drop for RawPool<T, S, F> {
+    fn drop(&mut self) {
+        //  No guard may outlive the pool, hence all objects are idle.
+        let mut next = self.idle.take();
+
+        while let Some(handle) = next {
+            //  Safety:
+            //  -   `handle` is valid, as all handles in the idle list.
+            unsafe {
+                next = self.slot(&handle).next.take();
+                self.storage.destroy(&handle);
+            }
+        }
+    }
+}
+
+/// A guard over an object checked out of a `RawPool`, returning it to the pool on drop.
+pub struct PoolGuard<'a, T: Reusable, S: ElementStorage, F: FnMut() -> T> {
+    pool: &'a RawPool<T, S, F>,
+    //  Only ever `None` during `drop`.
+    handle: Option<S::Handle<RawPoolSlot<T, S>>>,
+}
+
+impl<'a, T: Reusable + Debug, S: ElementStorage, F: FnMut() -> T> Debug for PoolGuard<'a, T, S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", **self)
+    }
+}
+
+impl<'a, T: Reusable, S: ElementStorage, F: FnMut() -> T> Deref for PoolGuard<'a, T, S, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        //  Safety:
+        //  -   The handle is valid, and the slot is exclusively owned by `self`.
+        //  -   The storage cannot relocate the slot whilst the pool is borrowed.
+        unsafe { &*self.value() }
+    }
+}
+
+impl<'a, T: Reusable, S: ElementStorage, F: FnMut() -> T> DerefMut for PoolGuard<'a, T, S, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        //  Safety:
+        //  -   The handle is valid, and the slot is exclusively owned by `self`.
+        //  -   The storage cannot relocate the slot whilst the pool is borrowed.
+        unsafe { &mut *self.value() }
+    }
+}
+
+impl<'a, T: Reusable, S: ElementStorage, F: FnMut() -> T> Drop for PoolGuard<'a, T, S, F> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            //  Safety:
+            //  -   `handle` is valid, and the slot is exclusively owned by `self`.
+            unsafe {
+                (*self.pool.slot(&handle).value.get()).reset();
+                self.pool.release(handle);
+            }
+        }
+    }
+}
+
+/// A PoC Pool storage helper.
+///
+/// Reserves enough space for storing a pool slot containing `T`, for a handle of size similar to `H`.
+pub struct RawPoolSlotStorage<T, H>(Option<H>, MaybeUninit<T>);
+
+//
+//  Implementation
+//
+
+//  The slots are mutated through shared references to the pool, hence to the storage, and therefore only through
+//  their cells: the link by the pool, and the value by the guard holding the slot.
+struct RawPoolSlot<T, S: ElementStorage> {
+    next: Cell<Option<S::Handle<Self>>>,
+    value: UnsafeCell<T>,
+}
+
+impl<T: Reusable, S: ElementStorage, F: FnMut() -> T> RawPool<T, S, F> {
+    //  Internal: returns a reference to the slot of `handle`.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn slot(&self, handle: &S::Handle<RawPoolSlot<T, S>>) -> &RawPoolSlot<T, S> {
+        &*self.storage.get(handle).as_ptr()
+    }
+}
+
+impl<'a, T: Reusable, S: ElementStorage, F: FnMut() -> T> PoolGuard<'a, T, S, F> {
+    fn value(&self) -> *mut T {
+        let handle = self.handle.as_ref().expect("Handle is only taken in drop");
+
+        //  Safety:
+        //  -   `handle` is valid, as the guard is live.
+        unsafe { self.pool.slot(handle).value.get() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::TrackingElement;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    impl Reusable for String {
+        fn reset(&mut self) {
+            self.clear();
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Buffer(u32);
+
+    impl Reusable for Buffer {
+        fn reset(&mut self) {
+            self.0 = 0;
+        }
+    }
+
+    #[test]
+    fn inline() {
+        type Storage = TrackingElement<RawPoolSlotStorage<Buffer, usize>, 2>;
+
+        let mut pool = RawPool::<_, Storage, _>::with_factory(Buffer::default);
+
+        assert!(pool.checkout().is_none());
+
+        assert_eq!(Err(Buffer(0)), pool.try_reserve(3));
+        assert_eq!(2, pool.available());
+        assert_eq!(2, pool.total());
+
+        let mut first = pool.checkout().unwrap();
+        let mut second = pool.checkout().unwrap();
+
+        assert!(pool.checkout().is_none());
+
+        first.0 = 1;
+        second.0 = 2;
+
+        assert_eq!(Buffer(1), *first);
+        assert_eq!(Buffer(2), *second);
+
+        drop(first);
+
+        assert_eq!(1, pool.available());
+        assert_eq!(Buffer(0), *pool.checkout().unwrap());
+        assert_eq!("Buffer(2)", format!("{:?}", second));
+        assert_eq!("RawPool{ available: 1, total: 2 }", format!("{:?}", pool));
+    }
+
+    #[test]
+    fn reuse() {
+        let allocator = SpyAllocator::default();
+
+        let mut pool = RawPool::new(AllocStorage::new(allocator.clone()), || {
+            String::with_capacity(16)
+        });
+        pool.try_reserve(1).unwrap();
+
+        let allocated = allocator.allocated();
+
+        for i in 0..4 {
+            let mut guard = pool.checkout().unwrap();
+
+            assert!(guard.is_empty());
+
+            guard.push_str(&i.to_string());
+        }
+
+        assert_eq!(allocated, allocator.allocated());
+
+        drop(pool);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn inline_reuse() {
+        type Storage = TrackingElement<RawPoolSlotStorage<Buffer, usize>, 3>;
+
+        let mut pool = RawPool::<_, Storage, _>::with_factory(Buffer::default);
+        pool.try_reserve(3).unwrap();
+
+        for round in 1..4 {
+            let mut guards = [
+                pool.checkout().unwrap(),
+                pool.checkout().unwrap(),
+                pool.checkout().unwrap(),
+            ];
+
+            assert!(pool.checkout().is_none());
+
+            for (i, guard) in guards.iter_mut().enumerate() {
+                assert_eq!(Buffer(0), **guard);
+
+                guard.0 = round * 10 + i as u32;
+            }
+
+            assert_eq!(Buffer(round * 10 + 1), *guards[1]);
+            assert_eq!(0, pool.available());
+        }
+
+        assert_eq!(3, pool.available());
+        assert_eq!(3, pool.total());
+    }
+
+    #[test]
+    fn allocation_failure() {
+        let mut pool = RawPool::new(AllocStorage::new(NonAllocator), Buffer::default);
+
+        assert_eq!(Err(Buffer(0)), pool.try_reserve(1));
+        assert_eq!(0, pool.total());
+    }
+} // mod tests
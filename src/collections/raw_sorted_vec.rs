@@ -0,0 +1,221 @@
+//! Proof-of-Concept implementation of a sorted Vec parameterized by a Storage.
+
+use core::{
+    borrow::Borrow,
+    fmt::{self, Debug},
+    ops::Deref,
+};
+
+use crate::{defaults::DefaultRangeStorage, traits::RangeStorage};
+
+use super::{InsertError, RawVec};
+
+/// A PoC sorted Vec, with the semantics of a set.
+///
+/// The elements are kept sorted, and unique, within a single range, hence look-ups are binary searches over contiguous
+/// memory. For a small number of elements, this is typically faster than a node-based set, especially with an inline
+/// storage.
+///
+/// The elements are accessible as a sorted slice, but not mutably, so as to preserve the ordering.
+pub struct RawSortedVec<T: Ord, S: RangeStorage = DefaultRangeStorage> {
+    inner: RawVec<T, S>,
+}
+
+impl<T: Ord, S: RangeStorage> RawSortedVec<T, S> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self {
+            inner: RawVec::new(storage),
+        }
+    }
+
+    /// Returns the number of elements `self` can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns whether `self` contains `value`.
+    pub fn contains<Q: ?Sized + Ord>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.search(value).is_ok()
+    }
+
+    /// Returns a reference to the element equal to `value`, if any.
+    pub fn get<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        self.search(value).ok().map(|index| &self.inner[index])
+    }
+
+    /// Attempts to insert `value`, at its sorted position.
+    ///
+    /// Returns `Ok(true)` if `value` was inserted, `Ok(false)` if an equal element was already present, in which case
+    /// `value` is dropped, and an error holding `value` if `self` could not grow.
+    pub fn try_insert(&mut self, value: T) -> Result<bool, InsertError<T>> {
+        match self.search(&value) {
+            Ok(_) => Ok(false),
+            Err(index) => self.inner.try_insert(index, value).map(|_| true),
+        }
+    }
+
+    /// Removes the element equal to `value`, if any, and returns it.
+    pub fn remove<Q: ?Sized + Ord>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+    {
+        let index = self.search(value).ok()?;
+
+        Some(self.inner.remove(index))
+    }
+
+    /// Removes the smallest element, if any, and returns it.
+    pub fn pop_first(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.inner.remove(0))
+        }
+    }
+
+    /// Removes the greatest element, if any, and returns it.
+    pub fn pop_last(&mut self) -> Option<T> {
+        self.inner.pop()
+    }
+
+    /// Retains only the elements for which `f` returns `true`, destroying the others.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.inner.retain(f)
+    }
+
+    /// Clears `self`, destroying all elements.
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    /// Returns the underlying Vec, whose elements are sorted and unique.
+    pub fn into_inner(self) -> RawVec<T, S> {
+        self.inner
+    }
+
+    fn search<Q: ?Sized + Ord>(&self, value: &Q) -> Result<usize, usize>
+    where
+        T: Borrow<Q>,
+    {
+        self.inner
+            .binary_search_by(|element| element.borrow().cmp(value))
+    }
+}
+
+impl<T: Ord + Debug, S: RangeStorage> Debug for RawSortedVec<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", self.inner)
+    }
+}
+
+impl<T: Ord, S: Default + RangeStorage> Default for RawSortedVec<T, S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<T: Ord, S: RangeStorage> Deref for RawSortedVec<T, S> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<T: Ord, S: RangeStorage> From<RawVec<T, S>> for RawSortedVec<T, S> {
+    /// Sorts, and deduplicates, the elements of `vec`.
+    fn from(mut vec: RawVec<T, S>) -> Self {
+        vec.sort_unstable();
+        vec.dedup();
+
+        Self { inner: vec }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    #[test]
+    fn inline() {
+        type Set = RawSortedVec<u8, NonTrackingRange<u8, u8, 4>>;
+
+        let mut set = Set::default();
+
+        assert_eq!(Ok(true), set.try_insert(3));
+        assert_eq!(Ok(true), set.try_insert(1).map_err(InsertError::into_value));
+        assert_eq!(Ok(false), set.try_insert(3));
+        assert_eq!(Ok(true), set.try_insert(2));
+        assert_eq!(Ok(true), set.try_insert(4));
+        assert_eq!(Err(0), set.try_insert(0).map_err(InsertError::into_value));
+        assert_eq!(Ok(false), set.try_insert(4));
+
+        assert_eq!([1, 2, 3, 4], *set);
+        assert!(set.contains(&2));
+        assert!(!set.contains(&0));
+
+        assert_eq!(Some(2), set.remove(&2));
+        assert_eq!(None, set.remove(&2));
+        assert_eq!(Some(1), set.pop_first());
+        assert_eq!(Some(4), set.pop_last());
+
+        assert_eq!("[3]", format!("{:?}", set));
+    }
+
+    #[test]
+    fn borrow() {
+        type Set = RawSortedVec<String, AllocStorage<SpyAllocator>>;
+
+        let allocator = SpyAllocator::default();
+
+        let mut set = Set::new(AllocStorage::new(allocator.clone()));
+
+        for word in &["world", "hello", "all"] {
+            set.try_insert(word.to_string()).unwrap();
+        }
+
+        assert_eq!(["all", "hello", "world"], *set);
+        assert_eq!(Some(&"hello".to_string()), set.get("hello"));
+        assert_eq!(Some("all".to_string()), set.remove("all"));
+
+        set.retain(|word| word.len() > 5);
+
+        assert!(set.is_empty());
+
+        drop(set);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn from_vec() {
+        let mut vec = RawVec::<u8, _>::new(NonTrackingRange::<u8, u8, 8>::default());
+        vec.try_extend_from_slice(&[3, 1, 3, 2, 1]).unwrap();
+
+        let set = RawSortedVec::from(vec);
+
+        assert_eq!([1, 2, 3], *set);
+        assert_eq!([1, 2, 3], *set.into_inner());
+    }
+
+    #[test]
+    fn allocation_failure() {
+        type Set = RawSortedVec<u8, AllocStorage<NonAllocator>>;
+
+        let mut set = Set::default();
+
+        assert_eq!(Err(1), set.try_insert(1).map_err(InsertError::into_value));
+    }
+} // mod tests
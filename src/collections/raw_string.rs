@@ -0,0 +1,319 @@
+//! Proof-of-Concept implementation of a String parameterized by a Storage.
+
+use core::{
+    fmt::{self, Debug, Display},
+    ops::Deref,
+    str::{self, Utf8Error},
+};
+
+#[cfg(any(test, feature = "alloc"))]
+use alloc::alloc::Global;
+
+#[cfg(any(test, feature = "alloc"))]
+use crate::{allocator::AllocStorage, fallback::FallbackRange, inline::NonTrackingRange};
+
+use crate::{defaults::DefaultRangeStorage, error::StorageError, traits::RangeStorage};
+
+use super::RawVec;
+
+/// A PoC String, a UTF-8 encoded sequence of bytes.
+pub struct RawString<S: RangeStorage = DefaultRangeStorage> {
+    inner: RawVec<u8, S>,
+}
+
+/// The error returned by `RawString::from_utf8`, holding on to the bytes which failed validation.
+pub struct FromUtf8Error<S: RangeStorage> {
+    bytes: RawVec<u8, S>,
+    error: Utf8Error,
+}
+
+/// A small-string optimized PoC String.
+///
+/// Strings of up to `N` bytes are stored inline, longer ones are stored in memory obtained from `A`.
+#[cfg(any(test, feature = "alloc"))]
+pub type SmallString<const N: usize, A = Global> =
+    RawString<FallbackRange<NonTrackingRange<u8, u8, N>, AllocStorage<A>>>;
+
+impl<S: RangeStorage> RawString<S> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self {
+            inner: RawVec::new(storage),
+        }
+    }
+
+    /// Converts `bytes` into a string, without copying, if they are valid UTF-8.
+    ///
+    /// On failure, the bytes are returned as part of the error.
+    pub fn from_utf8(bytes: RawVec<u8, S>) -> Result<Self, FromUtf8Error<S>> {
+        match str::from_utf8(&bytes) {
+            Ok(_) => Ok(Self { inner: bytes }),
+            Err(error) => Err(FromUtf8Error { bytes, error }),
+        }
+    }
+
+    /// Attempts to create a new instance in `storage`, copying `bytes` and replacing invalid UTF-8 sequences by
+    /// U+FFFD REPLACEMENT CHARACTER.
+    pub fn from_utf8_lossy_in(mut bytes: &[u8], storage: S) -> Result<Self, StorageError> {
+        let mut result = Self::new(storage);
+
+        loop {
+            match str::from_utf8(bytes) {
+                Ok(valid) => {
+                    result.try_push_str(valid)?;
+                    return Ok(result);
+                }
+                Err(error) => {
+                    let (valid, rest) = bytes.split_at(error.valid_up_to());
+
+                    //  Safety:
+                    //  -   `valid` was just validated.
+                    result.try_push_str(unsafe { str::from_utf8_unchecked(valid) })?;
+                    result.try_push(char::REPLACEMENT_CHARACTER)?;
+
+                    //  A truncated sequence at the end has no length, and is replaced as a whole.
+                    bytes = &rest[error.error_len().unwrap_or(rest.len())..];
+                }
+            }
+        }
+    }
+
+    /// Converts `self` into its bytes, without copying.
+    pub fn into_bytes(self) -> RawVec<u8, S> {
+        self.inner
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the length of `self`, in bytes.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns the number of bytes `self` can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns the content of `self`.
+    pub fn as_str(&self) -> &str {
+        //  Safety:
+        //  -   `self.inner` only ever contains UTF-8, as it is only ever appended whole `str`.
+        unsafe { str::from_utf8_unchecked(&self.inner) }
+    }
+
+    /// Clears `self`, resetting its length to 0.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Attempts to append `c` at the back.
+    ///
+    /// On failure, `self` is left unchanged.
+    pub fn try_push(&mut self, c: char) -> Result<(), StorageError> {
+        self.try_push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Attempts to append `s` at the back.
+    ///
+    /// On failure, `self` is left unchanged.
+    pub fn try_push_str(&mut self, s: &str) -> Result<(), StorageError> {
+        self.inner.try_extend_from_slice(s.as_bytes())
+    }
+}
+
+impl<S: RangeStorage> Debug for RawString<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", self.as_str())
+    }
+}
+
+impl<S: Default + RangeStorage> Default for RawString<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: RangeStorage> Deref for RawString<S> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<S: RangeStorage> Display for RawString<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl<S: Default + RangeStorage> From<&str> for RawString<S> {
+    /// Creates a new instance, containing a copy of `s`.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate `s`.
+    fn from(s: &str) -> Self {
+        let mut result = Self::default();
+        result.try_push_str(s).expect("Sufficient capacity");
+        result
+    }
+}
+
+impl<S: RangeStorage> PartialEq<str> for RawString<S> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a, S: RangeStorage> PartialEq<&'a str> for RawString<S> {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<S: RangeStorage> PartialEq<RawString<S>> for str {
+    fn eq(&self, other: &RawString<S>) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl<S: RangeStorage> PartialEq<RawString<S>> for &str {
+    fn eq(&self, other: &RawString<S>) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl<S: RangeStorage> FromUtf8Error<S> {
+    /// Returns the bytes which failed validation.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the bytes which failed validation, without copying.
+    pub fn into_bytes(self) -> RawVec<u8, S> {
+        self.bytes
+    }
+
+    /// Returns the details of the validation failure.
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl<S: RangeStorage> Debug for FromUtf8Error<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "FromUtf8Error{{ bytes: {:?}, error: {:?} }}",
+            self.as_bytes(),
+            self.error
+        )
+    }
+}
+
+impl<S: RangeStorage> Display for FromUtf8Error<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    #[test]
+    fn push() {
+        let mut string = SmallString::<8>::default();
+
+        string.try_push_str("Hello").unwrap();
+        string.try_push(',').unwrap();
+        string.try_push('é').unwrap();
+
+        assert_eq!(8, string.len());
+        assert_eq!("Hello,é", string);
+        assert_eq!(*"Hello,é", string);
+        assert_eq!("\"Hello,é\"", format!("{:?}", string));
+
+        string.try_push_str(" World!").unwrap();
+
+        assert_eq!("Hello,é World!", format!("{}", string));
+    }
+
+    #[test]
+    fn no_allocation_below_capacity() {
+        let allocator = SpyAllocator::default();
+
+        let mut string = SmallString::<16, _>::new(FallbackRange::new(
+            NonTrackingRange::default(),
+            AllocStorage::new(allocator.clone()),
+        ));
+
+        string.try_push_str("Hello, World!").unwrap();
+        string.try_push_str("!!!").unwrap();
+
+        assert_eq!(16, string.len());
+        assert_eq!(0, allocator.allocated());
+
+        string.try_push('!').unwrap();
+
+        assert_eq!(1, allocator.allocated());
+
+        drop(string);
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn from_str() {
+        let short = SmallString::<4>::from("abc");
+        let long = SmallString::<4>::from("abcdef");
+
+        assert_eq!("abc", short);
+        assert_eq!("abcdef", long);
+        assert!(long.capacity() >= 6);
+    }
+
+    #[test]
+    fn from_utf8() {
+        let mut bytes = RawVec::new(AllocStorage::<Global>::default());
+        bytes.try_extend_from_slice("Hello, é".as_bytes()).unwrap();
+
+        let pointer = bytes.as_ptr();
+
+        let string = RawString::from_utf8(bytes).unwrap();
+
+        assert_eq!("Hello, é", string);
+        assert_eq!(pointer, string.as_ptr());
+
+        let mut bytes = string.into_bytes();
+        bytes.pop();
+
+        let error = RawString::from_utf8(bytes).unwrap_err();
+
+        assert_eq!(7, error.utf8_error().valid_up_to());
+        assert_eq!(b"Hello, \xc3", error.as_bytes());
+        assert_eq!(pointer, error.into_bytes().as_ptr());
+    }
+
+    #[test]
+    fn from_utf8_lossy_in() {
+        let bytes = b"Hello\xff, W\xf0\x9f\x98orld \xe2\x82";
+
+        let string = SmallString::<32>::from_utf8_lossy_in(bytes, Default::default()).unwrap();
+
+        assert_eq!("Hello\u{FFFD}, W\u{FFFD}orld \u{FFFD}", string);
+
+        let storage =
+            FallbackRange::new(NonTrackingRange::default(), AllocStorage::new(NonAllocator));
+
+        SmallString::<4, _>::from_utf8_lossy_in(b"abc\xff", storage).unwrap_err();
+    }
+} // mod tests
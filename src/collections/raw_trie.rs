@@ -0,0 +1,397 @@
+//! Proof-of-Concept implementation of a byte-keyed Trie parameterized by a Storage.
+
+use core::fmt::{self, Debug};
+
+use crate::{defaults::DefaultElementStorage, traits::ElementStorage};
+
+use super::InsertError;
+
+/// A PoC Trie, associating byte strings to values, with longest-prefix-match look-ups.
+///
+/// Each node is stored in the element storage, and holds one byte of key, an optional value, and links to its first
+/// child and next sibling. Keys are matched byte per byte, hence routing prefixes should be expressed as whole bytes.
+pub struct RawTrie<V, S: ElementStorage = DefaultElementStorage> {
+    len: usize,
+    root: RawTrieNode<V, S>,
+    storage: S,
+}
+
+/// A node of a `RawTrie`, exposed so as to size the storage.
+pub struct RawTrieNode<V, S: ElementStorage> {
+    byte: u8,
+    value: Option<V>,
+    child: Option<S::Handle<Self>>,
+    sibling: Option<S::Handle<Self>>,
+}
+
+impl<V, S: ElementStorage> RawTrie<V, S> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        Self {
+            len: 0,
+            root: RawTrieNode::new(0, None),
+            storage,
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of values in `self`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Attempts to associate `value` to `key`, returning the previous value, if any.
+    ///
+    /// On failure, returns `value`, leaving `self` unchanged.
+    pub fn try_insert(&mut self, key: &[u8], value: V) -> Result<Option<V>, InsertError<V>> {
+        let mut node: *mut RawTrieNode<V, S> = &mut self.root;
+
+        for (depth, byte) in key.iter().enumerate() {
+            //  Safety:
+            //  -   `node` points to either `self.root`, or a live node.
+            let link = unsafe { self.find(&mut (*node).child, *byte) };
+
+            //  Safety:
+            //  -   `link` points to a link of a live node.
+            if unsafe { (*link).is_none() } {
+                match self.storage.try_create(RawTrieNode::new(*byte, None)) {
+                    //  Safety:
+                    //  -   `link` points to a link of a live node.
+                    Ok(handle) => unsafe { *link = Some(handle) },
+                    Err((_, error)) => {
+                        let root: *mut _ = &mut self.root.child;
+
+                        //  Safety:
+                        //  -   Only value-less leaves are pruned, which were just created.
+                        unsafe { self.prune(root, &key[..depth]) };
+                        return Err(InsertError::new(value, error));
+                    }
+                }
+            }
+
+            //  Safety:
+            //  -   `link` points to a link of a live node, and is not `None`.
+            node = unsafe { self.node_ptr((*link).as_ref().expect("Link was just filled")) };
+        }
+
+        //  Safety:
+        //  -   `node` points to either `self.root`, or a live node.
+        let previous = unsafe { (*node).value.replace(value) };
+
+        if previous.is_none() {
+            self.len += 1;
+        }
+
+        Ok(previous)
+    }
+
+    /// Returns a reference to the value associated to `key`, if any.
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        self.node(key)?.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value associated to `key`, if any.
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        let node = self.node_mut(key)?;
+
+        //  Safety:
+        //  -   `node` points to either `self.root`, or a live node, and `self` is borrowed mutably.
+        unsafe { (*node).value.as_mut() }
+    }
+
+    /// Returns the length of the longest prefix of `key` associated to a value, and this value, if any.
+    pub fn longest_prefix_match(&self, key: &[u8]) -> Option<(usize, &V)> {
+        let mut node = &self.root;
+        let mut result = node.value.as_ref().map(|value| (0, value));
+
+        for (depth, byte) in key.iter().enumerate() {
+            node = match self.child(node, *byte) {
+                Some(child) => child,
+                None => break,
+            };
+
+            if let Some(value) = &node.value {
+                result = Some((depth + 1, value));
+            }
+        }
+
+        result
+    }
+
+    /// Removes the value associated to `key`, if any, and returns it.
+    ///
+    /// The nodes which no longer lead to any value are destroyed.
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        let node = self.node_mut(key)?;
+
+        //  Safety:
+        //  -   `node` points to either `self.root`, or a live node, and `self` is borrowed mutably.
+        let value = unsafe { (*node).value.take() }?;
+
+        self.len -= 1;
+
+        let root: *mut _ = &mut self.root.child;
+
+        //  Safety:
+        //  -   `root` is the root link.
+        unsafe { self.prune(root, key) };
+
+        Some(value)
+    }
+
+    /// Clears `self`, destroying all values and nodes.
+    pub fn clear(&mut self) {
+        self.root.value = None;
+
+        let children = self.root.child.take();
+
+        //  Safety:
+        //  -   `children` is the root link, now detached.
+        unsafe { self.destroy_list(children) };
+
+        self.len = 0;
+    }
+
+    fn node(&self, key: &[u8]) -> Option<&RawTrieNode<V, S>> {
+        key.iter()
+            .try_fold(&self.root, |node, byte| self.child(node, *byte))
+    }
+
+    fn node_mut(&mut self, key: &[u8]) -> Option<*mut RawTrieNode<V, S>> {
+        let mut node: *mut RawTrieNode<V, S> = &mut self.root;
+
+        for byte in key {
+            //  Safety:
+            //  -   `node` points to either `self.root`, or a live node.
+            //  -   `link` points to a link of a live node.
+            unsafe {
+                let link = self.find(&mut (*node).child, *byte);
+                node = self.node_ptr((*link).as_ref()?);
+            }
+        }
+
+        Some(node)
+    }
+
+    fn child(&self, node: &RawTrieNode<V, S>, byte: u8) -> Option<&RawTrieNode<V, S>> {
+        let mut next = node.child.as_ref();
+
+        while let Some(handle) = next {
+            //  Safety:
+            //  -   `handle` is valid, as all links of live nodes.
+            let child = unsafe { self.storage.get(handle).as_ref() };
+
+            if child.byte == byte {
+                return Some(child);
+            }
+
+            next = child.sibling.as_ref();
+        }
+
+        None
+    }
+
+    //  Internal: returns a pointer to the node.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn node_ptr(&self, handle: &S::Handle<RawTrieNode<V, S>>) -> *mut RawTrieNode<V, S> {
+        self.storage.get(handle).as_ptr()
+    }
+
+    //  Internal: returns a pointer to the link holding the sibling with `byte`, or to the trailing empty link.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `link` points to a link of either `self.root`, or a live node.
+    unsafe fn find(
+        &self,
+        mut link: *mut Option<S::Handle<RawTrieNode<V, S>>>,
+        byte: u8,
+    ) -> *mut Option<S::Handle<RawTrieNode<V, S>>> {
+        while let Some(handle) = &*link {
+            let node = self.node_ptr(handle);
+
+            if (*node).byte == byte {
+                break;
+            }
+
+            link = &mut (*node).sibling;
+        }
+
+        link
+    }
+
+    //  Internal: destroys the nodes along `key`, starting from `link`, which no longer lead to any value.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `link` points to a link of either `self.root`, or a live node.
+    unsafe fn prune(&mut self, link: *mut Option<S::Handle<RawTrieNode<V, S>>>, key: &[u8]) {
+        let (byte, rest) = match key.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+
+        let link = self.find(link, *byte);
+
+        let node = match &*link {
+            Some(handle) => self.node_ptr(handle),
+            None => return,
+        };
+
+        self.prune(&mut (*node).child, rest);
+
+        if (*node).value.is_some() || (*node).child.is_some() {
+            return;
+        }
+
+        let handle = (*link).take().expect("Link was just matched");
+        *link = (*node).sibling.take();
+
+        self.storage.destroy(&handle);
+    }
+
+    //  Internal: destroys the nodes of the list, and all their descendants.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `list` is detached, and all its nodes are live.
+    unsafe fn destroy_list(&mut self, mut list: Option<S::Handle<RawTrieNode<V, S>>>) {
+        while let Some(handle) = list {
+            let node = self.node_ptr(&handle);
+
+            let children = (*node).child.take();
+            self.destroy_list(children);
+
+            list = (*node).sibling.take();
+            self.storage.destroy(&handle);
+        }
+    }
+}
+
+impl<V, S: ElementStorage> Debug for RawTrie<V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "RawTrie{{ len: {} }}", self.len)
+    }
+}
+
+impl<V, S: Default + ElementStorage> Default for RawTrie<V, S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<V, S: ElementStorage> Drop for RawTrie<V, S> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+//
+//  Implementation
+//
+
+impl<V, S: ElementStorage> RawTrieNode<V, S> {
+    fn new(byte: u8, value: Option<V>) -> Self {
+        Self {
+            byte,
+            value,
+            child: None,
+            sibling: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::TrackingElement;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type Trie = RawTrie<u32, TrackingElement<[usize; 6], 4>>;
+
+    #[test]
+    fn longest_prefix_match() {
+        let mut trie = Trie::default();
+
+        assert_eq!(Ok(None), trie.try_insert(&[10], 1));
+        assert_eq!(Ok(None), trie.try_insert(&[10, 1, 2], 3));
+        assert_eq!(Ok(None), trie.try_insert(&[], 0));
+        assert_eq!(Ok(Some(1)), trie.try_insert(&[10], 10));
+
+        assert_eq!(3, trie.len());
+        assert_eq!(Some(&10), trie.get(&[10]));
+        assert_eq!(None, trie.get(&[10, 1]));
+
+        assert_eq!(Some((3, &3)), trie.longest_prefix_match(&[10, 1, 2, 7]));
+        assert_eq!(Some((1, &10)), trie.longest_prefix_match(&[10, 1, 3]));
+        assert_eq!(Some((0, &0)), trie.longest_prefix_match(&[192, 168]));
+
+        *trie.get_mut(&[]).unwrap() = 42;
+
+        assert_eq!(Some((0, &42)), trie.longest_prefix_match(&[]));
+    }
+
+    #[test]
+    fn insert_failure() {
+        let mut trie = Trie::default();
+
+        trie.try_insert(&[1, 2], 12).unwrap();
+        trie.try_insert(&[3], 3).unwrap();
+
+        //  Only one node left, the partial path is pruned.
+        assert_eq!(
+            Err(456),
+            trie.try_insert(&[4, 5, 6], 456)
+                .map_err(InsertError::into_value)
+        );
+
+        trie.try_insert(&[1, 3], 13).unwrap();
+
+        assert_eq!(Some((2, &13)), trie.longest_prefix_match(&[1, 3, 5]));
+    }
+
+    #[test]
+    fn remove() {
+        let mut trie = Trie::default();
+
+        trie.try_insert(&[1, 2, 3], 123).unwrap();
+        trie.try_insert(&[1], 1).unwrap();
+
+        assert_eq!(Some(123), trie.remove(&[1, 2, 3]));
+        assert_eq!(None, trie.remove(&[1, 2, 3]));
+        assert_eq!(None, trie.remove(&[1, 2]));
+
+        //  The nodes of `[1, 2, 3]`, but `[1]`, were pruned, leaving room.
+        trie.try_insert(&[4, 5, 6], 456).unwrap();
+
+        assert_eq!(Some((1, &1)), trie.longest_prefix_match(&[1, 2, 3]));
+        assert_eq!("RawTrie{ len: 2 }", format!("{:?}", trie));
+    }
+
+    #[test]
+    fn drop_values() {
+        let allocator = SpyAllocator::default();
+
+        let mut trie = RawTrie::new(AllocStorage::new(allocator.clone()));
+
+        trie.try_insert(b"hello", "hello".to_string()).unwrap();
+        trie.try_insert(b"help", "help".to_string()).unwrap();
+        trie.try_insert(b"world", "world".to_string()).unwrap();
+
+        assert_eq!(Some("help".to_string()), trie.remove(b"help"));
+
+        drop(trie);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
@@ -3,15 +3,31 @@
 use core::{
     cmp,
     fmt::{self, Debug},
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
     ops::{Deref, DerefMut},
     ptr,
 };
 
-use crate::traits::{Capacity, RangeStorage};
+#[cfg(feature = "serde")]
+use core::marker::PhantomData;
+
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, DeserializeSeed, SeqAccess, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    defaults::DefaultRangeStorage,
+    error::StorageError,
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::{BoundedRangeStorage, Capacity, RangeExt, RangeStorage},
+};
+
+use super::{EvictionStrategy, InsertError};
 
 /// A PoC Vec.
-pub struct RawVec<T, S: RangeStorage> {
+pub struct RawVec<T, S: RangeStorage = DefaultRangeStorage> {
     len: S::Capacity,
     data: S::Handle<T>,
     storage: S,
@@ -19,13 +35,9 @@ pub struct RawVec<T, S: RangeStorage> {
 
 impl<T, S: RangeStorage> RawVec<T, S> {
     /// Creates a new instance.
-    pub fn new(mut storage: S) -> Self {
-        let zero = Self::into_capacity(0);
-
-        let len = zero;
-        let data = storage
-            .allocate(zero)
-            .expect("Zero-capacity allocation should always succeed");
+    pub fn new(storage: S) -> Self {
+        let len = Self::into_capacity(0);
+        let data = storage.dangling();
 
         Self { len, data, storage }
     }
@@ -40,13 +52,399 @@ impl<T, S: RangeStorage> RawVec<T, S> {
         self.len.into_usize()
     }
 
+    /// Returns the number of elements `self` can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.raw_slice().len()
+    }
+
+    /// Returns the elements of `self`, as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    /// Returns the elements of `self`, as a mutable slice.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
     /// Clears `self`, destroying all elements and resetting its length to 0.
     pub fn clear(&mut self) {
         while let Some(_) = self.pop() {}
     }
 
+    /// Shortens `self` to `len` elements, destroying the remaining ones.
+    ///
+    /// Has no effect if `len` is greater than or equal to the current length.
+    pub fn truncate(&mut self, len: usize) {
+        let old_len = self.len();
+
+        if len >= old_len {
+            return;
+        }
+
+        //  Set the length first, so as to avoid double drops should a destructor panic.
+        self.len = Self::into_capacity(len);
+
+        let slice = self.raw_slice_mut();
+
+        //  Safety:
+        //  -   `len < old_len <= slice.len()`.
+        let tail = unsafe { slice.get_unchecked_mut(len..old_len) };
+
+        //  Safety:
+        //  -   The elements in `len..old_len` are initialized, and no longer accessible.
+        unsafe { ptr::drop_in_place(MaybeUninit::slice_assume_init_mut(tail)) };
+    }
+
+    /// Returns the spare capacity of `self`, past its last element.
+    ///
+    /// The slice may be filled, then the elements committed with `set_len`, for example to read directly into `self`.
+    ///
+    /// The slice borrows `self`, and therefore cannot outlive a subsequent growth which could relocate the range.
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let len = self.len();
+
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   `&mut self` guarantees that no other reference to the elements is alive.
+        unsafe {
+            self.storage
+                .slice_mut(&self.data, len, self.capacity() - len)
+        }
+    }
+
+    /// Forces the length of `self` to `new_len`.
+    ///
+    /// No element is either dropped or initialized.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `new_len <= self.capacity()`.
+    /// -   Assumes that the elements in `self.len()..new_len` are initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity());
+
+        self.len = Self::into_capacity(new_len);
+    }
+
+    /// Destroys all elements, deallocates the range, and returns the storage for reuse.
+    pub fn into_storage(mut self) -> S {
+        self.clear();
+
+        let (handle, _, mut storage) = self.into_raw_parts();
+
+        //  Safety:
+        //  -   `handle` is valid, and issued by `storage`.
+        unsafe { storage.deallocate(&handle) };
+
+        storage
+    }
+
+    /// Decomposes `self` into its handle, length, and storage.
+    ///
+    /// The elements are neither destroyed nor deallocated, it is up to the caller to do so, or to reassemble an
+    /// instance with `from_raw_parts`.
+    pub fn into_raw_parts(self) -> (S::Handle<T>, usize, S) {
+        let len = self.len();
+
+        //  Safety:
+        //  -   `self.data` and `self.storage` are valid.
+        //  -   The immediate `forget` avoids double-frees.
+        let data = unsafe { ptr::read(&self.data) };
+        let storage = unsafe { ptr::read(&self.storage) };
+        mem::forget(self);
+
+        (data, len, storage)
+    }
+
+    /// Creates an instance from its handle, length, and storage.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by `storage`.
+    /// -   Assumes that the first `len` elements of the range are initialized.
+    pub unsafe fn from_raw_parts(handle: S::Handle<T>, len: usize, storage: S) -> Self {
+        debug_assert!(len <= storage.get(&handle).len());
+
+        let len = Self::into_capacity(len);
+
+        Self {
+            len,
+            data: handle,
+            storage,
+        }
+    }
+
+    /// Attempts to reserve capacity for at least `additional` more elements.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), StorageError> {
+        let required = self
+            .len()
+            .checked_add(additional)
+            .ok_or_else(|| StorageError::array::<T>(usize::MAX).with_context(CONTEXT))?;
+        let capacity = self.capacity();
+
+        if required <= capacity {
+            return Ok(());
+        }
+
+        let maximum = self.storage.maximum_capacity::<T>().into_usize();
+
+        if required > maximum {
+            return Err(StorageError::array::<T>(required).with_context(CONTEXT));
+        }
+
+        let new_capacity = Self::into_capacity(cmp::min(cmp::max(required, capacity * 2), maximum));
+
+        //  Safety:
+        //  -   `self.data` is a valid handle pointing to valid data.
+        self.data = unsafe { self.storage.try_grow(&self.data, new_capacity)? };
+
+        Ok(())
+    }
+
+    /// Attempts to shrink the capacity of `self` to `min_capacity`, or its length if greater.
+    ///
+    /// On failure, for example if the storage cannot shrink ranges, `self` is left unchanged.
+    pub fn try_shrink_to(&mut self, min_capacity: usize) -> Result<(), StorageError> {
+        let new_capacity = cmp::max(self.len(), min_capacity);
+
+        if new_capacity >= self.capacity() {
+            return Ok(());
+        }
+
+        //  Safety:
+        //  -   `self.data` is a valid handle pointing to valid data.
+        //  -   `new_capacity >= self.len()`, hence all elements are preserved.
+        self.data = unsafe {
+            self.storage
+                .try_shrink(&self.data, Self::into_capacity(new_capacity))?
+        };
+
+        Ok(())
+    }
+
+    /// Attempts to shrink the capacity of `self` to its length.
+    ///
+    /// On failure, for example if the storage cannot shrink ranges, `self` is left unchanged.
+    pub fn try_shrink_to_fit(&mut self) -> Result<(), StorageError> {
+        self.try_shrink_to(0)
+    }
+
+    /// Attempts to append clones of the elements of `source` at the back.
+    ///
+    /// On failure, `self` is left unchanged.
+    pub fn try_extend_from_slice(&mut self, source: &[T]) -> Result<(), StorageError>
+    where
+        T: Clone,
+    {
+        self.try_reserve(source.len())?;
+
+        let len = self.len();
+
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   `len..len + source.len()` is within capacity, as it was reserved, and uninitialized.
+        unsafe { self.storage.clone_from_slice(&self.data, len, source) };
+
+        self.len = Self::into_capacity(len + source.len());
+
+        Ok(())
+    }
+
+    /// Attempts to resize `self` to `new_len` elements, appending clones of `value` or destroying the excess elements.
+    ///
+    /// On failure, `self` is left unchanged, and `value` is returned.
+    pub fn try_resize(&mut self, new_len: usize, value: T) -> Result<(), InsertError<T>>
+    where
+        T: Clone,
+    {
+        let len = self.len();
+
+        if new_len <= len {
+            self.truncate(new_len);
+            return Ok(());
+        }
+
+        if let Err(error) = self.try_reserve(new_len - len) {
+            return Err(InsertError::new(value, error));
+        }
+
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   `len..new_len` is within capacity, as it was reserved, and uninitialized.
+        unsafe { self.storage.fill(&self.data, len..new_len, value) };
+
+        self.len = Self::into_capacity(new_len);
+
+        Ok(())
+    }
+
+    /// Attempts to insert a new element at `index`, shifting all elements after it to the right.
+    ///
+    /// #   Panics
+    ///
+    /// If `index > self.len()`.
+    pub fn try_insert(&mut self, index: usize, e: T) -> Result<(), InsertError<T>> {
+        let len = self.len();
+
+        assert!(index <= len, "index {} out of bounds {}", index, len);
+
+        if let Err(error) = self.try_reserve(1) {
+            return Err(InsertError::new(e, error));
+        }
+
+        let slice = self.raw_slice_mut();
+
+        //  Safety:
+        //  -   `index <= len < slice.len()`, as capacity was reserved.
+        unsafe {
+            let pointer = slice.as_mut_ptr().add(index) as *mut T;
+            ptr::copy(pointer, pointer.add(1), len - index);
+            ptr::write(pointer, e);
+        }
+
+        self.len = Self::into_capacity(len + 1);
+
+        Ok(())
+    }
+
+    /// Removes the element at `index`, shifting all elements after it to the left.
+    ///
+    /// #   Panics
+    ///
+    /// If `index >= self.len()`.
+    pub fn remove(&mut self, index: usize) -> T {
+        let len = self.len();
+
+        assert!(index < len, "index {} out of bounds {}", index, len);
+
+        let slice = self.raw_slice_mut();
+
+        //  Safety:
+        //  -   `index < len <= slice.len()`.
+        //  -   The element at `index` is initialized, and its slot overwritten right after.
+        let result = unsafe {
+            let pointer = slice.as_mut_ptr().add(index) as *mut T;
+            let result = ptr::read(pointer);
+            ptr::copy(pointer.add(1), pointer, len - index - 1);
+            result
+        };
+
+        self.len = Self::into_capacity(len - 1);
+
+        result
+    }
+
+    /// Removes the element at `index`, replacing it with the last element.
+    ///
+    /// This does not preserve ordering, but is O(1).
+    ///
+    /// #   Panics
+    ///
+    /// If `index >= self.len()`.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        let len = self.len();
+
+        assert!(index < len, "index {} out of bounds {}", index, len);
+
+        let slice = self.raw_slice_mut();
+
+        //  Safety:
+        //  -   `index < len <= slice.len()`, and `len - 1 < len <= slice.len()`.
+        //  -   The element at `index` is initialized, and its slot overwritten right after, unless it is the last.
+        let result = unsafe {
+            let base = slice.as_mut_ptr() as *mut T;
+            let result = ptr::read(base.add(index));
+            ptr::copy(base.add(len - 1), base.add(index), 1);
+            result
+        };
+
+        self.len = Self::into_capacity(len - 1);
+
+        result
+    }
+
+    /// Retains only the elements for which `f` returns `true`, destroying the others, in order.
+    ///
+    /// Should `f`, or a destructor, panic, the elements not yet visited are leaked.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let len = self.len();
+
+        //  Set the length first, so as to avoid double drops should `f` or a destructor panic.
+        self.len = Self::into_capacity(0);
+
+        let base = self.raw_slice_mut().as_mut_ptr() as *mut T;
+        let mut kept = 0;
+
+        for index in 0..len {
+            //  Safety:
+            //  -   `index < len <= capacity`, and the element at `index` is initialized, and not yet visited.
+            //  -   `kept <= index`, hence the slot at `kept` is either the element itself, or was moved from.
+            unsafe {
+                let element = base.add(index);
+
+                if f(&*element) {
+                    ptr::copy(element, base.add(kept), 1);
+                    kept += 1;
+                    self.len = Self::into_capacity(kept);
+                } else {
+                    ptr::drop_in_place(element);
+                }
+            }
+        }
+    }
+
+    /// Removes consecutive elements for which `same_bucket` returns `true`, destroying them.
+    ///
+    /// `same_bucket` is passed the candidate for removal first, and the last retained element second.
+    ///
+    /// Should `same_bucket`, or a destructor, panic, the elements not yet visited are leaked.
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same_bucket: F) {
+        let len = self.len();
+
+        if len <= 1 {
+            return;
+        }
+
+        //  Set the length first, so as to avoid double drops should `same_bucket` or a destructor panic.
+        self.len = Self::into_capacity(1);
+
+        let base = self.raw_slice_mut().as_mut_ptr() as *mut T;
+        let mut kept = 1;
+
+        for index in 1..len {
+            //  Safety:
+            //  -   `index < len <= capacity`, and the element at `index` is initialized, and not yet visited.
+            //  -   `0 < kept <= index`, hence the element at `kept - 1` is initialized and retained.
+            unsafe {
+                let element = base.add(index);
+
+                if same_bucket(&mut *element, &mut *base.add(kept - 1)) {
+                    ptr::drop_in_place(element);
+                } else {
+                    ptr::copy(element, base.add(kept), 1);
+                    kept += 1;
+                    self.len = Self::into_capacity(kept);
+                }
+            }
+        }
+    }
+
+    /// Removes consecutive elements which map to the same key, destroying them.
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, mut key: F) {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes consecutive equal elements, destroying them.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+
     /// Attempts to push a new element at the back.
-    pub fn try_push(&mut self, e: T) -> Result<(), T> {
+    pub fn try_push(&mut self, e: T) -> Result<(), InsertError<T>> {
         let len = self.len();
 
         let slice = self.raw_slice_mut();
@@ -72,9 +470,40 @@ impl<T, S: RangeStorage> RawVec<T, S> {
     ///
     /// If cannot grow.
     pub fn push(&mut self, e: T) {
-        self.try_push(e)
-            .map_err(|_| ())
-            .expect("Sufficient capacity");
+        self.try_push(e).expect("Sufficient capacity");
+    }
+
+    /// Pushes an element at the back, without ever growing: if `self` is full, `strategy` picks an element to evict.
+    ///
+    /// The evicted element is removed, shifting all elements after it to the left, before `e` is pushed. Evicting the
+    /// oldest element, with `DropOldest`, thus turns `self` into a bounded sample buffer, in arrival order.
+    ///
+    /// Returns the element left out, if any: either the evicted element, or `e` itself if rejected, as is always the
+    /// case if the capacity is 0.
+    ///
+    /// #   Panics
+    ///
+    /// If `strategy` picks an index out of bounds.
+    pub fn push_or_evict<E: EvictionStrategy<T>>(&mut self, e: T, mut strategy: E) -> Option<T> {
+        if self.len() < self.capacity() {
+            //  There is room, hence this never grows.
+            self.push(e);
+            return None;
+        }
+
+        if self.is_empty() {
+            return Some(e);
+        }
+
+        let index = match strategy.victim(self, &e) {
+            Some(index) => index,
+            None => return Some(e),
+        };
+
+        let evicted = self.remove(index);
+        self.push(e);
+
+        Some(evicted)
     }
 
     /// Pops the back element, if any.
@@ -101,8 +530,83 @@ impl<T, S: RangeStorage> RawVec<T, S> {
 
         Some(result)
     }
+
+    /// Freezes `self`, preventing any further modification or allocation.
+    ///
+    /// The resulting view can be shared across threads, as long as `T` and `S` allow it.
+    pub fn freeze(self) -> FrozenVec<T, S> {
+        FrozenVec { inner: self }
+    }
+}
+
+impl<T, S: BoundedRangeStorage<T>> RawVec<T, S> {
+    /// The maximum capacity of any instance, known at compile time.
+    pub const CAPACITY: usize = S::MAX_CAPACITY;
+
+    /// Attempts to create a new instance, with capacity for at least `N` elements.
+    ///
+    /// Whether `N` elements fit within the storage is checked at compile time; the reservation may still fail at
+    /// run-time, for example if memory is exhausted.
+    pub fn try_with_fixed_capacity<const N: usize>(storage: S) -> Result<Self, StorageError> {
+        const {
+            assert!(
+                N <= S::MAX_CAPACITY,
+                "N exceeds the maximum capacity of the storage"
+            )
+        };
+
+        let mut result = Self::new(storage);
+        result.try_reserve(N)?;
+
+        Ok(result)
+    }
+}
+
+/// A frozen view of a PoC Vec.
+///
+/// No further allocation is ever performed, hence pointers to the elements are stable as long as the view is not moved.
+///
+/// The view is `Send`, or `Sync`, whenever both the elements and the storage are, regardless of the handle; storages
+/// are therefore expected to resolve handles without mutating them.
+pub struct FrozenVec<T, S: RangeStorage> {
+    inner: RawVec<T, S>,
+}
+
+impl<T, S: RangeStorage> FrozenVec<T, S> {
+    /// Thaws `self`, returning the underlying Vec.
+    pub fn thaw(self) -> RawVec<T, S> {
+        self.inner
+    }
+}
+
+impl<T: Debug, S: RangeStorage> Debug for FrozenVec<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", self.inner)
+    }
+}
+
+impl<T, S: RangeStorage> Deref for FrozenVec<T, S> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
 }
 
+//  Safety:
+//  -   The elements and storage are owned, and are safe to send if `T` and `S` are.
+//  -   `S::Handle<T>` is deliberately not required to be `Send`: the handles of storages are raw pointers, indices, or
+//      the inline memory of the range, which opt out of `Send` for lack of synchronization, not for affinity to a
+//      thread, and the handle is only ever sent along with the storage which resolves it.
+unsafe impl<T: Send, S: RangeStorage + Send> Send for FrozenVec<T, S> {}
+
+//  Safety:
+//  -   No mutation is possible through a shared reference, hence only read-only access to `T` and `S` is shared.
+//  -   `S::Handle<T>` is deliberately not required to be `Sync`: it is only ever passed by reference to `S::get`, which
+//      resolves it without mutating it, and the range is then only read through the resulting pointer. Notably, the
+//      `UnsafeCell` of an inline range is never written to through a `FrozenVec`.
+unsafe impl<T: Sync, S: RangeStorage + Sync> Sync for FrozenVec<T, S> {}
+
 impl<T: Debug, S: RangeStorage> Debug for RawVec<T, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         let slice: &[T] = &*self;
@@ -110,6 +614,15 @@ impl<T: Debug, S: RangeStorage> Debug for RawVec<T, S> {
     }
 }
 
+impl<T, S: MemoryUsage + RangeStorage> MemoryUsage for RawVec<T, S> {
+    fn mem_usage(&self) -> MemUsage {
+        let storage = self.storage.mem_usage();
+        let inline = storage.inline_bytes + mem::size_of::<Self>() - mem::size_of::<S>();
+
+        MemUsage::with_payload(inline, storage.heap_bytes, self.len() * mem::size_of::<T>())
+    }
+}
+
 impl<T, S: Default + RangeStorage> Default for RawVec<T, S> {
     fn default() -> Self {
         RawVec::new(S::default())
@@ -158,51 +671,159 @@ impl<T, S: RangeStorage> Drop for RawVec<T, S> {
     }
 }
 
-//
-//  Implementation
-//
-
-impl<T, S: RangeStorage> RawVec<T, S> {
-    fn into_capacity(n: usize) -> S::Capacity {
-        S::Capacity::from_usize(n).expect("n <= S::maximum_capacity()")
+#[cfg(feature = "postcard")]
+impl<S: RangeStorage> RawVec<u8, S> {
+    /// Attempts to serialize `value` with postcard, into a new instance backed by `storage`.
+    ///
+    /// With an inline storage, the whole encoding is performed without any memory allocation.
+    pub fn try_serialize_postcard<T: ?Sized + Serialize>(
+        value: &T,
+        storage: S,
+    ) -> Result<Self, postcard::Error> {
+        postcard::serialize_with_flavor(value, Self::new(storage))
     }
+}
 
-    fn raw_slice(&self) -> &[MaybeUninit<T>] {
-        //  Safety:
-        //  -   `self.data` is valid and points to valid data.
-        let range = unsafe { self.storage.get(&self.data) };
+#[cfg(feature = "postcard")]
+impl<S: RangeStorage> postcard::ser_flavors::Flavor for RawVec<u8, S> {
+    type Output = Self;
 
-        //  Safety:
-        //  -   `range` points to valid data.
-        //  -   The lifetime of the slice is actually that of `self.storage`.
-        unsafe { &*range.as_ptr() }
+    fn try_extend(&mut self, data: &[u8]) -> Result<(), postcard::Error> {
+        self.try_extend_from_slice(data)
+            .map_err(|_| postcard::Error::SerializeBufferFull)
     }
 
-    fn raw_slice_mut(&mut self) -> &mut [MaybeUninit<T>] {
-        //  Safety:
-        //  -   `self.data` is valid and points to valid data.
-        let range = unsafe { self.storage.get(&self.data) };
-
-        //  Safety:
-        //  -   `range` points to valid data.
-        //  -   The lifetime of the slice is actually that of `self.storage`.
-        unsafe { &mut *range.as_ptr() }
+    fn try_push(&mut self, data: u8) -> Result<(), postcard::Error> {
+        RawVec::try_push(self, data).map_err(|_| postcard::Error::SerializeBufferFull)
+    }
+
+    fn finalize(self) -> Result<Self, postcard::Error> {
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: Serialize, S: RangeStorage> Serialize for RawVec<T, S> {
+    fn serialize<Se: Serializer>(&self, serializer: Se) -> Result<Se::Ok, Se::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>, S: Default + RangeStorage> Deserialize<'de> for RawVec<T, S> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut result = Self::default();
+        RawVecSeed::new(&mut result).deserialize(deserializer)?;
+        Ok(result)
+    }
+}
+
+/// Deserializes a sequence in place, appending its elements to an existing `RawVec`.
+///
+/// Each element is written into the storage as soon as it is parsed, without any intermediate buffer, and the
+/// storage need not implement `Default`.
+///
+/// On failure, the elements deserialized so far are kept.
+#[cfg(feature = "serde")]
+pub struct RawVecSeed<'a, T, S: RangeStorage> {
+    vec: &'a mut RawVec<T, S>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T, S: RangeStorage> RawVecSeed<'a, T, S> {
+    /// Creates an instance, appending to `vec`.
+    pub fn new(vec: &'a mut RawVec<T, S>) -> Self {
+        Self { vec }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, 'de, T: Deserialize<'de>, S: RangeStorage> DeserializeSeed<'de> for RawVecSeed<'a, T, S> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_seq(RawVecVisitor {
+            vec: self.vec,
+            _marker: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'a, T, S: RangeStorage> Debug for RawVecSeed<'a, T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "RawVecSeed")
+    }
+}
+
+//
+//  Implementation
+//
+
+const CONTEXT: &str = "RawVec";
+
+#[cfg(feature = "serde")]
+struct RawVecVisitor<'a, 'de, T, S: RangeStorage> {
+    vec: &'a mut RawVec<T, S>,
+    _marker: PhantomData<&'de ()>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a, 'de, T: Deserialize<'de>, S: RangeStorage> Visitor<'de> for RawVecVisitor<'a, 'de, T, S> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(element) = seq.next_element()? {
+            if self.vec.try_push(element).is_err() {
+                return Err(de::Error::custom(format_args!(
+                    "insufficient capacity, after {} elements",
+                    self.vec.len()
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, S: RangeStorage> RawVec<T, S> {
+    fn into_capacity(n: usize) -> S::Capacity {
+        S::Capacity::from_usize(n).expect("n <= S::maximum_capacity()")
+    }
+
+    fn raw_slice(&self) -> &[MaybeUninit<T>] {
+        //  Safety:
+        //  -   `self.data` is valid and points to valid data.
+        let range = unsafe { self.storage.get(&self.data) };
+
+        //  Safety:
+        //  -   `range` points to valid data.
+        //  -   The lifetime of the slice is actually that of `self.storage`.
+        unsafe { &*range.as_ptr() }
+    }
+
+    fn raw_slice_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        //  Safety:
+        //  -   `self.data` is valid and points to valid data.
+        let range = unsafe { self.storage.get(&self.data) };
+
+        //  Safety:
+        //  -   `range` points to valid data.
+        //  -   The lifetime of the slice is actually that of `self.storage`.
+        unsafe { &mut *range.as_ptr() }
     }
 
     #[inline(never)]
-    fn try_push_grow(&mut self, e: T) -> Result<(), T> {
+    fn try_push_grow(&mut self, e: T) -> Result<(), InsertError<T>> {
         let len = self.len.into_usize();
-        let new_cap = cmp::max(1, len * 2);
 
-        //  Safety:
-        //  -   `self.data` is a valid handle pointing to valid data.
-        self.data = match unsafe {
-            self.storage
-                .try_grow(&self.data, Self::into_capacity(new_cap))
-        } {
-            Ok(handle) => handle,
-            Err(_) => return Err(e),
-        };
+        if let Err(error) = self.try_reserve(1) {
+            return Err(InsertError::new(e, error));
+        }
 
         let slice = self.raw_slice_mut();
 
@@ -223,6 +844,8 @@ mod test_inline {
 
     use core::mem;
 
+    use std::{sync::Arc, thread};
+
     use crate::inline::NonTrackingRange;
 
     use super::*;
@@ -254,6 +877,18 @@ mod test_inline {
     );
     }
 
+    #[test]
+    fn fixed_capacity() {
+        type Storage = NonTrackingRange<u8, u32, 4>;
+
+        assert_eq!(16, RawVec::<u8, Storage>::CAPACITY);
+        assert_eq!(4, RawVec::<u32, Storage>::CAPACITY);
+
+        let vec = RawVec::<u16, Storage>::try_with_fixed_capacity::<8>(Storage::default()).unwrap();
+
+        assert_eq!(8, vec.capacity());
+    }
+
     #[test]
     fn try_push_failure() {
         type Storage = NonTrackingRange<u8, u8, 1>;
@@ -262,7 +897,313 @@ mod test_inline {
         let mut vec = Vec::default();
         vec.push(0);
 
-        assert_eq!(Err(42), vec.try_push(42));
+        assert_eq!(Err(42), vec.try_push(42).map_err(InsertError::into_value));
+    }
+
+    #[test]
+    fn insert_remove() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+
+        vec.try_insert(0, 1).unwrap();
+        vec.try_insert(0, 0).unwrap();
+        vec.try_insert(2, 3).unwrap();
+        vec.try_insert(2, 2).unwrap();
+
+        assert_eq!(
+            Err(4),
+            vec.try_insert(4, 4).map_err(InsertError::into_value)
+        );
+        assert_eq!([0, 1, 2, 3], *vec);
+
+        assert_eq!(1, vec.remove(1));
+        assert_eq!(3, vec.remove(2));
+        assert_eq!([0, 2], *vec);
+    }
+
+    #[test]
+    #[should_panic]
+    fn remove_out_of_bounds() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.push(0);
+
+        vec.remove(1);
+    }
+
+    #[test]
+    fn swap_remove() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.try_extend_from_slice(&[1, 2, 3, 4]).unwrap();
+
+        assert_eq!(2, vec.swap_remove(1));
+        assert_eq!([1, 4, 3], &*vec);
+
+        assert_eq!(3, vec.swap_remove(2));
+        assert_eq!([1, 4], &*vec);
+    }
+
+    #[test]
+    #[should_panic]
+    fn swap_remove_out_of_bounds() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.push(1);
+
+        vec.swap_remove(1);
+    }
+
+    #[test]
+    fn retain_dedup_search() {
+        type Storage = NonTrackingRange<u8, u8, 8>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.try_extend_from_slice(&[1, 1, 2, 3, 3, 3, 4, 5])
+            .unwrap();
+
+        vec.dedup();
+
+        assert_eq!([1, 2, 3, 4, 5], &*vec);
+
+        vec.retain(|e| *e % 2 == 1);
+
+        assert_eq!([1, 3, 5], &*vec);
+        assert_eq!(Ok(1), vec.binary_search(&3));
+        assert_eq!(Err(2), vec.binary_search(&4));
+
+        vec.dedup_by_key(|e| *e / 4);
+
+        assert_eq!([1, 5], &*vec);
+    }
+
+    #[test]
+    fn reserve_truncate() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+
+        vec.try_reserve(4).unwrap();
+        vec.try_reserve(5).unwrap_err();
+
+        for i in 0..4 {
+            vec.push(i);
+        }
+
+        vec.truncate(6);
+        assert_eq!([0, 1, 2, 3], *vec);
+
+        vec.truncate(1);
+        assert_eq!([0], *vec);
+    }
+
+    #[test]
+    fn read_into_spare_capacity() {
+        type Storage = NonTrackingRange<u8, u8, 8>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.push(0);
+
+        assert_eq!(3, read_into(&mut vec, b"abc"));
+        assert_eq!(4, read_into(&mut vec, b"defghijk"));
+        assert_eq!(0, read_into(&mut vec, b"l"));
+
+        assert_eq!(b"\0abcdefg", &*vec);
+    }
+
+    #[test]
+    fn push_capped_capacity() {
+        type Storage = NonTrackingRange<u8, u8, 1000>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+
+        for i in 0..255 {
+            vec.push(i);
+        }
+
+        assert_eq!(255, vec.capacity());
+        assert_eq!(Err(42), vec.try_push(42).map_err(InsertError::into_value));
+    }
+
+    #[test]
+    fn freeze_shared() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.push(1);
+        vec.push(2);
+
+        let frozen = Arc::new(vec.freeze());
+
+        let other = frozen.clone();
+        let sum = thread::spawn(move || other.iter().sum::<u8>())
+            .join()
+            .unwrap();
+
+        assert_eq!(3, sum);
+        assert_eq!([1, 2], **frozen);
+    }
+
+    #[test]
+    fn freeze_thaw() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.push(1);
+
+        let mut vec = vec.freeze().thaw();
+        vec.push(2);
+
+        assert_eq!([1, 2], *vec);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let vec: Vec = serde_json::from_str("[1, 2, 3]").unwrap();
+
+        assert_eq!([1, 2, 3], &*vec);
+        assert_eq!("[1,2,3]", serde_json::to_string(&vec).unwrap());
+
+        serde_json::from_str::<Vec>("[1, 2, 3, 4, 5]").unwrap_err();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_in_place() {
+        type Storage = NonTrackingRange<u8, u8, 4>;
+        type Vec = RawVec<u8, Storage>;
+
+        let mut vec = Vec::default();
+        vec.push(0);
+
+        let mut deserializer = serde_json::Deserializer::from_str("[1, 2, 3, 4]");
+
+        RawVecSeed::new(&mut vec)
+            .deserialize(&mut deserializer)
+            .unwrap_err();
+
+        assert_eq!([0, 1, 2, 3], &*vec);
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_round_trip() {
+        type Vec = RawVec<u16, NonTrackingRange<u8, u16, 4>>;
+        type Buffer = RawVec<u8, NonTrackingRange<u8, u8, 8>>;
+
+        let mut vec = Vec::default();
+        vec.try_extend_from_slice(&[1, 2, 300]).unwrap();
+
+        let buffer = Buffer::try_serialize_postcard(&vec, NonTrackingRange::default()).unwrap();
+
+        assert_eq!([3, 1, 2, 172, 2], &*buffer);
+
+        let vec: Vec = postcard::from_bytes(&buffer).unwrap();
+
+        assert_eq!([1, 2, 300], &*vec);
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn postcard_buffer_full() {
+        type Buffer = RawVec<u8, NonTrackingRange<u8, u8, 4>>;
+
+        let error =
+            Buffer::try_serialize_postcard(&[1u32, 2, 3, 4, 5], NonTrackingRange::default())
+                .unwrap_err();
+
+        assert_eq!(postcard::Error::SerializeBufferFull, error);
+    }
+
+    #[test]
+    fn shrink_unsupported() {
+        let mut vec = RawVec::<u8, NonTrackingRange<u8, u8, 4>>::default();
+
+        vec.push(1);
+
+        assert_eq!(
+            Err(Some("RangeStorage::try_shrink")),
+            vec.try_shrink_to_fit().map_err(|error| error.context())
+        );
+        assert_eq!(4, vec.capacity());
+        assert_eq!([1], vec.as_slice());
+    }
+
+    #[test]
+    fn push_or_evict() {
+        use crate::collections::{DropOldest, RejectNewest};
+
+        let mut samples = RawVec::<u32, NonTrackingRange<u8, u32, 3>>::default();
+
+        for sample in 1..=3 {
+            assert_eq!(None, samples.push_or_evict(sample, DropOldest));
+        }
+
+        assert_eq!(Some(1), samples.push_or_evict(4, DropOldest));
+        assert_eq!([2, 3, 4], *samples);
+
+        assert_eq!(Some(5), samples.push_or_evict(5, RejectNewest));
+        assert_eq!([2, 3, 4], *samples);
+
+        //  Evicts the smallest sample, keeping the largest ones.
+        let smallest = |samples: &[u32], sample: &u32| {
+            let (index, min) = samples.iter().enumerate().min_by_key(|(_, s)| **s)?;
+
+            if min < sample {
+                Some(index)
+            } else {
+                None
+            }
+        };
+
+        assert_eq!(Some(2), samples.push_or_evict(7, smallest));
+        assert_eq!(Some(1), samples.push_or_evict(1, smallest));
+        assert_eq!([3, 4, 7], *samples);
+        assert_eq!(3, samples.capacity());
+    }
+
+    #[test]
+    fn push_or_evict_zero_capacity() {
+        use crate::collections::DropOldest;
+
+        let mut samples = RawVec::<u32, NonTrackingRange<u8, u32, 0>>::default();
+
+        assert_eq!(Some(1), samples.push_or_evict(1, DropOldest));
+        assert!(samples.is_empty());
+    }
+
+    //  Reads as much of `source` as fits into the spare capacity of `vec`, returning the number of bytes read.
+    pub(super) fn read_into<S: RangeStorage>(vec: &mut RawVec<u8, S>, source: &[u8]) -> usize {
+        let spare = vec.spare_capacity_mut();
+        let read = cmp::min(spare.len(), source.len());
+
+        for (slot, byte) in spare.iter_mut().zip(&source[..read]) {
+            slot.write(*byte);
+        }
+
+        //  Safety:
+        //  -   `read` elements of the spare capacity were just initialized.
+        unsafe { vec.set_len(vec.len() + read) };
+
+        read
     }
 } // mod test_inline
 
@@ -274,6 +1215,7 @@ mod test_allocator {
     use crate::allocator::AllocStorage;
     use crate::utils::{NonAllocator, SpyAllocator};
 
+    use super::test_inline::read_into;
     use super::*;
 
     #[test]
@@ -323,6 +1265,218 @@ mod test_allocator {
 
         let mut vec = Vec::default();
 
-        assert_eq!(Err(42), vec.try_push(42));
+        assert_eq!(Err(42), vec.try_push(42).map_err(InsertError::into_value));
+    }
+
+    #[test]
+    fn reserve() {
+        type Storage = AllocStorage<SpyAllocator>;
+        type Vec = RawVec<String, Storage>;
+
+        let allocator = SpyAllocator::default();
+
+        let mut vec = Vec::new(AllocStorage::new(allocator.clone()));
+        vec.try_reserve(5).unwrap();
+
+        assert_eq!(5, vec.capacity());
+        assert_eq!(1, allocator.allocated());
+
+        for i in 0..5 {
+            vec.try_insert(0, i.to_string()).unwrap();
+        }
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(["4", "3", "2", "1", "0"], *vec);
+
+        vec.truncate(2);
+
+        assert_eq!(["4", "3"], *vec);
+    }
+
+    #[test]
+    fn read_into_spare_capacity() {
+        type Storage = AllocStorage<SpyAllocator>;
+        type Vec = RawVec<u8, Storage>;
+
+        let allocator = SpyAllocator::default();
+
+        let mut vec = Vec::new(AllocStorage::new(allocator.clone()));
+
+        assert_eq!(0, read_into(&mut vec, b"abc"));
+
+        vec.try_reserve(4).unwrap();
+        assert_eq!(3, read_into(&mut vec, b"abc"));
+
+        //  Relocates the range, the spare capacity is re-acquired afterwards.
+        vec.try_reserve(8).unwrap();
+        assert_eq!(5, read_into(&mut vec, b"defgh"));
+
+        assert_eq!(b"abcdefgh", &*vec);
+        assert_eq!(2, allocator.allocated());
+    }
+
+    #[test]
+    fn into_storage() {
+        type Storage = AllocStorage<SpyAllocator>;
+        type Vec = RawVec<String, Storage>;
+
+        let allocator = SpyAllocator::default();
+
+        let mut vec = Vec::new(AllocStorage::new(allocator.clone()));
+        vec.push("Hello".to_string());
+
+        let mut vec = Vec::new(vec.into_storage());
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+
+        vec.push("World".to_string());
+
+        assert_eq!(["World".to_string()], &*vec);
+    }
+
+    #[test]
+    fn raw_parts() {
+        type Storage = AllocStorage<SpyAllocator>;
+        type Vec = RawVec<String, Storage>;
+
+        let allocator = SpyAllocator::default();
+
+        let mut vec = Vec::new(AllocStorage::new(allocator.clone()));
+        vec.push("Hello".to_string());
+
+        let (handle, len, storage) = vec.into_raw_parts();
+
+        assert_eq!(1, len);
+        assert_eq!(0, allocator.deallocated());
+
+        //  Safety:
+        //  -   All parts were obtained from `into_raw_parts`.
+        let vec = unsafe { Vec::from_raw_parts(handle, len, storage) };
+
+        assert_eq!(["Hello".to_string()], &*vec);
+
+        mem::drop(vec);
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn reserve_huge() {
+        type Storage = AllocStorage<SpyAllocator>;
+        type Vec = RawVec<[u8; 1 << 12], Storage>;
+
+        let allocator = SpyAllocator::default();
+
+        let mut vec = Vec::new(AllocStorage::new(allocator.clone()));
+        let maximum = isize::MAX as usize >> 12;
+
+        vec.try_reserve(maximum + 1).unwrap_err();
+        vec.try_reserve(usize::MAX).unwrap_err();
+
+        assert_eq!(0, allocator.allocated());
+    }
+
+    #[test]
+    fn extend_resize() {
+        type Storage = AllocStorage<SpyAllocator>;
+        type Vec = RawVec<String, Storage>;
+
+        let allocator = SpyAllocator::default();
+
+        let mut vec = Vec::new(AllocStorage::new(allocator.clone()));
+        vec.try_extend_from_slice(&["a".to_string(), "b".to_string()])
+            .unwrap();
+        vec.try_resize(4, "c".to_string()).unwrap();
+
+        assert_eq!(["a", "b", "c", "c"], &*vec);
+
+        vec.try_resize(1, "d".to_string()).unwrap();
+
+        assert_eq!(["a"], &*vec);
+
+        mem::drop(vec);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn extend_failure() {
+        type Storage = AllocStorage<NonAllocator>;
+        type Vec = RawVec<String, Storage>;
+
+        let mut vec = Vec::default();
+
+        vec.try_extend_from_slice(&["a".to_string()]).unwrap_err();
+        vec.try_resize(1, "a".to_string()).unwrap_err();
+
+        assert!(vec.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_in_place() {
+        //  The storage borrows the arena, hence cannot implement `Default`.
+        let allocator = crate::utils::ArenaAllocator::<64>::new();
+
+        let mut vec = RawVec::<u32, _>::new(AllocStorage::new(&allocator));
+
+        let mut deserializer = serde_json::Deserializer::from_str("[1, 2, 3]");
+        RawVecSeed::new(&mut vec)
+            .deserialize(&mut deserializer)
+            .unwrap();
+
+        assert_eq!([1, 2, 3], &*vec);
+        assert_eq!(4 * vec.capacity(), allocator.live_bytes());
+    }
+
+    #[test]
+    fn retain_dedup_drop() {
+        type Storage = AllocStorage<SpyAllocator>;
+        type Vec = RawVec<String, Storage>;
+
+        let mut vec = Vec::default();
+
+        for word in &["a", "a", "b", "c", "c", "d"] {
+            vec.push(word.to_string());
+        }
+
+        vec.dedup();
+        vec.retain(|e| e != "b");
+
+        assert_eq!(["a", "c", "d"], &*vec);
+        assert_eq!("a", vec.swap_remove(0));
+        assert_eq!(["d", "c"], &*vec);
+    }
+
+    #[test]
+    fn shrink() {
+        let allocator = SpyAllocator::default();
+        let mut vec = RawVec::<u32, _>::new(AllocStorage::new(allocator.clone()));
+
+        for i in 0..5 {
+            vec.push(i);
+        }
+
+        assert_eq!(8, vec.capacity());
+
+        vec.try_shrink_to(6).unwrap();
+
+        assert_eq!(6, vec.capacity());
+
+        vec.try_shrink_to_fit().unwrap();
+        vec.as_mut_slice()[0] = 5;
+
+        assert_eq!(5, vec.capacity());
+        assert_eq!([5, 1, 2, 3, 4], vec.as_slice());
+
+        vec.clear();
+        vec.try_shrink_to_fit().unwrap();
+
+        assert_eq!(0, vec.capacity());
+
+        drop(vec);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
     }
 } // mod test_allocator
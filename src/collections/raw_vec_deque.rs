@@ -0,0 +1,501 @@
+//! Proof-of-Concept implementation of a VecDeque parameterized by a Storage.
+
+use core::{
+    cmp,
+    fmt::{self, Debug},
+    mem::MaybeUninit,
+    ptr,
+};
+
+use crate::{
+    defaults::DefaultRangeStorage,
+    error::StorageError,
+    traits::{Capacity, RangeExt, RangeStorage},
+};
+
+use super::InsertError;
+
+/// A PoC VecDeque, a double-ended queue implemented as a ring buffer.
+///
+/// The elements occupy `head..head + len` of the range, wrapping around its end. Growing the range moves the front
+/// elements -- those before the wrap-around point -- to the end of the new range, so that the elements remain
+/// contiguous modulo the new capacity; this move is folded into the copy of the range, see
+/// `RangeStorage::try_grow_with_gap`.
+pub struct RawVecDeque<T, S: RangeStorage = DefaultRangeStorage> {
+    head: usize,
+    len: usize,
+    data: S::Handle<T>,
+    storage: S,
+}
+
+impl<T, S: RangeStorage> RawVecDeque<T, S> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        let data = storage.dangling();
+
+        Self {
+            head: 0,
+            len: 0,
+            data,
+            storage,
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements in `self`.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of elements `self` can hold without growing.
+    pub fn capacity(&self) -> usize {
+        //  Safety:
+        //  -   `self.data` is valid.
+        unsafe { self.storage.get(&self.data) }.len()
+    }
+
+    /// Attempts to reserve capacity for at least `additional` more elements.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), StorageError> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or_else(|| StorageError::array::<T>(usize::MAX).with_context(CONTEXT))?;
+        let old_capacity = self.capacity();
+
+        if required <= old_capacity {
+            return Ok(());
+        }
+
+        let maximum = self.storage.maximum_capacity::<T>().into_usize();
+
+        if required > maximum {
+            return Err(StorageError::array::<T>(required).with_context(CONTEXT));
+        }
+
+        let new_capacity = cmp::min(cmp::max(required, old_capacity * 2), maximum);
+        let new_capacity = S::Capacity::from_usize(new_capacity)
+            .ok_or_else(|| StorageError::array::<T>(new_capacity).with_context(CONTEXT))?;
+
+        if self.head + self.len <= old_capacity {
+            //  Safety:
+            //  -   `self.data` is a valid handle pointing to valid data.
+            self.data = unsafe { self.storage.try_grow(&self.data, new_capacity)? };
+
+            return Ok(());
+        }
+
+        //  The front elements, in `self.head..old_capacity`, are moved towards the end of the new range as part of the
+        //  copy, making room after the back elements, in `0..self.head`.
+        let gap = new_capacity.into_usize() - old_capacity;
+
+        //  Safety:
+        //  -   `self.data` is a valid handle pointing to valid data.
+        //  -   `self.head` is within bounds, and `new_capacity` accomodates the gap.
+        self.data = unsafe {
+            self.storage
+                .try_grow_with_gap(&self.data, new_capacity, self.head, gap)?
+        };
+
+        let front = old_capacity - self.head;
+        let (moved_head, new_head) = (self.head + gap, self.capacity() - front);
+
+        //  The storage may grant more than `new_capacity`, in which case the front elements are moved to the end.
+        if moved_head != new_head {
+            //  Safety:
+            //  -   `self.data` is valid.
+            //  -   `moved_head..moved_head + front` holds the front elements, and `new_head..` is within bounds.
+            unsafe {
+                let base = self.storage.get(&self.data).as_mut_ptr();
+                ptr::copy(base.add(moved_head), base.add(new_head), front);
+            }
+        }
+
+        self.head = new_head;
+
+        Ok(())
+    }
+
+    /// Returns a reference to the element at `index`, counting from the front, if any.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+
+        //  Safety:
+        //  -   `index < self.len`, hence the slot holds an initialized element.
+        Some(unsafe { &*self.slot(index) })
+    }
+
+    /// Returns a mutable reference to the element at `index`, counting from the front, if any.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+
+        //  Safety:
+        //  -   `index < self.len`, hence the slot holds an initialized element.
+        Some(unsafe { &mut *self.slot(index) })
+    }
+
+    /// Returns a reference to the front element, if any.
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Returns a reference to the back element, if any.
+    pub fn back(&self) -> Option<&T> {
+        self.get(self.len.checked_sub(1)?)
+    }
+
+    /// Attempts to push a new element at the back.
+    ///
+    /// On failure, `self` is left unchanged, and `e` is returned.
+    pub fn try_push_back(&mut self, e: T) -> Result<(), InsertError<T>> {
+        if let Err(error) = self.try_reserve(1) {
+            return Err(InsertError::new(e, error));
+        }
+
+        //  Safety:
+        //  -   `self.len < self.capacity()`, as capacity was reserved, hence the slot is uninitialized.
+        unsafe { ptr::write(self.slot(self.len), e) };
+
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Pushes a new element at the back.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn push_back(&mut self, e: T) {
+        self.try_push_back(e).expect("Sufficient capacity");
+    }
+
+    /// Attempts to push a new element at the front.
+    ///
+    /// On failure, `self` is left unchanged, and `e` is returned.
+    pub fn try_push_front(&mut self, e: T) -> Result<(), InsertError<T>> {
+        if let Err(error) = self.try_reserve(1) {
+            return Err(InsertError::new(e, error));
+        }
+
+        let capacity = self.capacity();
+
+        self.head = (self.head + capacity - 1) % capacity;
+        self.len += 1;
+
+        //  Safety:
+        //  -   The slot before the former head is uninitialized, as capacity was reserved.
+        unsafe { ptr::write(self.slot(0), e) };
+
+        Ok(())
+    }
+
+    /// Pushes a new element at the front.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate the new element.
+    pub fn push_front(&mut self, e: T) {
+        self.try_push_front(e).expect("Sufficient capacity");
+    }
+
+    /// Pops the back element, if any.
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.len -= 1;
+
+        //  Safety:
+        //  -   The slot held the back element, which is no longer accessible.
+        Some(unsafe { ptr::read(self.slot(self.len)) })
+    }
+
+    /// Pops the front element, if any.
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        //  Safety:
+        //  -   The slot holds the front element, which is no longer accessible afterwards.
+        let result = unsafe { ptr::read(self.slot(0)) };
+
+        self.head = (self.head + 1) % self.capacity();
+        self.len -= 1;
+
+        Some(result)
+    }
+
+    /// Returns the elements, as two slices, the first holding the front elements and the second the back ones.
+    ///
+    /// The second slice is empty unless the elements wrap around the end of the range.
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (front, back) = self.split();
+
+        //  Safety:
+        //  -   `self.head..self.head + front` and `0..back` are within bounds, and hold the initialized elements.
+        //  -   Mutable references to the elements only ever exist while `self` is mutably borrowed.
+        unsafe {
+            let front = self.storage.slice_ref(&self.data, self.head, front);
+            let back = self.storage.slice_ref(&self.data, 0, back);
+
+            (
+                MaybeUninit::slice_assume_init_ref(front),
+                MaybeUninit::slice_assume_init_ref(back),
+            )
+        }
+    }
+
+    /// Returns the elements, as two mutable slices, the first holding the front elements and the second the back ones.
+    ///
+    /// The second slice is empty unless the elements wrap around the end of the range.
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (front, back) = self.split();
+
+        //  Safety:
+        //  -   `self.head..self.head + front` and `0..back` are within bounds, disjoint, and hold the initialized
+        //      elements.
+        //  -   `&mut self` guarantees that no other reference to the elements is alive.
+        unsafe {
+            let front = self.storage.slice_mut(&self.data, self.head, front);
+            let back = self.storage.slice_mut(&self.data, 0, back);
+
+            (
+                MaybeUninit::slice_assume_init_mut(front),
+                MaybeUninit::slice_assume_init_mut(back),
+            )
+        }
+    }
+
+    /// Returns an iterator over the elements, from front to back.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let (front, back) = self.as_slices();
+
+        front.iter().chain(back)
+    }
+
+    /// Clears `self`, destroying all elements.
+    pub fn clear(&mut self) {
+        let (front, back) = self.as_mut_slices();
+        let (front, back): (*mut [T], *mut [T]) = (front, back);
+
+        self.head = 0;
+        self.len = 0;
+
+        //  Safety:
+        //  -   `front` and `back` hold the initialized elements, which are no longer accessible through `self`.
+        unsafe {
+            ptr::drop_in_place(front);
+            ptr::drop_in_place(back);
+        }
+    }
+}
+
+impl<T: Debug, S: RangeStorage> Debug for RawVecDeque<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, S: Default + RangeStorage> Default for RawVecDeque<T, S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<T, S: RangeStorage> Drop for RawVecDeque<T, S> {
+    fn drop(&mut self) {
+        self.clear();
+
+        //  Safety:
+        //  -   `self.data` is valid.
+        unsafe { self.storage.deallocate(&self.data) };
+    }
+}
+
+//
+//  Implementation
+//
+
+const CONTEXT: &str = "RawVecDeque";
+
+impl<T, S: RangeStorage> RawVecDeque<T, S> {
+    //  Returns a pointer to the slot of the element at `index`, counting from the front.
+    //
+    //  Requires `index < self.capacity()`.
+    fn slot(&self, index: usize) -> *mut T {
+        //  Safety:
+        //  -   `self.data` is valid.
+        let range = unsafe { self.storage.get(&self.data) };
+
+        debug_assert!(index < range.len());
+
+        //  Safety:
+        //  -   The physical index is within bounds, as it is taken modulo the capacity.
+        unsafe { range.as_mut_ptr().add((self.head + index) % range.len()) as *mut T }
+    }
+
+    //  Returns the number of elements in `self.head..`, and in `0..self.head`, respectively.
+    fn split(&self) -> (usize, usize) {
+        let front = cmp::min(self.len, self.capacity() - self.head);
+
+        (front, self.len - front)
+    }
+}
+
+#[cfg(test)]
+mod test_inline {
+
+    use crate::inline::NonTrackingRange;
+
+    use super::*;
+
+    type Deque = RawVecDeque<u8, NonTrackingRange<u8, u8, 4>>;
+
+    #[test]
+    fn wrap_around() {
+        let mut deque = Deque::default();
+
+        deque.push_back(2);
+        deque.push_back(3);
+        deque.push_front(1);
+        deque.push_front(0);
+
+        assert_eq!("[0, 1, 2, 3]", format!("{:?}", deque));
+        assert_eq!((&[0u8, 1][..], &[2u8, 3][..]), deque.as_slices());
+
+        assert_eq!(4, deque.try_push_back(4).unwrap_err().into_value());
+
+        assert_eq!(Some(0), deque.pop_front());
+        assert_eq!(Some(3), deque.pop_back());
+
+        deque.push_back(4);
+        deque.push_back(5);
+
+        assert_eq!(Some(&1), deque.front());
+        assert_eq!(Some(&5), deque.back());
+        assert_eq!(
+            vec![5, 4, 2, 1],
+            deque.iter().rev().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut deque = Deque::default();
+
+        deque.push_front(1);
+        deque.push_front(0);
+
+        *deque.get_mut(1).unwrap() = 2;
+
+        assert_eq!(Some(&2), deque.get(1));
+        assert_eq!(None, deque.get(2));
+    }
+} // mod test_inline
+
+#[cfg(test)]
+mod test_allocator {
+
+    use crate::allocator::AllocStorage;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn grow_wrapped() {
+        let allocator = SpyAllocator::default();
+        let mut deque = RawVecDeque::<String, _>::new(AllocStorage::new(allocator.clone()));
+
+        for i in 0..4 {
+            deque.push_back(i.to_string());
+        }
+
+        //  Wrap around, then grow.
+        deque.pop_front();
+        deque.push_back("4".to_string());
+
+        assert_eq!(4, deque.capacity());
+        assert_eq!(1, deque.as_slices().1.len());
+
+        deque.push_front("0".to_string());
+
+        assert_eq!(8, deque.capacity());
+        assert_eq!(
+            "[\"0\", \"1\", \"2\", \"3\", \"4\"]",
+            format!("{:?}", deque)
+        );
+
+        drop(deque);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn drop_elements() {
+        let allocator = SpyAllocator::default();
+        let mut deque = RawVecDeque::<String, _>::new(AllocStorage::new(allocator.clone()));
+
+        deque.push_front("World".to_string());
+        deque.push_front("Hello".to_string());
+
+        //  The second element pushed at the front wrapped around.
+        let (front, back) = deque.as_mut_slices();
+        front[0].push('!');
+
+        assert_eq!(["World"], back);
+        assert_eq!(Some(&"Hello!".to_string()), deque.front());
+
+        deque.clear();
+
+        assert!(deque.is_empty());
+        assert_eq!(None, deque.pop_back());
+    }
+} // mod test_allocator
+
+#[cfg(test)]
+mod test_small {
+
+    use crate::small::SingleRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn grow_wrapped() {
+        let allocator = SpyAllocator::default();
+        let mut deque =
+            RawVecDeque::<u64, _>::new(SingleRange::<[u64; 4], _>::new(allocator.clone()));
+
+        for i in 0..4 {
+            deque.push_back(i);
+        }
+
+        //  Wrap around, then grow out of the inline storage.
+        deque.pop_front();
+        deque.push_back(4);
+
+        assert_eq!(4, deque.capacity());
+        assert_eq!(0, allocator.allocated());
+        assert_eq!(1, deque.as_slices().1.len());
+
+        deque.push_front(0);
+
+        assert_eq!(8, deque.capacity());
+        assert_eq!(1, allocator.allocated());
+        assert_eq!("[0, 1, 2, 3, 4]", format!("{:?}", deque));
+
+        drop(deque);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod test_small
@@ -0,0 +1,125 @@
+//! Macro composing stacks of storages, without hand-writing the nested composite types.
+
+/// Expands to the type of a composite storage, attempting each storage in turn, from left to right.
+///
+/// The kind of composite is selected by the leading keyword:
+///
+/// -   `element`, and `range`, nest `fallback::FallbackElement`, and `fallback::FallbackRange`, respectively.
+/// -   `single_element`, and `single_range`, nest `alternative::SingleElement`, and `alternative::SingleRange`,
+///     respectively, with `DefaultBuilder` as builders, hence all storages must implement `Default`.
+///
+/// ```ignore
+/// type Storage = compose_storage!(element: TrackingElement<[usize; 4], 2> => AllocStorage<Global>);
+/// ```
+///
+/// is equivalent to `FallbackElement<TrackingElement<[usize; 4], 2>, AllocStorage<Global>>`, whilst a third storage
+/// would nest a further `FallbackElement` as the second storage.
+#[macro_export]
+macro_rules! compose_storage {
+    (element: $last:ty) => { $last };
+    (element: $first:ty => $($rest:ty)=>+) => {
+        $crate::fallback::FallbackElement<$first, $crate::compose_storage!(element: $($rest)=>+)>
+    };
+
+    (range: $last:ty) => { $last };
+    (range: $first:ty => $($rest:ty)=>+) => {
+        $crate::fallback::FallbackRange<$first, $crate::compose_storage!(range: $($rest)=>+)>
+    };
+
+    (single_element: $last:ty) => { $last };
+    (single_element: $first:ty => $($rest:ty)=>+) => {
+        $crate::alternative::SingleElement<
+            $first,
+            $crate::compose_storage!(single_element: $($rest)=>+),
+            $crate::alternative::DefaultBuilder,
+            $crate::alternative::DefaultBuilder,
+        >
+    };
+
+    (single_range: $last:ty) => { $last };
+    (single_range: $first:ty => $($rest:ty)=>+) => {
+        $crate::alternative::SingleRange<
+            $first,
+            $crate::compose_storage!(single_range: $($rest)=>+),
+            $crate::alternative::DefaultBuilder,
+            $crate::alternative::DefaultBuilder,
+        >
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use alloc::alloc::Global;
+
+    use crate::allocator::AllocStorage;
+    use crate::alternative::{self, DefaultBuilder};
+    use crate::collections::{RawBox, RawVec};
+    use crate::fallback::FallbackRange;
+    use crate::inline::{NonTrackingElement, NonTrackingRange, TrackingElement};
+    use crate::traits::ElementStorage;
+
+    #[test]
+    fn element() {
+        type Storage = compose_storage!(
+            element: TrackingElement<u32, 1> => TrackingElement<u64, 1> => AllocStorage<Global>
+        );
+
+        let mut storage = Storage::default();
+
+        let first = storage.create(1u32).unwrap();
+        let second = storage.create(2u64).unwrap();
+        let third = storage.create([3u64; 4]).unwrap();
+
+        unsafe {
+            assert_eq!(1, *storage.get(&first).as_ref());
+            assert_eq!(2, *storage.get(&second).as_ref());
+            assert_eq!([3; 4], *storage.get(&third).as_ref());
+
+            storage.destroy(&first);
+            storage.destroy(&second);
+            storage.destroy(&third);
+        }
+    }
+
+    #[test]
+    fn range() {
+        type Storage = compose_storage!(
+            range: NonTrackingRange<u8, u8, 2> => NonTrackingRange<u8, u8, 4> => AllocStorage<Global>
+        );
+
+        let mut vec = RawVec::<u8, Storage>::new(FallbackRange::default());
+
+        for i in 0..16 {
+            vec.push(i);
+        }
+
+        assert_eq!(120, vec.iter().map(|i| *i as u32).sum::<u32>());
+    }
+
+    #[test]
+    fn single() {
+        type Element = compose_storage!(
+            single_element: NonTrackingElement<u32> => NonTrackingElement<u64> => AllocStorage<Global>
+        );
+
+        type Range = compose_storage!(
+            single_range: NonTrackingRange<u8, u8, 2> => AllocStorage<Global>
+        );
+
+        let element: Element =
+            alternative::SingleElement::first(NonTrackingElement::default(), DefaultBuilder);
+
+        let boxed = RawBox::new_in([1u64; 4], element);
+
+        assert_eq!([1; 4], *boxed);
+
+        let mut vec = RawVec::<u8, Range>::default();
+
+        vec.push(1);
+        vec.push(2);
+        vec.push(3);
+
+        assert_eq!([1, 2, 3], *vec);
+    }
+} // mod tests
@@ -0,0 +1,100 @@
+//! Default storages, used by the collections when their storage parameter is omitted.
+//!
+//! Downstream code writes `RawVec<T>`, or `RawBox<T>`, and the workspace decides the backend through features:
+//!
+//! -   By default, with the `alloc` feature, the storages are backed by the global allocator.
+//! -   With the `inline-default` feature, or without the `alloc` feature, the storages are inline.
+//!
+//! Cargo unifies features across the workspace, hence a single crate enabling `inline-default` switches the defaults
+//! of all crates. Code requiring a specific backend should name it rather than rely on the defaults.
+
+#[cfg(any(test, feature = "alloc"))]
+use alloc::alloc::Global;
+
+#[cfg(any(test, feature = "alloc"))]
+use crate::allocator::AllocStorage;
+use crate::{
+    inline::{NonTrackingElement, NonTrackingRange},
+    traits::{ElementStorage, RangeStorage},
+};
+
+/// A configuration of default storages, one for elements and one for ranges.
+pub trait DefaultStorage {
+    /// The storage used by the collections of elements, such as `RawBox` or `RawLinkedList`.
+    type Element: ElementStorage + Default;
+
+    /// The storage used by the collections of ranges, such as `RawVec` or `RawString`.
+    type Range: RangeStorage + Default;
+}
+
+/// Storages backed by the global allocator.
+#[cfg(any(test, feature = "alloc"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GlobalStorage;
+
+#[cfg(any(test, feature = "alloc"))]
+impl DefaultStorage for GlobalStorage {
+    type Element = AllocStorage<Global>;
+    type Range = AllocStorage<Global>;
+}
+
+/// Inline storages, able to hold `N` words.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InlineStorage<const N: usize>;
+
+impl<const N: usize> DefaultStorage for InlineStorage<N> {
+    type Element = NonTrackingElement<[usize; N]>;
+    type Range = NonTrackingRange<usize, usize, N>;
+}
+
+/// The configuration of default storages, as selected by the features of the crate.
+#[cfg(all(any(test, feature = "alloc"), not(feature = "inline-default")))]
+pub type Defaults = GlobalStorage;
+
+/// The configuration of default storages, as selected by the features of the crate.
+#[cfg(not(all(any(test, feature = "alloc"), not(feature = "inline-default"))))]
+pub type Defaults = InlineStorage<16>;
+
+/// The default storage of the collections of elements.
+pub type DefaultElementStorage = <Defaults as DefaultStorage>::Element;
+
+/// The default storage of the collections of ranges.
+pub type DefaultRangeStorage = <Defaults as DefaultStorage>::Range;
+
+#[cfg(test)]
+mod tests {
+
+    use crate::collections::{RawBox, RawString, RawVec};
+
+    use super::*;
+
+    #[test]
+    fn omitted_parameters() {
+        let mut vec: RawVec<u8> = RawVec::default();
+
+        for i in 1..=3 {
+            vec.push(i);
+        }
+
+        let string: RawString = RawString::from("Hello");
+
+        let boxed: RawBox<u32> = RawBox::new(42, DefaultElementStorage::default()).unwrap();
+
+        assert_eq!([1u8, 2, 3], *vec);
+        assert_eq!("Hello", &*string);
+        assert_eq!(42, *boxed);
+    }
+
+    #[test]
+    fn explicit_configuration() {
+        type Inline = InlineStorage<2>;
+
+        let mut vec: RawVec<usize, <Inline as DefaultStorage>::Range> = RawVec::default();
+
+        vec.push(1);
+        vec.push(2);
+
+        assert_eq!(3, vec.try_push(3).unwrap_err().into_value());
+        assert_eq!([1, 2], *vec);
+    }
+} // mod tests
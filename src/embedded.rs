@@ -0,0 +1,138 @@
+//! Adapters handing out the buffers of embedded network stacks from the collections of this crate.
+//!
+//! Network stacks in the style of smoltcp build their socket and packet buffers over a `ManagedSlice`, which borrows
+//! the memory rather than allocating it. Both `RawVec` and the guards of `RawPool` convert into a `ManagedSlice`,
+//! hence firmware can carve its packet storage out of a pool over an inline storage, placed wherever it sees fit:
+//!
+//! ```ignore
+//! type Storage = TrackingElement<RawPoolSlotStorage<[u8; 1536], usize>, 4>;
+//!
+//! let mut pool: PacketPool<Storage, 1536> = packet_pool(Storage::default());
+//! pool.try_reserve(4).unwrap();
+//!
+//! let (mut rx, mut tx) = (pool.checkout().unwrap(), pool.checkout().unwrap());
+//!
+//! let socket = tcp::Socket::new(tcp::SocketBuffer::new(&mut rx), tcp::SocketBuffer::new(&mut tx));
+//! ```
+//!
+//! The buffers are zeroed on their return to the pool, so that no payload leaks from one socket to the next.
+
+use managed::ManagedSlice;
+
+use crate::{
+    collections::{PoolGuard, RawPool, RawVec, Reusable},
+    traits::{ElementStorage, RangeStorage},
+};
+
+/// A pool of packet buffers, of `N` bytes each.
+pub type PacketPool<S, const N: usize> = RawPool<[u8; N], S, fn() -> [u8; N]>;
+
+/// Creates a new, empty, pool of zeroed packet buffers.
+pub fn packet_pool<S: ElementStorage, const N: usize>(storage: S) -> PacketPool<S, N> {
+    RawPool::new(storage, || [0; N])
+}
+
+impl<const N: usize> Reusable for [u8; N] {
+    fn reset(&mut self) {
+        self.fill(0);
+    }
+}
+
+impl<'a, T, S: RangeStorage> From<&'a mut RawVec<T, S>> for ManagedSlice<'a, T> {
+    /// Borrows the elements of the vector, excluding its spare capacity.
+    fn from(vec: &'a mut RawVec<T, S>) -> Self {
+        ManagedSlice::Borrowed(vec.as_mut_slice())
+    }
+}
+
+impl<'a, 'p, T, B, S, F> From<&'a mut PoolGuard<'p, B, S, F>> for ManagedSlice<'a, T>
+where
+    B: Reusable + AsMut<[T]>,
+    S: ElementStorage,
+    F: FnMut() -> B,
+{
+    /// Borrows the buffer checked out of the pool, which returns to the pool once the guard is dropped.
+    fn from(guard: &'a mut PoolGuard<'p, B, S, F>) -> Self {
+        ManagedSlice::Borrowed((**guard).as_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use smoltcp::{
+        socket::tcp,
+        storage::{PacketBuffer, PacketMetadata},
+    };
+
+    use crate::collections::RawPoolSlotStorage;
+    use crate::inline::{NonTrackingRange, TrackingElement};
+
+    use super::*;
+
+    type Storage = TrackingElement<RawPoolSlotStorage<[u8; 64], usize>, 2>;
+    type Pool = PacketPool<Storage, 64>;
+
+    #[test]
+    fn socket_buffers() {
+        let mut pool: Pool = packet_pool(Storage::default());
+        pool.try_reserve(2).unwrap();
+
+        let mut rx = pool.checkout().unwrap();
+        let mut tx = pool.checkout().unwrap();
+
+        {
+            let socket = tcp::Socket::new(
+                tcp::SocketBuffer::new(&mut rx),
+                tcp::SocketBuffer::new(&mut tx),
+            );
+
+            assert_eq!(64, socket.recv_capacity());
+            assert_eq!(64, socket.send_capacity());
+            assert_eq!(0, pool.available());
+        }
+
+        drop(rx);
+
+        assert_eq!(1, pool.available());
+    }
+
+    #[test]
+    fn packet_buffers() {
+        let mut metadata: RawVec<_, NonTrackingRange<u8, PacketMetadata<u8>, 4>> =
+            RawVec::default();
+
+        while metadata.len() < metadata.capacity() {
+            metadata.push(PacketMetadata::EMPTY);
+        }
+
+        let pool = {
+            let mut pool: Pool = packet_pool(Storage::default());
+            pool.try_reserve(1).unwrap();
+            pool
+        };
+
+        let mut payload = pool.checkout().unwrap();
+
+        {
+            let mut buffer = PacketBuffer::new(&mut metadata, &mut payload);
+
+            assert_eq!(4, buffer.packet_capacity());
+            assert_eq!(64, buffer.payload_capacity());
+
+            buffer.enqueue(5, 42).unwrap().copy_from_slice(b"Hello");
+
+            let (header, packet) = buffer.dequeue().unwrap();
+
+            assert_eq!(42, header);
+            assert_eq!(b"Hello", packet);
+        }
+
+        assert_eq!(b"Hello", &payload[..5]);
+
+        drop(payload);
+
+        //  The buffer was zeroed on its return to the pool.
+        assert_eq!([0; 64], *pool.checkout().unwrap());
+    }
+} // mod tests
@@ -0,0 +1,219 @@
+//! Allocation-free diagnostics for storage failures.
+//!
+//! `AllocError` carries no information, which leaves a failure propagated out of a deeply nested composite storage
+//! undiagnosable. `StorageError` records the `Layout` which could not be allocated, and a `&'static str` context
+//! naming the culprit, neither of which requires allocation nor formatting machinery to create or to propagate.
+//!
+//! The storages report their failures as `StorageError`, the storage which failed naming itself in the context;
+//! composites and wrappers forward the failure of the storage they delegate to as is, and only name themselves when
+//! the failure is theirs, such as a budget being exhausted. The collections return the failure of their storage, along
+//! with the value which could not be inserted, if any.
+//!
+//! On targets where even `core::fmt` is too heavy, `StorageError::to_bits` packs the layout in a single integer, and
+//! the `defmt` feature implements `defmt::Format`, deferring all formatting to the host.
+
+use core::{
+    alloc::{AllocError, Layout},
+    fmt::{self, Display},
+    mem,
+};
+
+/// A storage failure, along with the layout which could not be allocated, and the context in which it occurred.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StorageError {
+    layout: Layout,
+    context: Option<&'static str>,
+}
+
+impl StorageError {
+    /// Creates an instance, without context.
+    pub const fn new(layout: Layout) -> Self {
+        Self {
+            layout,
+            context: None,
+        }
+    }
+
+    /// Creates an instance, without context, for an array of `capacity` elements of `T`.
+    ///
+    /// The size of an array too large to be allocated saturates, which leaves it recognizable as an oversized request.
+    pub fn array<T>(capacity: usize) -> Self {
+        let align = mem::align_of::<T>();
+
+        let layout = Layout::array::<T>(capacity)
+            .or_else(|_| Layout::from_size_align(isize::MAX as usize & !(align - 1), align))
+            .expect("Saturated size is valid");
+
+        Self::new(layout)
+    }
+
+    /// Sets the context of `self`, unless already set.
+    ///
+    /// The innermost context, closest to the failure, is the most specific, and is therefore kept as the error is
+    /// propagated outward.
+    pub const fn with_context(self, context: &'static str) -> Self {
+        match self.context {
+            Some(_) => self,
+            None => Self {
+                layout: self.layout,
+                context: Some(context),
+            },
+        }
+    }
+
+    /// Returns the layout which could not be allocated.
+    pub const fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    /// Returns the context in which the failure occurred, if any.
+    pub const fn context(&self) -> Option<&'static str> {
+        self.context
+    }
+
+    /// Returns the layout, packed as `size << 6 | log2(align)`.
+    ///
+    /// Sizes exceeding 58 bits saturate, which leaves them recognizable as oversized requests.
+    pub const fn to_bits(&self) -> u64 {
+        const MAX_SIZE: u64 = u64::MAX >> ALIGN_BITS;
+
+        let size = self.layout.size() as u64;
+        let size = if size > MAX_SIZE { MAX_SIZE } else { size };
+
+        size << ALIGN_BITS | self.layout.align().trailing_zeros() as u64
+    }
+
+    /// Returns the size and alignment packed in `bits`, as per `to_bits`.
+    pub const fn from_bits(bits: u64) -> (u64, usize) {
+        (bits >> ALIGN_BITS, 1 << (bits & ALIGN_MASK))
+    }
+}
+
+impl Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        if let Some(context) = self.context {
+            write!(f, "{}: ", context)?;
+        }
+
+        write!(
+            f,
+            "failed to allocate {} bytes, aligned to {}",
+            self.layout.size(),
+            self.layout.align()
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for StorageError {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "StorageError{{ context: {=str}, layout: {=u64:#x} }}",
+            self.context.unwrap_or(""),
+            self.to_bits()
+        )
+    }
+}
+
+impl From<StorageError> for AllocError {
+    fn from(_: StorageError) -> Self {
+        AllocError
+    }
+}
+
+/// Attaches diagnostics to the failures of storage operations.
+pub trait StorageResultExt<T> {
+    /// Converts the failure, if any, into a `StorageError` with the given context.
+    ///
+    /// The `layout` is only used if the failure does not already carry one.
+    fn context(self, context: &'static str, layout: Layout) -> Result<T, StorageError>;
+}
+
+impl<T> StorageResultExt<T> for Result<T, AllocError> {
+    fn context(self, context: &'static str, layout: Layout) -> Result<T, StorageError> {
+        self.map_err(|_| StorageError::new(layout).with_context(context))
+    }
+}
+
+impl<T> StorageResultExt<T> for Result<T, StorageError> {
+    fn context(self, context: &'static str, _: Layout) -> Result<T, StorageError> {
+        self.map_err(|error| error.with_context(context))
+    }
+}
+
+//
+//  Implementation
+//
+
+const ALIGN_BITS: u32 = 6;
+
+const ALIGN_MASK: u64 = (1 << ALIGN_BITS) - 1;
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{
+        allocator::AllocStorage, collections::RawVec, fallback::FallbackRange,
+        inline::NonTrackingRange, utils::NonAllocator,
+    };
+
+    use super::*;
+
+    #[test]
+    fn innermost_context() {
+        let layout = Layout::new::<[u32; 3]>();
+
+        let inner: Result<(), AllocError> = Err(AllocError);
+
+        let error = inner
+            .context("arena", layout)
+            .context("graph", Layout::new::<u8>())
+            .unwrap_err();
+
+        assert_eq!(Some("arena"), error.context());
+        assert_eq!(layout, error.layout());
+        assert_eq!(
+            "arena: failed to allocate 12 bytes, aligned to 4",
+            error.to_string()
+        );
+        assert_eq!(
+            "failed to allocate 12 bytes, aligned to 4",
+            StorageError::new(layout).to_string()
+        );
+    }
+
+    #[test]
+    fn bits() {
+        let error = StorageError::new(Layout::from_size_align(48, 16).unwrap());
+
+        assert_eq!(48 << 6 | 4, error.to_bits());
+        assert_eq!((48, 16), StorageError::from_bits(error.to_bits()));
+
+        let huge = StorageError::new(Layout::from_size_align(isize::MAX as usize, 1).unwrap());
+
+        assert_eq!((u64::MAX >> 6, 1), StorageError::from_bits(huge.to_bits()));
+    }
+
+    #[test]
+    fn nested_composite() {
+        let storage = FallbackRange::new(
+            NonTrackingRange::<u8, u32, 4>::default(),
+            AllocStorage::new(NonAllocator),
+        );
+
+        let mut vec = RawVec::new(storage);
+
+        for i in 0..4 {
+            vec.push(i);
+        }
+
+        //  The failure is reported by the storage which failed, through both the composite and the collection.
+        let error = vec.try_push(4).unwrap_err();
+
+        assert_eq!(4, *error.value());
+        assert_eq!(Some("AllocStorage"), error.error().context());
+        assert_eq!(Layout::new::<[u32; 8]>(), error.error().layout());
+        assert_eq!(AllocError, AllocError::from(error));
+    }
+} // mod tests
@@ -1,15 +1,20 @@
 //! Fallback implementation of `ElementStorage`.
 
 use core::{
-    alloc::AllocError,
+    alloc::Layout,
     fmt::{self, Debug},
     marker::Unsize,
+    mem,
     ptr::NonNull,
 };
 
 use rfc2580::Pointee;
 
-use crate::traits::ElementStorage;
+use crate::{
+    error::StorageError,
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::{AddressStability, ConstantTimeStorage, ElementStorage, IndexedStorage},
+};
 
 /// FallbackElement is a fallback implementation of 2 ElementStorage.
 ///
@@ -22,7 +27,7 @@ pub struct FallbackElement<F, S> {
 
 impl<F, S> FallbackElement<F, S> {
     /// Creates an instance.
-    pub fn new(first: F, second: S) -> Self {
+    pub const fn new(first: F, second: S) -> Self {
         Self { first, second }
     }
 }
@@ -34,6 +39,8 @@ where
 {
     type Handle<T: ?Sized + Pointee> = FallbackElementHandle<F::Handle<T>, S::Handle<T>>;
 
+    const ADDRESS_STABILITY: AddressStability = F::ADDRESS_STABILITY.and(S::ADDRESS_STABILITY);
+
     unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
         use FallbackElementHandle::*;
 
@@ -64,6 +71,37 @@ where
         }
     }
 
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        use FallbackElementHandle::*;
+
+        match handle {
+            First(first) => First(self.first.uncoerce(first)),
+            Second(second) => Second(self.second.uncoerce(second)),
+        }
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        use FallbackElementHandle::*;
+
+        match handle {
+            First(first) => self.first.recycle(first).map(First),
+            Second(second) => self.second.recycle(second).map(Second),
+        }
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        //  New elements are preferably stored in the first storage.
+        self.first
+            .slot_layout::<T>()
+            .or_else(|| self.second.slot_layout::<T>())
+    }
+
     fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
         use FallbackElementHandle::*;
 
@@ -76,7 +114,7 @@ where
     fn allocate<T: ?Sized + Pointee>(
         &mut self,
         meta: T::MetaData,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         use FallbackElementHandle::*;
 
         self.first
@@ -86,12 +124,39 @@ where
     }
 }
 
+impl<F, S> IndexedStorage for FallbackElement<F, S>
+where
+    F: IndexedStorage,
+    S: IndexedStorage,
+{
+    const SLOT_COUNT: usize = F::SLOT_COUNT + S::SLOT_COUNT;
+
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize {
+        use FallbackElementHandle::*;
+
+        match handle {
+            First(first) => self.first.index(first),
+            Second(second) => F::SLOT_COUNT + self.second.index(second),
+        }
+    }
+}
+
+impl<F: ConstantTimeStorage, S: ConstantTimeStorage> ConstantTimeStorage for FallbackElement<F, S> {}
+
 impl<F, S> Debug for FallbackElement<F, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "FallbackElement")
     }
 }
 
+impl<F: MemoryUsage, S: MemoryUsage> MemoryUsage for FallbackElement<F, S> {
+    fn mem_usage(&self) -> MemUsage {
+        let padding = mem::size_of::<Self>() - mem::size_of::<F>() - mem::size_of::<S>();
+
+        self.first.mem_usage() + self.second.mem_usage() + MemUsage::with_payload(padding, 0, 0)
+    }
+}
+
 impl<F: Default, S: Default> Default for FallbackElement<F, S> {
     fn default() -> Self {
         Self::new(F::default(), S::default())
@@ -106,8 +171,44 @@ pub enum FallbackElementHandle<F, S> {
     Second(S),
 }
 
+impl<F: Clone, S: Clone> Clone for FallbackElementHandle<F, S> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::First(handle) => Self::First(handle.clone()),
+            Self::Second(handle) => Self::Second(handle.clone()),
+        }
+    }
+}
+
+impl<F: Copy, S: Copy> Copy for FallbackElementHandle<F, S> {}
+
 impl<F, S> Debug for FallbackElementHandle<F, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "FallbackElementHandle")
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use crate::inline::{DualElement, TrackingElement};
+
+    use super::*;
+
+    #[test]
+    fn index_tiered() {
+        type Storage = FallbackElement<DualElement<u32>, TrackingElement<u32, 3>>;
+
+        assert_eq!(5, Storage::SLOT_COUNT);
+
+        let mut storage = Storage::default();
+
+        let handles: Vec<_> = (0..4u32).map(|i| storage.create(i).unwrap()).collect();
+        let indices: Vec<_> = handles
+            .iter()
+            .map(|handle| unsafe { storage.index(handle) })
+            .collect();
+
+        assert_eq!(vec![0, 1, 2, 3], indices);
+    }
+} // mod tests
@@ -1,14 +1,17 @@
 //! Alternative implementation of `RangeStorage`.
 
 use core::{
-    alloc::AllocError,
     cmp,
     fmt::{self, Debug},
-    mem::MaybeUninit,
+    mem::{self, MaybeUninit},
     ptr::{self, NonNull},
 };
 
-use crate::traits::{Capacity, RangeStorage};
+use crate::{
+    error::StorageError,
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::{Capacity, ConstantTimeStorage, RangeStorage},
+};
 
 /// FallbackRange is a composite of 2 RangeStorage.
 ///
@@ -21,7 +24,7 @@ pub struct FallbackRange<F, S> {
 
 impl<F, S> FallbackRange<F, S> {
     /// Creates an instance.
-    pub fn new(first: F, second: S) -> Self {
+    pub const fn new(first: F, second: S) -> Self {
         Self { first, second }
     }
 }
@@ -36,16 +39,15 @@ where
     type Capacity = S::Capacity;
 
     fn maximum_capacity<T>(&self) -> Self::Capacity {
-        let first = self.first.maximum_capacity::<T>();
+        //  A single range is allocated from either storage, never both.
+        let first = self.first.maximum_capacity::<T>().into_usize();
         let second = self.second.maximum_capacity::<T>();
 
-        let result = first.into_usize().saturating_add(second.into_usize());
-
-        if let Some(result) = S::Capacity::from_usize(result) {
-            result
-        } else {
-            second
+        if first <= second.into_usize() {
+            return second;
         }
+
+        S::Capacity::from_usize(first).unwrap_or_else(S::Capacity::max)
     }
 
     unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
@@ -66,16 +68,20 @@ where
         }
     }
 
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        FallbackRangeHandle::First(self.first.dangling())
+    }
+
     unsafe fn try_grow<T>(
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         use FallbackRangeHandle::*;
 
         match handle {
             First(first) => {
-                let first_capacity = into_first::<F, S>(new_capacity);
+                let first_capacity = into_first::<T, F, S>(new_capacity);
 
                 match first_capacity
                     .and_then(|new_capacity| self.first.try_grow(first, new_capacity))
@@ -96,14 +102,48 @@ where
         }
     }
 
+    unsafe fn try_grow_with<T, G>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: G,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        G: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        use FallbackRangeHandle::*;
+
+        match handle {
+            First(first) => {
+                //  Growing in place cannot be rolled back, hence the new range is allocated anew, in either storage.
+                let new = self.allocate::<T>(new_capacity)?;
+                let to = self.get(&new);
+
+                transfer(self.first.get(first), to);
+
+                if let Err(error) = init(to) {
+                    self.deallocate(&new);
+                    return Err(error);
+                }
+
+                self.first.deallocate(first);
+                Ok(new)
+            }
+            Second(second) => self
+                .second
+                .try_grow_with(second, new_capacity, init)
+                .map(Second),
+        }
+    }
+
     unsafe fn try_shrink<T>(
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         use FallbackRangeHandle::*;
 
-        let first_capacity = into_first::<F, S>(new_capacity);
+        let first_capacity = into_first::<T, F, S>(new_capacity);
 
         match handle {
             First(first) => self
@@ -124,10 +164,22 @@ where
         }
     }
 
-    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, AllocError> {
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
         use FallbackRangeHandle::*;
 
-        let first_capacity = into_first::<F, S>(capacity);
+        match handle {
+            First(first) => self.first.cast(first).map(First),
+            Second(second) => self.second.cast(second).map(Second),
+        }
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        use FallbackRangeHandle::*;
+
+        let first_capacity = into_first::<T, F, S>(capacity);
 
         if let Ok(first) = first_capacity.and_then(|cap| self.first.allocate(cap)) {
             Ok(First(first))
@@ -137,12 +189,22 @@ where
     }
 }
 
+impl<F: ConstantTimeStorage, S: ConstantTimeStorage> ConstantTimeStorage for FallbackRange<F, S> {}
+
 impl<F, S> Debug for FallbackRange<F, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "FallbackRange")
     }
 }
 
+impl<F: MemoryUsage, S: MemoryUsage> MemoryUsage for FallbackRange<F, S> {
+    fn mem_usage(&self) -> MemUsage {
+        let padding = mem::size_of::<Self>() - mem::size_of::<F>() - mem::size_of::<S>();
+
+        self.first.mem_usage() + self.second.mem_usage() + MemUsage::with_payload(padding, 0, 0)
+    }
+}
+
 impl<F: Default, S: Default> Default for FallbackRange<F, S> {
     fn default() -> Self {
         Self::new(F::default(), S::default())
@@ -167,10 +229,13 @@ impl<F, S> Debug for FallbackRangeHandle<F, S> {
 //  Implementation
 //
 
-fn into_first<F: RangeStorage, S: RangeStorage>(
+fn into_first<T, F: RangeStorage, S: RangeStorage>(
     capacity: S::Capacity,
-) -> Result<F::Capacity, AllocError> {
-    F::Capacity::from_usize(capacity.into_usize()).ok_or(AllocError)
+) -> Result<F::Capacity, StorageError> {
+    let capacity = capacity.into_usize();
+
+    F::Capacity::from_usize(capacity)
+        .ok_or_else(|| StorageError::array::<T>(capacity).with_context("FallbackRange"))
 }
 
 unsafe fn transfer<T>(from: NonNull<[MaybeUninit<T>]>, mut to: NonNull<[MaybeUninit<T>]>) {
@@ -183,3 +248,105 @@ unsafe fn transfer<T>(from: NonNull<[MaybeUninit<T>]>, mut to: NonNull<[MaybeUni
         cmp::min(from.len(), to.len()),
     );
 }
+
+#[cfg(test)]
+mod tests {
+
+    use core::alloc::Layout;
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    type Storage = FallbackRange<NonTrackingRange<u8, u8, 4>, AllocStorage<SpyAllocator>>;
+
+    fn rejected<T>(_: NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError> {
+        Err(StorageError::new(Layout::new::<T>()).with_context("init"))
+    }
+
+    unsafe fn fill(range: NonNull<[MaybeUninit<u8>]>, elements: core::ops::Range<usize>) {
+        for i in elements {
+            range.as_mut_ptr().add(i).write(MaybeUninit::new(i as u8));
+        }
+    }
+
+    #[test]
+    fn try_grow_with_rollback() {
+        let allocator = SpyAllocator::default();
+        let mut storage = Storage::new(
+            NonTrackingRange::default(),
+            AllocStorage::new(allocator.clone()),
+        );
+
+        let handle = storage.allocate::<u8>(4).unwrap();
+        unsafe { fill(storage.get(&handle), 0..4) };
+
+        let result = unsafe { storage.try_grow_with(&handle, 8, rejected) };
+
+        assert_eq!(Some("init"), result.unwrap_err().context());
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+
+        //  `handle` remains valid, and untouched.
+        let range = unsafe { storage.get(&handle).as_ref() };
+
+        assert_eq!([0, 1, 2, 3], unsafe {
+            MaybeUninit::slice_assume_init_ref(range)
+        });
+    }
+
+    #[test]
+    fn try_grow_with_commit() {
+        let allocator = SpyAllocator::default();
+        let mut storage = Storage::new(
+            NonTrackingRange::default(),
+            AllocStorage::new(allocator.clone()),
+        );
+
+        let handle = storage.allocate::<u8>(4).unwrap();
+        unsafe { fill(storage.get(&handle), 0..4) };
+
+        let handle = unsafe {
+            storage.try_grow_with(&handle, 8, |range| {
+                fill(range, 4..8);
+                Ok(())
+            })
+        }
+        .unwrap();
+
+        assert!(matches!(handle, FallbackRangeHandle::Second(_)));
+
+        let range = unsafe { storage.get(&handle).as_ref() };
+
+        assert_eq!([0, 1, 2, 3, 4, 5, 6, 7], unsafe {
+            MaybeUninit::slice_assume_init_ref(&range[..8])
+        });
+
+        //  Growing from the second storage is transactional as well.
+        let result = unsafe { storage.try_grow_with(&handle, 16, rejected) };
+
+        assert_eq!(Some("init"), result.unwrap_err().context());
+        assert_eq!(2, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+
+        unsafe { storage.deallocate(&handle) };
+
+        assert_eq!(2, allocator.deallocated());
+    }
+
+    #[test]
+    fn allocate_failure() {
+        let mut storage = FallbackRange::new(
+            NonTrackingRange::<u8, u32, 4>::default(),
+            AllocStorage::new(NonAllocator),
+        );
+
+        //  The failure of the second storage is reported, as the last resort.
+        let error = storage.allocate::<u32>(8).unwrap_err();
+
+        assert_eq!(Some("AllocStorage"), error.context());
+        assert_eq!(Layout::new::<[u32; 8]>(), error.layout());
+    }
+} // mod tests
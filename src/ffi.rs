@@ -0,0 +1,249 @@
+//! C API over the fixed pools, that is `TrackingElement`, so that C components may share the pools of the Rust side.
+//!
+//! Since C cannot instantiate generics, each pool type is exposed as a `PoolApi`: a table of its layout and of
+//! `extern "C"` functions, typically exported as a static by the Rust side:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub static SENSOR_POOL: PoolApi = PoolApi::new::<[u64; 4], 16>();
+//! ```
+//!
+//! And declared on the C side as:
+//!
+//! ```c
+//! struct pool_api {
+//!     size_t pool_size;
+//!     size_t pool_align;
+//!     size_t slot_size;
+//!     size_t slot_count;
+//!     void (*init)(void* pool);
+//!     bool (*allocate)(void* pool, size_t* handle);
+//!     void (*deallocate)(void* pool, size_t handle);
+//!     void* (*get)(const void* pool, size_t handle);
+//! };
+//!
+//! extern const struct pool_api SENSOR_POOL;
+//! ```
+//!
+//! A pool is either created by C code, by calling `init` on a suitably sized and aligned memory block, or created by
+//! Rust code, and passed to C code as a pointer.
+//!
+//! The handles are the raw representation of the Rust handles, see `TrackingElementHandle::into_raw`, hence a slot
+//! allocated on either side may be resolved, and deallocated, on the other.
+//!
+//! Unwinding across the C boundary is undefined behavior, hence a panic within any of the functions, such as one
+//! raised by the `on_full` hook of the pool, aborts the process instead.
+
+use core::{
+    ffi::c_void,
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+use crate::{
+    inline::{TrackingElement, TrackingElementHandle},
+    traits::ElementStorage,
+};
+
+/// The C API of a `TrackingElement<S, N>`, see the module documentation.
+///
+/// All functions take a pointer to the pool as first argument, which must point to a `TrackingElement<S, N>`,
+/// initialized by `init` unless calling `init` itself.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PoolApi {
+    /// The size of the pool, in bytes.
+    pub pool_size: usize,
+    /// The alignment of the pool, in bytes.
+    pub pool_align: usize,
+    /// The size of a slot, in bytes.
+    pub slot_size: usize,
+    /// The number of slots.
+    pub slot_count: usize,
+    /// Initializes the pool, with all slots free.
+    ///
+    /// The pool is assumed to point to a block of memory of at least `pool_size` bytes, aligned on `pool_align`.
+    pub init: unsafe extern "C" fn(pool: *mut c_void),
+    /// Allocates a slot, storing its handle in `handle`, and returns whether the allocation succeeded.
+    pub allocate: unsafe extern "C" fn(pool: *mut c_void, handle: *mut usize) -> bool,
+    /// Deallocates the slot of `handle`, which is assumed to have been allocated from the pool.
+    pub deallocate: unsafe extern "C" fn(pool: *mut c_void, handle: usize),
+    /// Returns a pointer to the slot of `handle`, which is assumed to have been allocated from the pool.
+    ///
+    /// The pointer is invalidated if the pool is moved, whereas the handle is not.
+    pub get: unsafe extern "C" fn(pool: *const c_void, handle: usize) -> *mut c_void,
+}
+
+impl PoolApi {
+    /// Creates the C API of a `TrackingElement<S, N>`.
+    pub const fn new<S, const N: usize>() -> Self {
+        Self {
+            pool_size: mem::size_of::<TrackingElement<S, N>>(),
+            pool_align: mem::align_of::<TrackingElement<S, N>>(),
+            slot_size: mem::size_of::<S>(),
+            slot_count: N,
+            init: init::<S, N>,
+            allocate: allocate::<S, N>,
+            deallocate: deallocate::<S, N>,
+            get: get::<S, N>,
+        }
+    }
+}
+
+//
+//  Implementation
+//
+
+//  The slots are handed out uninitialized, as C code is responsible for their content.
+type Slot<S> = MaybeUninit<S>;
+
+//  Aborts the process if dropped during unwinding, by panicking anew; forgotten on the normal path.
+//
+//  `catch_unwind` is not available in `no_std`, hence the double panic.
+struct AbortOnUnwind;
+
+impl AbortOnUnwind {
+    fn defuse(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for AbortOnUnwind {
+    fn drop(&mut self) {
+        panic!("Panic unwinding across the C boundary, aborting");
+    }
+}
+
+unsafe extern "C" fn init<S, const N: usize>(pool: *mut c_void) {
+    let guard = AbortOnUnwind;
+
+    ptr::write(pool as *mut TrackingElement<S, N>, TrackingElement::new());
+
+    guard.defuse();
+}
+
+unsafe extern "C" fn allocate<S, const N: usize>(pool: *mut c_void, handle: *mut usize) -> bool {
+    let guard = AbortOnUnwind;
+
+    let result = allocate_impl::<S, N>(pool, handle);
+
+    guard.defuse();
+
+    result
+}
+
+unsafe fn allocate_impl<S, const N: usize>(pool: *mut c_void, handle: *mut usize) -> bool {
+    let pool = &mut *(pool as *mut TrackingElement<S, N>);
+
+    let meta = rfc2580::into_non_null_parts(NonNull::<Slot<S>>::dangling()).0;
+
+    match pool.allocate::<Slot<S>>(meta) {
+        Ok(allocated) => {
+            *handle = allocated.into_raw();
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+unsafe extern "C" fn deallocate<S, const N: usize>(pool: *mut c_void, handle: usize) {
+    let guard = AbortOnUnwind;
+
+    let pool = &mut *(pool as *mut TrackingElement<S, N>);
+
+    pool.deallocate(&TrackingElementHandle::<Slot<S>>::from_raw(handle));
+
+    guard.defuse();
+}
+
+unsafe extern "C" fn get<S, const N: usize>(pool: *const c_void, handle: usize) -> *mut c_void {
+    let guard = AbortOnUnwind;
+
+    let pool = &*(pool as *const TrackingElement<S, N>);
+
+    let result = pool
+        .get(&TrackingElementHandle::<Slot<S>>::from_raw(handle))
+        .as_ptr() as *mut c_void;
+
+    guard.defuse();
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+
+    use alloc::alloc::{alloc, dealloc, Layout};
+
+    use super::*;
+
+    static POOL: PoolApi = PoolApi::new::<u64, 2>();
+
+    #[test]
+    fn layout() {
+        assert_eq!(mem::size_of::<TrackingElement<u64, 2>>(), POOL.pool_size);
+        assert_eq!(mem::align_of::<TrackingElement<u64, 2>>(), POOL.pool_align);
+        assert_eq!(8, POOL.slot_size);
+        assert_eq!(2, POOL.slot_count);
+    }
+
+    #[test]
+    fn created_by_c() {
+        let layout = Layout::from_size_align(POOL.pool_size, POOL.pool_align).unwrap();
+
+        unsafe {
+            let pool = alloc(layout) as *mut c_void;
+
+            (POOL.init)(pool);
+
+            let (mut first, mut second, mut third) = (0, 0, 0);
+
+            assert!((POOL.allocate)(pool, &mut first));
+            assert!((POOL.allocate)(pool, &mut second));
+            assert!(!(POOL.allocate)(pool, &mut third));
+
+            *((POOL.get)(pool, first) as *mut u64) = 1;
+            *((POOL.get)(pool, second) as *mut u64) = 2;
+
+            assert_eq!(1, *((POOL.get)(pool, first) as *const u64));
+            assert_eq!(2, *((POOL.get)(pool, second) as *const u64));
+
+            (POOL.deallocate)(pool, first);
+
+            assert!((POOL.allocate)(pool, &mut third));
+            assert_eq!(first, third);
+
+            dealloc(pool as *mut u8, layout);
+        }
+    }
+
+    #[test]
+    fn shared_with_rust() {
+        let mut storage = TrackingElement::<u64, 2>::new();
+
+        let handle = storage.create(7u64).unwrap();
+
+        //  Taken after the direct use of `storage` above, and only used until the direct use below.
+        let pool = &mut storage as *mut _ as *mut c_void;
+
+        let raw = unsafe {
+            assert_eq!(7, *((POOL.get)(pool, handle.into_raw()) as *const u64));
+
+            let mut raw = 0;
+            assert!((POOL.allocate)(pool, &mut raw));
+
+            *((POOL.get)(pool, raw) as *mut u64) = 8;
+
+            raw
+        };
+
+        let other = TrackingElementHandle::<u64>::from_raw(raw);
+
+        unsafe {
+            assert_eq!(8, *storage.get(&other).as_ref());
+
+            storage.destroy(&handle);
+            storage.deallocate(&other);
+        }
+    }
+} // mod tests
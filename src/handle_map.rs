@@ -0,0 +1,267 @@
+//! A table remapping the indices of elements migrated within, or between, storages.
+//!
+//! Whenever elements are relocated -- by `TrackingElement::compact`, for example -- every handle to them must be
+//! patched before any further use. Rather than each caller building its own table of old to new indices, the
+//! `HandleMap` records the relocations once, and then patches any number of handles.
+//!
+//! The table itself lives in a `RangeStorage`, hence an inline storage may be used to migrate without allocating.
+
+use core::{
+    fmt::{self, Debug},
+    mem::MaybeUninit,
+};
+
+use rfc2580::Pointee;
+
+use crate::{
+    defaults::DefaultRangeStorage,
+    error::StorageError,
+    inline::TrackingElementHandle,
+    traits::{Capacity, RangeStorage},
+};
+
+/// A table mapping the old index of each element to its new index.
+///
+/// Until recorded otherwise, each index maps to itself.
+pub struct HandleMap<S: RangeStorage = DefaultRangeStorage> {
+    len: usize,
+    data: S::Handle<usize>,
+    storage: S,
+}
+
+impl<S: RangeStorage> HandleMap<S> {
+    /// Creates a new, empty, instance.
+    pub fn new(storage: S) -> Self {
+        let data = storage.dangling();
+
+        Self {
+            len: 0,
+            data,
+            storage,
+        }
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of indices mapped, that is one past the largest index.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Attempts to reset `self` to the identity mapping of `len` indices, growing the table as necessary.
+    ///
+    /// On failure, `self` is left unchanged.
+    pub fn try_reset(&mut self, len: usize) -> Result<(), StorageError> {
+        if len > self.capacity() {
+            let capacity = S::Capacity::from_usize(len)
+                .ok_or_else(|| StorageError::array::<usize>(len).with_context("HandleMap"))?;
+
+            //  Safety:
+            //  -   `self.data` is valid.
+            self.data = unsafe { self.storage.try_grow(&self.data, capacity)? };
+        }
+
+        //  Safety:
+        //  -   `self.data` is valid.
+        let slots = unsafe { self.storage.get(&self.data).as_mut() };
+
+        for (index, slot) in slots[..len].iter_mut().enumerate() {
+            slot.write(index);
+        }
+
+        self.len = len;
+
+        Ok(())
+    }
+
+    /// Resets `self` to the identity mapping of `len` indices.
+    ///
+    /// #   Panics
+    ///
+    /// If the storage cannot accomodate `len` indices.
+    pub fn reset(&mut self, len: usize) {
+        self.try_reset(len).expect("Sufficient capacity");
+    }
+
+    /// Records that the element at index `from` now resides at index `to`.
+    ///
+    /// #   Panics
+    ///
+    /// If `from` is out of bounds.
+    pub fn record(&mut self, from: usize, to: usize) {
+        assert!(from < self.len, "{} out of bounds {}", from, self.len);
+
+        self.indices_mut()[from] = to;
+    }
+
+    /// Returns the new index of the element formerly at index `from`.
+    ///
+    /// #   Panics
+    ///
+    /// If `from` is out of bounds.
+    pub fn get(&self, from: usize) -> usize {
+        assert!(from < self.len, "{} out of bounds {}", from, self.len);
+
+        self.indices()[from]
+    }
+
+    /// Returns an iterator over the relocations, as pairs of old and new index, in increasing order of old index.
+    pub fn relocations(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.indices()
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(from, to)| from != to)
+    }
+
+    /// Returns a copy of `handle`, patched to refer to the new index of its element.
+    ///
+    /// #   Panics
+    ///
+    /// If the index of `handle` is out of bounds.
+    pub fn remap<T: ?Sized + Pointee>(
+        &self,
+        handle: &TrackingElementHandle<T>,
+    ) -> TrackingElementHandle<T> {
+        handle.with_index(self.get(handle.index()))
+    }
+}
+
+impl<S: RangeStorage> Debug for HandleMap<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_map().entries(self.relocations()).finish()
+    }
+}
+
+impl<S: Default + RangeStorage> Default for HandleMap<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S: RangeStorage> Drop for HandleMap<S> {
+    fn drop(&mut self) {
+        //  Safety:
+        //  -   `self.data` is valid.
+        unsafe { self.storage.deallocate(&self.data) };
+    }
+}
+
+//
+//  Implementation
+//
+
+impl<S: RangeStorage> HandleMap<S> {
+    fn capacity(&self) -> usize {
+        //  Safety:
+        //  -   `self.data` is valid.
+        unsafe { self.storage.get(&self.data) }.len()
+    }
+
+    fn indices(&self) -> &[usize] {
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   The first `self.len` indices are initialized.
+        unsafe {
+            MaybeUninit::slice_assume_init_ref(&self.storage.get(&self.data).as_ref()[..self.len])
+        }
+    }
+
+    fn indices_mut(&mut self) -> &mut [usize] {
+        //  Safety:
+        //  -   `self.data` is valid.
+        //  -   The first `self.len` indices are initialized.
+        unsafe {
+            MaybeUninit::slice_assume_init_mut(
+                &mut self.storage.get(&self.data).as_mut()[..self.len],
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::{NonTrackingRange, TrackingElement};
+    use crate::traits::ElementStorage;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn identity() {
+        let mut map = HandleMap::<NonTrackingRange<u8, usize, 4>>::default();
+
+        assert!(map.is_empty());
+
+        map.reset(3);
+
+        assert_eq!(3, map.len());
+        assert_eq!(2, map.get(2));
+        assert_eq!(0, map.relocations().count());
+
+        map.record(2, 0);
+
+        assert_eq!(0, map.get(2));
+        assert_eq!("{2: 0}", format!("{:?}", map));
+
+        //  Resetting forgets all relocations.
+        map.reset(4);
+
+        assert_eq!("{}", format!("{:?}", map));
+        assert!(map.try_reset(5).is_err());
+        assert_eq!(4, map.len());
+    }
+
+    #[test]
+    #[should_panic]
+    fn record_out_of_bounds() {
+        let mut map = HandleMap::<NonTrackingRange<u8, usize, 4>>::default();
+
+        map.reset(2);
+        map.record(2, 0);
+    }
+
+    #[test]
+    fn compact() {
+        let mut storage = TrackingElement::<u32, 4>::new();
+
+        let handles: Vec<_> = (0..4u32).map(|i| storage.create(i).unwrap()).collect();
+
+        unsafe { storage.destroy(&handles[0]) };
+        unsafe { storage.destroy(&handles[1]) };
+
+        let mut map = HandleMap::<NonTrackingRange<u8, usize, 4>>::default();
+
+        unsafe { storage.compact_into(&mut map).unwrap() };
+
+        assert_eq!(2, map.relocations().count());
+
+        for (value, handle) in handles.iter().enumerate().skip(2) {
+            let handle = map.remap(handle);
+
+            assert!(handle.index() < 2);
+            assert_eq!(value as u32, unsafe { *storage.get(&handle).as_ref() });
+        }
+    }
+
+    #[test]
+    fn allocator() {
+        let allocator = SpyAllocator::default();
+
+        let mut map = HandleMap::new(AllocStorage::new(allocator.clone()));
+
+        map.reset(100);
+        map.record(99, 0);
+
+        assert_eq!(vec![(99, 0)], map.relocations().collect::<Vec<_>>());
+
+        drop(map);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+} // mod tests
@@ -1,9 +1,98 @@
 //! Simple implementations of the various inline storages.
 
+mod bump_element;
+mod dual_element;
 mod non_tracking_element;
 mod non_tracking_range;
 mod tracking_elements;
 
+pub use bump_element::{ArenaBox, BumpElement, BumpElementHandle};
+pub use dual_element::{DualElement, DualElementHandle};
 pub use non_tracking_element::NonTrackingElement;
 pub use non_tracking_range::NonTrackingRange;
-pub use tracking_elements::{TrackingElement, TrackingElementHandle};
+pub use tracking_elements::{
+    AddressOrdered, FreeListOrder, Lifo, TrackingElement, TrackingElementExhaustion,
+    TrackingElementHandle, TrackingElementView,
+};
+
+//  Statistical check that the `ConstantTimeStorage` backends allocate and deallocate in time independent of their
+//  occupancy; timing is inherently noisy, hence the check is only performed on demand, with the `timing-tests` feature.
+#[cfg(all(test, feature = "timing-tests"))]
+mod timing_tests {
+
+    use core::ptr::{self, NonNull};
+
+    use std::time::Instant;
+
+    use crate::traits::{ConstantTimeStorage, ElementStorage};
+
+    use super::*;
+
+    const SLOTS: usize = 4096;
+
+    //  Returns the median duration, in nanoseconds, of a batch of allocation and deallocation round-trips.
+    fn median_round_trip<S: ConstantTimeStorage + ElementStorage>(storage: &mut S) -> u128 {
+        let meta = rfc2580::into_non_null_parts(NonNull::<u64>::dangling()).0;
+
+        let mut samples: Vec<u128> = (0..101)
+            .map(|_| {
+                let start = Instant::now();
+
+                for _ in 0..1_000 {
+                    let handle = storage.allocate::<u64>(meta).unwrap();
+
+                    //  Safety:
+                    //  -   `handle` is valid, fresh off the press.
+                    unsafe {
+                        ptr::write_volatile(storage.get(&handle).as_ptr(), 1);
+                        storage.deallocate(&handle);
+                    }
+                }
+
+                start.elapsed().as_nanos()
+            })
+            .collect();
+
+        samples.sort_unstable();
+        samples[samples.len() / 2]
+    }
+
+    fn assert_bounded(empty: u128, full: u128) {
+        assert!(
+            full <= 4 * empty.max(1),
+            "empty: {}ns, full: {}ns",
+            empty,
+            full
+        );
+    }
+
+    #[test]
+    fn tracking_element() {
+        let mut storage = Box::new(TrackingElement::<u64, SLOTS>::new());
+
+        let empty = median_round_trip(&mut *storage);
+
+        for _ in 0..SLOTS - 1 {
+            storage.create(0u64).unwrap();
+        }
+
+        let full = median_round_trip(&mut *storage);
+
+        assert_bounded(empty, full);
+    }
+
+    #[test]
+    fn bump_element() {
+        let mut storage = Box::new(BumpElement::<[u64; SLOTS]>::new());
+
+        let empty = median_round_trip(&mut *storage);
+
+        for _ in 0..SLOTS - 1 {
+            storage.create(0u64).unwrap();
+        }
+
+        let full = median_round_trip(&mut *storage);
+
+        assert_bounded(empty, full);
+    }
+} // mod timing_tests
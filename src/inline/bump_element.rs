@@ -0,0 +1,471 @@
+//! Inline bump implementation of `ElementStorage`.
+
+use core::{
+    alloc::Layout,
+    cell::{Cell, UnsafeCell},
+    fmt::{self, Debug},
+    marker::{PhantomData, Unsize},
+    mem::{self, MaybeUninit},
+    ops::{Deref, DerefMut},
+    ptr::{self, NonNull},
+};
+
+use rfc2580::{self, Pointee};
+
+use crate::{
+    error::StorageError,
+    traits::{AddressStability, ConstantTimeStorage, ElementStorage, TaggedStorage},
+    utils,
+};
+
+/// Generic inline bump ElementStorage.
+///
+/// Elements are carved out of `S` one after the other, and memory is only reclaimed when the last element is
+/// deallocated, or when the storage is reset.
+///
+/// On top of the storage API, `alloc` and `alloc_copy` offer arena-style allocation through a shared reference.
+///
+/// `S` is the underlying storage, used to specify the size and alignment of the arena.
+pub struct BumpElement<S> {
+    top: Cell<usize>,
+    data: UnsafeCell<MaybeUninit<S>>,
+}
+
+impl<S> BumpElement<S> {
+    /// Creates an instance.
+    pub const fn new() -> Self {
+        Self {
+            top: Cell::new(0),
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Returns the number of bytes in use, including padding.
+    pub fn used(&self) -> usize {
+        self.top.get()
+    }
+
+    /// Resets the storage, reclaiming all its memory.
+    ///
+    /// The elements are not destroyed, and all handles are invalidated.
+    pub fn reset(&mut self) {
+        self.top.set(0);
+    }
+
+    /// Moves `value` into the arena, returning a guard which dereferences to it.
+    ///
+    /// The value is dropped with the guard, whereas its memory is only reclaimed when the storage is reset.
+    ///
+    /// On failure, returns `value`.
+    pub fn alloc<T>(&self, value: T) -> Result<ArenaBox<'_, T>, T> {
+        match self.write(value) {
+            Ok(pointer) => Ok(ArenaBox {
+                pointer,
+                _marker: PhantomData,
+            }),
+            Err(value) => Err(value),
+        }
+    }
+
+    /// Copies `value` into the arena, returning a reference to it.
+    ///
+    /// Since `T` is `Copy`, there is nothing to drop, and therefore no guard.
+    ///
+    /// On failure, returns `value`.
+    //  Each call returns a reference to distinct memory, as is customary for arenas.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_copy<T: Copy>(&self, value: T) -> Result<&mut T, T> {
+        //  Safety:
+        //  -   `pointer` points to a valid value, which is only reachable through the returned reference.
+        self.write(value)
+            .map(|pointer| unsafe { &mut *pointer.as_ptr() })
+    }
+}
+
+impl<S> ElementStorage for BumpElement<S> {
+    type Handle<T: ?Sized + Pointee> = BumpElementHandle<T>;
+
+    const ADDRESS_STABILITY: AddressStability = AddressStability::ALLOCATIONS;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let layout = Layout::for_value_raw(self.get(handle).as_ptr());
+
+        //  Reclaim the memory, if it was the last element.
+        if handle.offset() + layout.size() == self.top.get() {
+            self.top.set(handle.offset());
+        }
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        //  Safety:
+        //  -   `handle` is assumed to be within range, as part of being valid.
+        let pointer = (self.data.get() as *mut u8).add(handle.offset());
+
+        //  Safety:
+        //  -   `pointer` is derived from a non-null pointer.
+        let pointer = NonNull::new_unchecked(pointer);
+
+        rfc2580::from_non_null_parts(handle.1, pointer)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut U).0;
+
+        BumpElementHandle(handle.0, meta)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut T).0;
+
+        BumpElementHandle(handle.0, meta)
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let start = self.bump(utils::layout_of::<T>(meta))?;
+
+        Ok(BumpElementHandle(start, meta))
+    }
+}
+
+impl<S> TaggedStorage for BumpElement<S> {
+    const TAG_BITS: u32 = usize::BITS - TAG_SHIFT;
+
+    fn tag<T: ?Sized + Pointee>(handle: &Self::Handle<T>) -> usize {
+        handle.0 >> TAG_SHIFT
+    }
+
+    fn set_tag<T: ?Sized + Pointee>(handle: &mut Self::Handle<T>, tag: usize) {
+        assert!(tag < (1 << Self::TAG_BITS), "tag {} out of range", tag);
+
+        handle.0 = handle.offset() | (tag << TAG_SHIFT);
+    }
+}
+
+impl<S> ConstantTimeStorage for BumpElement<S> {}
+
+impl<S> Debug for BumpElement<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "BumpElement{{ used: {}, capacity: {} }}",
+            self.top.get(),
+            mem::size_of::<S>()
+        )
+    }
+}
+
+impl<S> Default for BumpElement<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The Handle for BumpElement.
+///
+/// The handle is an offset within the arena, and therefore remains valid when the storage is moved.
+pub struct BumpElementHandle<T: ?Sized + Pointee>(usize, T::MetaData);
+
+impl<T: ?Sized + Pointee> BumpElementHandle<T> {
+    fn offset(&self) -> usize {
+        self.0 & OFFSET_MASK
+    }
+}
+
+impl<T: ?Sized + Pointee> Clone for BumpElementHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized + Pointee> Copy for BumpElementHandle<T> {}
+
+impl<T: ?Sized + Pointee> Debug for BumpElementHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "BumpElementHandle({})", self.offset())
+    }
+}
+
+/// A guard over a value allocated by `BumpElement::alloc`.
+///
+/// The guard dereferences to the value, and drops it when dropped.
+pub struct ArenaBox<'arena, T> {
+    pointer: NonNull<T>,
+    _marker: PhantomData<&'arena mut T>,
+}
+
+impl<'arena, T> ArenaBox<'arena, T> {
+    /// Leaks the guard, returning a reference to the value which is never dropped.
+    pub fn leak(this: Self) -> &'arena mut T {
+        let pointer = this.pointer;
+        mem::forget(this);
+
+        //  Safety:
+        //  -   `pointer` points to a valid value, which is only reachable through the returned reference.
+        unsafe { &mut *pointer.as_ptr() }
+    }
+}
+
+impl<'arena, T: Debug> Debug for ArenaBox<'arena, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let value: &T = self;
+        write!(f, "ArenaBox({:?})", value)
+    }
+}
+
+impl<'arena, T> Deref for ArenaBox<'arena, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        //  Safety:
+        //  -   `self.pointer` points to a valid value, for the lifetime of `self`.
+        unsafe { &*self.pointer.as_ptr() }
+    }
+}
+
+impl<'arena, T> DerefMut for ArenaBox<'arena, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        //  Safety:
+        //  -   `self.pointer` points to a valid value, for the lifetime of `self`.
+        unsafe { &mut *self.pointer.as_ptr() }
+    }
+}
+
+impl<'arena, T> Drop for ArenaBox<'arena, T> {
+    fn drop(&mut self) {
+        //  Safety:
+        //  -   `self.pointer` points to a valid value, never to be used again.
+        unsafe { ptr::drop_in_place(self.pointer.as_ptr()) };
+    }
+}
+
+//
+//  Implementation
+//
+
+impl<S> BumpElement<S> {
+    //  Carves out memory for `layout`, returning its offset.
+    fn bump(&self, layout: Layout) -> Result<usize, StorageError> {
+        let error = || StorageError::new(layout).with_context("BumpElement");
+
+        if layout.align() > mem::align_of::<S>() {
+            return Err(error());
+        }
+
+        //  `top` is at most `size_of::<S>()`, hence this cannot overflow.
+        let start = (self.top.get() + layout.align() - 1) & !(layout.align() - 1);
+        let end = start.checked_add(layout.size()).ok_or_else(error)?;
+
+        //  Offsets overlapping the tag bits are (theoretically) excluded as well.
+        if end > mem::size_of::<S>() || end > OFFSET_MASK {
+            return Err(error());
+        }
+
+        self.top.set(end);
+
+        Ok(start)
+    }
+
+    //  Moves `value` into the arena, returning a pointer to it.
+    fn write<T>(&self, value: T) -> Result<NonNull<T>, T> {
+        let start = match self.bump(Layout::new::<T>()) {
+            Ok(start) => start,
+            Err(_) => return Err(value),
+        };
+
+        //  Safety:
+        //  -   `start` is within the arena, as bumped.
+        let pointer = unsafe { (self.data.get() as *mut u8).add(start) as *mut T };
+
+        //  Safety:
+        //  -   `pointer` is suitably aligned, and points to memory no other element overlaps.
+        unsafe { ptr::write(pointer, value) };
+
+        //  Safety:
+        //  -   `pointer` is derived from a non-null pointer.
+        Ok(unsafe { NonNull::new_unchecked(pointer) })
+    }
+}
+
+//  The top 8 bits of the offset are reserved for tags.
+const TAG_SHIFT: u32 = usize::BITS - 8;
+const OFFSET_MASK: usize = usize::MAX >> 8;
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn new_unconditional_success() {
+        BumpElement::<u8>::new();
+    }
+
+    #[test]
+    fn new_const() {
+        const fn storage() -> BumpElement<[u32; 2]> {
+            BumpElement::new()
+        }
+
+        let mut storage = storage();
+        let handle = storage.create(1u32).unwrap();
+
+        assert_eq!(1, unsafe { *storage.get(&handle).as_ptr() });
+    }
+
+    #[test]
+    fn create_success() {
+        let mut storage = BumpElement::<[u32; 4]>::new();
+
+        let h1 = storage.create(1u8).unwrap();
+        let h2 = storage.create(2u32).unwrap();
+
+        assert_eq!(8, storage.used());
+        assert_eq!(1, unsafe { *storage.get(&h1).as_ptr() });
+        assert_eq!(2, unsafe { *storage.get(&h2).as_ptr() });
+    }
+
+    #[test]
+    fn create_insufficient_size() {
+        let mut storage = BumpElement::<[u8; 4]>::new();
+
+        storage.create([1u8, 2, 3]).unwrap();
+        storage.create([4u8, 5]).unwrap_err();
+    }
+
+    #[test]
+    fn create_insufficient_alignment() {
+        let mut storage = BumpElement::<[u8; 32]>::new();
+        storage.create(1u32).unwrap_err();
+    }
+
+    #[test]
+    fn deallocate_last() {
+        let mut storage = BumpElement::<[u32; 4]>::new();
+
+        let h1 = storage.create(1u32).unwrap();
+        let h2 = storage.create(2u32).unwrap();
+
+        unsafe { storage.destroy(&h1) };
+
+        assert_eq!(8, storage.used());
+
+        unsafe { storage.destroy(&h2) };
+
+        assert_eq!(4, storage.used());
+    }
+
+    #[test]
+    fn reset() {
+        let mut storage = BumpElement::<[u32; 1]>::new();
+
+        storage.create(1u32).unwrap();
+        storage.create(2u32).unwrap_err();
+
+        storage.reset();
+
+        let handle = storage.create(3u32).unwrap();
+
+        assert_eq!(3, unsafe { *storage.get(&handle).as_ptr() });
+    }
+
+    #[test]
+    fn get_accross_moves() {
+        let mut storage = BumpElement::<[u32; 4]>::new();
+
+        let h1 = storage.create(1u8).unwrap();
+        let h2 = storage.create(2u32).unwrap();
+
+        let storage = storage;
+
+        assert_eq!(1, unsafe { *storage.get(&h1).as_ptr() });
+        assert_eq!(2, unsafe { *storage.get(&h2).as_ptr() });
+    }
+
+    #[test]
+    fn tag() {
+        type Storage = BumpElement<[u32; 4]>;
+
+        let mut storage = Storage::new();
+
+        let _ = storage.create(1u32).unwrap();
+        let mut handle = storage.create(2u32).unwrap();
+
+        Storage::set_tag(&mut handle, 3);
+
+        assert_eq!(3, Storage::tag(&handle));
+        assert_eq!(2, unsafe { *storage.get(&handle).as_ptr() });
+
+        unsafe { storage.destroy(&handle) };
+
+        assert_eq!(4, storage.used());
+    }
+
+    #[test]
+    fn alloc() {
+        use std::rc::Rc;
+
+        let arena = BumpElement::<[usize; 4]>::new();
+        let shared = Rc::new(());
+
+        let mut first = arena.alloc(Rc::clone(&shared)).unwrap();
+        let second = arena.alloc(Rc::clone(&shared)).unwrap();
+
+        assert_eq!(3, Rc::strong_count(&shared));
+
+        *first = Rc::new(());
+
+        assert_eq!(2, Rc::strong_count(&shared));
+
+        drop(second);
+
+        assert_eq!(1, Rc::strong_count(&shared));
+        assert_eq!(2 * mem::size_of::<usize>(), arena.used());
+
+        let leaked = ArenaBox::leak(first);
+
+        assert_eq!(1, Rc::strong_count(leaked));
+    }
+
+    #[test]
+    fn alloc_copy() {
+        let arena = BumpElement::<[u32; 2]>::new();
+
+        let first = arena.alloc_copy(1u32).unwrap();
+        let second = arena.alloc_copy(2u32).unwrap();
+
+        *first += *second;
+
+        assert_eq!(3, *first);
+        assert_eq!(Err(3), arena.alloc_copy(3u32));
+    }
+
+    #[test]
+    fn coerce_unsize() {
+        let mut storage = BumpElement::<[u8; 4]>::new();
+        let handle = storage.create([1u8, 2]).unwrap();
+
+        let handle = unsafe { storage.coerce::<[u8], _>(&handle) };
+        let element = unsafe { storage.get(&handle) };
+
+        assert_eq!(&[1, 2], unsafe { element.as_ref() });
+    }
+} // mod tests
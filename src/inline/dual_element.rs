@@ -0,0 +1,323 @@
+//! Inline implementation of `ElementStorage` with exactly two slots.
+
+use core::{
+    alloc::Layout,
+    cell::UnsafeCell,
+    fmt::{self, Debug},
+    marker::Unsize,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use rfc2580::{self, Pointee};
+
+use crate::{
+    error::StorageError,
+    traits::{
+        AddressStability, ConstantTimeStorage, ElementStorage, IndexedStorage, TaggedStorage,
+    },
+    utils,
+};
+
+/// Generic inline ElementStorage with exactly two slots.
+///
+/// Geared towards sentinel-based designs, such as the head and tail sentinels of a doubly-linked list, which require
+/// two fixed nodes and no more.
+///
+/// `S` is the underlying storage of each slot, used to specify the size and alignment.
+pub struct DualElement<S> {
+    occupied: [bool; 2],
+    data: [UnsafeCell<MaybeUninit<S>>; 2],
+}
+
+impl<S> DualElement<S> {
+    /// Creates an instance.
+    pub const fn new() -> Self {
+        Self {
+            occupied: [false; 2],
+            data: [
+                UnsafeCell::new(MaybeUninit::uninit()),
+                UnsafeCell::new(MaybeUninit::uninit()),
+            ],
+        }
+    }
+
+    /// Returns the number of occupied slots.
+    pub fn len(&self) -> usize {
+        self.occupied.iter().filter(|occupied| **occupied).count()
+    }
+
+    /// Returns whether both slots are free.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<S> ElementStorage for DualElement<S> {
+    type Handle<T: ?Sized + Pointee> = DualElementHandle<T>;
+
+    const ADDRESS_STABILITY: AddressStability = AddressStability::ALLOCATIONS;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        debug_assert!(self.occupied[handle.index()]);
+
+        self.occupied[handle.index()] = false;
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        //  Safety:
+        //  -   `handle.index()` is either 0 or 1.
+        let slot = self.data.get_unchecked(handle.index());
+
+        //  Safety:
+        //  -   `slot.get()` is derived from a reference, hence non-null.
+        let pointer = NonNull::new_unchecked(slot.get() as *mut u8);
+
+        rfc2580::from_non_null_parts(handle.1, pointer)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut U).0;
+
+        DualElementHandle(handle.0, meta)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut T).0;
+
+        DualElementHandle(handle.0, meta)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        utils::validate_layout_for::<S>(Layout::new::<U>())
+            .map_err(|error| error.with_context("DualElement"))?;
+
+        let meta = rfc2580::into_non_null_parts(NonNull::<U>::dangling()).0;
+
+        //  The tag, if any, is preserved.
+        Ok(DualElementHandle(handle.0, meta))
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        utils::validate_layout_for::<S>(Layout::new::<T>()).ok()?;
+
+        Some(Layout::new::<S>())
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let layout = utils::layout_of::<T>(meta);
+
+        utils::validate_layout_for::<S>(layout)
+            .map_err(|error| error.with_context("DualElement"))?;
+
+        let index = self
+            .occupied
+            .iter()
+            .position(|occupied| !*occupied)
+            .ok_or_else(|| StorageError::new(layout).with_context("DualElement"))?;
+
+        self.occupied[index] = true;
+
+        Ok(DualElementHandle(index as u8, meta))
+    }
+}
+
+impl<S> TaggedStorage for DualElement<S> {
+    //  The lowest bit is the index.
+    const TAG_BITS: u32 = u8::BITS - 1;
+
+    fn tag<T: ?Sized + Pointee>(handle: &Self::Handle<T>) -> usize {
+        (handle.0 >> 1) as usize
+    }
+
+    fn set_tag<T: ?Sized + Pointee>(handle: &mut Self::Handle<T>, tag: usize) {
+        assert!(tag < (1 << Self::TAG_BITS), "tag {} out of range", tag);
+
+        handle.0 = (handle.0 & 1) | ((tag as u8) << 1);
+    }
+}
+
+impl<S> IndexedStorage for DualElement<S> {
+    const SLOT_COUNT: usize = 2;
+
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize {
+        handle.index()
+    }
+}
+
+impl<S> ConstantTimeStorage for DualElement<S> {}
+
+impl<S> Debug for DualElement<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "DualElement{{ occupied: {:?} }}", self.occupied)
+    }
+}
+
+impl<S> Default for DualElement<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The Handle for DualElement.
+///
+/// The handle is the index of its slot, and therefore remains valid when the storage is moved.
+pub struct DualElementHandle<T: ?Sized + Pointee>(u8, T::MetaData);
+
+impl<T: ?Sized + Pointee> DualElementHandle<T> {
+    /// Returns the index of the slot, either 0 or 1.
+    pub fn index(&self) -> usize {
+        (self.0 & 1) as usize
+    }
+}
+
+impl<T: ?Sized + Pointee> Clone for DualElementHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized + Pointee> Copy for DualElementHandle<T> {}
+
+impl<T: ?Sized + Pointee> Debug for DualElementHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "DualElementHandle({})", self.index())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::mem;
+
+    use super::*;
+
+    #[test]
+    fn new_unconditional_success() {
+        DualElement::<u8>::new();
+    }
+
+    #[test]
+    fn new_const() {
+        const fn storage() -> DualElement<u32> {
+            DualElement::new()
+        }
+
+        let mut storage = storage();
+        storage.create(1u32).unwrap();
+
+        assert_eq!(1, storage.len());
+    }
+
+    #[test]
+    fn handle_size() {
+        assert_eq!(1, mem::size_of::<DualElementHandle<u64>>());
+    }
+
+    #[test]
+    fn create_success() {
+        let mut storage = DualElement::<u32>::new();
+
+        let head = storage.create(1u32).unwrap();
+        let tail = storage.create(2u8).unwrap();
+
+        assert_eq!(2, storage.len());
+        assert_eq!((0, 1), (head.index(), tail.index()));
+        assert_eq!(1, unsafe { *storage.get(&head).as_ptr() });
+        assert_eq!(2, unsafe { *storage.get(&tail).as_ptr() });
+    }
+
+    #[test]
+    fn create_exhausted() {
+        let mut storage = DualElement::<u32>::new();
+
+        storage.create(1u32).unwrap();
+        storage.create(2u32).unwrap();
+        storage.create(3u32).unwrap_err();
+    }
+
+    #[test]
+    fn create_insufficient_size() {
+        let mut storage = DualElement::<u8>::new();
+        storage.create(1u16).unwrap_err();
+    }
+
+    #[test]
+    fn create_insufficient_alignment() {
+        let mut storage = DualElement::<[u8; 4]>::new();
+        storage.create(1u32).unwrap_err();
+    }
+
+    #[test]
+    fn destroy_reuse() {
+        let mut storage = DualElement::<u32>::new();
+
+        let head = storage.create(1u32).unwrap();
+        let tail = storage.create(2u32).unwrap();
+
+        unsafe { storage.destroy(&head) };
+
+        assert_eq!(1, storage.len());
+
+        let head = storage.create(3u32).unwrap();
+
+        assert_eq!(0, head.index());
+        assert_eq!(3, unsafe { *storage.get(&head).as_ptr() });
+        assert_eq!(2, unsafe { *storage.get(&tail).as_ptr() });
+    }
+
+    #[test]
+    fn tag() {
+        type Storage = DualElement<u32>;
+
+        let mut storage = Storage::new();
+
+        let _ = storage.create(1u32).unwrap();
+        let mut handle = storage.create(2u32).unwrap();
+
+        Storage::set_tag(&mut handle, 127);
+
+        assert_eq!(127, Storage::tag(&handle));
+        assert_eq!(1, handle.index());
+        assert_eq!(2, unsafe { *storage.get(&handle).as_ptr() });
+
+        let handle = unsafe { storage.coerce::<dyn Debug, _>(&handle) };
+
+        assert_eq!(127, Storage::tag(&handle));
+
+        unsafe { storage.destroy(&handle) };
+
+        assert_eq!(1, storage.len());
+    }
+
+    #[test]
+    fn coerce_unsize() {
+        let mut storage = DualElement::<[u8; 4]>::new();
+        let handle = storage.create([1u8, 2]).unwrap();
+
+        let handle = unsafe { storage.coerce::<[u8], _>(&handle) };
+        let element = unsafe { storage.get(&handle) };
+
+        assert_eq!(&[1, 2], unsafe { element.as_ref() });
+    }
+} // mod tests
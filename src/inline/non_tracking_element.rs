@@ -1,7 +1,7 @@
 //! Simple implementation of `ElementStorage<T>`.
 
 use core::{
-    alloc::AllocError,
+    alloc::Layout,
     cell::UnsafeCell,
     fmt::{self, Debug},
     marker::PhantomData,
@@ -12,7 +12,11 @@ use core::{
 
 use rfc2580::{self, Pointee};
 
-use crate::{traits::ElementStorage, utils};
+use crate::{
+    error::StorageError,
+    traits::{AddressStability, ConstantTimeStorage, ElementStorage},
+    utils,
+};
 
 pub struct NonTrackingElementHandle<T: ?Sized + Pointee, S> {
     data: UnsafeCell<MaybeUninit<S>>,
@@ -34,6 +38,9 @@ pub struct NonTrackingElement<S> {
 impl<S> ElementStorage for NonTrackingElement<S> {
     type Handle<T: ?Sized + Pointee> = NonTrackingElementHandle<T, S>;
 
+    //  The element lives within its handle, and moves along with it.
+    const ADDRESS_STABILITY: AddressStability = AddressStability::NONE;
+
     unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, _handle: &Self::Handle<T>) {
         // do nothing
     }
@@ -62,11 +69,37 @@ impl<S> ElementStorage for NonTrackingElement<S> {
         new_handle
     }
 
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut T).0;
+
+        let new_handle = NonTrackingElementHandle {
+            data: UnsafeCell::new(MaybeUninit::uninit()),
+            meta,
+        };
+        ptr::copy_nonoverlapping::<MaybeUninit<S>>(handle.data.get(), new_handle.data.get(), 1);
+        new_handle
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        utils::validate_layout_for::<S>(Layout::new::<T>()).ok()?;
+
+        //  The element is stored within the handle itself.
+        Some(Layout::new::<S>())
+    }
+
     fn allocate<T: ?Sized + Pointee>(
         &mut self,
         meta: T::MetaData,
-    ) -> Result<Self::Handle<T>, AllocError> {
-        let _ = utils::validate_layout::<T, S>(meta)?;
+    ) -> Result<Self::Handle<T>, StorageError> {
+        utils::validate_layout::<T, S>(meta)
+            .map_err(|error| error.with_context("NonTrackingElement"))?;
 
         Ok(NonTrackingElementHandle {
             data: UnsafeCell::new(MaybeUninit::uninit()),
@@ -83,6 +116,8 @@ impl<S> NonTrackingElement<S> {
     }
 }
 
+impl<S> ConstantTimeStorage for NonTrackingElement<S> {}
+
 impl<S> Default for NonTrackingElement<S> {
     fn default() -> Self {
         Self::new()
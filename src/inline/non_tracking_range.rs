@@ -1,17 +1,18 @@
 //! Simple implementation of `RangeStorage`.
 
 use core::{
-    alloc::AllocError,
+    alloc::Layout,
     cell::UnsafeCell,
-    cmp,
     fmt::{self, Debug},
     marker::PhantomData,
     mem::{self, MaybeUninit},
-    ptr::NonNull,
+    ptr::{self, NonNull},
 };
 
 use crate::{
-    traits::{Capacity, RangeStorage},
+    error::StorageError,
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::{BoundedRangeStorage, Capacity, ConstantTimeStorage, RangeStorage},
     utils,
 };
 
@@ -26,7 +27,8 @@ pub struct NonTrackingRange<C, S, const N: usize> {
 }
 
 impl<C: Capacity, S, const N: usize> NonTrackingRange<C, S, N> {
-    pub(crate) fn new() -> Self {
+    /// Creates a new instance.
+    pub const fn new() -> Self {
         Self {
             _marker: PhantomData,
         }
@@ -39,14 +41,7 @@ impl<C: Capacity, S, const N: usize> RangeStorage for NonTrackingRange<C, S, N>
     type Capacity = C;
 
     fn maximum_capacity<T>(&self) -> Self::Capacity {
-        assert!(mem::size_of::<S>().checked_mul(N).is_some());
-
-        //  The maximum capacity cannot exceed what can fit in an `isize`.
-        let capacity = cmp::min(C::max().into_usize(), N);
-
-        C::from_usize(mem::size_of::<S>() * capacity / mem::size_of::<T>())
-            .or_else(|| C::from_usize(capacity))
-            .expect("Cannot fail, since capacity <= C::max()")
+        Self::capacity_of::<T>()
     }
 
     unsafe fn deallocate<T>(&mut self, _handle: &Self::Handle<T>) {
@@ -56,33 +51,97 @@ impl<C: Capacity, S, const N: usize> RangeStorage for NonTrackingRange<C, S, N>
     unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
         let pointer: NonNull<MaybeUninit<T>> = NonNull::from(&handle.data).cast();
 
-        NonNull::slice_from_raw_parts(pointer, N)
+        NonNull::slice_from_raw_parts(pointer, Self::capacity_of::<T>().into_usize())
     }
 
-    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, AllocError> {
-        utils::validate_array_layout::<T, [MaybeUninit<S>; N]>(capacity.into_usize())?;
-        Ok(NonTrackingRangeHandle {
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        NonTrackingRangeHandle {
             data: UnsafeCell::new(MaybeUninit::uninit_array()),
             _marker: PhantomData,
-        })
+        }
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        //  The range spans the whole of the inline memory whatever the type of its elements, hence only the alignment
+        //  matters.
+        if mem::align_of::<U>() > mem::align_of::<S>() {
+            return Err(StorageError::new(Layout::new::<U>()).with_context("NonTrackingRange"));
+        }
+
+        let new_handle = self.dangling::<U>();
+        ptr::copy_nonoverlapping(handle.data.get(), new_handle.data.get(), 1);
+
+        Ok(new_handle)
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        utils::validate_array_layout::<T, [MaybeUninit<S>; N]>(capacity.into_usize())
+            .map_err(|error| error.with_context("NonTrackingRange"))?;
+        Ok(self.dangling())
     }
 }
 
+impl<C: Capacity, S, T, const N: usize> BoundedRangeStorage<T> for NonTrackingRange<C, S, N> {
+    const MAX_CAPACITY: usize = Self::max_capacity_of::<T>();
+}
+
+impl<C: Capacity, S, const N: usize> ConstantTimeStorage for NonTrackingRange<C, S, N> {}
+
 impl<C: Capacity, S, const N: usize> Default for NonTrackingRange<C, S, N> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+//  The range lives within its handle, and is accounted for by the collection holding it.
+impl<C: Capacity, S, const N: usize> MemoryUsage for NonTrackingRange<C, S, N> {
+    fn mem_usage(&self) -> MemUsage {
+        MemUsage::default()
+    }
+}
+
 impl<T, S, const N: usize> Debug for NonTrackingRangeHandle<T, S, N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "NonTrackingRangeHandle")
     }
 }
 
+//
+//  Implementation
+//
+
+impl<C: Capacity, S, const N: usize> NonTrackingRange<C, S, N> {
+    fn capacity_of<T>() -> C {
+        C::from_usize(<Self as BoundedRangeStorage<T>>::MAX_CAPACITY).expect("capacity <= C::max()")
+    }
+
+    //  Returns the number of elements of type `T` which fit within `[S; N]`, capped by `C::max()`.
+    const fn max_capacity_of<T>() -> usize {
+        if mem::align_of::<T>() > mem::align_of::<S>() {
+            return 0;
+        }
+
+        let capacity = match mem::size_of::<T>() {
+            0 => usize::MAX,
+            size => mem::size_of::<[MaybeUninit<S>; N]>() / size,
+        };
+
+        if capacity < C::MAX_USIZE {
+            capacity
+        } else {
+            C::MAX_USIZE
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
+    use crate::traits::RangeExt;
+
     use super::*;
 
     #[test]
@@ -107,4 +166,113 @@ mod tests {
         let mut storage = NonTrackingRange::<u8, u8, 42>::new();
         storage.allocate::<u32>(1).unwrap_err();
     }
+
+    #[test]
+    fn maximum_capacity() {
+        let mut storage = NonTrackingRange::<u16, u32, 3>::new();
+
+        assert_eq!(12, storage.maximum_capacity::<u8>());
+        assert_eq!(6, storage.maximum_capacity::<u16>());
+        assert_eq!(1, storage.maximum_capacity::<[u32; 3]>());
+        assert_eq!(0, storage.maximum_capacity::<[u32; 4]>());
+        assert_eq!(0, storage.maximum_capacity::<[u8; 1 << 40]>());
+        assert_eq!(0, storage.maximum_capacity::<u64>());
+        assert_eq!(u16::MAX, storage.maximum_capacity::<()>());
+
+        let handle = storage.allocate::<u8>(12).unwrap();
+        assert_eq!(12, unsafe { storage.get(&handle) }.len());
+
+        storage.allocate::<u8>(13).unwrap_err();
+    }
+
+    #[test]
+    fn maximum_capacity_capped() {
+        let storage = NonTrackingRange::<u8, u8, 1000>::new();
+
+        assert_eq!(u8::MAX, storage.maximum_capacity::<u8>());
+        assert_eq!(250, storage.maximum_capacity::<[u8; 4]>());
+    }
+
+    #[test]
+    fn max_capacity() {
+        type Storage = NonTrackingRange<u8, u32, 100>;
+
+        assert_eq!(255, <Storage as BoundedRangeStorage<u8>>::MAX_CAPACITY);
+        assert_eq!(100, <Storage as BoundedRangeStorage<u32>>::MAX_CAPACITY);
+        assert_eq!(0, <Storage as BoundedRangeStorage<u64>>::MAX_CAPACITY);
+
+        let storage = Storage::new();
+
+        assert_eq!(25, storage.maximum_capacity::<[u32; 4]>());
+        assert_eq!(25, <Storage as BoundedRangeStorage<[u32; 4]>>::MAX_CAPACITY);
+    }
+
+    #[test]
+    fn fill_copy() {
+        let mut storage = NonTrackingRange::<u8, u8, 6>::new();
+        let handle = storage.allocate::<u8>(6).unwrap();
+
+        unsafe { storage.fill(&handle, 0..2, 1) };
+        unsafe { storage.copy_from_slice(&handle, 2, &[2, 3, 4, 5]) };
+
+        let slice = unsafe { storage.get(&handle).as_ref() };
+        let slice = unsafe { MaybeUninit::slice_assume_init_ref(slice) };
+
+        assert_eq!([1, 1, 2, 3, 4, 5], slice);
+    }
+
+    #[test]
+    fn slice() {
+        let mut storage = NonTrackingRange::<u8, u8, 6>::new();
+        let handle = storage.allocate::<u8>(6).unwrap();
+
+        unsafe { storage.copy_from_slice(&handle, 0, &[1, 2, 3, 4, 5, 6]) };
+
+        let back = unsafe { storage.slice_mut(&handle, 3, 3) };
+        back[0].write(7);
+
+        let pointer = unsafe { storage.slice(&handle, 2, 2) };
+        assert_eq!(2, pointer.len());
+
+        let window = unsafe { storage.slice_ref(&handle, 2, 2) };
+        let window = unsafe { MaybeUninit::slice_assume_init_ref(window) };
+
+        assert_eq!([3, 7], window);
+        assert_eq!(0, unsafe { storage.slice_ref(&handle, 6, 0) }.len());
+    }
+
+    #[test]
+    fn cast() {
+        let mut storage = NonTrackingRange::<u8, u32, 2>::new();
+        let handle = storage.allocate::<u32>(2).unwrap();
+
+        unsafe {
+            storage.copy_from_slice(&handle, 0, &[0x01020304u32.to_le(), 0x05060708u32.to_le()])
+        };
+
+        let bytes = unsafe { storage.cast::<u32, u8>(&handle) }.unwrap();
+        let slice = unsafe { MaybeUninit::slice_assume_init_ref(storage.slice_ref(&bytes, 0, 8)) };
+
+        assert_eq!([4, 3, 2, 1, 8, 7, 6, 5], slice);
+
+        unsafe { storage.cast::<u8, u64>(&bytes) }.unwrap_err();
+    }
+
+    #[test]
+    #[should_panic]
+    fn slice_out_of_bounds() {
+        let mut storage = NonTrackingRange::<u8, u8, 6>::new();
+        let handle = storage.allocate::<u8>(6).unwrap();
+
+        unsafe { storage.slice(&handle, 4, 3) };
+    }
+
+    #[test]
+    #[should_panic]
+    fn copy_out_of_bounds() {
+        let mut storage = NonTrackingRange::<u8, u8, 6>::new();
+        let handle = storage.allocate::<u8>(6).unwrap();
+
+        unsafe { storage.copy_from_slice(&handle, 4, &[1, 2, 3]) };
+    }
 } // mod tests
@@ -1,49 +1,368 @@
 //! Inline implementation of ElementStorage.
 
 use core::{
-    alloc::AllocError,
+    alloc::Layout,
     fmt::{self, Debug},
-    marker::Unsize,
-    mem::MaybeUninit,
-    ptr::NonNull,
+    marker::{PhantomData, Unsize},
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
 };
 
 use rfc2580::{self, Pointee};
 
-use crate::{traits::ElementStorage, utils};
+use crate::{
+    error::StorageError,
+    handle_map::HandleMap,
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::{
+        AddressStability, ConstantTimeStorage, ElementStorage, IndexedStorage, RangeStorage,
+        TaggedStorage,
+    },
+    utils,
+};
 
 /// Generic inline ElementStorage.
 ///
 /// `S` is the underlying storage, used to specify the size and alignment.
-pub struct TrackingElement<S, const N: usize> {
+///
+/// `O` is the order in which free slots are handed out, see `FreeListOrder`.
+pub struct TrackingElement<S, const N: usize, O: FreeListOrder = Lifo> {
     next: usize,
+    on_full: Option<fn(TrackingElementExhaustion)>,
     data: [Overlay<S>; N],
+    _order: PhantomData<O>,
+}
+
+/// The order in which a TrackingElement hands out its free slots.
+pub trait FreeListOrder {
+    /// Whether the free slots are kept sorted by index, rather than recycled most recently freed first.
+    const ADDRESS_ORDERED: bool;
+}
+
+/// Hands out the most recently freed slot first.
+///
+/// Allocation and deallocation are constant time, but the slot handed out depends on the history of allocations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Lifo;
+
+impl FreeListOrder for Lifo {
+    const ADDRESS_ORDERED: bool = false;
+}
+
+/// Hands out the free slot with the lowest index first.
+///
+/// The slot handed out only depends on which slots are live, not on the order in which they were freed, hence the
+/// layout of a collection is reproducible, and its live elements are packed towards the front of the storage.
+///
+/// Deallocation is linear in the number of free slots, and the storage is therefore not a `ConstantTimeStorage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AddressOrdered;
+
+impl FreeListOrder for AddressOrdered {
+    const ADDRESS_ORDERED: bool = true;
 }
 
-impl<S, const N: usize> TrackingElement<S, N> {
+/// The statistics passed to the `on_full` hook of a TrackingElement, at the moment of exhaustion.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TrackingElementExhaustion {
+    /// The number of slots, all of which are occupied.
+    pub capacity: usize,
+    /// The layout of the rejected allocation.
+    pub requested: Layout,
+}
+
+impl<S, const N: usize, O: FreeListOrder> TrackingElement<S, N, O> {
     /// Creates an instance.
     pub fn new() -> Self {
         unsafe { Self::default() }
     }
+
+    /// Sets the hook invoked whenever an allocation is rejected because all slots are occupied, or clears it.
+    ///
+    /// The hook is invoked prior to returning the error, allowing the application to log, shed load, or free slots
+    /// for a subsequent attempt. It is not invoked by the views created by `split_at_mut`.
+    pub fn set_on_full(&mut self, on_full: Option<fn(TrackingElementExhaustion)>) {
+        self.on_full = on_full;
+    }
+
+    /// Compacts the live elements to the front of the storage.
+    ///
+    /// `patch` is invoked with the old and new index of each relocated element, in this order.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that none of the elements is pinned.
+    /// -   The handles to relocated elements, and all of their copies, must be patched before any further use.
+    pub unsafe fn compact<P: FnMut(usize, usize)>(&mut self, mut patch: P) {
+        let mut free = [false; N];
+
+        let mut next = self.next;
+        while next != INVALID_NEXT {
+            free[next] = true;
+
+            //  Safety:
+            //  -   `next` is within range, and pointed to, hence contains `next`.
+            next = self.data.get_unchecked(next).next;
+        }
+
+        let (mut low, mut high) = (0, N);
+
+        loop {
+            while low < high && !free[low] {
+                low += 1;
+            }
+
+            while low < high && free[high - 1] {
+                high -= 1;
+            }
+
+            if low >= high {
+                break;
+            }
+
+            //  `low` is the first free slot, and `high - 1` the last live slot, after it.
+            let from = high - 1;
+
+            let base = self.data.as_mut_ptr();
+
+            //  Safety:
+            //  -   `from` and `low` are within range, and distinct.
+            ptr::copy_nonoverlapping(base.add(from), base.add(low), 1);
+
+            free[low] = false;
+            free[from] = true;
+
+            patch(from, low);
+        }
+
+        //  All slots from `low` onwards are now free, rebuild the linked-list in order.
+        for index in low..N {
+            self.data.get_unchecked_mut(index).next = if index + 1 < N {
+                index + 1
+            } else {
+                INVALID_NEXT
+            };
+        }
+
+        self.next = if low < N { low } else { INVALID_NEXT };
+    }
+
+    /// Compacts the live elements to the front of the storage, recording their relocations in `map`.
+    ///
+    /// `map` is reset to the identity mapping of all `N` slots first; if it cannot accomodate them, nothing is
+    /// relocated and an error is returned.
+    ///
+    /// #   Safety
+    ///
+    /// -   As per `compact`, the handles to relocated elements must be patched, with `HandleMap::remap`.
+    pub unsafe fn compact_into<M: RangeStorage>(
+        &mut self,
+        map: &mut HandleMap<M>,
+    ) -> Result<(), StorageError> {
+        map.try_reset(N)?;
+
+        self.compact(|from, to| map.record(from, to));
+
+        Ok(())
+    }
+
+    /// Splits the storage into two disjoint views, over the slots `[0, mid)` and `[mid, N)`, for the duration of `f`.
+    ///
+    /// Each view is an independent ElementStorage, allowing two collections to share the storage simultaneously.
+    /// Handles remain valid across the split: a handle issued by `self` may be used with the view owning its slot, and
+    /// a handle issued by a view may be used with `self` once `f` returns.
+    ///
+    /// If `f` panics, the free slots of `self` are leaked.
+    ///
+    /// #   Panics
+    ///
+    /// If `mid > N`.
+    pub fn split_at_mut<R, F>(&mut self, mid: usize, f: F) -> R
+    where
+        F: for<'a> FnOnce(TrackingElementView<'a, S>, TrackingElementView<'a, S>) -> R,
+    {
+        assert!(mid <= N, "mid {} out of bounds {}", mid, N);
+
+        let (mut left_next, mut right_next) = (INVALID_NEXT, INVALID_NEXT);
+        let (mut left_tail, mut right_tail) = (INVALID_NEXT, INVALID_NEXT);
+
+        //  Partition the free slots, preserving their order.
+        let mut next = mem::replace(&mut self.next, INVALID_NEXT);
+
+        while next != INVALID_NEXT {
+            let index = next;
+
+            //  Safety:
+            //  -   `index` is within range, and pointed to, hence contains `next`.
+            next = unsafe { self.data.get_unchecked(index).next };
+            self.data[index].next = INVALID_NEXT;
+
+            let (head, tail) = if index < mid {
+                (&mut left_next, &mut left_tail)
+            } else {
+                (&mut right_next, &mut right_tail)
+            };
+
+            if *tail == INVALID_NEXT {
+                *head = index;
+            } else {
+                self.data[*tail].next = index;
+            }
+
+            *tail = index;
+        }
+
+        let (left, right) = self.data.split_at_mut(mid);
+
+        let result = f(
+            TrackingElementView {
+                base: 0,
+                next: &mut left_next,
+                data: left,
+            },
+            TrackingElementView {
+                base: mid,
+                next: &mut right_next,
+                data: right,
+            },
+        );
+
+        //  Merge the free slots back, appending those of the right view to those of the left view.
+        if left_next == INVALID_NEXT {
+            self.next = right_next;
+        } else {
+            let mut tail = left_next;
+
+            //  Safety:
+            //  -   `tail` is within range, and pointed to, hence contains `next`.
+            while unsafe { self.data[tail].next } != INVALID_NEXT {
+                tail = unsafe { self.data[tail].next };
+            }
+
+            self.data[tail].next = right_next;
+            self.next = left_next;
+        }
+
+        //  The views recycle their slots most recently freed first, regardless of the order of `self`.
+        if O::ADDRESS_ORDERED {
+            self.sort_free_list();
+        }
+
+        result
+    }
+
+    /// The size, in bytes, of a snapshot, see `snapshot`.
+    pub const SNAPSHOT_SIZE: usize = mem::size_of::<usize>() + mem::size_of::<[Overlay<S>; N]>();
+
+    /// Writes a snapshot of the occupancy of the storage, and of the raw bytes of its slots, into the first
+    /// `SNAPSHOT_SIZE` bytes of `buffer`.
+    ///
+    /// The snapshot is a plain copy of the storage, to be restored by `restore`; the `on_full` hook is not part of it.
+    ///
+    /// #   Panics
+    ///
+    /// If `buffer` is shorter than `SNAPSHOT_SIZE`.
+    pub fn snapshot(&self, buffer: &mut [MaybeUninit<u8>]) {
+        assert!(
+            buffer.len() >= Self::SNAPSHOT_SIZE,
+            "buffer of {} bytes shorter than snapshot of {} bytes",
+            buffer.len(),
+            Self::SNAPSHOT_SIZE
+        );
+
+        let (next, data) = buffer.split_at_mut(mem::size_of::<usize>());
+
+        //  Safety:
+        //  -   `next` and `data` are large enough, as checked above, and bytes have no alignment requirement.
+        //  -   `MaybeUninit<u8>` may hold any byte, including the uninitialized bytes of free slots, or padding.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                &self.next as *const usize as *const MaybeUninit<u8>,
+                next.as_mut_ptr(),
+                mem::size_of::<usize>(),
+            );
+            ptr::copy_nonoverlapping(
+                self.data.as_ptr() as *const MaybeUninit<u8>,
+                data.as_mut_ptr(),
+                mem::size_of::<[Overlay<S>; N]>(),
+            );
+        }
+    }
+
+    /// Restores the occupancy of the storage, and the raw bytes of its slots, from a snapshot written by `snapshot`.
+    ///
+    /// The elements are restored bitwise, hence this is only suitable for plain-old-data elements, such as integers,
+    /// or handles into this storage.
+    ///
+    /// #   Panics
+    ///
+    /// If `buffer` is shorter than `SNAPSHOT_SIZE`.
+    ///
+    /// #   Safety
+    ///
+    /// -   `buffer` must hold a snapshot of a storage of this very type, written by `snapshot` within this program.
+    /// -   The elements live at the time of the snapshot must be valid once copied bitwise, that is they must neither
+    ///     own resources, nor point into the storage, which may have moved since.
+    /// -   The elements live prior to restoring are overwritten without being dropped; their handles must no longer be
+    ///     used, unless the elements were also live at the time of the snapshot.
+    pub unsafe fn restore(&mut self, buffer: &[MaybeUninit<u8>]) {
+        assert!(
+            buffer.len() >= Self::SNAPSHOT_SIZE,
+            "buffer of {} bytes shorter than snapshot of {} bytes",
+            buffer.len(),
+            Self::SNAPSHOT_SIZE
+        );
+
+        let (next, data) = buffer.split_at(mem::size_of::<usize>());
+
+        ptr::copy_nonoverlapping(
+            next.as_ptr(),
+            &mut self.next as *mut usize as *mut MaybeUninit<u8>,
+            mem::size_of::<usize>(),
+        );
+        ptr::copy_nonoverlapping(
+            data.as_ptr(),
+            self.data.as_mut_ptr() as *mut MaybeUninit<u8>,
+            mem::size_of::<[Overlay<S>; N]>(),
+        );
+    }
 }
 
-impl<S, const N: usize> ElementStorage for TrackingElement<S, N> {
+impl<S, const N: usize, O: FreeListOrder> ElementStorage for TrackingElement<S, N, O> {
     type Handle<T: ?Sized + Pointee> = TrackingElementHandle<T>;
 
+    //  Compaction relocates elements, but is explicit, and requires patching all handles anyway.
+    const ADDRESS_STABILITY: AddressStability = AddressStability::ALLOCATIONS;
+
     unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
         //  Safety:
         //  -   `handle` is assumed to be within range, as part of being valid.
-        let slot = self.data.get_unchecked_mut(handle.0);
+        let index = handle.index();
 
-        //  Place slot back in linked-list.
-        slot.next = self.next;
-        self.next = handle.0;
+        if !O::ADDRESS_ORDERED || self.next > index {
+            //  Place slot back at the front of the linked-list.
+            self.data.get_unchecked_mut(index).next = self.next;
+            self.next = index;
+            return;
+        }
+
+        //  Place slot back after the last free slot preceding it.
+        let mut previous = self.next;
+
+        //  Safety:
+        //  -   `previous` is within range, and pointed to, hence contains `next`.
+        while self.data.get_unchecked(previous).next < index {
+            previous = self.data.get_unchecked(previous).next;
+        }
+
+        self.data.get_unchecked_mut(index).next = self.data.get_unchecked(previous).next;
+        self.data.get_unchecked_mut(previous).next = index;
     }
 
     unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
         //  Safety:
         //  -   `handle` is assumed to be within range.
-        let slot = self.data.get_unchecked(handle.0);
+        let slot = self.data.get_unchecked(handle.index());
 
         let pointer: NonNull<u8> = NonNull::from(&slot.data).cast();
 
@@ -65,14 +384,57 @@ impl<S, const N: usize> ElementStorage for TrackingElement<S, N> {
         TrackingElementHandle(handle.0, meta)
     }
 
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut T).0;
+
+        TrackingElementHandle(handle.0, meta)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        utils::validate_layout_for::<S>(Layout::new::<U>())
+            .map_err(|error| error.with_context("TrackingElement"))?;
+
+        let meta = rfc2580::into_non_null_parts(NonNull::<U>::dangling()).0;
+
+        //  The tag, if any, is preserved.
+        Ok(TrackingElementHandle(handle.0, meta))
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        utils::validate_layout_for::<S>(Layout::new::<T>()).ok()?;
+
+        Some(Layout::new::<Overlay<S>>())
+    }
+
     fn allocate<T: ?Sized + Pointee>(
         &mut self,
         meta: T::MetaData,
-    ) -> Result<Self::Handle<T>, AllocError> {
-        let _ = utils::validate_layout::<T, S>(meta)?;
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let layout = utils::layout_of::<T>(meta);
 
-        if self.next == INVALID_NEXT {
-            return Err(AllocError);
+        utils::validate_layout_for::<S>(layout)
+            .map_err(|error| error.with_context("TrackingElement"))?;
+
+        //  Either exhausted, as `INVALID_NEXT > INDEX_MASK`, or the slot index would overlap the tag bits.
+        if self.next > INDEX_MASK {
+            if let Some(on_full) = self.on_full {
+                on_full(TrackingElementExhaustion {
+                    capacity: N,
+                    requested: layout,
+                });
+            }
+
+            return Err(StorageError::new(layout).with_context("TrackingElement"));
         }
 
         //  Pop slot from linked list.
@@ -80,7 +442,7 @@ impl<S, const N: usize> ElementStorage for TrackingElement<S, N> {
 
         //  Safety:
         //  -   `handle.0` is within bounds by invariant.
-        let slot = unsafe { self.data.get_unchecked_mut(handle.0) };
+        let slot = unsafe { self.data.get_unchecked_mut(handle.index()) };
 
         //  Safety:
         //  -   By invariant, if pointed it contains the "next" field.
@@ -90,7 +452,31 @@ impl<S, const N: usize> ElementStorage for TrackingElement<S, N> {
     }
 }
 
-impl<S, const N: usize> Debug for TrackingElement<S, N> {
+impl<S, const N: usize, O: FreeListOrder> TaggedStorage for TrackingElement<S, N, O> {
+    const TAG_BITS: u32 = usize::BITS - TAG_SHIFT;
+
+    fn tag<T: ?Sized + Pointee>(handle: &Self::Handle<T>) -> usize {
+        handle.0 >> TAG_SHIFT
+    }
+
+    fn set_tag<T: ?Sized + Pointee>(handle: &mut Self::Handle<T>, tag: usize) {
+        assert!(tag < (1 << Self::TAG_BITS), "tag {} out of range", tag);
+
+        handle.0 = handle.index() | (tag << TAG_SHIFT);
+    }
+}
+
+impl<S, const N: usize, O: FreeListOrder> IndexedStorage for TrackingElement<S, N, O> {
+    const SLOT_COUNT: usize = N;
+
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize {
+        handle.index()
+    }
+}
+
+impl<S, const N: usize> ConstantTimeStorage for TrackingElement<S, N, Lifo> {}
+
+impl<S, const N: usize, O: FreeListOrder> Debug for TrackingElement<S, N, O> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "TrackingElement{{ next: ")?;
         display_next(f, self.next)?;
@@ -114,7 +500,28 @@ impl<S, const N: usize> Debug for TrackingElement<S, N> {
     }
 }
 
-impl<S, const N: usize> Default for TrackingElement<S, N> {
+impl<S, const N: usize, O: FreeListOrder> MemoryUsage for TrackingElement<S, N, O> {
+    fn mem_usage(&self) -> MemUsage {
+        let mut live = N;
+
+        let mut next = self.next;
+        while next != INVALID_NEXT {
+            live -= 1;
+
+            //  Safety:
+            //  -   `next` is within range, and pointed to, hence contains `next`.
+            next = unsafe { self.data.get_unchecked(next).next };
+        }
+
+        MemUsage::with_payload(
+            mem::size_of::<Self>(),
+            0,
+            live * mem::size_of::<Overlay<S>>(),
+        )
+    }
+}
+
+impl<S, const N: usize, O: FreeListOrder> Default for TrackingElement<S, N, O> {
     fn default() -> Self {
         Self::new()
     }
@@ -123,6 +530,38 @@ impl<S, const N: usize> Default for TrackingElement<S, N> {
 /// The Handle for TrackingElements.
 pub struct TrackingElementHandle<T: ?Sized + Pointee>(usize, T::MetaData);
 
+impl<T: ?Sized + Pointee> TrackingElementHandle<T> {
+    /// Returns the index of the slot the handle refers to.
+    pub fn index(&self) -> usize {
+        self.0 & INDEX_MASK
+    }
+
+    /// Returns a copy of the handle, referring to the slot at `index` instead.
+    ///
+    /// This is meant to patch handles after a call to `TrackingElement::compact`, and preserves the tag, if any.
+    pub fn with_index(&self, index: usize) -> Self {
+        debug_assert!(index <= INDEX_MASK);
+
+        TrackingElementHandle(index | (self.0 & !INDEX_MASK), self.1)
+    }
+}
+
+impl<T: Pointee> TrackingElementHandle<T> {
+    /// Returns the raw representation of the handle, including its tag, if any.
+    ///
+    /// This is meant to pass handles across FFI boundaries, see `from_raw`.
+    pub fn into_raw(self) -> usize {
+        self.0
+    }
+
+    /// Reconstitutes a handle from its raw representation, as returned by `into_raw`.
+    pub fn from_raw(raw: usize) -> Self {
+        let meta = rfc2580::into_non_null_parts(NonNull::<T>::dangling()).0;
+
+        TrackingElementHandle(raw, meta)
+    }
+}
+
 impl<T: ?Sized + Pointee> Clone for TrackingElementHandle<T> {
     fn clone(&self) -> Self {
         *self
@@ -133,7 +572,129 @@ impl<T: ?Sized + Pointee> Copy for TrackingElementHandle<T> {}
 
 impl<T: ?Sized + Pointee> Debug for TrackingElementHandle<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        write!(f, "TrackingElementHandle({})", self.0)
+        write!(f, "TrackingElementHandle({})", self.index())
+    }
+}
+
+/// A view over a subset of the slots of a TrackingElement, see `TrackingElement::split_at_mut`.
+pub struct TrackingElementView<'a, S> {
+    base: usize,
+    next: &'a mut usize,
+    data: &'a mut [Overlay<S>],
+}
+
+impl<'a, S> ElementStorage for TrackingElementView<'a, S> {
+    type Handle<T: ?Sized + Pointee> = TrackingElementHandle<T>;
+
+    //  The view borrows the slots, hence moving the view does not move them.
+    const ADDRESS_STABILITY: AddressStability = AddressStability::PINNED;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        //  Safety:
+        //  -   `handle` is assumed to be within range, as part of being valid.
+        let slot = self.data.get_unchecked_mut(handle.index() - self.base);
+
+        //  Place slot back in linked-list.
+        slot.next = *self.next;
+        *self.next = handle.index();
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        //  Safety:
+        //  -   `handle` is assumed to be within range.
+        let slot = self.data.get_unchecked(handle.index() - self.base);
+
+        let pointer: NonNull<u8> = NonNull::from(&slot.data).cast();
+
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        rfc2580::from_non_null_parts(handle.1, pointer)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut U).0;
+
+        TrackingElementHandle(handle.0, meta)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        //  Safety:
+        //  -   `handle` is assumed to point to a valid element.
+        let element = self.get(handle);
+
+        let meta = rfc2580::into_raw_parts(element.as_ptr() as *mut T).0;
+
+        TrackingElementHandle(handle.0, meta)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        utils::validate_layout_for::<S>(Layout::new::<U>())
+            .map_err(|error| error.with_context("TrackingElementView"))?;
+
+        let meta = rfc2580::into_non_null_parts(NonNull::<U>::dangling()).0;
+
+        //  The tag, if any, is preserved.
+        Ok(TrackingElementHandle(handle.0, meta))
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        utils::validate_layout_for::<S>(Layout::new::<T>()).ok()?;
+
+        Some(Layout::new::<Overlay<S>>())
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let layout = utils::layout_of::<T>(meta);
+
+        utils::validate_layout_for::<S>(layout)
+            .map_err(|error| error.with_context("TrackingElementView"))?;
+
+        //  Either exhausted, as `INVALID_NEXT > INDEX_MASK`, or the slot index would overlap the tag bits.
+        if *self.next > INDEX_MASK {
+            return Err(StorageError::new(layout).with_context("TrackingElementView"));
+        }
+
+        //  Pop slot from linked list.
+        let handle = TrackingElementHandle(*self.next, meta);
+
+        //  Safety:
+        //  -   `handle.0` is within bounds by invariant.
+        let slot = unsafe { self.data.get_unchecked_mut(handle.index() - self.base) };
+
+        //  Safety:
+        //  -   By invariant, if pointed it contains the "next" field.
+        *self.next = unsafe { slot.next };
+
+        Ok(handle)
+    }
+}
+
+impl<'a, S> ConstantTimeStorage for TrackingElementView<'a, S> {}
+
+impl<'a, S> Debug for TrackingElementView<'a, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "TrackingElementView{{ slots: {}..{} }}",
+            self.base,
+            self.base + self.data.len()
+        )
     }
 }
 
@@ -143,7 +704,11 @@ impl<T: ?Sized + Pointee> Debug for TrackingElementHandle<T> {
 
 const INVALID_NEXT: usize = usize::MAX;
 
-impl<S, const N: usize> TrackingElement<S, N> {
+//  The top 8 bits of the index are reserved for tags.
+const TAG_SHIFT: u32 = usize::BITS - 8;
+const INDEX_MASK: usize = usize::MAX >> 8;
+
+impl<S, const N: usize, O: FreeListOrder> TrackingElement<S, N, O> {
     //  Creates a default instance.
     //
     //  #   Safety
@@ -154,7 +719,12 @@ impl<S, const N: usize> TrackingElement<S, N> {
 
         if N == 0 {
             let next = INVALID_NEXT;
-            return Self { next, data };
+            return Self {
+                next,
+                on_full: None,
+                data,
+                _order: PhantomData,
+            };
         }
 
         //  Created linked-list of slots, using INVALID_NEXT as sentinel.
@@ -166,7 +736,35 @@ impl<S, const N: usize> TrackingElement<S, N> {
 
         data[last].next = INVALID_NEXT;
 
-        Self { next: 0, data }
+        Self {
+            next: 0,
+            on_full: None,
+            data,
+            _order: PhantomData,
+        }
+    }
+
+    //  Relinks the free slots in increasing order of index.
+    fn sort_free_list(&mut self) {
+        let mut free = [false; N];
+
+        let mut next = self.next;
+        while next != INVALID_NEXT {
+            free[next] = true;
+
+            //  Safety:
+            //  -   `next` is within range, and pointed to, hence contains `next`.
+            next = unsafe { self.data.get_unchecked(next).next };
+        }
+
+        let mut head = INVALID_NEXT;
+
+        for index in (0..N).rev().filter(|index| free[*index]) {
+            self.data[index].next = head;
+            head = index;
+        }
+
+        self.next = head;
     }
 }
 
@@ -192,6 +790,10 @@ fn display_next(f: &mut fmt::Formatter<'_>, n: usize) -> Result<(), fmt::Error>
 #[cfg(test)]
 mod tests {
 
+    use core::any::Any;
+
+    use crate::utils::Packet;
+
     use super::*;
 
     #[test]
@@ -260,4 +862,366 @@ mod tests {
 
         assert_eq!(&[1, 2], unsafe { element.as_ref() });
     }
+
+    #[test]
+    fn downcast() {
+        let mut storage = TrackingElement::<u32, 5>::new();
+
+        let _ = storage.create(1u32).unwrap();
+        let handle = storage.create(2u32).unwrap();
+
+        let handle = unsafe { storage.coerce::<dyn Any, _>(&handle) };
+
+        assert!(unsafe { storage.downcast::<u16>(&handle) }.is_none());
+
+        let handle = unsafe { storage.downcast::<u32>(&handle) }.unwrap();
+
+        assert_eq!(1, handle.index());
+        assert_eq!(2, unsafe { *storage.get(&handle).as_ptr() });
+    }
+
+    #[test]
+    fn custom_dst() {
+        let mut storage = TrackingElement::<[u16; 4], 5>::new();
+        let handle = storage
+            .create(Packet {
+                tag: 1u8,
+                payload: [2u16, 3, 4],
+            })
+            .unwrap();
+
+        let handle = unsafe { storage.coerce::<Packet<[u16]>, _>(&handle) };
+        let element = unsafe { storage.get(&handle) };
+        let packet = unsafe { element.as_ref() };
+
+        assert_eq!(1, packet.tag);
+        assert_eq!([2, 3, 4], &packet.payload);
+
+        storage
+            .create(Packet {
+                tag: 1u8,
+                payload: [2u16, 3, 4, 5],
+            })
+            .unwrap_err();
+    }
+
+    #[test]
+    fn compact() {
+        let mut storage = TrackingElement::<u8, 5>::new();
+
+        let handles: Vec<_> = (0..4u8).map(|i| storage.create(i).unwrap()).collect();
+
+        unsafe { storage.destroy(&handles[0]) };
+        unsafe { storage.destroy(&handles[2]) };
+
+        let mut relocated = Vec::new();
+        unsafe { storage.compact(|from, to| relocated.push((from, to))) };
+
+        assert_eq!(vec![(3, 0)], relocated);
+        assert_eq!(
+            "TrackingElement{ next: 2 -> 3 -> 4 -> null }",
+            format!("{:?}", storage)
+        );
+
+        let h1 = handles[1];
+        let h3 = handles[3].with_index(0);
+
+        assert_eq!(1, unsafe { *storage.get(&h1).as_ptr() });
+        assert_eq!(3, unsafe { *storage.get(&h3).as_ptr() });
+    }
+
+    #[test]
+    fn compact_full() {
+        let mut storage = TrackingElement::<u8, 2>::new();
+
+        let h0 = storage.create(0u8).unwrap();
+        let h1 = storage.create(1u8).unwrap();
+
+        unsafe { storage.compact(|_, _| panic!("Nothing to relocate")) };
+
+        assert_eq!("TrackingElement{ next: null }", format!("{:?}", storage));
+        assert_eq!(0, unsafe { *storage.get(&h0).as_ptr() });
+        assert_eq!(1, unsafe { *storage.get(&h1).as_ptr() });
+    }
+
+    #[test]
+    fn tag() {
+        type Storage = TrackingElement<u32, 2>;
+
+        let mut storage = Storage::new();
+
+        let _ = storage.create(1u32).unwrap();
+        let mut handle = storage.create(2u32).unwrap();
+
+        assert_eq!(8, Storage::TAG_BITS);
+        assert_eq!(0, Storage::tag(&handle));
+
+        Storage::set_tag(&mut handle, 255);
+
+        assert_eq!(255, Storage::tag(&handle));
+        assert_eq!(1, handle.index());
+        assert_eq!(255, Storage::tag(&handle.with_index(0)));
+        assert_eq!(2, unsafe { *storage.get(&handle).as_ptr() });
+
+        unsafe { storage.destroy(&handle) };
+
+        let handle = storage.create(3u32).unwrap();
+
+        assert_eq!(1, handle.index());
+        assert_eq!(0, Storage::tag(&handle));
+    }
+
+    #[test]
+    #[should_panic]
+    fn tag_out_of_range() {
+        type Storage = TrackingElement<u32, 2>;
+
+        let mut storage = Storage::new();
+        let mut handle = storage.create(1u32).unwrap();
+
+        Storage::set_tag(&mut handle, 256);
+    }
+
+    #[test]
+    fn split_at_mut() {
+        use crate::collections::RawLinkedList;
+
+        let mut storage = TrackingElement::<[u64; 4], 4>::new();
+
+        let kept = storage.create(0u32).unwrap();
+
+        storage.split_at_mut(2, |left, right| {
+            let mut left = RawLinkedList::new(left);
+            let mut right = RawLinkedList::new(right);
+
+            left.push(1u32);
+
+            right.push(3u32);
+            right.push(4u32);
+
+            assert_eq!(Some(&1), left.front());
+            assert_eq!(Some(&4), right.front());
+
+            right.pop();
+        });
+
+        assert_eq!(
+            "TrackingElement{ next: 1 -> 2 -> 3 -> null }",
+            format!("{:?}", storage)
+        );
+        assert_eq!(0, unsafe { *storage.get(&kept).as_ptr() });
+    }
+
+    #[test]
+    fn split_at_mut_handles() {
+        let mut storage = TrackingElement::<u32, 4>::new();
+
+        let h0 = storage.create(0u32).unwrap();
+
+        let (h1, h3) = storage.split_at_mut(1, |mut left, mut right| {
+            left.create(9u32).unwrap_err();
+
+            assert_eq!(0, unsafe { *left.get(&h0).as_ptr() });
+
+            (right.create(1u32).unwrap(), right.create(3u32).unwrap())
+        });
+
+        assert_eq!((1, 2), (h1.index(), h3.index()));
+        assert_eq!(1, unsafe { *storage.get(&h1).as_ptr() });
+        assert_eq!(3, unsafe { *storage.get(&h3).as_ptr() });
+
+        assert_eq!(
+            "TrackingElement{ next: 3 -> null }",
+            format!("{:?}", storage)
+        );
+    }
+
+    #[test]
+    fn snapshot_restore() {
+        type Storage = TrackingElement<u32, 4>;
+
+        let mut buffer = [MaybeUninit::<u8>::uninit(); Storage::SNAPSHOT_SIZE];
+
+        let mut storage = Storage::new();
+
+        let h0 = storage.create(0u32).unwrap();
+        let h1 = storage.create(1u32).unwrap();
+
+        storage.snapshot(&mut buffer);
+
+        unsafe {
+            *storage.get(&h0).as_ptr() = 10;
+            storage.destroy(&h1);
+        }
+
+        let h2 = storage.create(2u32).unwrap();
+        let h3 = storage.create(3u32).unwrap();
+
+        assert_eq!((1, 2), (h2.index(), h3.index()));
+
+        //  Safety:
+        //  -   The snapshot was taken from a storage of the same type.
+        //  -   The elements are plain integers.
+        unsafe { storage.restore(&buffer) };
+
+        assert_eq!(0, unsafe { *storage.get(&h0).as_ptr() });
+        assert_eq!(1, unsafe { *storage.get(&h1).as_ptr() });
+        assert_eq!(
+            "TrackingElement{ next: 2 -> 3 -> null }",
+            format!("{:?}", storage)
+        );
+
+        //  The snapshot can be restored any number of times, including into another instance.
+        let mut other = Storage::new();
+
+        unsafe { other.restore(&buffer) };
+
+        assert_eq!(1, unsafe { *other.get(&h1).as_ptr() });
+        assert_eq!(2, other.create(2u32).unwrap().index());
+    }
+
+    #[test]
+    #[should_panic]
+    fn snapshot_buffer_too_short() {
+        type Storage = TrackingElement<u32, 4>;
+
+        let mut buffer = [MaybeUninit::<u8>::uninit(); Storage::SNAPSHOT_SIZE - 1];
+
+        Storage::new().snapshot(&mut buffer);
+    }
+
+    #[test]
+    fn mem_usage() {
+        type Storage = TrackingElement<u64, 4>;
+
+        let mut storage = Storage::new();
+
+        let h0 = storage.create(0u64).unwrap();
+        storage.create(1u32).unwrap();
+
+        unsafe { storage.destroy(&h0) };
+
+        let usage = storage.mem_usage();
+
+        //  The free slots, and the head of the free list, are overhead.
+        assert_eq!(mem::size_of::<Storage>(), usage.inline_bytes);
+        assert_eq!(0, usage.heap_bytes);
+        assert_eq!(8, usage.payload_bytes());
+    }
+
+    #[test]
+    fn recycle() {
+        let mut storage = TrackingElement::<u32, 1>::new();
+
+        let handle = storage.create(1u32).unwrap();
+
+        unsafe { storage.recycle::<_, u64>(&handle) }.unwrap_err();
+
+        let recycled = unsafe { storage.recycle::<_, [u8; 4]>(&handle) }.unwrap();
+
+        unsafe { storage.get(&recycled).as_ptr().write([1, 2, 3, 4]) };
+
+        assert_eq!([1, 2, 3, 4], unsafe { *storage.get(&recycled).as_ref() });
+
+        //  The slot was neither released, nor re-acquired.
+        storage.create(2u32).unwrap_err();
+
+        unsafe { storage.destroy(&recycled) };
+
+        storage.create(3u32).unwrap();
+    }
+
+    #[test]
+    fn indexed_side_table() {
+        let mut storage = TrackingElement::<u32, 4>::new();
+        let mut side_table = [0u8; TrackingElement::<u32, 4>::SLOT_COUNT];
+
+        let handles = [
+            storage.create(0u32).unwrap(),
+            storage.create(1u32).unwrap(),
+            storage.create(2u32).unwrap(),
+        ];
+
+        unsafe { storage.destroy(&handles[1]) };
+
+        for handle in [handles[0], handles[2]] {
+            side_table[unsafe { storage.index(&handle) }] += 1;
+        }
+
+        assert_eq!([1, 0, 1, 0], side_table);
+    }
+
+    #[test]
+    fn on_full() {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        static EXHAUSTED: AtomicUsize = AtomicUsize::new(0);
+
+        fn on_full(exhaustion: TrackingElementExhaustion) {
+            assert_eq!(2, exhaustion.capacity);
+            assert_eq!(Layout::new::<u16>(), exhaustion.requested);
+
+            EXHAUSTED.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let mut storage = TrackingElement::<u32, 2>::new();
+        storage.set_on_full(Some(on_full));
+
+        //  Rejections due to the layout are not exhaustion.
+        storage.create(1u64).unwrap_err();
+
+        storage.create(1u32).unwrap();
+        storage.create(2u32).unwrap();
+
+        storage.create(3u16).unwrap_err();
+        storage.create(4u16).unwrap_err();
+
+        assert_eq!(2, EXHAUSTED.load(Ordering::Relaxed));
+
+        storage.set_on_full(None);
+        storage.create(5u16).unwrap_err();
+
+        assert_eq!(2, EXHAUSTED.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn free_list_order() {
+        fn recycled<O: FreeListOrder>() -> [usize; 3] {
+            let mut storage = TrackingElement::<u32, 4, O>::new();
+
+            let handles: Vec<_> = (0..4).map(|i| storage.create(i).unwrap()).collect();
+
+            for index in [2, 0, 3] {
+                unsafe { storage.destroy(&handles[index]) };
+            }
+
+            [(); 3].map(|_| storage.create(9u32).unwrap().index())
+        }
+
+        assert_eq!([3, 0, 2], recycled::<Lifo>());
+        assert_eq!([0, 2, 3], recycled::<AddressOrdered>());
+    }
+
+    #[test]
+    fn address_ordered_split() {
+        let mut storage = TrackingElement::<u32, 4, AddressOrdered>::new();
+
+        let h0 = storage.create(0u32).unwrap();
+
+        storage.split_at_mut(2, |_, mut right| {
+            let (h2, h3) = (right.create(2u32).unwrap(), right.create(3u32).unwrap());
+
+            unsafe {
+                right.destroy(&h2);
+                right.destroy(&h3);
+            }
+        });
+
+        unsafe { storage.destroy(&h0) };
+
+        assert_eq!(
+            "TrackingElement{ next: 0 -> 1 -> 2 -> 3 -> null }",
+            format!("{:?}", storage)
+        );
+    }
 }
@@ -1,7 +1,10 @@
-#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 //  Language Features
 #![feature(coerce_unsized)]
+#![feature(const_fn)]
+#![feature(const_panic)]
 #![feature(generic_associated_types)]
+#![feature(inline_const)]
 #![feature(unsize)]
 #![feature(untagged_unions)]
 //  Library Features
@@ -14,18 +17,54 @@
 #![feature(nonnull_slice_from_raw_parts)]
 #![feature(slice_ptr_get)]
 #![feature(slice_ptr_len)]
+#![cfg_attr(feature = "std", feature(backtrace))]
 //  Lints
 #![allow(incomplete_features)]
 #![deny(missing_docs)]
 
 //! TODO
 
+#[cfg(any(test, feature = "alloc"))]
+extern crate alloc;
+
 pub mod allocator;
 pub mod alternative;
 pub mod collections;
+pub mod defaults;
+#[cfg(any(test, feature = "embedded"))]
+pub mod embedded;
+pub mod error;
 pub mod fallback;
+#[cfg(any(test, feature = "ffi"))]
+pub mod ffi;
+pub mod handle_map;
 pub mod inline;
+pub mod memory_usage;
+pub mod policy;
+#[cfg(any(test, feature = "alloc"))]
+pub mod registry;
+#[cfg(any(test, feature = "alloc"))]
+pub mod shared;
+pub mod shared_pool;
 pub mod small;
+#[cfg(any(test, feature = "alloc"))]
+pub mod storage_box;
+#[cfg(any(test, feature = "alloc"))]
+pub mod storage_vec;
 pub mod traits;
+#[cfg(any(test, feature = "wasm"))]
+pub mod wasm;
+pub mod wrapper;
 
+mod compose;
+mod store_dyn;
 mod utils;
+
+#[cfg(feature = "derive")]
+pub use storage_poc_derive::{ElementStorage, RangeStorage};
+
+//  Re-exports for the code generated by the derive macros.
+#[doc(hidden)]
+pub mod __private {
+    pub use rfc2580::Pointee;
+}
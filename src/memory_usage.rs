@@ -0,0 +1,189 @@
+//! Reporting of the memory used by storages, and by the collections built atop them.
+//!
+//! The memory used is split between the bytes occupied by the value itself, that is `mem::size_of_val`, including any
+//! inline storage, and the bytes allocated out of line. Among those, the overhead is any byte not occupied by the
+//! payload: for a storage, the payload is the memory of its live elements, or ranges; for a collection, it is its
+//! elements proper, hence the overhead further includes the handles, links, and spare capacity.
+//!
+//! Composites, such as `FallbackElement`, aggregate the memory used by their storages, and collections aggregate the
+//! memory used by their storage.
+//!
+//! Allocator-backed storages do not track their allocations, and therefore cannot report them; wrapping them in a
+//! `StatsStorage` does, as it reports the bytes it counted in their stead. Any other storage wrapped in a
+//! `StatsStorage` reports its own memory, to which the wrapper adds its own size.
+
+use core::ops::{Add, AddAssign};
+
+/// The memory used by a storage, or a collection, see `MemoryUsage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemUsage {
+    /// Number of bytes occupied by the value itself, including any inline storage.
+    pub inline_bytes: usize,
+    /// Number of bytes allocated out of line.
+    pub heap_bytes: usize,
+    /// Number of bytes, among the above, not occupied by the payload: handles, free slots, spare capacity, etc...
+    pub overhead_bytes: usize,
+}
+
+impl MemUsage {
+    /// Creates an instance, computing the overhead as the bytes not occupied by `payload_bytes`.
+    pub fn with_payload(inline_bytes: usize, heap_bytes: usize, payload_bytes: usize) -> Self {
+        let overhead_bytes = (inline_bytes + heap_bytes).saturating_sub(payload_bytes);
+
+        Self {
+            inline_bytes,
+            heap_bytes,
+            overhead_bytes,
+        }
+    }
+
+    /// Returns the total number of bytes used.
+    pub fn total_bytes(&self) -> usize {
+        self.inline_bytes + self.heap_bytes
+    }
+
+    /// Returns the number of bytes occupied by the payload.
+    pub fn payload_bytes(&self) -> usize {
+        self.total_bytes() - self.overhead_bytes
+    }
+}
+
+impl Add for MemUsage {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl AddAssign for MemUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.inline_bytes += other.inline_bytes;
+        self.heap_bytes += other.heap_bytes;
+        self.overhead_bytes += other.overhead_bytes;
+    }
+}
+
+/// A storage, or collection, able to report the memory it uses.
+pub trait MemoryUsage {
+    /// Whether the bytes allocated out of line are tracked, and reported, or not.
+    ///
+    /// Allocator-backed storages do not track their allocations, and only report the memory of the value itself.
+    const TRACKS_ALLOCATIONS: bool = true;
+
+    /// Returns the memory currently used.
+    fn mem_usage(&self) -> MemUsage;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::mem;
+
+    use alloc::alloc::Global;
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::{RawLinkedList, RawLinkedListNodeStorage, RawVec};
+    use crate::fallback::{FallbackElement, FallbackRange};
+    use crate::inline::{NonTrackingRange, TrackingElement};
+    use crate::wrapper::StatsStorage;
+
+    use crate::traits::ElementStorage;
+
+    use super::*;
+
+    const WORD: usize = mem::size_of::<usize>();
+
+    #[test]
+    fn add() {
+        let usage = MemUsage::with_payload(8, 16, 12) + MemUsage::with_payload(4, 0, 4);
+
+        assert_eq!(MemUsage::with_payload(12, 16, 16), usage);
+        assert_eq!((28, 16), (usage.total_bytes(), usage.payload_bytes()));
+    }
+
+    #[test]
+    fn fallback_element() {
+        type NodeStorage = RawLinkedListNodeStorage<usize, usize>;
+        type Inline = TrackingElement<NodeStorage, 2>;
+        type Heap = StatsStorage<AllocStorage<Global>>;
+        type List = RawLinkedList<usize, FallbackElement<Inline, Heap>>;
+
+        let mut list = List::new(FallbackElement::new(
+            Inline::default(),
+            StatsStorage::new(AllocStorage::default()),
+        ));
+
+        for i in 0..3 {
+            list.push(i);
+        }
+
+        let usage = list.mem_usage();
+
+        //  The third node spilled over to the heap, and its link is overhead, just like those of the inline nodes.
+        assert_eq!(mem::size_of::<List>(), usage.inline_bytes);
+        assert_eq!(List::NODE_LAYOUT.size(), usage.heap_bytes);
+        assert_eq!(3 * WORD, usage.payload_bytes());
+    }
+
+    #[test]
+    fn fallback_range() {
+        type Inline = NonTrackingRange<usize, u32, 4>;
+        type Heap = StatsStorage<AllocStorage<Global>>;
+        type Vec = RawVec<u32, FallbackRange<Inline, Heap>>;
+
+        let mut vec = Vec::new(FallbackRange::new(
+            Inline::default(),
+            StatsStorage::new(AllocStorage::default()),
+        ));
+
+        vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+
+        assert_eq!(0, vec.mem_usage().heap_bytes);
+        assert_eq!(12, vec.mem_usage().payload_bytes());
+
+        vec.try_extend_from_slice(&[4, 5]).unwrap();
+
+        let usage = vec.mem_usage();
+
+        assert_eq!(vec.capacity() * 4, usage.heap_bytes);
+        assert_eq!(20, usage.payload_bytes());
+    }
+
+    #[test]
+    fn stats_inline() {
+        type Inline = TrackingElement<u64, 2>;
+
+        let mut storage = StatsStorage::new(Inline::default());
+
+        let handle = storage.create(1u64).unwrap();
+
+        //  The element lives inline, and is reported by the wrapped storage.
+        let usage = storage.mem_usage();
+
+        assert_eq!(mem::size_of::<StatsStorage<Inline>>(), usage.inline_bytes);
+        assert_eq!(0, usage.heap_bytes);
+        assert_eq!(8, usage.payload_bytes());
+
+        unsafe { storage.destroy(&handle) };
+    }
+
+    #[test]
+    fn stats_allocator() {
+        type Heap = StatsStorage<AllocStorage<Global>>;
+
+        let mut storage = Heap::default();
+
+        let handle = storage.create(1u64).unwrap();
+
+        //  The element lives out of line, and is reported by the wrapper in lieu of the wrapped storage.
+        let usage = storage.mem_usage();
+
+        assert_eq!(mem::size_of::<Heap>(), usage.inline_bytes);
+        assert_eq!(8, usage.heap_bytes);
+        assert_eq!(8, usage.payload_bytes());
+
+        unsafe { storage.destroy(&handle) };
+    }
+} // mod tests
@@ -0,0 +1,44 @@
+//! Policies for handling allocation failures, for the façade collections.
+//!
+//! The façades, `storage_box::Box` and `storage_vec::Vec`, are parameterized by a policy deciding whether their
+//! allocating methods panic on failure, as their standard counterparts do, or return a `Result`, as is customary in
+//! kernel or embedded code. The same collection code thus serves both audiences, the `try_` methods being available
+//! regardless of the policy.
+
+/// A policy for handling allocation failures.
+pub trait OomPolicy {
+    /// The outcome of an allocating method, whose success is `T` and failure is `E`.
+    type Output<T, E>;
+
+    /// Converts the result of an allocating method into its outcome, according to the policy.
+    fn handle<T, E>(result: Result<T, E>) -> Self::Output<T, E>;
+}
+
+/// Panics on allocation failure, for parity with the standard collections.
+///
+/// This is the default policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Panic;
+
+impl OomPolicy for Panic {
+    type Output<T, E> = T;
+
+    fn handle<T, E>(result: Result<T, E>) -> T {
+        match result {
+            Ok(value) => value,
+            Err(_) => panic!("Sufficient capacity"),
+        }
+    }
+}
+
+/// Returns allocation failures to the caller, as a `Result`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Fallible;
+
+impl OomPolicy for Fallible {
+    type Output<T, E> = Result<T, E>;
+
+    fn handle<T, E>(result: Result<T, E>) -> Result<T, E> {
+        result
+    }
+}
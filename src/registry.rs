@@ -0,0 +1,166 @@
+//! Registry of per-type pools, for heterogeneous stores such as the component stores of an ECS.
+//!
+//! The storage traits are not object-safe, as their methods are generic over the element type, hence the pools are
+//! type-erased as a whole -- as `dyn Any` -- and recovered through typed accessors, keyed by `TypeId`.
+//!
+//! There is no `DynElementStorage`, that is no object-safe storage to map `TypeId` to, hence each pool is a typed
+//! collection instead: a `RawVec<T, S>`, which in turn requires `S: RangeStorage` rather than an element storage.
+
+use core::{
+    any::{Any, TypeId},
+    fmt::{self, Debug},
+};
+
+use alloc::{boxed::Box, collections::BTreeMap};
+
+use crate::{
+    collections::{InsertError, RawVec},
+    traits::RangeStorage,
+};
+
+/// A registry of pools, one per type, each created lazily upon first insertion.
+///
+/// The pool of `T` is a `RawVec<T, S>`, whose storage is obtained from the factory. A common backing region is shared
+/// by having the factory hand out shared storages, such as clones of an `Rc<RefCell<S>>`.
+///
+/// The registry itself, that is the map from `TypeId` to pool, is allocated with the global allocator.
+pub struct TypedPools<S: RangeStorage, F: FnMut() -> S> {
+    pools: BTreeMap<TypeId, Box<dyn Any>>,
+    factory: F,
+}
+
+impl<S: RangeStorage + 'static, F: FnMut() -> S> TypedPools<S, F> {
+    /// Creates a new, empty, instance, creating the storage of each pool with `factory`.
+    pub fn new(factory: F) -> Self {
+        Self {
+            pools: BTreeMap::new(),
+            factory,
+        }
+    }
+
+    /// Returns whether `self` has no pool, or not.
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// Returns the number of pools.
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Returns the pool of `T`, if already created.
+    pub fn pool<T: 'static>(&self) -> Option<&RawVec<T, S>> {
+        self.pools
+            .get(&TypeId::of::<T>())
+            .map(|pool| pool.downcast_ref().expect("Pool keyed by its type"))
+    }
+
+    /// Returns the pool of `T`, creating it if necessary.
+    pub fn pool_mut<T: 'static>(&mut self) -> &mut RawVec<T, S> {
+        let factory = &mut self.factory;
+
+        self.pools
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(RawVec::<T, S>::new(factory())))
+            .downcast_mut()
+            .expect("Pool keyed by its type")
+    }
+
+    /// Attempts to append `value` to the pool of `T`, returning its index within the pool.
+    ///
+    /// On failure, returns `value`.
+    pub fn try_insert<T: 'static>(&mut self, value: T) -> Result<usize, InsertError<T>> {
+        let pool = self.pool_mut::<T>();
+        let index = pool.len();
+
+        pool.try_push(value)?;
+
+        Ok(index)
+    }
+
+    /// Returns a reference to the value of type `T` at `index`, if any.
+    pub fn get<T: 'static>(&self, index: usize) -> Option<&T> {
+        self.pool::<T>()?.get(index)
+    }
+
+    /// Returns a mutable reference to the value of type `T` at `index`, if any.
+    pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
+        self.pools
+            .get_mut(&TypeId::of::<T>())?
+            .downcast_mut::<RawVec<T, S>>()
+            .expect("Pool keyed by its type")
+            .get_mut(index)
+    }
+}
+
+impl<S: RangeStorage, F: FnMut() -> S> Debug for TypedPools<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "TypedPools{{ pools: {} }}", self.pools.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::cell::RefCell;
+
+    use alloc::rc::Rc;
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::NonTrackingRange;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(i32, i32);
+
+    #[derive(Debug, PartialEq)]
+    struct Velocity(i32, i32);
+
+    #[test]
+    fn shared_backing() {
+        let allocator = SpyAllocator::default();
+        let backing = Rc::new(RefCell::new(AllocStorage::new(allocator.clone())));
+
+        let mut world = TypedPools::new(|| backing.clone());
+
+        assert!(world.is_empty());
+        assert!(world.pool::<Position>().is_none());
+
+        let entity = world.try_insert(Position(0, 0)).unwrap();
+        assert_eq!(entity, world.try_insert(Velocity(1, 2)).unwrap());
+
+        let velocity = world.get::<Velocity>(entity).map(|v| (v.0, v.1)).unwrap();
+        let position = world.get_mut::<Position>(entity).unwrap();
+
+        position.0 += velocity.0;
+        position.1 += velocity.1;
+
+        assert_eq!(Some(&Position(1, 2)), world.get::<Position>(entity));
+        assert_eq!(None, world.get::<Velocity>(entity + 1));
+        assert_eq!("TypedPools{ pools: 2 }", format!("{:?}", world));
+
+        //  Both pools allocated from the common backing.
+        assert_eq!(2, allocator.allocated());
+
+        drop(world);
+
+        assert_eq!(2, allocator.deallocated());
+    }
+
+    #[test]
+    fn inline_pools() {
+        let mut world = TypedPools::new(NonTrackingRange::<u8, u16, 1>::default);
+
+        world.try_insert(1u8).unwrap();
+        world.try_insert(2u8).unwrap();
+
+        assert_eq!(3, world.try_insert(3u8).unwrap_err().into_value());
+
+        world.pool_mut::<u16>().push(4);
+
+        assert_eq!([1, 2], **world.pool::<u8>().unwrap());
+        assert_eq!([4], **world.pool::<u16>().unwrap());
+    }
+} // mod tests
@@ -0,0 +1,361 @@
+//! Implementations of the storage traits for shared storages, so that multiple collections may share one storage.
+//!
+//! A storage is shared by wrapping it in either `Rc<RefCell<S>>` or, with the `std` feature, `Arc<Mutex<S>>`, then
+//! handing a clone of the wrapper to each collection.
+//!
+//! #   Locking
+//!
+//! Each call to a storage method borrows, or locks, the storage for the duration of the call only, and never invokes
+//! user code whilst doing so: in particular, `destroy` drops the value prior to borrowing the storage to deallocate
+//! it, hence a destructor may itself use the shared storage.
+//!
+//! The borrow, or lock, only protects the bookkeeping of the storage, not the elements: each element belongs to the
+//! collection holding its handle, which is responsible for synchronizing accesses to it, as usual. The pointers
+//! obtained from `get` remain valid until the storage is moved -- which it never is, once shared -- or until the
+//! element is deallocated, or relocated by a later allocation, as per the usual storage contracts.
+//!
+//! A storage is never relocated whilst shared, hence inline storages can be shared just like any other.
+
+use core::{alloc::Layout, cell::RefCell, marker::Unsize, mem::MaybeUninit, ptr::NonNull};
+
+use alloc::rc::Rc;
+
+#[cfg(any(test, feature = "std"))]
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use rfc2580::Pointee;
+
+use crate::{
+    error::StorageError,
+    traits::{AddressStability, ElementStorage, RangeStorage},
+};
+
+impl<S: ElementStorage> ElementStorage for Rc<RefCell<S>> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    //  The storage is never moved once shared.
+    const ADDRESS_STABILITY: AddressStability = AddressStability {
+        across_moves: true,
+        ..S::ADDRESS_STABILITY
+    };
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        self.borrow_mut().deallocate(handle)
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.borrow().get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.borrow().coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.borrow().uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        self.borrow_mut().recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.borrow().slot_layout::<T>()
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        self.borrow_mut().allocate(meta)
+    }
+}
+
+impl<S: RangeStorage> RangeStorage for Rc<RefCell<S>> {
+    type Handle<T> = S::Handle<T>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        self.borrow().maximum_capacity::<T>()
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        self.borrow_mut().deallocate(handle)
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        self.borrow().get(handle)
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        self.borrow().dangling()
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        self.borrow_mut().try_grow(handle, new_capacity)
+    }
+
+    //  `init` is invoked whilst the storage is borrowed, hence must not access it.
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        self.borrow_mut().try_grow_with(handle, new_capacity, init)
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        self.borrow_mut()
+            .try_grow_with_gap(handle, new_capacity, gap_at, gap_len)
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        self.borrow_mut().try_shrink(handle, new_capacity)
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        self.borrow_mut().cast(handle)
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        self.borrow_mut().allocate(capacity)
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl<S: ElementStorage> ElementStorage for Arc<Mutex<S>> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    //  The storage is never moved once shared.
+    const ADDRESS_STABILITY: AddressStability = AddressStability {
+        across_moves: true,
+        ..S::ADDRESS_STABILITY
+    };
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        lock(self).deallocate(handle)
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        lock(self).get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        lock(self).coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        lock(self).uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        lock(self).recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        lock(self).slot_layout::<T>()
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        lock(self).allocate(meta)
+    }
+}
+
+#[cfg(any(test, feature = "std"))]
+impl<S: RangeStorage> RangeStorage for Arc<Mutex<S>> {
+    type Handle<T> = S::Handle<T>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        lock(self).maximum_capacity::<T>()
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        lock(self).deallocate(handle)
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        lock(self).get(handle)
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        lock(self).dangling()
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        lock(self).try_grow(handle, new_capacity)
+    }
+
+    //  `init` is invoked whilst the storage is locked, hence must not access it.
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        lock(self).try_grow_with(handle, new_capacity, init)
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        lock(self).try_grow_with_gap(handle, new_capacity, gap_at, gap_len)
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        lock(self).try_shrink(handle, new_capacity)
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        lock(self).cast(handle)
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        lock(self).allocate(capacity)
+    }
+}
+
+//
+//  Implementation
+//
+
+//  No user code is invoked whilst the lock is held, bar the `init` of `try_grow_with`, hence the storage can only be
+//  poisoned by a panic within its own bookkeeping, or midway through a transactional growth, after which its state
+//  cannot be trusted.
+#[cfg(any(test, feature = "std"))]
+fn lock<S>(storage: &Mutex<S>) -> MutexGuard<'_, S> {
+    storage.lock().expect("Storage not poisoned")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::thread;
+
+    use crate::collections::{RawBox, RawVec};
+    use crate::inline::{NonTrackingRange, TrackingElement};
+
+    use super::*;
+
+    #[test]
+    fn by_ref() {
+        let mut storage = TrackingElement::<u32, 2>::new();
+
+        let first = RawBox::new(1u32, storage.by_ref()).unwrap();
+
+        assert_eq!(1, *first);
+
+        drop(first);
+
+        let second = RawBox::new(2u32, &mut storage).unwrap();
+
+        assert_eq!(2, *second);
+    }
+
+    #[test]
+    fn rc_refcell() {
+        let storage = Rc::new(RefCell::new(TrackingElement::<u32, 2>::new()));
+
+        let first = RawBox::new(1u32, storage.clone()).unwrap();
+        let second = RawBox::new(2u32, storage.clone()).unwrap();
+
+        //  Both slots are now used, through either box.
+        let third = RawBox::new(3u32, storage.clone()).map_err(|(value, _)| value);
+
+        assert_eq!(Err(3), third.map(|_| ()));
+        assert_eq!(3, *first + *second);
+
+        drop(first);
+
+        let fourth = RawBox::new(4u32, storage).unwrap();
+
+        assert_eq!(6, *second + *fourth);
+    }
+
+    #[test]
+    fn arc_mutex() {
+        let storage = Arc::new(Mutex::new(NonTrackingRange::<u8, u32, 4>::default()));
+
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let storage = storage.clone();
+
+                thread::spawn(move || {
+                    let mut vec = RawVec::new(storage);
+
+                    for j in 0..4 {
+                        vec.try_push(i * 4 + j).unwrap();
+                    }
+
+                    vec.iter().sum::<u32>()
+                })
+            })
+            .collect();
+
+        let sum: u32 = threads.into_iter().map(|t| t.join().unwrap()).sum();
+
+        assert_eq!((0..16).sum::<u32>(), sum);
+    }
+} // mod tests
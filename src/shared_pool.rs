@@ -0,0 +1,445 @@
+//! A storage shared by several collections, each within its own quota, without requiring `alloc`.
+//!
+//! Where `Rc<RefCell<S>>` shares a storage without distinguishing its users, a `SharedPool` hands out one
+//! `PoolClient` per collection, each enforcing its own `Budget`, and accounts for the usage of each client separately.
+//! Once the pool is exhausted, the accounts tell which collection holds the slots, and which was refused.
+//!
+//! #   Pattern
+//!
+//! The pool is created once, for example as a local of `main` or a leaked `'static`, and each collection is created
+//! from a client of the pool:
+//!
+//! ```ignore
+//! type Slot = RawBTreeMapNodeStorage<u32, u32, usize>;
+//!
+//! let pool = SharedPool::<TrackingElement<Slot, 16>, 2>::new(TrackingElement::new());
+//!
+//! //  At most 8 list nodes, so that the map always has room.
+//! let mut list = RawLinkedList::new(pool.client(Budget { max_live_elements: 8, ..Budget::unlimited() }).unwrap());
+//! let mut map = RawBTreeMap::new(pool.client(Budget::unlimited()).unwrap());
+//!
+//! //  ...
+//!
+//! if let Err(_) = map.try_insert(key, value) {
+//!     let hog = pool.largest_client();
+//!     //  ...
+//! }
+//! ```
+//!
+//! The slots of the pool must be large enough for the nodes of every collection.
+//!
+//! #   Locking
+//!
+//! As with `Rc<RefCell<S>>`, each call borrows the storage for the duration of the call only, and the storage is never
+//! moved whilst borrowed by the clients.
+
+use core::{
+    alloc::Layout,
+    cell::{Cell, RefCell},
+    fmt::{self, Debug},
+    marker::Unsize,
+    ptr::NonNull,
+};
+
+use rfc2580::Pointee;
+
+use crate::{
+    error::StorageError,
+    traits::{AddressStability, ElementStorage},
+    utils,
+    wrapper::{Budget, Rejection},
+};
+
+/// A storage shared by up to `C` clients, each with its own quota.
+pub struct SharedPool<S, const C: usize> {
+    storage: RefCell<S>,
+    clients: Cell<usize>,
+    usages: Cell<[Usage; C]>,
+}
+
+/// The usage of a `SharedPool` by one of its clients.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Usage {
+    /// The quota of the client.
+    pub budget: Budget,
+    /// Number of elements currently alive.
+    pub live_elements: usize,
+    /// Number of bytes currently allocated.
+    pub live_bytes: usize,
+    /// Largest number of elements alive at any one time.
+    pub peak_elements: usize,
+    /// Number of allocations rejected, whether by the quota or by the storage.
+    pub rejections: usize,
+    /// The reason of the latest rejection, if any.
+    ///
+    /// `Rejection::Storage` indicates that the allocation was within the quota, but that the pool was exhausted.
+    pub last_rejection: Option<Rejection>,
+}
+
+impl<S, const C: usize> SharedPool<S, C> {
+    /// Creates a pool sharing `storage`, without any client.
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage: RefCell::new(storage),
+            clients: Cell::new(0),
+            usages: Cell::new([Usage::new(Budget::unlimited()); C]),
+        }
+    }
+
+    /// Registers a new client, enforcing `budget`, if fewer than `C` clients are registered.
+    pub fn client(&self, budget: Budget) -> Option<PoolClient<'_, S, C>> {
+        let id = self.clients.get();
+
+        if id == C {
+            return None;
+        }
+
+        let mut usages = self.usages.get();
+        usages[id] = Usage::new(budget);
+
+        self.clients.set(id + 1);
+        self.usages.set(usages);
+
+        Some(PoolClient { pool: self, id })
+    }
+
+    /// Returns the number of registered clients.
+    pub fn clients(&self) -> usize {
+        self.clients.get()
+    }
+
+    /// Returns the usage of the client `id`.
+    ///
+    /// #   Panics
+    ///
+    /// If no such client is registered.
+    pub fn usage(&self, id: usize) -> Usage {
+        assert!(
+            id < self.clients(),
+            "{} out of bounds {}",
+            id,
+            self.clients()
+        );
+
+        self.usages.get()[id]
+    }
+
+    /// Returns an iterator over the usage of each client, in order of registration.
+    pub fn usages(&self) -> impl Iterator<Item = Usage> + '_ {
+        (0..self.clients()).map(move |id| self.usage(id))
+    }
+
+    /// Returns the client holding the most elements, if any holds any.
+    ///
+    /// This is the client to blame, or to shed load from, once the pool is exhausted.
+    pub fn largest_client(&self) -> Option<usize> {
+        self.usages()
+            .enumerate()
+            .filter(|(_, usage)| usage.live_elements > 0)
+            .max_by_key(|(_, usage)| usage.live_elements)
+            .map(|(id, _)| id)
+    }
+}
+
+impl<S, const C: usize> Debug for SharedPool<S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        f.debug_list().entries(self.usages()).finish()
+    }
+}
+
+/// A client of a `SharedPool`, to be handed to a single collection.
+///
+/// The handles are those of the pool, and may be resolved by any client of the same pool, but must be deallocated by
+/// the client which allocated them, as each element is accounted for the client which allocated it.
+pub struct PoolClient<'a, S, const C: usize> {
+    pool: &'a SharedPool<S, C>,
+    id: usize,
+}
+
+impl<'a, S, const C: usize> PoolClient<'a, S, C> {
+    /// Returns the identifier of the client, within its pool.
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns the usage of the pool by the client.
+    pub fn usage(&self) -> Usage {
+        self.pool.usages.get()[self.id]
+    }
+}
+
+impl<'a, S: ElementStorage, const C: usize> ElementStorage for PoolClient<'a, S, C> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    //  The storage is never moved whilst borrowed by the clients.
+    const ADDRESS_STABILITY: AddressStability = AddressStability {
+        across_moves: true,
+        ..S::ADDRESS_STABILITY
+    };
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        let mut storage = self.pool.storage.borrow_mut();
+
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let layout = Layout::for_value_raw(storage.get(handle).as_ptr());
+
+        storage.deallocate(handle);
+
+        self.update(|usage| {
+            debug_assert!(
+                usage.live_elements > 0 && usage.live_bytes >= layout.size(),
+                "Element deallocated by a client which did not allocate it"
+            );
+
+            usage.live_elements = usage.live_elements.saturating_sub(1);
+            usage.live_bytes = usage.live_bytes.saturating_sub(layout.size());
+        });
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.pool.storage.borrow().get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.pool.storage.borrow().coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.pool.storage.borrow().uncoerce(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.pool.storage.borrow().slot_layout::<T>()
+    }
+
+    fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
+        let size = Layout::new::<T>().size();
+
+        if self.admit(Layout::new::<T>()).is_err() {
+            return Err(value);
+        }
+
+        let result = self.pool.storage.borrow_mut().create(value);
+
+        self.on_result(&result, size);
+
+        result
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let layout = utils::layout_of::<T>(meta);
+
+        self.admit(layout)?;
+
+        let result = self.pool.storage.borrow_mut().allocate(meta);
+
+        self.on_result(&result, layout.size());
+
+        result
+    }
+}
+
+impl<'a, S, const C: usize> Debug for PoolClient<'a, S, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "PoolClient{{ id: {}, usage: {:?} }}",
+            self.id,
+            self.usage()
+        )
+    }
+}
+
+//
+//  Implementation
+//
+
+impl Usage {
+    const fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            live_elements: 0,
+            live_bytes: 0,
+            peak_elements: 0,
+            rejections: 0,
+            last_rejection: None,
+        }
+    }
+
+    fn reject(&mut self, rejection: Rejection) {
+        self.rejections += 1;
+        self.last_rejection = Some(rejection);
+    }
+}
+
+impl<'a, S, const C: usize> PoolClient<'a, S, C> {
+    fn update<F: FnOnce(&mut Usage)>(&self, f: F) {
+        let mut usages = self.pool.usages.get();
+        f(&mut usages[self.id]);
+        self.pool.usages.set(usages);
+    }
+
+    //  Checks whether an element of `layout` fits within the quota.
+    fn admit(&self, layout: Layout) -> Result<(), StorageError> {
+        let usage = self.usage();
+        let size = layout.size();
+
+        let rejection = if size > usage.budget.max_element_size {
+            Some(Rejection::ElementSize)
+        } else if usage.live_elements + 1 > usage.budget.max_live_elements {
+            Some(Rejection::LiveElements)
+        } else if usage.live_bytes.saturating_add(size) > usage.budget.max_live_bytes {
+            Some(Rejection::LiveBytes)
+        } else {
+            None
+        };
+
+        match rejection {
+            Some(rejection) => {
+                self.update(|usage| usage.reject(rejection));
+                Err(StorageError::new(layout).with_context("PoolClient"))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn on_result<H, E>(&self, result: &Result<H, E>, size: usize) {
+        self.update(|usage| match result {
+            Ok(_) => {
+                usage.live_elements += 1;
+                usage.live_bytes += size;
+                usage.peak_elements = usage.peak_elements.max(usage.live_elements);
+            }
+            Err(_) => usage.reject(Rejection::Storage),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::{RawBTreeMap, RawBTreeMapNodeStorage, RawLinkedList};
+    use crate::inline::TrackingElement;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    type Slot = RawBTreeMapNodeStorage<u32, u32, usize>;
+
+    #[test]
+    fn list_and_map() {
+        let pool = SharedPool::<TrackingElement<Slot, 3>, 2>::new(TrackingElement::new());
+
+        let quota = Budget {
+            max_live_elements: 2,
+            ..Budget::unlimited()
+        };
+
+        let mut list = RawLinkedList::new(pool.client(quota).unwrap());
+        let mut map = RawBTreeMap::new(pool.client(Budget::unlimited()).unwrap());
+
+        assert!(pool.client(Budget::unlimited()).is_none());
+
+        list.push(1u32);
+        list.push(2);
+
+        //  The list exceeds its own quota, whilst the pool still has room.
+        list.try_push(3).unwrap_err();
+
+        assert_eq!(Some(Rejection::LiveElements), pool.usage(0).last_rejection);
+
+        //  A single node holds 11 entries, splitting it requires 2 more nodes, of which the pool only has 1 left.
+        for key in 0..11 {
+            map.insert(key, key);
+        }
+
+        map.try_insert(11, 11).unwrap_err();
+
+        let usage = pool.usage(1);
+
+        assert_eq!(1, usage.live_elements);
+        assert_eq!(Some(Rejection::Storage), usage.last_rejection);
+        assert_eq!(Some(0), pool.largest_client());
+
+        //  Releasing the list makes room for the map.
+        drop(list);
+
+        map.insert(11, 11);
+
+        assert_eq!(0, pool.usage(0).live_elements);
+        assert_eq!(3, pool.usage(1).live_elements);
+        assert_eq!(Some(1), pool.largest_client());
+    }
+
+    #[test]
+    fn accounting() {
+        let allocator = SpyAllocator::default();
+        let pool = SharedPool::<_, 2>::new(AllocStorage::new(allocator.clone()));
+
+        {
+            let mut first = RawLinkedList::new(pool.client(Budget::unlimited()).unwrap());
+            let mut second = RawLinkedList::new(pool.client(Budget::unlimited()).unwrap());
+
+            for i in 0..3u64 {
+                first.push(i);
+                second.push(i);
+            }
+
+            second.pop();
+
+            assert_eq!(3, pool.usage(0).live_elements);
+            assert_eq!(2, pool.usage(1).live_elements);
+            assert_eq!(3, pool.usage(1).peak_elements);
+            assert_eq!(
+                2 * RawLinkedList::<u64, AllocStorage<SpyAllocator>>::NODE_LAYOUT.size(),
+                pool.usage(1).live_bytes
+            );
+        }
+
+        assert!(pool
+            .usages()
+            .all(|usage| usage.live_elements == 0 && usage.live_bytes == 0));
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn cross_client_get() {
+        let pool = SharedPool::<TrackingElement<u32, 2>, 2>::new(TrackingElement::new());
+
+        let mut first = pool.client(Budget::unlimited()).unwrap();
+        let second = pool.client(Budget::unlimited()).unwrap();
+
+        let handle = first.create(7u32).unwrap();
+
+        assert_eq!(7, unsafe { *second.get(&handle).as_ptr() });
+
+        unsafe { first.destroy(&handle) };
+
+        assert!(pool.usages().all(|usage| usage.live_elements == 0));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "Element deallocated by a client which did not allocate it")]
+    fn cross_client_deallocate() {
+        let pool = SharedPool::<TrackingElement<u32, 2>, 2>::new(TrackingElement::new());
+
+        let mut first = pool.client(Budget::unlimited()).unwrap();
+        let mut second = pool.client(Budget::unlimited()).unwrap();
+
+        let handle = first.create(7u32).unwrap();
+
+        unsafe { second.destroy(&handle) };
+    }
+} // mod tests
@@ -1,7 +1,7 @@
 //! Small implementation of `ElementStorage`.
 
 use core::{
-    alloc::{AllocError, Allocator},
+    alloc::{Allocator, Layout},
     fmt::{self, Debug},
     marker::Unsize,
     ptr::NonNull,
@@ -12,8 +12,9 @@ use rfc2580::Pointee;
 use crate::{
     allocator::{self, AllocatorBuilder},
     alternative::{self, DefaultBuilder},
+    error::StorageError,
     inline,
-    traits::ElementStorage,
+    traits::{AddressStability, ElementStorage},
 };
 
 /// Generic inline ElementStorage.
@@ -35,6 +36,8 @@ impl<S: Default, A> SingleElement<S, A> {
 impl<S, A: Allocator> ElementStorage for SingleElement<S, A> {
     type Handle<T: ?Sized + Pointee> = <Inner<S, A> as ElementStorage>::Handle<T>;
 
+    const ADDRESS_STABILITY: AddressStability = <Inner<S, A> as ElementStorage>::ADDRESS_STABILITY;
+
     unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
         self.inner.deallocate(handle)
     }
@@ -50,6 +53,24 @@ impl<S, A: Allocator> ElementStorage for SingleElement<S, A> {
         self.inner.coerce(handle)
     }
 
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.inner.uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        self.inner.recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.inner.slot_layout::<T>()
+    }
+
     fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
         self.inner.create(value)
     }
@@ -57,7 +78,7 @@ impl<S, A: Allocator> ElementStorage for SingleElement<S, A> {
     fn allocate<T: ?Sized + Pointee>(
         &mut self,
         meta: T::MetaData,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         self.inner.allocate(meta)
     }
 }
@@ -1,7 +1,7 @@
 //! Small implementation of `RangeStorage`.
 
 use core::{
-    alloc::{AllocError, Allocator},
+    alloc::Allocator,
     fmt::{self, Debug},
     mem::MaybeUninit,
     ptr::NonNull,
@@ -10,6 +10,7 @@ use core::{
 use crate::{
     allocator::{self, AllocatorBuilder},
     alternative::{self, DefaultBuilder},
+    error::StorageError,
     inline,
     traits::RangeStorage,
 };
@@ -47,23 +48,57 @@ impl<S, A: Allocator> RangeStorage for SingleRange<S, A> {
         self.inner.get(handle)
     }
 
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        self.inner.dangling()
+    }
+
     unsafe fn try_grow<T>(
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         self.inner.try_grow(handle, new_capacity)
     }
 
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        self.inner.try_grow_with(handle, new_capacity, init)
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        self.inner
+            .try_grow_with_gap(handle, new_capacity, gap_at, gap_len)
+    }
+
     unsafe fn try_shrink<T>(
         &mut self,
         handle: &Self::Handle<T>,
         new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
+    ) -> Result<Self::Handle<T>, StorageError> {
         self.inner.try_shrink(handle, new_capacity)
     }
 
-    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, AllocError> {
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        self.inner.cast(handle)
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
         self.inner.allocate(capacity)
     }
 }
@@ -94,6 +129,8 @@ type Inner<S, A> = alternative::SingleRange<
 #[cfg(test)]
 mod tests {
 
+    use core::alloc::Layout;
+
     use crate::utils::{NonAllocator, SpyAllocator};
 
     use super::*;
@@ -138,4 +175,102 @@ mod tests {
         let mut storage = SingleRange::<[u8; 2], _>::new(NonAllocator);
         storage.allocate::<String>(1).unwrap_err();
     }
+
+    #[test]
+    fn try_grow_with_switch() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = SingleRange::<[u64; 4], _>::new(allocator.clone());
+        let handle = storage.allocate::<u64>(4).unwrap();
+
+        unsafe {
+            write(storage.get(&handle), 0..4);
+
+            let handle = storage
+                .try_grow_with(&handle, 8, |range| {
+                    write(range, 4..8);
+                    Ok(())
+                })
+                .unwrap();
+
+            assert_eq!(1, allocator.allocated());
+            assert_eq!((0..8).collect::<Vec<_>>(), read(storage.get(&handle), 8));
+
+            storage.deallocate(&handle);
+        }
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn try_grow_with_switch_rollback() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = SingleRange::<[u64; 4], _>::new(allocator.clone());
+        let handle = storage.allocate::<u64>(4).unwrap();
+
+        unsafe {
+            write(storage.get(&handle), 0..4);
+
+            let error = storage
+                .try_grow_with(&handle, 8, |_| {
+                    Err(StorageError::new(Layout::new::<u64>()).with_context("init"))
+                })
+                .unwrap_err();
+
+            assert_eq!(Some("init"), error.context());
+
+            assert_eq!(1, allocator.allocated());
+            assert_eq!(1, allocator.deallocated());
+
+            //  Still within the inline storage, hence `handle` is still valid.
+            assert_eq!((0..4).collect::<Vec<_>>(), read(storage.get(&handle), 4));
+
+            let other = storage.allocate::<u64>(2).unwrap();
+
+            assert_eq!(1, allocator.allocated());
+
+            storage.deallocate(&other);
+            storage.deallocate(&handle);
+        }
+    }
+
+    #[test]
+    fn try_grow_with_gap_switch() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = SingleRange::<[u64; 4], _>::new(allocator.clone());
+        let handle = storage.allocate::<u64>(4).unwrap();
+
+        unsafe {
+            write(storage.get(&handle), 0..4);
+
+            let handle = storage.try_grow_with_gap(&handle, 8, 1, 4).unwrap();
+
+            assert_eq!(1, allocator.allocated());
+
+            //  Fill the gap, so as to read the whole range back.
+            write(storage.get(&handle), 1..5);
+
+            assert_eq!(vec![0, 1, 2, 3, 4, 1, 2, 3], read(storage.get(&handle), 8));
+
+            storage.deallocate(&handle);
+        }
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    unsafe fn write(range: NonNull<[MaybeUninit<u64>]>, values: core::ops::Range<u64>) {
+        let base = range.as_mut_ptr();
+
+        for value in values {
+            base.add(value as usize).write(MaybeUninit::new(value));
+        }
+    }
+
+    unsafe fn read(range: NonNull<[MaybeUninit<u64>]>, len: usize) -> Vec<u64> {
+        let base = range.as_mut_ptr();
+
+        (0..len).map(|i| base.add(i).read().assume_init()).collect()
+    }
 } // mod tests
@@ -0,0 +1,285 @@
+//! A `Box`-compatible façade over `RawBox`.
+//!
+//! The façade replicates `Box::new`, `Box::pin`, `downcast`, and the coercion to trait objects, with the storage
+//! defaulting to `AllocStorage<Global>`, so that switching from `std::boxed::Box` is a matter of switching the import,
+//! and the storage can be tuned later on.
+//!
+//! The policy, see `policy::OomPolicy`, decides whether the constructors taking a storage panic on failure, as
+//! `std::boxed::Box` does, or return a `Result`. It defaults to `Panic`.
+//!
+//! As the policy determines the return type of those constructors, it must be known at the call site: the type path
+//! `Box::<_, _>` selects the default policy, as in `Box::<_, _>::new_in(value, storage)`.
+
+use core::{
+    alloc::Allocator,
+    any::Any,
+    fmt::{self, Debug, Display},
+    marker::{PhantomData, Unsize},
+    ops::{CoerceUnsized, Deref, DerefMut},
+    pin::Pin,
+};
+
+use alloc::alloc::Global;
+
+use rfc2580::Pointee;
+
+use crate::{
+    allocator::AllocStorage,
+    collections::RawBox,
+    policy::{OomPolicy, Panic},
+    traits::ElementStorage,
+};
+
+/// A `Box`-compatible façade over `RawBox`.
+pub struct Box<T: ?Sized + Pointee, S: ElementStorage = AllocStorage<Global>, P = Panic> {
+    inner: RawBox<T, S>,
+    _policy: PhantomData<P>,
+}
+
+impl<T: Pointee> Box<T> {
+    /// Creates an instance containing `value`.
+    ///
+    /// #   Panics
+    ///
+    /// If the value cannot be allocated.
+    pub fn new(value: T) -> Self {
+        Self::new_in(value, AllocStorage::default())
+    }
+
+    /// Creates a pinned instance containing `value`.
+    ///
+    /// #   Panics
+    ///
+    /// If the value cannot be allocated.
+    pub fn pin(value: T) -> Pin<Self> {
+        Self::pin_in(value, AllocStorage::default())
+    }
+}
+
+impl<T: Pointee, S: ElementStorage, P: OomPolicy> Box<T, S, P> {
+    /// Creates an instance containing `value`, stored in `storage`.
+    ///
+    /// Failure to allocate the value is handled according to `P`, returning both `value` and `storage`.
+    pub fn new_in(value: T, storage: S) -> P::Output<Self, (T, S)> {
+        P::handle(Self::try_new_in(value, storage))
+    }
+
+    /// Attempts to create an instance containing `value`, stored in `storage`.
+    ///
+    /// On failure, returns both `value` and `storage`.
+    pub fn try_new_in(value: T, storage: S) -> Result<Self, (T, S)> {
+        RawBox::new(value, storage).map(Self::from)
+    }
+}
+
+impl<T: Pointee, A: Allocator, P: OomPolicy> Box<T, AllocStorage<A>, P> {
+    /// Creates a pinned instance containing `value`, allocated by `storage`.
+    ///
+    /// The value is allocated by the allocator, and therefore does not move when the Box is moved.
+    ///
+    /// Failure to allocate the value is handled according to `P`, returning both `value` and `storage`.
+    pub fn pin_in(
+        value: T,
+        storage: AllocStorage<A>,
+    ) -> P::Output<Pin<Self>, (T, AllocStorage<A>)> {
+        //  Safety:
+        //  -   The value is stored in the allocator, and not within the Box, hence is never moved.
+        let result =
+            Self::try_new_in(value, storage).map(|boxed| unsafe { Pin::new_unchecked(boxed) });
+
+        P::handle(result)
+    }
+}
+
+impl<T: ?Sized + Pointee, S: ElementStorage, P> Box<T, S, P> {
+    /// Coerces to another Box, typically a trait object.
+    pub fn coerce<U: ?Sized + Pointee>(self) -> Box<U, S, P>
+    where
+        T: Unsize<U>,
+    {
+        Box::from(self.inner.coerce())
+    }
+
+    /// Returns the underlying `RawBox`.
+    pub fn into_raw(self) -> RawBox<T, S> {
+        self.inner
+    }
+}
+
+impl<S: ElementStorage, P> Box<dyn Any, S, P> {
+    /// Attempts to downcast the Box to a concrete type.
+    pub fn downcast<T: Any + Pointee>(self) -> Result<Box<T, S, P>, Self> {
+        self.inner.downcast().map(Box::from).map_err(Box::from)
+    }
+}
+
+impl<T, U, S, P> CoerceUnsized<Box<U, S, P>> for Box<T, S, P>
+where
+    T: ?Sized + Pointee,
+    U: ?Sized + Pointee,
+    S: ElementStorage,
+    RawBox<T, S>: CoerceUnsized<RawBox<U, S>>,
+{
+}
+
+impl<T: Clone + Pointee, S: Default + ElementStorage, P: OomPolicy> Clone for Box<T, S, P> {
+    fn clone(&self) -> Self {
+        Self::try_new_in((**self).clone(), S::default())
+            .map_err(|_| ())
+            .expect("Sufficient capacity")
+    }
+}
+
+impl<T: ?Sized + Pointee + Debug, S: ElementStorage, P> Debug for Box<T, S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let value: &T = self;
+        Debug::fmt(value, f)
+    }
+}
+
+impl<T: Default + Pointee, S: Default + ElementStorage, P: OomPolicy> Default for Box<T, S, P> {
+    fn default() -> Self {
+        Self::try_new_in(T::default(), S::default())
+            .map_err(|_| ())
+            .expect("Sufficient capacity")
+    }
+}
+
+impl<T: ?Sized + Pointee, S: ElementStorage, P> Deref for Box<T, S, P> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: ?Sized + Pointee, S: ElementStorage, P> DerefMut for Box<T, S, P> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: ?Sized + Pointee + Display, S: ElementStorage, P> Display for Box<T, S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        let value: &T = self;
+        Display::fmt(value, f)
+    }
+}
+
+impl<T: ?Sized + Pointee, S: ElementStorage, P> From<RawBox<T, S>> for Box<T, S, P> {
+    fn from(inner: RawBox<T, S>) -> Self {
+        Self {
+            inner,
+            _policy: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized + Pointee + PartialEq, S: ElementStorage, P> PartialEq for Box<T, S, P> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: ?Sized + Pointee + Eq, S: ElementStorage, P> Eq for Box<T, S, P> {}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::policy::Fallible;
+    use crate::small::SingleElement;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    #[test]
+    fn std_like() {
+        let mut boxed = Box::new(1);
+
+        *boxed += 1;
+
+        assert_eq!(2, *boxed);
+        assert_eq!(Box::new(2), boxed);
+        assert_eq!("2", format!("{}", boxed));
+    }
+
+    #[test]
+    fn pin() {
+        let allocator = SpyAllocator::default();
+
+        let pinned =
+            Box::<_, _>::pin_in(String::from("Pinned"), AllocStorage::new(allocator.clone()));
+        let moved = pinned;
+
+        assert_eq!("Pinned", &**moved);
+
+        drop(moved);
+
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn coerce_trait() {
+        let boxed: Box<dyn Debug> = Box::new([1, 2, 3]).coerce();
+
+        assert_eq!("[1, 2, 3]", format!("{:?}", boxed));
+    }
+
+    #[test]
+    fn downcast() {
+        let allocator = SpyAllocator::default();
+
+        let boxed: Box<dyn Any, _> =
+            Box::<_, _>::new_in(42u32, AllocStorage::new(allocator.clone())).coerce();
+
+        let boxed = boxed.downcast::<String>().unwrap_err();
+        let boxed = boxed.downcast::<u32>().unwrap();
+
+        assert_eq!(42, *boxed);
+
+        drop(boxed);
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn small_storage() {
+        type Small<T> = Box<T, SingleElement<[u8; 4], NonAllocator>>;
+
+        let boxed: Small<[u8]> = Small::new_in([1u8, 2, 3], SingleElement::default()).coerce();
+
+        assert_eq!([1, 2, 3], *boxed);
+
+        Small::try_new_in([1u8; 8], SingleElement::default()).unwrap_err();
+    }
+
+    #[test]
+    fn fallible() {
+        type Small<T> = Box<T, SingleElement<u32, NonAllocator>, Fallible>;
+
+        let boxed = Small::new_in(42u32, SingleElement::default()).unwrap();
+
+        assert_eq!(42, *boxed);
+
+        let (value, _) = Small::new_in(42u64, SingleElement::default()).unwrap_err();
+
+        assert_eq!(42, value);
+
+        let pinned = Box::<_, _, Fallible>::pin_in(1u8, AllocStorage::new(NonAllocator));
+
+        assert_eq!(1, pinned.unwrap_err().0);
+    }
+
+    #[test]
+    fn small_storage_downcast() {
+        type Small<T> = Box<T, SingleElement<u32, NonAllocator>>;
+
+        let boxed: Small<dyn Any> = Small::new_in(42u32, SingleElement::default()).coerce();
+
+        let boxed = boxed.downcast::<u16>().unwrap_err();
+        let boxed = boxed.downcast::<u32>().unwrap();
+
+        assert_eq!(42, *boxed);
+    }
+} // mod tests
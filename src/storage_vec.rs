@@ -0,0 +1,457 @@
+//! A `Vec`-compatible façade over `RawVec`.
+//!
+//! The façade replicates the most common methods of `std::vec::Vec`, with the panicking variants alongside their
+//! fallible `try_` twins, so as to measure how much of the standard API a storage-based design can express.
+//!
+//! The storage defaults to `AllocStorage<Global>`, so that switching from `std::vec::Vec` is a matter of switching the
+//! import, and the storage can be tuned later on.
+//!
+//! The policy, see `policy::OomPolicy`, decides whether the allocating methods panic on failure, as `std::vec::Vec`
+//! does, or return a `Result`. It defaults to `Panic`. The implementations of the standard traits, such as `Clone` or
+//! `Extend`, always panic on failure, their signatures leaving no other choice.
+//!
+//! As the policy determines the return type of the allocating constructors, it must be known at the call site: the
+//! type path `Vec::<T, _>` selects the default policy, as in `Vec::<T, _>::with_capacity_in(capacity, storage)`.
+
+use core::{
+    fmt::{self, Debug},
+    iter::FromIterator,
+    marker::PhantomData,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
+
+use alloc::alloc::Global;
+
+use crate::{
+    allocator::AllocStorage,
+    collections::{InsertError, RawVec},
+    error::StorageError,
+    policy::{OomPolicy, Panic},
+    traits::RangeStorage,
+};
+
+/// A `Vec`-compatible façade over `RawVec`.
+pub struct Vec<T, S: RangeStorage = AllocStorage<Global>, P = Panic> {
+    inner: RawVec<T, S>,
+    _policy: PhantomData<P>,
+}
+
+impl<T> Vec<T> {
+    /// Creates a new, empty, instance.
+    pub fn new() -> Self {
+        Self::new_in(AllocStorage::default())
+    }
+
+    /// Creates a new instance with a capacity of at least `capacity` elements.
+    ///
+    /// #   Panics
+    ///
+    /// If the capacity cannot be allocated.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, AllocStorage::default())
+    }
+}
+
+impl<T, S: RangeStorage, P: OomPolicy> Vec<T, S, P> {
+    /// Creates a new, empty, instance using `storage`.
+    pub fn new_in(storage: S) -> Self {
+        Self::from(RawVec::new(storage))
+    }
+
+    /// Creates a new instance with a capacity of at least `capacity` elements, using `storage`.
+    ///
+    /// Failure to allocate the capacity is handled according to `P`.
+    pub fn with_capacity_in(capacity: usize, storage: S) -> P::Output<Self, StorageError> {
+        P::handle(Self::try_with_capacity_in(capacity, storage))
+    }
+
+    /// Attempts to create a new instance with a capacity of at least `capacity` elements, using `storage`.
+    pub fn try_with_capacity_in(capacity: usize, storage: S) -> Result<Self, StorageError> {
+        let mut result = Self::new_in(storage);
+        result.try_reserve(capacity)?;
+        Ok(result)
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns whether `self` is empty, or not.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the number of elements `self` can hold without growing.
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more elements.
+    ///
+    /// Failure to allocate the capacity is handled according to `P`.
+    pub fn reserve(&mut self, additional: usize) -> P::Output<(), StorageError> {
+        P::handle(self.try_reserve(additional))
+    }
+
+    /// Attempts to reserve capacity for at least `additional` more elements.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), StorageError> {
+        self.inner.try_reserve(additional)
+    }
+
+    /// Appends an element at the back.
+    ///
+    /// Failure to grow the capacity is handled according to `P`.
+    pub fn push(&mut self, value: T) -> P::Output<(), InsertError<T>> {
+        P::handle(self.try_push(value))
+    }
+
+    /// Attempts to append an element at the back, returning it on failure.
+    pub fn try_push(&mut self, value: T) -> Result<(), InsertError<T>> {
+        self.inner.try_push(value)
+    }
+
+    /// Removes the last element, and returns it, if any.
+    pub fn pop(&mut self) -> Option<T> {
+        self.inner.pop()
+    }
+
+    /// Inserts an element at `index`, shifting all elements after it to the right.
+    ///
+    /// Failure to grow the capacity is handled according to `P`.
+    ///
+    /// #   Panics
+    ///
+    /// If `index > self.len()`.
+    pub fn insert(&mut self, index: usize, value: T) -> P::Output<(), InsertError<T>> {
+        P::handle(self.try_insert(index, value))
+    }
+
+    /// Attempts to insert an element at `index`, shifting all elements after it to the right.
+    ///
+    /// #   Panics
+    ///
+    /// If `index > self.len()`.
+    pub fn try_insert(&mut self, index: usize, value: T) -> Result<(), InsertError<T>> {
+        self.inner.try_insert(index, value)
+    }
+
+    /// Removes and returns the element at `index`, shifting all elements after it to the left.
+    ///
+    /// #   Panics
+    ///
+    /// If `index >= self.len()`.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.inner.remove(index)
+    }
+
+    /// Removes and returns the element at `index`, replacing it with the last element.
+    ///
+    /// #   Panics
+    ///
+    /// If `index >= self.len()`.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        self.inner.swap_remove(index)
+    }
+
+    /// Retains only the elements for which `f` returns `true`, destroying the others.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.inner.retain(f)
+    }
+
+    /// Removes consecutive elements for which `same_bucket` returns `true`, destroying them.
+    pub fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, same_bucket: F) {
+        self.inner.dedup_by(same_bucket)
+    }
+
+    /// Removes consecutive elements which map to the same key, destroying them.
+    pub fn dedup_by_key<K: PartialEq, F: FnMut(&mut T) -> K>(&mut self, key: F) {
+        self.inner.dedup_by_key(key)
+    }
+
+    /// Shortens `self` to `len` elements, destroying the remaining ones.
+    pub fn truncate(&mut self, len: usize) {
+        self.inner.truncate(len)
+    }
+
+    /// Clears `self`, destroying all elements.
+    pub fn clear(&mut self) {
+        self.inner.clear()
+    }
+
+    /// Returns a slice of all elements.
+    pub fn as_slice(&self) -> &[T] {
+        &self.inner
+    }
+
+    /// Returns a mutable slice of all elements.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.inner
+    }
+
+    /// Returns the spare capacity of `self`, past its last element.
+    pub fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        self.inner.spare_capacity_mut()
+    }
+
+    /// Forces the length of `self` to `new_len`.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `new_len <= self.capacity()`.
+    /// -   Assumes that the elements in `self.len()..new_len` are initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        self.inner.set_len(new_len)
+    }
+
+    /// Returns the underlying `RawVec`.
+    pub fn into_raw(self) -> RawVec<T, S> {
+        self.inner
+    }
+}
+
+impl<T: Clone, S: RangeStorage, P: OomPolicy> Vec<T, S, P> {
+    /// Clones and appends all elements of `other`.
+    ///
+    /// Failure to grow the capacity is handled according to `P`, in which case `self` is left unmodified.
+    pub fn extend_from_slice(&mut self, other: &[T]) -> P::Output<(), StorageError> {
+        P::handle(self.try_extend_from_slice(other))
+    }
+
+    /// Attempts to clone and append all elements of `other`.
+    ///
+    /// On failure, `self` is left unmodified.
+    pub fn try_extend_from_slice(&mut self, other: &[T]) -> Result<(), StorageError> {
+        self.inner.try_extend_from_slice(other)
+    }
+
+    /// Resizes `self` to `new_len` elements, appending clones of `value` or dropping the excess elements.
+    ///
+    /// Failure to grow the capacity is handled according to `P`, in which case `self` is left unmodified.
+    pub fn resize(&mut self, new_len: usize, value: T) -> P::Output<(), InsertError<T>> {
+        P::handle(self.try_resize(new_len, value))
+    }
+
+    /// Attempts to resize `self` to `new_len` elements, appending clones of `value` or dropping the excess elements.
+    ///
+    /// On failure, `self` is left unmodified, and `value` is returned.
+    pub fn try_resize(&mut self, new_len: usize, value: T) -> Result<(), InsertError<T>> {
+        self.inner.try_resize(new_len, value)
+    }
+}
+
+impl<T: PartialEq, S: RangeStorage, P: OomPolicy> Vec<T, S, P> {
+    /// Removes consecutive equal elements, destroying them.
+    pub fn dedup(&mut self) {
+        self.inner.dedup()
+    }
+}
+
+impl<T: Clone, S: Default + RangeStorage, P: OomPolicy> Clone for Vec<T, S, P> {
+    fn clone(&self) -> Self {
+        let mut result = Self::default();
+        result
+            .try_extend_from_slice(self)
+            .expect("Sufficient capacity");
+        result
+    }
+}
+
+impl<T: Debug, S: RangeStorage, P: OomPolicy> Debug for Vec<T, S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "{:?}", self.as_slice())
+    }
+}
+
+impl<T, S: Default + RangeStorage, P: OomPolicy> Default for Vec<T, S, P> {
+    fn default() -> Self {
+        Self::new_in(S::default())
+    }
+}
+
+impl<T, S: RangeStorage, P: OomPolicy> Deref for Vec<T, S, P> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T, S: RangeStorage, P: OomPolicy> DerefMut for Vec<T, S, P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, S: RangeStorage, P: OomPolicy> Extend<T> for Vec<T, S, P> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for element in iter {
+            self.inner.push(element);
+        }
+    }
+}
+
+impl<T, S: Default + RangeStorage, P: OomPolicy> FromIterator<T> for Vec<T, S, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut result = Self::default();
+        result.extend(iter);
+        result
+    }
+}
+
+impl<T, S: RangeStorage, P> From<RawVec<T, S>> for Vec<T, S, P> {
+    fn from(inner: RawVec<T, S>) -> Self {
+        Self {
+            inner,
+            _policy: PhantomData,
+        }
+    }
+}
+
+impl<T, S, P, OS, OP> PartialEq<Vec<T, OS, OP>> for Vec<T, S, P>
+where
+    T: PartialEq,
+    S: RangeStorage,
+    P: OomPolicy,
+    OS: RangeStorage,
+    OP: OomPolicy,
+{
+    fn eq(&self, other: &Vec<T, OS, OP>) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq, S: RangeStorage, P: OomPolicy> Eq for Vec<T, S, P> {}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::inline::NonTrackingRange;
+    use crate::policy::Fallible;
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn std_like() {
+        let mut vec = Vec::new();
+
+        vec.push(1);
+        vec.push(3);
+        vec.insert(1, 2);
+        vec.extend(4..6);
+
+        assert_eq!([1, 2, 3, 4, 5], *vec);
+        assert_eq!(5, vec.len());
+
+        assert_eq!(2, vec.remove(1));
+        assert_eq!(Some(5), vec.pop());
+
+        vec.truncate(2);
+
+        assert_eq!("[1, 3]", format!("{:?}", vec));
+
+        vec.clear();
+
+        assert!(vec.is_empty());
+    }
+
+    #[test]
+    fn std_like_unordered() {
+        let mut vec: Vec<_> = [1, 1, 2, 3, 3, 4].iter().copied().collect();
+
+        vec.dedup();
+        vec.retain(|e| *e != 2);
+
+        assert_eq!([1, 3, 4], *vec);
+        assert_eq!(1, vec.swap_remove(0));
+        assert_eq!([4, 3], *vec);
+
+        vec.dedup_by_key(|e| *e > 2);
+
+        assert_eq!([4], *vec);
+    }
+
+    #[test]
+    fn with_capacity() {
+        let allocator = SpyAllocator::default();
+
+        let mut vec = Vec::<String, _>::with_capacity_in(4, AllocStorage::new(allocator.clone()));
+        vec.extend_from_slice(&["a".to_string(), "b".to_string()]);
+        vec.reserve(2);
+
+        assert_eq!(4, vec.capacity());
+        assert_eq!(1, allocator.allocated());
+    }
+
+    #[test]
+    fn inline_try() {
+        type Inline = Vec<u8, NonTrackingRange<u8, u8, 4>>;
+
+        let mut vec = Inline::default();
+
+        vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+        vec.try_extend_from_slice(&[4, 5]).unwrap_err();
+
+        assert_eq!([1, 2, 3], *vec);
+
+        vec.try_insert(0, 0).unwrap();
+
+        assert_eq!(Err(5), vec.try_push(5).map_err(InsertError::into_value));
+        vec.try_reserve(1).unwrap_err();
+
+        vec.resize(2, 9);
+        vec.try_resize(5, 9).unwrap_err();
+        vec.try_resize(3, 9).unwrap();
+
+        assert_eq!([0, 1, 9], *vec);
+    }
+
+    #[test]
+    #[should_panic]
+    fn inline_push_panics() {
+        type Inline = Vec<u8, NonTrackingRange<u8, u8, 1>>;
+
+        let mut vec = Inline::default();
+
+        vec.push(1);
+        vec.push(2);
+    }
+
+    #[test]
+    fn fallible() {
+        type Inline = Vec<u8, NonTrackingRange<u8, u8, 2>, Fallible>;
+
+        let mut vec = Inline::with_capacity_in(2, NonTrackingRange::default()).unwrap();
+
+        vec.push(1).unwrap();
+        vec.insert(0, 0).unwrap();
+
+        assert_eq!(2, vec.push(2).unwrap_err().into_value());
+        assert_eq!(
+            Err(Some("RawVec")),
+            vec.reserve(1).map_err(|error| error.context())
+        );
+        assert_eq!(
+            Err(Some("RawVec")),
+            vec.extend_from_slice(&[3]).map_err(|error| error.context())
+        );
+        assert_eq!(3, vec.resize(3, 3).unwrap_err().into_value());
+
+        assert_eq!([0, 1], *vec);
+        assert_eq!(Vec::<_>::from_iter(0..2), vec);
+    }
+
+    #[test]
+    fn clone_eq() {
+        let vec: Vec<_> = (0..4).collect();
+        let other = vec.clone();
+
+        let mut inline: Vec<i32, NonTrackingRange<u8, i32, 4>> = Vec::default();
+        inline.extend(0..4);
+
+        assert_eq!(vec, other);
+        assert_eq!(vec, inline);
+    }
+} // mod tests
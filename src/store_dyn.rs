@@ -0,0 +1,94 @@
+//! Macro storing trait objects, without relying on `Unsize`.
+
+/// Stores a value in a storage as a trait object, or any other unsized type, capturing its metadata explicitly.
+///
+/// Expands to a call to `ElementStorage::create_dyn`, hence evaluates to a `Result` of either the handle, or the value
+/// if it could not be stored. The storage is borrowed mutably, and the value is either an identifier, a literal, or a
+/// parenthesized expression.
+///
+/// ```ignore
+/// let handle: <Storage as ElementStorage>::Handle<dyn Debug> = store_dyn!(storage, value as dyn Debug).unwrap();
+/// ```
+///
+/// is equivalent to `storage.create_dyn::<dyn Debug, _>(value, |value| value)`, which only relies on the built-in
+/// coercion of references, and not on `feature(unsize)`.
+#[macro_export]
+macro_rules! store_dyn {
+    ($storage:expr, ($value:expr) as $target:ty) => {
+        $crate::traits::ElementStorage::create_dyn::<$target, _>(&mut $storage, $value, |value| {
+            value
+        })
+    };
+    ($storage:expr, $value:ident as $target:ty) => {
+        $crate::store_dyn!($storage, ($value) as $target)
+    };
+    ($storage:expr, $value:literal as $target:ty) => {
+        $crate::store_dyn!($storage, ($value) as $target)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::fmt::Debug;
+
+    use alloc::rc::Rc;
+
+    use crate::allocator::AllocStorage;
+    use crate::inline::TrackingElement;
+    use crate::traits::ElementStorage;
+    use crate::utils::SpyAllocator;
+
+    #[test]
+    fn store_dyn_inline() {
+        let mut storage = TrackingElement::<u32, 2>::new();
+
+        let value = [1u8, 2];
+
+        let first = store_dyn!(storage, 1u32 as dyn Debug).unwrap();
+        let second = store_dyn!(storage, value as dyn Debug).unwrap();
+
+        assert_eq!(3u8, store_dyn!(storage, 3u8 as dyn Debug).unwrap_err());
+
+        unsafe {
+            assert_eq!("1", format!("{:?}", storage.get(&first).as_ref()));
+            assert_eq!("[1, 2]", format!("{:?}", storage.get(&second).as_ref()));
+
+            storage.destroy(&first);
+            storage.destroy(&second);
+        }
+    }
+
+    #[test]
+    fn store_dyn_destroy() {
+        let allocator = SpyAllocator::default();
+        let mut storage = AllocStorage::new(allocator.clone());
+
+        let value = Rc::new(42);
+        let handle = store_dyn!(storage, (value.clone()) as dyn Debug).unwrap();
+
+        assert_eq!(2, Rc::strong_count(&value));
+
+        unsafe {
+            assert_eq!("42", format!("{:?}", storage.get(&handle).as_ref()));
+
+            storage.destroy(&handle);
+        }
+
+        //  The value is dropped through the vtable.
+        assert_eq!(1, Rc::strong_count(&value));
+        assert_eq!(1, allocator.deallocated());
+    }
+
+    #[test]
+    fn create_dyn_mismatch() {
+        static OTHER: u32 = 0;
+
+        let mut storage = TrackingElement::<u32, 2>::new();
+
+        //  The reference returned is not that of the value.
+        let result = storage.create_dyn::<dyn Debug, u32>(1, |_| &OTHER);
+
+        assert_eq!(1, result.unwrap_err());
+    }
+} // mod tests
@@ -1,15 +1,21 @@
 //! The various storages available.
 
 use core::{
-    alloc::AllocError,
+    alloc::Layout,
+    any::Any,
+    cmp,
     convert::TryInto,
     marker::Unsize,
-    mem::MaybeUninit,
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::Range,
+    pin::Pin,
     ptr::{self, NonNull},
 };
 
 use rfc2580::Pointee;
 
+use crate::{error::StorageError, utils};
+
 //
 //  Element Storage
 //
@@ -24,6 +30,11 @@ pub trait ElementStorage {
     /// The Handle used to obtain the elements.
     type Handle<T: ?Sized + Pointee>;
 
+    /// The stability of the addresses of the elements, across allocations and across moves of the storage.
+    ///
+    /// Defaults to `AddressStability::NONE`, which is always correct, if pessimistic.
+    const ADDRESS_STABILITY: AddressStability = AddressStability::NONE;
+
     /// Destroys the value stored within the storage.
     ///
     /// #   Safety
@@ -69,6 +80,80 @@ pub trait ElementStorage {
         handle: &Self::Handle<T>,
     ) -> Self::Handle<U>;
 
+    /// Reverts a coercion, recovering the handle to the concrete type.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that the value behind `handle` is of type `T`, for example as `handle` was coerced from a handle to
+    ///     `T`.
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T>;
+
+    /// Attempts to downcast the handle to the concrete type, returning `None` if the value is not of type `T`.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    unsafe fn downcast<T: Any + Pointee>(
+        &self,
+        handle: &Self::Handle<dyn Any>,
+    ) -> Option<Self::Handle<T>> {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let element = self.get(handle);
+
+        //  Safety:
+        //  -   The value behind `handle` is of type `T`, as per the check.
+        if element.as_ref().is::<T>() {
+            Some(self.uncoerce(handle))
+        } else {
+            None
+        }
+    }
+
+    /// Reuses the memory of `handle` for a value of type `U`, in place, avoiding a deallocate/allocate pair.
+    ///
+    /// This is typically meant for state machines replacing the payload of a node by a payload of another type. On
+    /// success, the returned handle refers to the same memory, uninitialized, and `handle` and all its copies are
+    /// invalidated. On failure, `handle` remains valid.
+    ///
+    /// The default implementation always fails; storages able to reuse memory in place override it.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that the value behind `handle` was already dropped, or moved out.
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        _handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        Err(StorageError::new(Layout::new::<U>()).with_context("ElementStorage::recycle"))
+    }
+
+    /// Returns the layout of the memory slot in which a value of type `T` is stored, if known.
+    ///
+    /// This is meant for introspection, for example to measure the per-element memory overhead of a collection, and
+    /// guard it against regressions. The slot may be larger than `T`: inline storages use fixed-size slots, and caching
+    /// storages round up to a size class. The bookkeeping of the storage, or of its allocator, is not accounted for.
+    ///
+    /// Returns `None` if `T` cannot be stored, or if the layout is unknown, which is the default.
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        None
+    }
+
+    /// Creates a "by reference" adapter for this instance of `ElementStorage`.
+    ///
+    /// The returned adapter also implements `ElementStorage`, and simply borrows `self`.
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
     /// Attempts to store `value` in a newly allocated memory slot.
     ///
     /// This may fail if memory cannot be allocated for it.
@@ -79,16 +164,68 @@ pub trait ElementStorage {
     ///     copies.
     /// -   This may relocate all existing elements, pointers should be re-acquired through their handles.
     fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
+        self.try_create(value).map_err(|(value, _)| value)
+    }
+
+    /// Attempts to store `value` in a newly allocated memory slot, as per `create`, returning the cause of the failure
+    /// along with `value` on failure.
+    ///
+    /// #   Safety
+    ///
+    /// -   As per `create`.
+    fn try_create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, (T, StorageError)> {
         let meta = rfc2580::into_non_null_parts(NonNull::from(&value)).0;
 
-        if let Ok(handle) = self.allocate(meta) {
+        match self.allocate(meta) {
+            Ok(handle) => {
+                //  Safety:
+                //  -   `handle` is valid.
+                let pointer = unsafe { self.get(&handle) };
+
+                //  Safety:
+                //  -   `pointer` points to a suitable memory area for `T`.
+                unsafe { ptr::write(pointer.as_ptr(), value) };
+
+                Ok(handle)
+            }
+            Err(error) => Err((value, error)),
+        }
+    }
+
+    /// Attempts to store `value` in a newly allocated memory slot, returning a handle to it as a `U`, typically a trait
+    /// object, without requiring `T: Unsize<U>`.
+    ///
+    /// The metadata of `U`, such as the vtable of a trait object, is captured by `coerce`, which converts a reference to
+    /// `value`, as in `|value| value as &dyn Trait`; see also the `store_dyn!` macro. This only relies on the built-in
+    /// coercion of references, hence does not require `feature(unsize)` from the caller.
+    ///
+    /// This may fail if memory cannot be allocated for it, or if `coerce` does not return its argument with the same
+    /// layout, in which case `value` is returned.
+    ///
+    /// #   Safety
+    ///
+    /// -   As per `create`.
+    fn create_dyn<U: ?Sized + Pointee, T: Pointee>(
+        &mut self,
+        value: T,
+        coerce: fn(&T) -> &U,
+    ) -> Result<Self::Handle<U>, T> {
+        let (meta, address) = rfc2580::into_non_null_parts(NonNull::from(coerce(&value)));
+
+        if address != NonNull::from(&value).cast()
+            || utils::layout_of::<U>(meta) != Layout::new::<T>()
+        {
+            return Err(value);
+        }
+
+        if let Ok(handle) = self.allocate::<U>(meta) {
             //  Safety:
             //  -   `handle` is valid.
             let pointer = unsafe { self.get(&handle) };
 
             //  Safety:
-            //  -   `pointer` points to a suitable memory area for `T`.
-            unsafe { ptr::write(pointer.as_ptr(), value) };
+            //  -   `pointer` points to a suitable memory area for `T`, as `U` has the layout of `T`.
+            unsafe { ptr::write(pointer.cast::<T>().as_ptr(), value) };
 
             Ok(handle)
         } else {
@@ -99,18 +236,181 @@ pub trait ElementStorage {
     /// Allocates memory, and returns a handle to it.
     ///
     /// This may fail if memory cannot be allocated for it.
+    ///
+    /// The layout of `T` is computed from `meta` alone: `T` may be a custom DST, as long as its unsized tail is a
+    /// slice, a `str`, or a trait object. Extern types are not supported.
     fn allocate<T: ?Sized + Pointee>(
         &mut self,
         meta: T::MetaData,
-    ) -> Result<Self::Handle<T>, AllocError>;
+    ) -> Result<Self::Handle<T>, StorageError>;
+}
+
+/// A storage whose handles have spare bits, available to store a small user-defined tag.
+///
+/// Tagging a handle neither grows it, nor affects what it refers to: a tagged handle may be used in lieu of the
+/// original, and copies of a handle carry its tag.
+///
+/// This is typically meant for the color bit of red-black tree nodes, or other such bookkeeping.
+pub trait TaggedStorage: ElementStorage {
+    /// The number of bits available for tagging, tags must be less than `1 << TAG_BITS`.
+    const TAG_BITS: u32;
+
+    /// Returns the tag of `handle`, 0 if it was never set.
+    fn tag<T: ?Sized + Pointee>(handle: &Self::Handle<T>) -> usize;
+
+    /// Sets the tag of `handle`.
+    ///
+    /// #   Panics
+    ///
+    /// If `tag` does not fit within `TAG_BITS` bits.
+    fn set_tag<T: ?Sized + Pointee>(handle: &mut Self::Handle<T>, tag: usize);
+}
+
+/// A storage whose elements occupy a fixed number of slots, each handle referring to a slot by index.
+///
+/// This allows maintaining side tables, such as per-slot metadata, in plain arrays densely indexed by handle.
+pub trait IndexedStorage: ElementStorage {
+    /// The number of slots; all indices are less than `SLOT_COUNT`.
+    const SLOT_COUNT: usize;
+
+    /// Returns the index of the slot `handle` refers to.
+    ///
+    /// Composite storages return a tiered index: the slots of their first storage, followed by those of their second.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize;
+}
+
+/// The stability of the addresses of the elements of a storage, see `ElementStorage::ADDRESS_STABILITY`.
+///
+/// An element is at a stable address if the pointer obtained from `get`, for its handle, remains the same until the
+/// handle is deallocated, regardless of the allocation and deallocation of other elements, or of moves of the storage,
+/// respectively.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AddressStability {
+    /// Whether the addresses are stable across the allocation, and deallocation, of other elements.
+    pub across_allocations: bool,
+    /// Whether the addresses are stable across moves of the storage.
+    pub across_moves: bool,
+}
+
+impl AddressStability {
+    /// No stability whatsoever.
+    pub const NONE: Self = Self {
+        across_allocations: false,
+        across_moves: false,
+    };
+
+    /// Stability across allocations, but not across moves, as is typical of inline storages.
+    pub const ALLOCATIONS: Self = Self {
+        across_allocations: true,
+        across_moves: false,
+    };
+
+    /// Stability across both allocations and moves, as is required of a `PinSafeStorage`.
+    pub const PINNED: Self = Self {
+        across_allocations: true,
+        across_moves: true,
+    };
+
+    /// Returns whether `self` provides at least the stability `required`.
+    pub const fn satisfies(self, required: Self) -> bool {
+        (self.across_allocations || !required.across_allocations)
+            && (self.across_moves || !required.across_moves)
+    }
+
+    /// Returns the stability provided by both `self` and `other`, for composites of two storages.
+    pub const fn and(self, other: Self) -> Self {
+        Self {
+            across_allocations: self.across_allocations && other.across_allocations,
+            across_moves: self.across_moves && other.across_moves,
+        }
+    }
+}
+
+/// Asserts that the addresses of the elements of `S` are at least as stable as `required`.
+///
+/// This is meant to be evaluated at compile time, so that a collection relying on stable addresses fails to compile,
+/// rather than misbehave, when instantiated with an unsuitable storage:
+///
+/// ```ignore
+/// const { assert_address_stability::<S>(AddressStability::ALLOCATIONS) };
+/// ```
+///
+/// #   Panics
+///
+/// If `S` does not provide the required stability; which is a compile-time error in a `const` context.
+pub const fn assert_address_stability<S: ?Sized + ElementStorage>(required: AddressStability) {
+    assert!(
+        S::ADDRESS_STABILITY.satisfies(required),
+        "Insufficient address stability of the storage"
+    );
+}
+
+/// A storage whose elements never move until deallocated, even when the storage itself is moved.
+///
+/// This is typically the case of storages whose elements live out of line, such as allocator-backed storages, and is
+/// never the case of inline storages.
+///
+/// #   Safety
+///
+/// -   Implementers guarantee that the pointer obtained from `get` for a given handle is the same until the handle is
+///     deallocated, regardless of moves of the storage, or of other allocations and deallocations.
+/// -   Implementers declare `AddressStability::PINNED` as their `ADDRESS_STABILITY`, which is checked at compile time.
+pub unsafe trait PinSafeStorage: ElementStorage {
+    /// Constructs a value in place, and returns its handle along with a pinned reference to it.
+    ///
+    /// The value is pinned until destroyed, hence it must be destroyed -- rather than merely deallocated -- before its
+    /// memory is reused, as per the `Pin` contract.
+    ///
+    /// This may fail if memory cannot be allocated for it, in which case `f` is not invoked.
+    fn create_pinned_with<T: Pointee, F: FnOnce() -> T>(
+        &mut self,
+        f: F,
+    ) -> Result<(Self::Handle<T>, Pin<&mut T>), StorageError> {
+        const { assert_address_stability::<Self>(AddressStability::PINNED) };
+
+        let meta = rfc2580::into_non_null_parts(NonNull::<T>::dangling()).0;
+
+        let handle = self.allocate::<T>(meta)?;
+
+        //  Safety:
+        //  -   `handle` is valid, fresh off the press.
+        let mut pointer = unsafe { self.get(&handle) };
+
+        //  Safety:
+        //  -   `pointer` points to a suitable memory area for `T`.
+        unsafe { ptr::write(pointer.as_ptr(), f()) };
+
+        //  Safety:
+        //  -   `pointer` points to a valid `T`, borrowed for as long as `self` is.
+        //  -   The value never moves until deallocated, as per the trait guarantee.
+        let pinned = unsafe { Pin::new_unchecked(pointer.as_mut()) };
+
+        Ok((handle, pinned))
+    }
 }
 
+/// A storage whose `allocate` and `deallocate` complete in bounded time, regardless of the number of elements, and
+/// without system calls, locks, or any other potentially blocking operation.
+///
+/// This allows real-time code to only accept such storages, by bounding its collections on this trait; `try_grow` and
+/// `try_shrink` are not covered, as they may copy the elements of the range.
+///
+/// User-provided hooks, such as the `on_full` hook of `TrackingElement`, are not covered either.
+pub trait ConstantTimeStorage {}
+
 //
 //  Range Storage
 //
 
 /// Capacity type for range storage.
 pub trait Capacity: Sized + Clone + Copy {
+    /// The maximum possible value of this type, as a `usize`, for use in constant expressions.
+    const MAX_USIZE: usize;
+
     /// The maximum possible value of this type.
     fn max() -> Self;
 
@@ -137,6 +437,9 @@ pub trait RangeStorage {
     type Capacity: Capacity;
 
     /// Indicates the maximum capacity of a single range possibly available for an element of type `T`.
+    ///
+    /// This is an upper bound: allocating, or growing to, a greater capacity is guaranteed to fail, whereas allocating
+    /// a lesser or equal capacity may still fail, for example if memory is exhausted.
     fn maximum_capacity<T>(&self) -> Self::Capacity;
 
     /// Deallocates the memory of the range associated to `handle`, without invoking any destructor.
@@ -157,15 +460,148 @@ pub trait RangeStorage {
     /// -   The pointer is only valid as long as the storage is not moved.
     unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]>;
 
+    /// Gets a pointer to the `len` elements starting at `offset` within the range of elements.
+    ///
+    /// This allows collections to hand out windowed access to a range, such as either half of a ring buffer, without
+    /// recomputing the pointer arithmetic at each call site.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   The pointer is only valid as long as the storage is not moved.
+    ///
+    /// #   Panics
+    ///
+    /// If `offset..offset + len` is out of the bounds of the range of `handle`.
+    unsafe fn slice<T>(
+        &self,
+        handle: &Self::Handle<T>,
+        offset: usize,
+        len: usize,
+    ) -> NonNull<[MaybeUninit<T>]> {
+        let range = self.get(handle);
+
+        assert!(
+            offset <= range.len() && len <= range.len() - offset,
+            "Window {}..{} out of the bounds of a range of {} elements",
+            offset,
+            offset.saturating_add(len),
+            range.len()
+        );
+
+        //  Safety:
+        //  -   `offset <= range.len()`, hence the resulting pointer is within, or one past the end of, the range.
+        let start = NonNull::new_unchecked(range.as_mut_ptr().add(offset));
+
+        NonNull::slice_from_raw_parts(start, len)
+    }
+
+    /// Returns a handle to a range requiring no allocation, typically of capacity 0.
+    ///
+    /// The handle is valid, and may be passed to `deallocate`, `get`, `try_grow`, and `try_shrink` as any handle issued
+    /// by this instance. Its capacity is that returned by `get`, which may be non-zero for inline storages.
+    ///
+    /// This allows collections to start empty without a fallible call to `allocate`.
+    fn dangling<T>(&self) -> Self::Handle<T>;
+
     /// Attempts to grow the internal storage to accomodate at least `new_capacity` elements in total.
     ///
     /// If the attempt succeeds, a new handle is returned and `handle` is invalidated.
     unsafe fn try_grow<T>(
         &mut self,
         _handle: &Self::Handle<T>,
-        _new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
-        Err(AllocError)
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        Err(StorageError::array::<T>(new_capacity.into_usize())
+            .with_context("RangeStorage::try_grow"))
+    }
+
+    /// Attempts to grow the internal storage to accomodate at least `new_capacity` elements in total, transactionally.
+    ///
+    /// The elements of `handle` are copied bitwise into a new range, then `init` is invoked on the new range, to
+    /// initialize further elements, or perform any other step which may fail. If `init` fails, the new range is
+    /// deallocated, and `handle` remains valid; otherwise, a new handle is returned and `handle` is invalidated.
+    ///
+    /// The default implementation allocates the new range prior to deallocating the old one, hence may fail where
+    /// `try_grow` would succeed, for example for storages able to hold a single range at a time. Storages whose
+    /// allocations may invalidate live handles, such as `alternative::SingleRange`, must override it.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Should `init` fail, the elements it initialized are not dropped.
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        let new = self.allocate::<T>(new_capacity)?;
+
+        //  Safety:
+        //  -   `handle` is assumed to be valid, and `new` is valid, fresh off the press.
+        let (from, to) = (self.get(handle), self.get(&new));
+
+        ptr::copy_nonoverlapping(
+            from.as_mut_ptr(),
+            to.as_mut_ptr(),
+            cmp::min(from.len(), to.len()),
+        );
+
+        if let Err(error) = init(to) {
+            self.deallocate(&new);
+            return Err(error);
+        }
+
+        self.deallocate(handle);
+
+        Ok(new)
+    }
+
+    /// Attempts to grow the internal storage to accomodate at least `new_capacity` elements in total, leaving a gap of
+    /// `gap_len` elements at `gap_at`.
+    ///
+    /// The elements of `handle` before `gap_at` are copied bitwise at the same indices of the new range, and those from
+    /// `gap_at` onwards `gap_len` indices further, leaving the gap uninitialized. This allows ring buffers, and gap
+    /// buffers, to grow and make room in a single copy, rather than growing then shifting their elements.
+    ///
+    /// The default implementation grows the range with `try_grow`, then shifts the elements from `gap_at` onwards in
+    /// place; storages able to copy into a new range directly may override it to save the shift.
+    ///
+    /// If the attempt succeeds, a new handle is returned and `handle` is invalidated.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that `gap_at` is at most the capacity of `handle`, and that `new_capacity` is at least that capacity
+    ///     plus `gap_len`.
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let old_capacity = self.get(handle).len();
+
+        debug_assert!(gap_at <= old_capacity);
+        debug_assert!(old_capacity + gap_len <= new_capacity.into_usize());
+
+        let tail = old_capacity - gap_at;
+
+        //  Allocating a new range while `handle` is live may invalidate it, for storages switching between
+        //  alternatives, hence the range is grown, then shifted.
+        let new = self.try_grow(handle, new_capacity)?;
+
+        //  Safety:
+        //  -   `new` is valid, and the shift is within bounds, as per the assumptions on `gap_at` and `new_capacity`.
+        let base = self.get(&new).as_mut_ptr();
+        ptr::copy(base.add(gap_at), base.add(gap_at + gap_len), tail);
+
+        Ok(new)
     }
 
     /// Attempts to shrink the internal storage to accomodate at least `new_capacity` elements in total.
@@ -174,9 +610,42 @@ pub trait RangeStorage {
     unsafe fn try_shrink<T>(
         &mut self,
         _handle: &Self::Handle<T>,
-        _new_capacity: Self::Capacity,
-    ) -> Result<Self::Handle<T>, AllocError> {
-        Err(AllocError)
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        Err(StorageError::array::<T>(new_capacity.into_usize())
+            .with_context("RangeStorage::try_shrink"))
+    }
+
+    /// Attempts to reinterpret the range of `handle` as a range of elements of type `U`, in place.
+    ///
+    /// This is typically meant for byte-level I/O into typed buffers, or for building byte-backed types, such as
+    /// strings, without copying. On success, the returned handle refers to the same memory, and `handle` and all its
+    /// copies are invalidated; the capacity of the new range is that of `U` elements fitting within the memory of the
+    /// old one. On failure, `handle` remains valid.
+    ///
+    /// Whether the layouts of `T` and `U` are compatible depends on the storage: an allocator-backed storage, for
+    /// example, requires the alignments to be equal and the size of the range to be a multiple of that of `U`, so as to
+    /// preserve the layout of the allocation. The default implementation always fails.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   The elements are reinterpreted bitwise, it is up to the caller to only read back valid values of `U`.
+    unsafe fn cast<T, U>(
+        &mut self,
+        _handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        Err(StorageError::new(Layout::new::<U>()).with_context("RangeStorage::cast"))
+    }
+
+    /// Creates a "by reference" adapter for this instance of `RangeStorage`.
+    ///
+    /// The returned adapter also implements `RangeStorage`, and simply borrows `self`.
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
     }
 
     /// Allocates memory for a new `Handle`, large enough to at least accomodate the required `capacity`.
@@ -188,14 +657,439 @@ pub trait RangeStorage {
     /// -   The Handle obtained is only valid until `self.destroy` or `self.deallocate` is invoked on it, or one of its
     ///     copies.
     /// -   This may relocate all existing ranges, which should be re-acquired through their handles.
-    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, AllocError>;
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError>;
+}
+
+/// A range storage whose maximum capacity, for elements of type `T`, is known at compile time.
+///
+/// This allows collections to check at compile time that a fixed capacity fits within the storage, rather than
+/// discovering at run-time that it does not.
+pub trait BoundedRangeStorage<T>: RangeStorage {
+    /// The maximum capacity of a single range of `T`; this is the value returned by `maximum_capacity::<T>`.
+    const MAX_CAPACITY: usize;
 }
 
+/// Extension methods for `RangeStorage`, initializing ranges in bulk and borrowing windows of ranges.
+///
+/// The methods encapsulate the accesses to `MaybeUninit` elements, leaving it up to the caller to track which elements
+/// are initialized.
+pub trait RangeExt: RangeStorage {
+    /// Initializes the elements within `range` with clones of `value`.
+    ///
+    /// Should `clone` panic, the elements initialized so far are leaked.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that no reference to the elements within `range` is alive.
+    /// -   The elements within `range` are overwritten, without being dropped.
+    ///
+    /// #   Panics
+    ///
+    /// If `range` is out of the bounds of the range of `handle`.
+    unsafe fn fill<T: Clone>(&self, handle: &Self::Handle<T>, range: Range<usize>, value: T) {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        //  -   No reference to the elements within `range` is assumed to be alive.
+        let slice = &mut self.get(handle).as_mut()[range];
+
+        for slot in slice {
+            slot.write(value.clone());
+        }
+    }
+
+    /// Initializes the elements starting at `start` with a copy of `source`.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that no reference to the elements within `start..start + source.len()` is alive.
+    /// -   The elements within `start..start + source.len()` are overwritten, without being dropped.
+    ///
+    /// #   Panics
+    ///
+    /// If `start..start + source.len()` is out of the bounds of the range of `handle`.
+    unsafe fn copy_from_slice<T: Copy>(
+        &self,
+        handle: &Self::Handle<T>,
+        start: usize,
+        source: &[T],
+    ) {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        //  -   No reference to the elements within the target range is assumed to be alive.
+        let slice = &mut self.get(handle).as_mut()[start..][..source.len()];
+
+        //  Safety:
+        //  -   `slice` and `source` have the same length.
+        //  -   `slice` and `source` do not overlap, as no reference to the elements of `slice` is alive.
+        ptr::copy_nonoverlapping(source.as_ptr(), slice.as_mut_ptr() as *mut T, source.len());
+    }
+
+    /// Initializes the elements starting at `start` with clones of the elements of `source`.
+    ///
+    /// `source` may be the initialized part of another range, whether from this or another storage.
+    ///
+    /// Should `clone` panic, the elements initialized so far are leaked.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that no reference to the elements within `start..start + source.len()` is alive.
+    /// -   The elements within `start..start + source.len()` are overwritten, without being dropped.
+    ///
+    /// #   Panics
+    ///
+    /// If `start..start + source.len()` is out of the bounds of the range of `handle`.
+    unsafe fn clone_from_slice<T: Clone>(
+        &self,
+        handle: &Self::Handle<T>,
+        start: usize,
+        source: &[T],
+    ) {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        //  -   No reference to the elements within the target range is assumed to be alive.
+        let slice = &mut self.get(handle).as_mut()[start..][..source.len()];
+
+        for (slot, element) in slice.iter_mut().zip(source) {
+            slot.write(element.clone());
+        }
+    }
+
+    /// Borrows the `len` elements starting at `offset` within the range of `handle`.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that no mutable reference to the elements of the window is alive for `'a`.
+    ///
+    /// #   Panics
+    ///
+    /// If `offset..offset + len` is out of the bounds of the range of `handle`.
+    unsafe fn slice_ref<'a, T>(
+        &'a self,
+        handle: &'a Self::Handle<T>,
+        offset: usize,
+        len: usize,
+    ) -> &'a [MaybeUninit<T>] {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        //  -   No mutable reference to the elements of the window is assumed to be alive.
+        //  -   The slice borrows both `self` and `handle`, hence cannot outlive either.
+        self.slice(handle, offset, len).as_ref()
+    }
+
+    /// Mutably borrows the `len` elements starting at `offset` within the range of `handle`.
+    ///
+    /// #   Safety
+    ///
+    /// -   Assumes that `handle` is valid, and was issued by this instance.
+    /// -   Assumes that no other reference to the elements of the window is alive for `'a`.
+    ///
+    /// #   Panics
+    ///
+    /// If `offset..offset + len` is out of the bounds of the range of `handle`.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn slice_mut<'a, T>(
+        &'a self,
+        handle: &'a Self::Handle<T>,
+        offset: usize,
+        len: usize,
+    ) -> &'a mut [MaybeUninit<T>] {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        //  -   No other reference to the elements of the window is assumed to be alive.
+        //  -   The slice borrows both `self` and `handle`, hence cannot outlive either.
+        self.slice(handle, offset, len).as_mut()
+    }
+}
+
+impl<S: RangeStorage> RangeExt for S {}
+
+//
+//  Implementations for references.
+//
+
+impl<S: ElementStorage> ElementStorage for &mut S {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    //  Moving the reference does not move the storage.
+    const ADDRESS_STABILITY: AddressStability = AddressStability {
+        across_moves: true,
+        ..S::ADDRESS_STABILITY
+    };
+
+    unsafe fn destroy<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        (**self).destroy(handle)
+    }
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        (**self).deallocate(handle)
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        (**self).get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        (**self).coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        (**self).uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        (**self).recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        (**self).slot_layout::<T>()
+    }
+
+    fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
+        (**self).create(value)
+    }
+
+    fn try_create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, (T, StorageError)> {
+        (**self).try_create(value)
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).allocate(meta)
+    }
+}
+
+impl<S: RangeStorage> RangeStorage for &mut S {
+    type Handle<T> = S::Handle<T>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        (**self).maximum_capacity::<T>()
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        (**self).deallocate(handle)
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        (**self).get(handle)
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        (**self).dangling()
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).try_grow(handle, new_capacity)
+    }
+
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        (**self).try_grow_with(handle, new_capacity, init)
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).try_grow_with_gap(handle, new_capacity, gap_at, gap_len)
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).try_shrink(handle, new_capacity)
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        (**self).cast(handle)
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        (**self).allocate(capacity)
+    }
+}
+
+impl<S: ConstantTimeStorage> ConstantTimeStorage for &mut S {}
+
+//
+//  Implementations for `ManuallyDrop`.
+//
+//  This allows embedding a storage whose destruction is deferred, or skipped altogether, for example in a static.
+//
+
+impl<S: ElementStorage> ElementStorage for ManuallyDrop<S> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    const ADDRESS_STABILITY: AddressStability = S::ADDRESS_STABILITY;
+
+    unsafe fn destroy<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        (**self).destroy(handle)
+    }
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        (**self).deallocate(handle)
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        (**self).get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        (**self).coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        (**self).uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        (**self).recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        (**self).slot_layout::<T>()
+    }
+
+    fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
+        (**self).create(value)
+    }
+
+    fn try_create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, (T, StorageError)> {
+        (**self).try_create(value)
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).allocate(meta)
+    }
+}
+
+impl<S: RangeStorage> RangeStorage for ManuallyDrop<S> {
+    type Handle<T> = S::Handle<T>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        (**self).maximum_capacity::<T>()
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        (**self).deallocate(handle)
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        (**self).get(handle)
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        (**self).dangling()
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).try_grow(handle, new_capacity)
+    }
+
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        (**self).try_grow_with(handle, new_capacity, init)
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).try_grow_with_gap(handle, new_capacity, gap_at, gap_len)
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        (**self).try_shrink(handle, new_capacity)
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        (**self).cast(handle)
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        (**self).allocate(capacity)
+    }
+}
+
+impl<S: ConstantTimeStorage> ConstantTimeStorage for ManuallyDrop<S> {}
+
 //
 //  Implementations of Capacity.
 //
 
 impl Capacity for usize {
+    const MAX_USIZE: usize = usize::MAX;
+
     fn max() -> usize {
         usize::MAX
     }
@@ -210,6 +1104,8 @@ impl Capacity for usize {
 }
 
 impl Capacity for u8 {
+    const MAX_USIZE: usize = u8::MAX as usize;
+
     fn max() -> Self {
         u8::MAX
     }
@@ -224,6 +1120,8 @@ impl Capacity for u8 {
 }
 
 impl Capacity for u16 {
+    const MAX_USIZE: usize = u16::MAX as usize;
+
     fn max() -> Self {
         u16::MAX
     }
@@ -239,6 +1137,8 @@ impl Capacity for u16 {
 
 #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
 impl Capacity for u32 {
+    const MAX_USIZE: usize = u32::MAX as usize;
+
     fn max() -> Self {
         u32::MAX
     }
@@ -251,3 +1151,101 @@ impl Capacity for u32 {
         self as usize
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use core::cell::RefCell;
+
+    use alloc::{alloc::Global, rc::Rc};
+
+    use crate::allocator::{AllocStorage, CachingStorage};
+    use crate::fallback::FallbackElement;
+    use crate::inline::{NonTrackingElement, TrackingElement};
+    use crate::small::SingleElement;
+    use crate::wrapper::{Budget, BudgetStorage, StatsStorage};
+
+    use super::*;
+
+    type Inline = TrackingElement<u64, 4>;
+
+    const _: () = assert_address_stability::<AllocStorage<Global>>(AddressStability::PINNED);
+    const _: () = assert_address_stability::<CachingStorage<Global>>(AddressStability::PINNED);
+    const _: () = assert_address_stability::<Inline>(AddressStability::ALLOCATIONS);
+    const _: () = assert_address_stability::<StatsStorage<Inline>>(AddressStability::ALLOCATIONS);
+
+    //  Indirection shields the elements from moves.
+    const _: () = assert_address_stability::<&mut Inline>(AddressStability::PINNED);
+    const _: () = assert_address_stability::<Rc<RefCell<Inline>>>(AddressStability::PINNED);
+
+    #[test]
+    fn census() {
+        assert_eq!(
+            AddressStability::NONE,
+            NonTrackingElement::<u64>::ADDRESS_STABILITY
+        );
+        assert_eq!(
+            AddressStability::NONE,
+            SingleElement::<u64, Global>::ADDRESS_STABILITY
+        );
+        assert_eq!(
+            AddressStability::ALLOCATIONS,
+            FallbackElement::<Inline, AllocStorage<Global>>::ADDRESS_STABILITY
+        );
+    }
+
+    #[test]
+    fn satisfies() {
+        use AddressStability as A;
+
+        assert!(A::PINNED.satisfies(A::ALLOCATIONS));
+        assert!(A::ALLOCATIONS.satisfies(A::NONE));
+        assert!(!A::ALLOCATIONS.satisfies(A::PINNED));
+        assert!(!A::NONE.satisfies(A::ALLOCATIONS));
+
+        assert_eq!(A::ALLOCATIONS, A::PINNED.and(A::ALLOCATIONS));
+    }
+
+    #[test]
+    fn try_grow_with_gap() {
+        fn check<S: RangeStorage>(mut storage: S) {
+            let capacity = |capacity| S::Capacity::from_usize(capacity).unwrap();
+
+            let handle = storage.allocate::<u8>(capacity(4)).unwrap();
+
+            unsafe {
+                let range = storage.get(&handle).as_mut();
+
+                for (index, slot) in range.iter_mut().enumerate() {
+                    slot.write(index as u8);
+                }
+
+                let old_capacity = range.len();
+                let handle = storage
+                    .try_grow_with_gap(&handle, capacity(old_capacity + 3), 1, 3)
+                    .unwrap();
+
+                let range = storage.get(&handle).as_ref();
+
+                assert_eq!(0, range[0].assume_init());
+
+                for index in 1..old_capacity {
+                    assert_eq!(index as u8, range[index + 3].assume_init());
+                }
+
+                storage.deallocate(&handle);
+            }
+        }
+
+        check(AllocStorage::<Global>::default());
+
+        //  A second range is rejected, hence the range may only be grown in place.
+        check(BudgetStorage::new(
+            AllocStorage::<Global>::default(),
+            Budget {
+                max_live_elements: 1,
+                ..Budget::unlimited()
+            },
+        ));
+    }
+} // mod tests
@@ -1,7 +1,7 @@
 //! Various utilities.
 
 use core::{
-    alloc::{AllocError, Layout},
+    alloc::Layout,
     fmt::{self, Debug},
     marker::PhantomData,
     mem, ptr,
@@ -9,6 +9,8 @@ use core::{
 
 use rfc2580::{self, MetaData, Pointee};
 
+use crate::error::StorageError;
+
 #[cfg(test)]
 pub(crate) use test::*;
 
@@ -28,6 +30,9 @@ impl<T: ?Sized> Default for PhantomInvariant<T> {
 }
 
 /// Computes the layout for a value with metadata `meta`.
+///
+/// The layout is computed from `meta` alone, hence `T` must be either sized, a slice, a `str`, a trait object, or a
+/// custom DST whose last field is one of those. Extern types, whose layout is unknown, are not supported.
 pub fn layout_of<T: ?Sized + Pointee>(meta: T::MetaData) -> Layout {
     let pointer = meta.assemble(ptr::null_mut());
 
@@ -38,36 +43,42 @@ pub fn layout_of<T: ?Sized + Pointee>(meta: T::MetaData) -> Layout {
 
 /// Validates that the layout of `storage` is sufficient to accomodate an instance of `T`.
 ///
-/// Return `Ok` on success, and `Err` on failure.
-pub fn validate_layout<T: ?Sized + Pointee, Storage>(meta: T::MetaData) -> Result<(), AllocError> {
+/// Return `Ok` on success, and `Err`, without context, on failure.
+pub fn validate_layout<T: ?Sized + Pointee, Storage>(
+    meta: T::MetaData,
+) -> Result<(), StorageError> {
     validate_layout_for::<Storage>(layout_of::<T>(meta))
 }
 
 /// Validates that the layout of `storage` is sufficient to accomodate an instance of `T`.
 ///
-/// Return `Ok` on success, and `Err` on failure.
-pub fn validate_array_layout<T, Storage>(capacity: usize) -> Result<(), AllocError> {
-    validate_layout_for::<Storage>(Layout::array::<T>(capacity).map_err(|_| AllocError)?)
+/// Return `Ok` on success, and `Err`, without context, on failure.
+pub fn validate_array_layout<T, Storage>(capacity: usize) -> Result<(), StorageError> {
+    validate_layout_for::<Storage>(StorageError::array::<T>(capacity).layout())
 }
 
 /// Validates that the layout of `storage` is sufficient for `layout`.
 ///
-/// Return `Ok` on success, and `Err` on failure.
-pub fn validate_layout_for<Storage>(layout: Layout) -> Result<(), AllocError> {
+/// Return `Ok` on success, and `Err`, without context, on failure.
+pub fn validate_layout_for<Storage>(layout: Layout) -> Result<(), StorageError> {
     let validated_size = layout.size() <= mem::size_of::<Storage>();
     let validated_alignment = layout.align() <= mem::align_of::<Storage>();
 
     if validated_size && validated_alignment {
         Ok(())
     } else {
-        Err(AllocError)
+        Err(StorageError::new(layout))
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    use core::{cell::Cell, ptr::NonNull};
+    use core::{
+        cell::{Cell, UnsafeCell},
+        mem::MaybeUninit,
+        ptr::NonNull,
+    };
 
     use std::{
         alloc::{AllocError, Allocator, Global, Layout},
@@ -111,4 +122,110 @@ mod test {
             Global.deallocate(ptr, layout)
         }
     }
+
+    //  An ArenaAllocator serves allocations from a fixed internal arena, never touching the global allocator.
+    //
+    //  Allocations are carved at deterministic offsets within the arena; memory is only reclaimed when the most recent
+    //  allocation is deallocated, or when all allocations are.
+    //
+    //  Use by reference, as moving the arena invalidates all allocations.
+    pub(crate) struct ArenaAllocator<const N: usize> {
+        arena: UnsafeCell<Arena<N>>,
+        top: Cell<usize>,
+        allocated: Cell<usize>,
+        deallocated: Cell<usize>,
+        live_bytes: Cell<usize>,
+    }
+
+    impl<const N: usize> ArenaAllocator<N> {
+        pub(crate) fn new() -> Self {
+            Self {
+                arena: UnsafeCell::new(Arena([MaybeUninit::uninit(); N])),
+                top: Cell::new(0),
+                allocated: Cell::new(0),
+                deallocated: Cell::new(0),
+                live_bytes: Cell::new(0),
+            }
+        }
+
+        pub(crate) fn allocated(&self) -> usize {
+            self.allocated.get()
+        }
+
+        pub(crate) fn deallocated(&self) -> usize {
+            self.deallocated.get()
+        }
+
+        pub(crate) fn live_bytes(&self) -> usize {
+            self.live_bytes.get()
+        }
+
+        //  Returns the offset of `pointer` within the arena.
+        pub(crate) fn offset_of<T: ?Sized>(&self, pointer: NonNull<T>) -> usize {
+            let base = self.arena.get() as usize;
+            let pointer = pointer.cast::<u8>().as_ptr() as usize;
+
+            assert!(base <= pointer && pointer <= base + N);
+
+            pointer - base
+        }
+    }
+
+    unsafe impl<const N: usize> Allocator for ArenaAllocator<N> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            if layout.align() > ARENA_ALIGNMENT {
+                return Err(AllocError);
+            }
+
+            let start = (self.top.get() + layout.align() - 1) & !(layout.align() - 1);
+            let end = start.checked_add(layout.size()).ok_or(AllocError)?;
+
+            if end > N {
+                return Err(AllocError);
+            }
+
+            self.top.set(end);
+            self.allocated.set(self.allocated.get() + 1);
+            self.live_bytes.set(self.live_bytes.get() + layout.size());
+
+            //  Safety:
+            //  -   `start <= N`, hence the pointer is within the arena, or one past its end.
+            let pointer = unsafe { (self.arena.get() as *mut u8).add(start) };
+
+            //  Safety:
+            //  -   `pointer` is derived from a non-null pointer.
+            let pointer = unsafe { NonNull::new_unchecked(pointer) };
+
+            Ok(NonNull::slice_from_raw_parts(pointer, layout.size()))
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            let offset = self.offset_of(ptr);
+
+            if offset + layout.size() == self.top.get() {
+                self.top.set(offset);
+            }
+
+            self.deallocated.set(self.deallocated.get() + 1);
+            self.live_bytes.set(self.live_bytes.get() - layout.size());
+
+            if self.allocated.get() == self.deallocated.get() {
+                self.top.set(0);
+            }
+        }
+    }
+
+    //  A custom DST, whose layout is not that of its unsized tail.
+    //
+    //  Obtained by coercing a sized `Packet`, for example `Packet<[u16; 3]>` into `Packet<[u16]>`.
+    #[derive(Debug)]
+    pub(crate) struct Packet<P: ?Sized> {
+        pub(crate) tag: u8,
+        pub(crate) payload: P,
+    }
+
+    const ARENA_ALIGNMENT: usize = 16;
+
+    #[repr(align(16))]
+    struct Arena<const N: usize>([MaybeUninit<u8>; N]);
 } // mod test
@@ -0,0 +1,401 @@
+//! Storage over the linear memory of WebAssembly, acquiring pages with `memory.grow`.
+//!
+//! `PageStorage` sub-allocates ranges from the pages it acquired, and only grows the memory when those are exhausted,
+//! by just enough pages to satisfy the allocation at hand. This keeps memory growth predictable, and independent of
+//! the default allocator.
+//!
+//! As linear memory never shrinks, neither does the storage: memory is only reclaimed when the last range is
+//! deallocated, or shrunk, in a stack-like fashion.
+
+use core::{
+    alloc::{AllocError, Layout},
+    cmp,
+    fmt::{self, Debug},
+    mem::{self, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+use crate::{
+    error::{StorageError, StorageResultExt},
+    traits::RangeStorage,
+};
+
+/// The size of a page of linear memory, in bytes.
+pub const PAGE_SIZE: usize = 65536;
+
+/// A linear memory, growing page by page.
+pub trait LinearMemory {
+    /// Grows the memory by `pages` pages, returning a pointer to the first of the new pages.
+    fn grow(&mut self, pages: usize) -> Result<NonNull<u8>, AllocError>;
+}
+
+/// The linear memory of the current module, that is memory 0.
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WasmMemory;
+
+#[cfg(target_arch = "wasm32")]
+impl LinearMemory for WasmMemory {
+    fn grow(&mut self, pages: usize) -> Result<NonNull<u8>, AllocError> {
+        let previous = core::arch::wasm32::memory_grow::<0>(pages);
+
+        if previous == usize::MAX {
+            return Err(AllocError);
+        }
+
+        NonNull::new((previous * PAGE_SIZE) as *mut u8).ok_or(AllocError)
+    }
+}
+
+/// A `RangeStorage` sub-allocating ranges from the pages of a linear memory.
+pub struct PageStorage<M> {
+    top: usize,
+    end: usize,
+    memory: M,
+}
+
+/// The `PageStorage` of the current module.
+#[cfg(target_arch = "wasm32")]
+pub type WasmStorage = PageStorage<WasmMemory>;
+
+impl<M> PageStorage<M> {
+    /// Creates an instance, which has yet to acquire any page.
+    pub const fn new(memory: M) -> Self {
+        Self {
+            top: 0,
+            end: 0,
+            memory,
+        }
+    }
+
+    /// Returns the number of bytes acquired, yet not allocated.
+    pub fn available(&self) -> usize {
+        self.end - self.top
+    }
+}
+
+impl<M: LinearMemory> RangeStorage for PageStorage<M> {
+    type Handle<T> = NonNull<[MaybeUninit<T>]>;
+
+    type Capacity = usize;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        //  The size of an allocation cannot exceed `isize::MAX`.
+        match mem::size_of::<T>() {
+            0 => usize::MAX,
+            size => isize::MAX as usize / size,
+        }
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        let (address, size) = Self::extent_of(*handle);
+
+        if size > 0 && address + size == self.top {
+            self.top = address;
+        }
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        *handle
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        NonNull::slice_from_raw_parts(NonNull::dangling(), 0)
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        debug_assert!(handle.len() < new_capacity);
+
+        let (address, size) = Self::extent_of(*handle);
+        let new_layout = Self::layout_for::<T>(new_capacity)?;
+
+        //  The last range may be extended in place.
+        if size > 0 && address + size == self.top && address + new_layout.size() <= self.end {
+            self.top = address + new_layout.size();
+            return Ok(Self::into_handle(address, new_capacity));
+        }
+
+        let new_handle = RangeStorage::allocate::<T>(self, new_capacity)?;
+
+        //  Safety:
+        //  -   `new_handle` is freshly allocated, hence does not overlap `handle`.
+        //  -   `new_handle` is larger than `handle`.
+        ptr::copy_nonoverlapping(handle.as_mut_ptr(), new_handle.as_mut_ptr(), handle.len());
+
+        RangeStorage::deallocate(self, handle);
+
+        Ok(new_handle)
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        debug_assert!(handle.len() > new_capacity);
+
+        let (address, size) = Self::extent_of(*handle);
+
+        if new_capacity == 0 {
+            RangeStorage::deallocate(self, handle);
+            return Ok(self.dangling());
+        }
+
+        //  The tail of the last range is reclaimed, other ranges simply shrink in place.
+        if size > 0 && address + size == self.top {
+            self.top = address + mem::size_of::<T>() * new_capacity;
+        }
+
+        Ok(Self::into_handle(address, new_capacity))
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        let (address, size) = Self::extent_of(*handle);
+
+        if size == 0 {
+            return Ok(self.dangling());
+        }
+
+        //  The extent of the range must be preserved, so that it may still be reclaimed.
+        match mem::size_of::<U>() {
+            element
+                if element > 0 && address % mem::align_of::<U>() == 0 && size % element == 0 =>
+            {
+                Ok(Self::into_handle(address, size / element))
+            }
+            _ => Err(StorageError::new(Layout::new::<U>()).with_context(CONTEXT)),
+        }
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        let layout = Self::layout_for::<T>(capacity)?;
+
+        if layout.size() == 0 {
+            return Ok(NonNull::slice_from_raw_parts(NonNull::dangling(), capacity));
+        }
+
+        let address = self.carve(layout)?;
+
+        Ok(Self::into_handle(address, capacity))
+    }
+}
+
+impl<M: Debug> Debug for PageStorage<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "PageStorage{{ top: {:#x}, end: {:#x}, memory: {:?} }}",
+            self.top, self.end, self.memory
+        )
+    }
+}
+
+impl<M: Default> Default for PageStorage<M> {
+    fn default() -> Self {
+        Self::new(M::default())
+    }
+}
+
+//
+//  Implementation
+//
+
+const CONTEXT: &str = "PageStorage";
+
+impl<M: LinearMemory> PageStorage<M> {
+    //  Computes the layout of an array of `capacity` elements of `T`.
+    fn layout_for<T>(capacity: usize) -> Result<Layout, StorageError> {
+        Layout::array::<T>(capacity)
+            .map_err(|_| StorageError::array::<T>(capacity).with_context(CONTEXT))
+    }
+
+    //  Carves out a block of memory matching `layout`, growing the memory as necessary.
+    fn carve(&mut self, layout: Layout) -> Result<usize, StorageError> {
+        if let Some(address) = self.carve_acquired(layout) {
+            return Ok(address);
+        }
+
+        //  Sufficient even if the new pages are not contiguous with the acquired ones, in which case the remainder of
+        //  the acquired ones is abandoned.
+        let required = layout
+            .size()
+            .checked_add(layout.align() - 1)
+            .ok_or_else(|| StorageError::new(layout).with_context(CONTEXT))?;
+        let pages = required / PAGE_SIZE + cmp::min(required % PAGE_SIZE, 1);
+
+        let start = self.memory.grow(pages).context(CONTEXT, layout)?.as_ptr() as usize;
+
+        if start != self.end {
+            self.top = start;
+        }
+
+        self.end = start + pages * PAGE_SIZE;
+
+        Ok(self.carve_acquired(layout).expect("Sufficient pages"))
+    }
+
+    //  Carves out a block of memory matching `layout`, from the pages acquired so far, if possible.
+    fn carve_acquired(&mut self, layout: Layout) -> Option<usize> {
+        let address = self.top.checked_add(layout.align() - 1)? & !(layout.align() - 1);
+        let top = address.checked_add(layout.size())?;
+
+        if self.end == 0 || top > self.end {
+            return None;
+        }
+
+        self.top = top;
+        Some(address)
+    }
+}
+
+impl<M> PageStorage<M> {
+    fn extent_of<T>(handle: NonNull<[MaybeUninit<T>]>) -> (usize, usize) {
+        (
+            handle.as_mut_ptr() as usize,
+            mem::size_of::<T>() * handle.len(),
+        )
+    }
+
+    fn into_handle<T>(address: usize, capacity: usize) -> NonNull<[MaybeUninit<T>]> {
+        debug_assert!(address != 0);
+
+        //  Safety:
+        //  -   `address` is non-null, as it lies within a page of memory.
+        let pointer = unsafe { NonNull::new_unchecked(address as *mut MaybeUninit<T>) };
+
+        NonNull::slice_from_raw_parts(pointer, capacity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use alloc::alloc::{alloc, dealloc};
+
+    use crate::collections::RawVec;
+
+    use super::*;
+
+    //  A linear memory of a few pages, optionally leaving a gap between successive growths.
+    struct TestMemory {
+        base: NonNull<u8>,
+        used: usize,
+        gap: usize,
+        grown: usize,
+    }
+
+    impl TestMemory {
+        const PAGES: usize = 8;
+
+        fn new(gap: usize) -> Self {
+            let base = NonNull::new(unsafe { alloc(Self::layout()) }).unwrap();
+
+            Self {
+                base,
+                used: 0,
+                gap,
+                grown: 0,
+            }
+        }
+
+        fn layout() -> Layout {
+            Layout::from_size_align(Self::PAGES * PAGE_SIZE, PAGE_SIZE).unwrap()
+        }
+    }
+
+    impl LinearMemory for TestMemory {
+        fn grow(&mut self, pages: usize) -> Result<NonNull<u8>, AllocError> {
+            let start = if self.grown == 0 {
+                0
+            } else {
+                self.used + self.gap
+            };
+
+            if start + pages > Self::PAGES {
+                return Err(AllocError);
+            }
+
+            self.used = start + pages;
+            self.grown += 1;
+
+            let pointer = unsafe { self.base.as_ptr().add(start * PAGE_SIZE) };
+
+            Ok(NonNull::new(pointer).unwrap())
+        }
+    }
+
+    impl Drop for TestMemory {
+        fn drop(&mut self) {
+            unsafe { dealloc(self.base.as_ptr(), Self::layout()) };
+        }
+    }
+
+    #[test]
+    fn contiguous() {
+        let mut storage = PageStorage::new(TestMemory::new(0));
+
+        let first = storage.allocate::<u8>(PAGE_SIZE - 16).unwrap();
+
+        assert_eq!(1, storage.memory.grown);
+        assert_eq!(16, storage.available());
+
+        //  Straddles the first and second pages.
+        let second = storage.allocate::<u64>(4).unwrap();
+
+        assert_eq!(2, storage.memory.grown);
+        assert_eq!(PAGE_SIZE - 16, storage.available());
+        assert_eq!(
+            first.as_mut_ptr() as usize + PAGE_SIZE - 16,
+            second.as_mut_ptr() as usize
+        );
+
+        unsafe {
+            storage.deallocate(&second);
+            storage.deallocate(&first);
+        }
+
+        assert_eq!(2 * PAGE_SIZE, storage.available());
+    }
+
+    #[test]
+    fn non_contiguous() {
+        let mut storage = PageStorage::new(TestMemory::new(1));
+
+        let first = storage.allocate::<u8>(PAGE_SIZE - 16).unwrap();
+        let second = storage.allocate::<u64>(4).unwrap();
+
+        assert_eq!(2, storage.memory.grown);
+        assert_eq!(PAGE_SIZE - 32, storage.available());
+        assert_eq!(
+            first.as_mut_ptr() as usize + 2 * PAGE_SIZE,
+            second.as_mut_ptr() as usize
+        );
+
+        storage.allocate::<u8>(8 * PAGE_SIZE).unwrap_err();
+    }
+
+    #[test]
+    fn vec() {
+        let mut vec = RawVec::new(PageStorage::new(TestMemory::new(0)));
+
+        for i in 0..10_000u32 {
+            vec.push(i);
+        }
+
+        assert_eq!((0..10_000u32).sum::<u32>(), vec.iter().sum::<u32>());
+
+        //  Each growth happened in place, within the first page.
+        let (handle, _, storage) = vec.into_raw_parts();
+
+        assert_eq!(16_384, handle.len());
+        assert_eq!(1, storage.memory.grown);
+        assert_eq!(0, storage.available());
+    }
+} // mod tests
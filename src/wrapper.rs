@@ -0,0 +1,25 @@
+//! Simple implementations of wrapper storages, which decorate another storage.
+//!
+//! The wrappers implement the same storage traits as the storage they decorate, and can therefore be layered on top of
+//! any storage, including composites.
+
+mod budget;
+mod late;
+mod reentrancy;
+mod retry;
+mod stats;
+
+pub use budget::{Budget, BudgetStorage, Rejection};
+pub use late::LateStorage;
+pub use reentrancy::ReentrancyGuard;
+pub use retry::{ExponentialBackoff, Immediate, RetryPolicy, RetryStorage};
+pub use stats::{
+    Latencies, LatencyHistogram, LatencyTimer, Stats, StatsClock, StatsReporter, StatsStorage,
+    StatsTimer, TimedOperation,
+};
+
+#[cfg(feature = "std")]
+pub use stats::{BacktraceReporter, InstantClock};
+
+#[cfg(feature = "metrics")]
+pub use stats::MetricsReporter;
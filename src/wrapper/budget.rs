@@ -0,0 +1,625 @@
+//! Admission control wrapper, for both `ElementStorage` and `RangeStorage`.
+
+use core::{
+    alloc::Layout,
+    fmt::{self, Debug},
+    marker::Unsize,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use rfc2580::Pointee;
+
+use crate::{
+    error::StorageError,
+    traits::{
+        AddressStability, BoundedRangeStorage, Capacity, ElementStorage, IndexedStorage,
+        PinSafeStorage, RangeStorage,
+    },
+    utils,
+};
+
+/// Budget enforced by a `BudgetStorage`.
+///
+/// Each limit is inclusive, and the default budget is unlimited.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    /// Maximum size, in bytes, of a single element or range.
+    pub max_element_size: usize,
+    /// Maximum number of elements, or ranges, alive at any one time.
+    pub max_live_elements: usize,
+    /// Maximum number of bytes allocated at any one time.
+    pub max_live_bytes: usize,
+}
+
+impl Budget {
+    /// Creates an unlimited budget.
+    pub const fn unlimited() -> Self {
+        Self {
+            max_element_size: usize::MAX,
+            max_live_elements: usize::MAX,
+            max_live_bytes: usize::MAX,
+        }
+    }
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Self::unlimited()
+    }
+}
+
+/// Reason for which a `BudgetStorage` rejected an allocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejection {
+    /// The element, or range, exceeded `Budget::max_element_size`.
+    ElementSize,
+    /// The allocation would have exceeded `Budget::max_live_elements`.
+    LiveElements,
+    /// The allocation would have exceeded `Budget::max_live_bytes`.
+    LiveBytes,
+    /// The allocation was admitted, but the underlying storage failed to allocate.
+    Storage,
+}
+
+/// BudgetStorage enforces a budget on the usage of the underlying storage.
+///
+/// Allocations exceeding the budget are rejected, without reaching the underlying storage, so that a collection
+/// sharing an allocator with others cannot starve them. The reason of the latest rejection is available through
+/// `last_rejection`.
+///
+/// Empty ranges occupy no memory, and are therefore not counted as live elements.
+pub struct BudgetStorage<S> {
+    storage: S,
+    budget: Budget,
+    live_elements: usize,
+    live_bytes: usize,
+    last_rejection: Option<Rejection>,
+}
+
+impl<S> BudgetStorage<S> {
+    /// Creates an instance wrapping `storage`, enforcing `budget`.
+    pub fn new(storage: S, budget: Budget) -> Self {
+        Self {
+            storage,
+            budget,
+            live_elements: 0,
+            live_bytes: 0,
+            last_rejection: None,
+        }
+    }
+
+    /// Returns the budget.
+    pub fn budget(&self) -> &Budget {
+        &self.budget
+    }
+
+    /// Returns the number of elements, or non-empty ranges, currently alive.
+    pub fn live_elements(&self) -> usize {
+        self.live_elements
+    }
+
+    /// Returns the number of bytes currently allocated.
+    pub fn live_bytes(&self) -> usize {
+        self.live_bytes
+    }
+
+    /// Returns the reason of the latest rejection, if any.
+    pub fn last_rejection(&self) -> Option<Rejection> {
+        self.last_rejection
+    }
+
+    /// Returns the underlying storage.
+    pub fn inner(&self) -> &S {
+        &self.storage
+    }
+}
+
+impl<S: ElementStorage> ElementStorage for BudgetStorage<S> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    const ADDRESS_STABILITY: AddressStability = S::ADDRESS_STABILITY;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let layout = Layout::for_value_raw(self.storage.get(handle).as_ptr());
+
+        self.storage.deallocate(handle);
+
+        self.on_release(1, layout.size());
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.storage.get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.storage.coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.storage.uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let old_size = Layout::for_value_raw(self.storage.get(handle).as_ptr()).size();
+        let new_size = Layout::new::<U>().size();
+
+        self.admit(Layout::new::<U>(), 0, new_size.saturating_sub(old_size))?;
+
+        let result = self.storage.recycle(handle);
+
+        if result.is_ok() {
+            self.on_release(0, old_size);
+        }
+
+        self.on_result(&result, 0, new_size);
+
+        result
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.storage.slot_layout::<T>()
+    }
+
+    fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
+        let size = Layout::new::<T>().size();
+
+        if self.admit(Layout::new::<T>(), 1, size).is_err() {
+            return Err(value);
+        }
+
+        let result = self.storage.create(value);
+
+        self.on_result(&result, 1, size);
+
+        result
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let layout = utils::layout_of::<T>(meta);
+
+        self.admit(layout, 1, layout.size())?;
+
+        let result = self.storage.allocate(meta);
+
+        self.on_result(&result, 1, layout.size());
+
+        result
+    }
+}
+
+impl<S: IndexedStorage> IndexedStorage for BudgetStorage<S> {
+    const SLOT_COUNT: usize = S::SLOT_COUNT;
+
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize {
+        self.storage.index(handle)
+    }
+}
+
+//  Safety:
+//  -   Elements are those of the underlying storage, which never moves them.
+unsafe impl<S: PinSafeStorage> PinSafeStorage for BudgetStorage<S> {}
+
+impl<T, S: BoundedRangeStorage<T>> BoundedRangeStorage<T> for BudgetStorage<S> {
+    const MAX_CAPACITY: usize = S::MAX_CAPACITY;
+}
+
+impl<S: RangeStorage> RangeStorage for BudgetStorage<S> {
+    type Handle<T> = S::Handle<T>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        self.storage.maximum_capacity::<T>()
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        let size = Self::counted_size_of(&self.storage, handle);
+
+        self.storage.deallocate(handle);
+
+        if size > 0 {
+            self.on_release(1, size);
+        }
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        self.storage.get(handle)
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        self.storage.dangling()
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let old_size = Self::counted_size_of(&self.storage, handle);
+        self.admit_range::<T>(new_capacity, old_size)?;
+
+        let result = self.storage.try_grow(handle, new_capacity);
+
+        match &result {
+            Ok(new_handle) => {
+                //  The underlying storage may have allocated more than requested.
+                let new_size = Self::counted_size_of(&self.storage, new_handle);
+                self.on_resize(old_size, new_size);
+            }
+            Err(_) => self.reject(Rejection::Storage),
+        }
+
+        result
+    }
+
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        let old_size = Self::counted_size_of(&self.storage, handle);
+        self.admit_range::<T>(new_capacity, old_size)?;
+
+        let result = self.storage.try_grow_with(handle, new_capacity, init);
+
+        match &result {
+            Ok(new_handle) => {
+                let new_size = Self::counted_size_of(&self.storage, new_handle);
+                self.on_resize(old_size, new_size);
+            }
+            Err(_) => self.reject(Rejection::Storage),
+        }
+
+        result
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let old_size = Self::counted_size_of(&self.storage, handle);
+        self.admit_range::<T>(new_capacity, old_size)?;
+
+        let result = self
+            .storage
+            .try_grow_with_gap(handle, new_capacity, gap_at, gap_len);
+
+        match &result {
+            Ok(new_handle) => {
+                let new_size = Self::counted_size_of(&self.storage, new_handle);
+                self.on_resize(old_size, new_size);
+            }
+            Err(_) => self.reject(Rejection::Storage),
+        }
+
+        result
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let old_size = Self::counted_size_of(&self.storage, handle);
+
+        let result = self.storage.try_shrink(handle, new_capacity);
+
+        if let Ok(new_handle) = &result {
+            let new_size = Self::counted_size_of(&self.storage, new_handle);
+            self.on_resize(old_size, new_size);
+        }
+
+        result
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        let old_size = Self::counted_size_of(&self.storage, handle);
+
+        let result = self.storage.cast(handle);
+
+        if let Ok(new_handle) = &result {
+            let new_size = Self::counted_size_of(&self.storage, new_handle);
+            self.on_resize(old_size, new_size);
+        }
+
+        result
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        self.admit_range::<T>(capacity, 0)?;
+
+        let result = self.storage.allocate(capacity);
+
+        match &result {
+            Ok(handle) => {
+                //  Safety:
+                //  -   `handle` is valid, fresh off the press.
+                let size = unsafe { Self::counted_size_of(&self.storage, handle) };
+                self.on_resize(0, size);
+            }
+            Err(_) => self.reject(Rejection::Storage),
+        }
+
+        result
+    }
+}
+
+impl<S: Default> Default for BudgetStorage<S> {
+    fn default() -> Self {
+        Self::new(S::default(), Budget::default())
+    }
+}
+
+impl<S> Debug for BudgetStorage<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "BudgetStorage{{ live_elements: {}, live_bytes: {}, budget: {:?} }}",
+            self.live_elements, self.live_bytes, self.budget
+        )
+    }
+}
+
+//
+//  Implementation
+//
+
+const CONTEXT: &str = "BudgetStorage";
+
+impl<S> BudgetStorage<S> {
+    //  Checks whether an element of `layout` may be admitted, given the additional elements and bytes it requires.
+    fn admit(&mut self, layout: Layout, elements: usize, bytes: usize) -> Result<(), StorageError> {
+        let rejection = if layout.size() > self.budget.max_element_size {
+            Some(Rejection::ElementSize)
+        } else if self.live_elements + elements > self.budget.max_live_elements {
+            Some(Rejection::LiveElements)
+        } else if self.live_bytes.saturating_add(bytes) > self.budget.max_live_bytes {
+            Some(Rejection::LiveBytes)
+        } else {
+            None
+        };
+
+        match rejection {
+            Some(rejection) => {
+                self.reject(rejection);
+                Err(StorageError::new(layout).with_context(CONTEXT))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn reject(&mut self, rejection: Rejection) {
+        self.last_rejection = Some(rejection);
+    }
+
+    fn on_result<H, E>(&mut self, result: &Result<H, E>, elements: usize, bytes: usize) {
+        match result {
+            Ok(_) => {
+                self.live_elements += elements;
+                self.live_bytes += bytes;
+            }
+            Err(_) => self.reject(Rejection::Storage),
+        }
+    }
+
+    fn on_release(&mut self, elements: usize, bytes: usize) {
+        self.live_elements -= elements;
+        self.live_bytes -= bytes;
+    }
+
+    fn on_resize(&mut self, old_size: usize, new_size: usize) {
+        match (old_size, new_size) {
+            (0, 0) => (),
+            (0, _) => {
+                self.live_elements += 1;
+                self.live_bytes += new_size;
+            }
+            (_, 0) => self.on_release(1, old_size),
+            _ => {
+                self.live_bytes -= old_size;
+                self.live_bytes += new_size;
+            }
+        }
+    }
+}
+
+impl<S: RangeStorage> BudgetStorage<S> {
+    //  Checks whether a range of `capacity` elements may replace a range of `old_size` bytes.
+    fn admit_range<T>(
+        &mut self,
+        capacity: S::Capacity,
+        old_size: usize,
+    ) -> Result<(), StorageError> {
+        let layout = match Layout::array::<T>(capacity.into_usize()) {
+            Ok(layout) => layout,
+            Err(_) => {
+                self.reject(Rejection::ElementSize);
+                return Err(StorageError::array::<T>(capacity.into_usize()).with_context(CONTEXT));
+            }
+        };
+
+        let size = layout.size();
+        let elements = if old_size == 0 && size > 0 { 1 } else { 0 };
+
+        self.admit(layout, elements, size.saturating_sub(old_size))
+    }
+
+    //  Returns the size of the range of `handle`, as counted.
+    //
+    //  A range no larger than the dangling range of the underlying storage requires no allocation, and is counted as
+    //  empty, as the dangling ranges of inline storages are not empty, yet are never admitted.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn counted_size_of<T>(storage: &S, handle: &S::Handle<T>) -> usize {
+        let size = Layout::for_value_raw(storage.get(handle).as_ptr()).size();
+
+        let dangling = storage.dangling::<T>();
+        let dangling = Layout::for_value_raw(storage.get(&dangling).as_ptr()).size();
+
+        if size <= dangling {
+            0
+        } else {
+            size
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::RawVec;
+    use crate::inline::{NonTrackingRange, TrackingElement};
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    fn budget(max_element_size: usize, max_live_elements: usize, max_live_bytes: usize) -> Budget {
+        Budget {
+            max_element_size,
+            max_live_elements,
+            max_live_bytes,
+        }
+    }
+
+    #[test]
+    fn element_size() {
+        let mut storage = BudgetStorage::new(TrackingElement::<u64, 4>::new(), budget(4, 4, 64));
+
+        storage.create(1u64).unwrap_err();
+
+        assert_eq!(Some(Rejection::ElementSize), storage.last_rejection());
+
+        storage.create(1u32).unwrap();
+
+        assert_eq!(1, storage.live_elements());
+        assert_eq!(4, storage.live_bytes());
+    }
+
+    #[test]
+    fn live_elements() {
+        let mut storage = BudgetStorage::new(TrackingElement::<u32, 4>::new(), budget(4, 2, 64));
+
+        let h1 = storage.create(1u32).unwrap();
+        storage.create(2u32).unwrap();
+        storage.create(3u32).unwrap_err();
+
+        assert_eq!(Some(Rejection::LiveElements), storage.last_rejection());
+
+        unsafe { storage.destroy(&h1) };
+
+        storage.create(3u32).unwrap();
+
+        assert_eq!(2, storage.live_elements());
+    }
+
+    #[test]
+    fn live_bytes() {
+        let mut storage = BudgetStorage::new(TrackingElement::<u32, 4>::new(), budget(4, 4, 6));
+
+        storage.create(1u32).unwrap();
+        storage.create(2u32).unwrap_err();
+
+        assert_eq!(Some(Rejection::LiveBytes), storage.last_rejection());
+
+        storage.create(2u16).unwrap();
+
+        assert_eq!(6, storage.live_bytes());
+    }
+
+    #[test]
+    fn storage_failure() {
+        let mut storage = BudgetStorage::new(AllocStorage::new(NonAllocator), Budget::unlimited());
+
+        storage.create(1u32).unwrap_err();
+
+        assert_eq!(Some(Rejection::Storage), storage.last_rejection());
+        assert_eq!(0, storage.live_elements());
+    }
+
+    #[test]
+    fn range_grow() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage =
+            BudgetStorage::new(AllocStorage::new(allocator.clone()), budget(64, 1, 16));
+
+        let handle = storage.dangling::<u32>();
+        let handle = unsafe { storage.try_grow(&handle, 2) }.unwrap();
+
+        assert_eq!(1, storage.live_elements());
+        assert_eq!(8, storage.live_bytes());
+
+        RangeStorage::allocate::<u32>(&mut storage, 1).unwrap_err();
+
+        assert_eq!(Some(Rejection::LiveElements), storage.last_rejection());
+
+        let handle = unsafe { storage.try_grow(&handle, 4) }.unwrap();
+
+        assert_eq!(16, storage.live_bytes());
+
+        unsafe { storage.try_grow(&handle, 5) }.unwrap_err();
+
+        assert_eq!(Some(Rejection::LiveBytes), storage.last_rejection());
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        assert_eq!(0, storage.live_elements());
+        assert_eq!(0, storage.live_bytes());
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn range_dangling_inline() {
+        type Inline = NonTrackingRange<usize, u32, 4>;
+
+        //  The dangling range of an inline storage is not empty, yet is never admitted.
+        drop(RawVec::<u32, _>::new(BudgetStorage::new(
+            Inline::default(),
+            budget(16, 1, 16),
+        )));
+
+        let mut vec =
+            RawVec::<u32, _>::new(BudgetStorage::new(Inline::default(), budget(16, 1, 16)));
+
+        vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+
+        let mut storage = vec.into_storage();
+
+        let handle = RangeStorage::allocate::<u32>(&mut storage, 4).unwrap();
+        let handle = unsafe { storage.cast::<u32, i32>(&handle) }.unwrap();
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        assert_eq!((0, 0), (storage.live_elements(), storage.live_bytes()));
+    }
+} // mod tests
@@ -0,0 +1,362 @@
+//! Late-initialized wrapper, for both `ElementStorage` and `RangeStorage`.
+
+use core::{
+    alloc::Layout,
+    fmt::{self, Debug},
+    marker::Unsize,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use rfc2580::Pointee;
+
+use crate::{
+    error::StorageError,
+    traits::{AddressStability, Capacity, ConstantTimeStorage, ElementStorage, RangeStorage},
+    utils,
+};
+
+/// LateStorage wraps a storage which is initialized after the wrapper itself.
+///
+/// This is typically meant for static singletons, as `new` is `const`, or for regions only provided at run-time, for
+/// example through FFI, whilst the wrapper is embedded in a larger struct constructed beforehand.
+///
+/// Until initialized, allocations fail, and the maximum capacity is 0. Dangling ranges are nonetheless available, of
+/// capacity 0, so that collections may be created beforehand, and grow once initialized. Since only an initialized
+/// storage issues any other handle, all other methods assume initialization.
+pub struct LateStorage<S>(Option<S>);
+
+impl<S> LateStorage<S> {
+    /// Creates an uninitialized instance.
+    pub const fn new() -> Self {
+        Self(None)
+    }
+
+    /// Returns whether `self` is initialized, or not.
+    pub fn is_initialized(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Initializes `self` with `storage`.
+    ///
+    /// If `self` is already initialized, returns `storage`, leaving `self` unchanged.
+    pub fn init(&mut self, storage: S) -> Result<(), S> {
+        if self.is_initialized() {
+            return Err(storage);
+        }
+
+        self.0 = Some(storage);
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying storage, if initialized.
+    pub fn storage(&self) -> Option<&S> {
+        self.0.as_ref()
+    }
+
+    /// Returns the underlying storage, if initialized.
+    pub fn into_inner(self) -> Option<S> {
+        self.0
+    }
+}
+
+impl<S: ElementStorage> ElementStorage for LateStorage<S> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    const ADDRESS_STABILITY: AddressStability = S::ADDRESS_STABILITY;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        self.initialized_mut().deallocate(handle)
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.initialized().get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.initialized().coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.initialized().uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        self.initialized_mut().recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.0.as_ref()?.slot_layout::<T>()
+    }
+
+    fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
+        match &mut self.0 {
+            Some(storage) => storage.create(value),
+            None => Err(value),
+        }
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        match &mut self.0 {
+            Some(storage) => storage.allocate(meta),
+            None => Err(StorageError::new(utils::layout_of::<T>(meta)).with_context(CONTEXT)),
+        }
+    }
+}
+
+/// The handles are those of the underlying storage, or `None` for the dangling ranges issued prior to initialization.
+impl<S: RangeStorage> RangeStorage for LateStorage<S> {
+    type Handle<T> = Option<S::Handle<T>>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        match &self.0 {
+            Some(storage) => storage.maximum_capacity::<T>(),
+            None => S::Capacity::from_usize(0).expect("0 <= S::Capacity::max()"),
+        }
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        if let Some(handle) = handle {
+            self.initialized_mut().deallocate(handle)
+        }
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        match handle {
+            Some(handle) => self.initialized().get(handle),
+            None => NonNull::slice_from_raw_parts(NonNull::dangling(), 0),
+        }
+    }
+
+    //  Prior to initialization, the dangling range is empty, and not issued by the underlying storage.
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        self.0.as_ref().map(|storage| storage.dangling())
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        match handle {
+            Some(handle) => self
+                .initialized_mut()
+                .try_grow(handle, new_capacity)
+                .map(Some),
+            None => self.allocate(new_capacity),
+        }
+    }
+
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        match handle {
+            Some(handle) => self
+                .initialized_mut()
+                .try_grow_with(handle, new_capacity, init)
+                .map(Some),
+            None => {
+                let new = self.allocate(new_capacity)?;
+
+                if let Err(error) = init(self.get(&new)) {
+                    self.deallocate(&new);
+                    return Err(error);
+                }
+
+                Ok(new)
+            }
+        }
+    }
+
+    //  Prior to initialization, the range is empty, hence there is nothing to shift.
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        match handle {
+            Some(handle) => self
+                .initialized_mut()
+                .try_grow_with_gap(handle, new_capacity, gap_at, gap_len)
+                .map(Some),
+            None => self.allocate(new_capacity),
+        }
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        match handle {
+            Some(handle) => self
+                .initialized_mut()
+                .try_shrink(handle, new_capacity)
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        match handle {
+            Some(handle) => self.initialized_mut().cast(handle).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        match &mut self.0 {
+            Some(storage) => storage.allocate(capacity).map(Some),
+            None => Err(StorageError::array::<T>(capacity.into_usize()).with_context(CONTEXT)),
+        }
+    }
+}
+
+impl<S: ConstantTimeStorage> ConstantTimeStorage for LateStorage<S> {}
+
+impl<S> Debug for LateStorage<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "LateStorage{{ initialized: {} }}", self.is_initialized())
+    }
+}
+
+impl<S> Default for LateStorage<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//
+//  Implementation
+//
+
+const CONTEXT: &str = "LateStorage";
+
+impl<S> LateStorage<S> {
+    fn initialized(&self) -> &S {
+        self.0.as_ref().expect("Initialized storage")
+    }
+
+    fn initialized_mut(&mut self) -> &mut S {
+        self.0.as_mut().expect("Initialized storage")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::{cell::RefCell, mem::ManuallyDrop};
+
+    use alloc::rc::Rc;
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::{RawBox, RawVec};
+    use crate::inline::{NonTrackingRange, TrackingElement};
+    use crate::utils::SpyAllocator;
+
+    use super::*;
+
+    #[test]
+    fn element() {
+        let mut storage = LateStorage::<TrackingElement<u32, 1>>::new();
+
+        storage.create(1u32).unwrap_err();
+
+        assert_eq!(
+            "LateStorage{ initialized: false }",
+            format!("{:?}", storage)
+        );
+
+        assert!(storage.init(TrackingElement::new()).is_ok());
+        assert!(storage.init(TrackingElement::new()).is_err());
+
+        let boxed = RawBox::new(2u32, storage).unwrap_or_else(|_| panic!("Sufficient capacity"));
+
+        assert_eq!(2, *boxed);
+    }
+
+    #[test]
+    fn range() {
+        let mut storage = LateStorage::<NonTrackingRange<u8, u8, 4>>::default();
+
+        assert_eq!(0, storage.maximum_capacity::<u8>());
+        storage.allocate::<u8>(0).unwrap_err();
+
+        assert!(storage.init(NonTrackingRange::default()).is_ok());
+
+        let mut vec = RawVec::new(storage);
+        vec.try_push(1u8).unwrap();
+
+        assert_eq!([1], *vec);
+    }
+
+    #[test]
+    fn dangling_uninitialized() {
+        let allocator = SpyAllocator::default();
+        let storage = Rc::new(RefCell::new(LateStorage::new()));
+
+        //  The collection is created before the storage is initialized, as it would be within a larger struct.
+        let mut vec = RawVec::<u32, _>::new(storage.clone());
+
+        assert_eq!(0, vec.capacity());
+        vec.try_push(1).unwrap_err();
+
+        assert!(storage
+            .borrow_mut()
+            .init(AllocStorage::new(allocator.clone()))
+            .is_ok());
+
+        vec.try_push(1).unwrap();
+        vec.try_push(2).unwrap();
+
+        assert_eq!([1, 2], *vec);
+
+        drop(vec);
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+    }
+
+    #[test]
+    fn manually_drop() {
+        let allocator = SpyAllocator::default();
+
+        let mut storage = ManuallyDrop::new(AllocStorage::new(allocator.clone()));
+
+        let handle = storage.create(1u32).unwrap();
+
+        assert_eq!(1, unsafe {
+            *<_ as ElementStorage>::get(&*storage, &handle).as_ref()
+        });
+
+        unsafe { storage.destroy(&handle) };
+
+        assert_eq!(allocator.allocated(), allocator.deallocated());
+
+        unsafe { ManuallyDrop::drop(&mut storage) };
+    }
+} // mod tests
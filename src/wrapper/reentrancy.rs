@@ -0,0 +1,393 @@
+//! Reentrancy detecting wrapper, for storages used from within user callbacks.
+
+use core::{
+    alloc::Layout,
+    cell::{Cell, RefCell},
+    fmt::{self, Debug},
+    marker::Unsize,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ptr::{self, NonNull},
+};
+
+use rfc2580::Pointee;
+
+use crate::{
+    error::StorageError,
+    traits::{AddressStability, ElementStorage},
+    utils,
+};
+
+/// ReentrancyGuard detects when the underlying storage is reentered, that is used from within a user callback invoked
+/// by one of its own methods, such as a `StatsReporter`, or a hook.
+///
+/// The guard is shared by reference: `&ReentrancyGuard` implements `ElementStorage`, hence several collections, and
+/// the elements they contain, may use the same guard.
+///
+/// Values are dropped before the underlying storage is entered, hence the destructor of an element may itself use the
+/// storage, for example to destroy the elements it owns. Other reentrant calls are bugs, and are handled as follows:
+///
+/// -   Deallocations are deferred, up to `D` of them, and performed once the outermost call completes.
+/// -   Allocations panic in debug builds, and fail in release builds.
+/// -   Any other call panics, as it cannot fail.
+///
+/// Only the handles no larger than 4 `usize`s can be deferred, which covers all the handles of this crate.
+pub struct ReentrancyGuard<S: ElementStorage, const D: usize = 4> {
+    storage: RefCell<S>,
+    deferred: Cell<[Option<Deferred<S>>; D]>,
+    reentries: Cell<usize>,
+}
+
+impl<S: ElementStorage, const D: usize> ReentrancyGuard<S, D> {
+    /// Creates an instance wrapping `storage`.
+    pub fn new(storage: S) -> Self {
+        Self {
+            storage: RefCell::new(storage),
+            deferred: Cell::new([None; D]),
+            reentries: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of reentrant calls detected so far, including those which were deferred.
+    pub fn reentries(&self) -> usize {
+        self.reentries.get()
+    }
+
+    /// Returns the underlying storage.
+    pub fn into_inner(self) -> S {
+        self.storage.into_inner()
+    }
+}
+
+impl<S: ElementStorage, const D: usize> Debug for ReentrancyGuard<S, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(
+            f,
+            "ReentrancyGuard{{ reentries: {} }}",
+            self.reentries.get()
+        )
+    }
+}
+
+impl<S: ElementStorage, const D: usize> ElementStorage for &ReentrancyGuard<S, D> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    //  The storage is never moved whilst borrowed.
+    const ADDRESS_STABILITY: AddressStability = AddressStability {
+        across_moves: true,
+        ..S::ADDRESS_STABILITY
+    };
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        let guard = *self;
+
+        match guard.storage.try_borrow_mut() {
+            Ok(mut storage) => storage.deallocate(handle),
+            Err(_) => return guard.defer(handle),
+        }
+
+        guard.drain();
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.borrow().get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.borrow().coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.borrow().uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        let guard = *self;
+
+        let result = guard.borrow_mut(Layout::new::<U>())?.recycle(handle);
+
+        guard.drain();
+
+        result
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.borrow().slot_layout::<T>()
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let guard = *self;
+
+        let result = guard
+            .borrow_mut(utils::layout_of::<T>(meta))?
+            .allocate(meta);
+
+        guard.drain();
+
+        result
+    }
+}
+
+//
+//  Implementation
+//
+
+//  The maximum size, in `usize`s, of a deferred handle.
+const HANDLE_WORDS: usize = 4;
+
+//  A deferred deallocation, erasing the type of the handle.
+struct Deferred<S> {
+    handle: MaybeUninit<[usize; HANDLE_WORDS]>,
+    deallocate: unsafe fn(&mut S, *const u8),
+}
+
+impl<S> Clone for Deferred<S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for Deferred<S> {}
+
+impl<S: ElementStorage, const D: usize> ReentrancyGuard<S, D> {
+    //  Borrows the storage, panicking if reentered.
+    fn borrow(&self) -> core::cell::Ref<'_, S> {
+        match self.storage.try_borrow() {
+            Ok(storage) => storage,
+            Err(_) => self.reentered("a read"),
+        }
+    }
+
+    //  Borrows the storage mutably, for an allocation of `layout`, panicking if reentered in debug builds, and failing
+    //  otherwise.
+    fn borrow_mut(&self, layout: Layout) -> Result<core::cell::RefMut<'_, S>, StorageError> {
+        match self.storage.try_borrow_mut() {
+            Ok(storage) => Ok(storage),
+            Err(_) if cfg!(debug_assertions) => self.reentered("an allocation"),
+            Err(_) => {
+                self.reentries.set(self.reentries.get() + 1);
+                Err(StorageError::new(layout).with_context("ReentrancyGuard"))
+            }
+        }
+    }
+
+    fn reentered(&self, operation: &str) -> ! {
+        self.reentries.set(self.reentries.get() + 1);
+
+        panic!("ReentrancyGuard: storage reentered by {}", operation)
+    }
+
+    //  Defers the deallocation of `handle`, panicking if it cannot be deferred.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid, as per `deallocate`.
+    unsafe fn defer<T: ?Sized + Pointee>(&self, handle: &S::Handle<T>) {
+        let fits = mem::size_of::<S::Handle<T>>() <= mem::size_of::<[usize; HANDLE_WORDS]>()
+            && mem::align_of::<S::Handle<T>>() <= mem::align_of::<usize>();
+
+        let mut deferred = self.deferred.get();
+
+        let slot = match deferred.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) if fits => slot,
+            _ => self.reentered("a deallocation, which could not be deferred"),
+        };
+
+        let mut erased = MaybeUninit::<[usize; HANDLE_WORDS]>::uninit();
+
+        //  Safety:
+        //  -   `erased` is large enough, and sufficiently aligned, as checked above.
+        //  -   The copy is never dropped, as it is read back into a `ManuallyDrop`.
+        ptr::copy_nonoverlapping(handle, erased.as_mut_ptr() as *mut S::Handle<T>, 1);
+
+        *slot = Some(Deferred {
+            handle: erased,
+            deallocate: deallocate_erased::<S, T>,
+        });
+
+        self.deferred.set(deferred);
+        self.reentries.set(self.reentries.get() + 1);
+    }
+
+    //  Performs the deferred deallocations, including those deferred meanwhile.
+    fn drain(&self) {
+        loop {
+            let mut deferred = self.deferred.get();
+
+            let next = match deferred.iter_mut().find_map(|slot| slot.take()) {
+                Some(next) => next,
+                None => return,
+            };
+
+            self.deferred.set(deferred);
+
+            let mut storage = self.storage.borrow_mut();
+
+            //  Safety:
+            //  -   The handle was valid when deferred, and its deallocation was merely postponed.
+            unsafe { (next.deallocate)(&mut storage, next.handle.as_ptr() as *const u8) };
+        }
+    }
+}
+
+//  #   Safety
+//
+//  -   Assumes that `handle` points to a valid `S::Handle<T>`.
+unsafe fn deallocate_erased<S: ElementStorage, T: ?Sized + Pointee>(
+    storage: &mut S,
+    handle: *const u8,
+) {
+    let handle = ManuallyDrop::new(ptr::read(handle as *const S::Handle<T>));
+
+    storage.deallocate(&*handle);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use alloc::rc::Rc;
+
+    use crate::collections::{RawLinkedList, RawLinkedListNodeStorage};
+    use crate::inline::{TrackingElement, TrackingElementHandle};
+    use crate::wrapper::{Stats, StatsReporter, StatsStorage};
+
+    use super::*;
+
+    #[test]
+    fn reentrant_drop() {
+        type Inline = TrackingElement<RawLinkedListNodeStorage<[usize; 2], usize>, 4>;
+
+        //  An element owning another element of the same storage, destroyed by its destructor.
+        struct Owner<'a> {
+            guard: &'a ReentrancyGuard<Inline>,
+            owned: TrackingElementHandle<u64>,
+        }
+
+        impl<'a> Drop for Owner<'a> {
+            fn drop(&mut self) {
+                unsafe { self.guard.destroy(&self.owned) };
+            }
+        }
+
+        let guard = ReentrancyGuard::<Inline>::new(Inline::new());
+
+        {
+            let mut list = RawLinkedList::new(&guard);
+
+            for i in 0..2 {
+                let owned = (&guard).create(i).unwrap();
+                list.push(Owner {
+                    guard: &guard,
+                    owned,
+                });
+            }
+        }
+
+        assert_eq!(0, guard.reentries());
+        assert_eq!(
+            "TrackingElement{ next: 0 -> 1 -> 2 -> 3 -> null }",
+            format!("{:?}", guard.into_inner())
+        );
+    }
+
+    //  A reporter deallocating, or allocating, from within the notification of an allocation.
+    #[derive(Clone, Default)]
+    struct Reentrant<'a> {
+        guard: Rc<Cell<Option<&'a Guard<'a>>>>,
+        action: Rc<Cell<Option<Action>>>,
+    }
+
+    enum Action {
+        Allocate,
+        Deallocate(TrackingElementHandle<u32>),
+    }
+
+    type Guard<'a> = ReentrancyGuard<StatsStorage<TrackingElement<u32, 4>, Reentrant<'a>>>;
+
+    impl<'a> StatsReporter for Reentrant<'a> {
+        fn report(&mut self, _stats: &Stats) {}
+
+        fn on_allocate(&mut self, _address: NonNull<u8>, _layout: Layout) {
+            let mut guard = match self.guard.get() {
+                Some(guard) => guard,
+                None => return,
+            };
+
+            match self.action.take() {
+                None => (),
+                Some(Action::Allocate) => {
+                    let _ = guard.create(0u32);
+                }
+                Some(Action::Deallocate(handle)) => unsafe { guard.deallocate(&handle) },
+            }
+        }
+    }
+
+    //  Creates a guard, whose reporter is connected to it by the caller, once the guard is pinned on its stack.
+    fn guard<'a>(reporter: &Reentrant<'a>) -> Guard<'a> {
+        ReentrancyGuard::new(StatsStorage::with_reporter(
+            TrackingElement::new(),
+            reporter.clone(),
+        ))
+    }
+
+    #[test]
+    fn reentrant_deallocate() {
+        let reporter = Reentrant::default();
+        let guard = guard(&reporter);
+
+        reporter.guard.set(Some(&guard));
+
+        let mut storage = &guard;
+        let first = storage.create(1u32).unwrap();
+
+        //  The deallocation of `first` is deferred until the allocation of `second` completes.
+        reporter.action.set(Some(Action::Deallocate(first)));
+
+        let second = storage.create(2u32).unwrap();
+
+        assert_eq!(1, guard.reentries());
+        assert_eq!(1, guard.storage.borrow().stats().deallocations);
+        assert_eq!(2, unsafe { *storage.get(&second).as_ptr() });
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "storage reentered by an allocation")]
+    fn reentrant_allocate() {
+        let reporter = Reentrant::default();
+        let guard = guard(&reporter);
+
+        reporter.guard.set(Some(&guard));
+        reporter.action.set(Some(Action::Allocate));
+
+        let _ = (&guard).create(1u32);
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[test]
+    fn reentrant_allocate() {
+        let reporter = Reentrant::default();
+        let guard = guard(&reporter);
+
+        reporter.guard.set(Some(&guard));
+        reporter.action.set(Some(Action::Allocate));
+
+        //  The reentrant allocation fails, whilst the outer one succeeds.
+        (&guard).create(1u32).unwrap();
+
+        assert_eq!(1, guard.reentries());
+        assert_eq!(1, guard.storage.borrow().stats().allocations);
+    }
+} // mod tests
@@ -0,0 +1,427 @@
+//! Retrying wrapper, for both `ElementStorage` and `RangeStorage`.
+
+use core::{
+    alloc::Layout,
+    fmt::{self, Debug},
+    hint,
+    marker::Unsize,
+    mem::MaybeUninit,
+    ptr::NonNull,
+};
+
+use rfc2580::Pointee;
+
+use crate::{
+    error::StorageError,
+    traits::{
+        AddressStability, BoundedRangeStorage, ElementStorage, IndexedStorage, PinSafeStorage,
+        RangeStorage,
+    },
+};
+
+/// A policy deciding whether to retry a failed allocation.
+///
+/// The policy is invoked after each failed attempt, and is free to act before answering, for example to free caches
+/// or to let other threads refill a shared pool.
+pub trait RetryPolicy {
+    /// Returns whether to retry, after `failures` consecutive failures of the current allocation.
+    fn retry(&mut self, failures: u32) -> bool;
+}
+
+impl<F: FnMut(u32) -> bool> RetryPolicy for F {
+    fn retry(&mut self, failures: u32) -> bool {
+        self(failures)
+    }
+}
+
+/// Retries immediately, up to the given number of times.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Immediate(pub u32);
+
+impl RetryPolicy for Immediate {
+    fn retry(&mut self, failures: u32) -> bool {
+        failures <= self.0
+    }
+}
+
+/// Retries up to `retries` times, spinning for an exponentially increasing number of iterations in between.
+///
+/// The first retry spins for `spins` iterations, and each subsequent retry spins twice as long as the previous one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    /// Maximum number of retries.
+    pub retries: u32,
+    /// Number of iterations to spin for, prior to the first retry.
+    pub spins: u32,
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn retry(&mut self, failures: u32) -> bool {
+        if failures > self.retries {
+            return false;
+        }
+
+        let spins = self
+            .spins
+            .saturating_mul(1u32.checked_shl(failures - 1).unwrap_or(u32::MAX));
+
+        for _ in 0..spins {
+            hint::spin_loop();
+        }
+
+        true
+    }
+}
+
+/// RetryStorage retries failed allocations of the underlying storage, according to its policy.
+///
+/// Allocating, creating, and growing are retried; deallocating cannot fail, and shrinking failures are assumed to be
+/// permanent.
+pub struct RetryStorage<S, P> {
+    storage: S,
+    policy: P,
+}
+
+impl<S, P> RetryStorage<S, P> {
+    /// Creates an instance wrapping `storage`, retrying according to `policy`.
+    pub fn new(storage: S, policy: P) -> Self {
+        Self { storage, policy }
+    }
+
+    /// Returns the underlying storage.
+    pub fn inner(&self) -> &S {
+        &self.storage
+    }
+
+    /// Returns the policy.
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+}
+
+impl<S: ElementStorage, P: RetryPolicy> ElementStorage for RetryStorage<S, P> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    const ADDRESS_STABILITY: AddressStability = S::ADDRESS_STABILITY;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        self.storage.deallocate(handle)
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.storage.get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.storage.coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.storage.uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        self.storage.recycle(handle)
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.storage.slot_layout::<T>()
+    }
+
+    fn create<T: Pointee>(&mut self, mut value: T) -> Result<Self::Handle<T>, T> {
+        let mut failures = 0;
+
+        loop {
+            match self.storage.create(value) {
+                Ok(handle) => return Ok(handle),
+                Err(returned) => value = returned,
+            }
+
+            failures += 1;
+
+            if !self.policy.retry(failures) {
+                return Err(value);
+            }
+        }
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let Self { storage, policy } = self;
+
+        with_retries(policy, || storage.allocate(meta))
+    }
+}
+
+impl<S: IndexedStorage, P: RetryPolicy> IndexedStorage for RetryStorage<S, P> {
+    const SLOT_COUNT: usize = S::SLOT_COUNT;
+
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize {
+        self.storage.index(handle)
+    }
+}
+
+//  Safety:
+//  -   Elements are those of the underlying storage, which never moves them.
+unsafe impl<S: PinSafeStorage, P: RetryPolicy> PinSafeStorage for RetryStorage<S, P> {}
+
+impl<T, S: BoundedRangeStorage<T>, P: RetryPolicy> BoundedRangeStorage<T> for RetryStorage<S, P> {
+    const MAX_CAPACITY: usize = S::MAX_CAPACITY;
+}
+
+impl<S: RangeStorage, P: RetryPolicy> RangeStorage for RetryStorage<S, P> {
+    type Handle<T> = S::Handle<T>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        self.storage.maximum_capacity::<T>()
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        self.storage.deallocate(handle)
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        self.storage.get(handle)
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        self.storage.dangling()
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let Self { storage, policy } = self;
+
+        //  Safety:
+        //  -   `handle` is assumed to be valid, and remains so on failure.
+        with_retries(policy, || unsafe { storage.try_grow(handle, new_capacity) })
+    }
+
+    //  `init` may only be invoked once, and its failures cannot be told apart from those of the storage, hence the
+    //  attempt is not retried.
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        self.storage.try_grow_with(handle, new_capacity, init)
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let Self { storage, policy } = self;
+
+        //  Safety:
+        //  -   `handle` is assumed to be valid, and remains so on failure.
+        with_retries(policy, || unsafe {
+            storage.try_grow_with_gap(handle, new_capacity, gap_at, gap_len)
+        })
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        self.storage.try_shrink(handle, new_capacity)
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        self.storage.cast(handle)
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        let Self { storage, policy } = self;
+
+        with_retries(policy, || storage.allocate(capacity))
+    }
+}
+
+impl<S: Default, P: Default> Default for RetryStorage<S, P> {
+    fn default() -> Self {
+        Self::new(S::default(), P::default())
+    }
+}
+
+impl<S, P> Debug for RetryStorage<S, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "RetryStorage")
+    }
+}
+
+//
+//  Implementation
+//
+
+fn with_retries<H, P, F>(policy: &mut P, mut attempt: F) -> Result<H, StorageError>
+where
+    P: RetryPolicy,
+    F: FnMut() -> Result<H, StorageError>,
+{
+    let mut failures = 0;
+
+    loop {
+        let error = match attempt() {
+            Ok(handle) => return Ok(handle),
+            Err(error) => error,
+        };
+
+        failures += 1;
+
+        //  The failure of the last attempt is reported, the storage failing again for the same reason.
+        if !policy.retry(failures) {
+            return Err(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use core::cell::Cell;
+
+    use std::{
+        alloc::{AllocError, Allocator, Global, Layout},
+        rc::Rc,
+    };
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::RawVec;
+
+    use super::*;
+
+    //  A FlakyAllocator fails a given number of allocations, before succeeding.
+    #[derive(Clone, Debug, Default)]
+    struct FlakyAllocator(Rc<Cell<u32>>);
+
+    impl FlakyAllocator {
+        fn failing(failures: u32) -> Self {
+            Self(Rc::new(Cell::new(failures)))
+        }
+
+        fn fail(&self, failures: u32) {
+            self.0.set(failures);
+        }
+    }
+
+    unsafe impl Allocator for FlakyAllocator {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            match self.0.get() {
+                0 => Global.allocate(layout),
+                n => {
+                    self.0.set(n - 1);
+                    Err(AllocError)
+                }
+            }
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn immediate() {
+        let allocator = FlakyAllocator::failing(2);
+
+        let mut storage = RetryStorage::new(AllocStorage::new(allocator.clone()), Immediate(2));
+
+        let handle = storage.create(1u32).unwrap();
+
+        assert_eq!(1, unsafe {
+            *ElementStorage::get(&storage, &handle).as_ptr()
+        });
+
+        unsafe { storage.destroy(&handle) };
+
+        allocator.fail(3);
+
+        assert_eq!(Err(2), storage.create(2u32));
+    }
+
+    #[test]
+    fn callback() {
+        let allocator = FlakyAllocator::failing(5);
+
+        let mut attempts = Vec::new();
+
+        {
+            let policy = |failures| {
+                attempts.push(failures);
+                //  Stand-in for freeing caches.
+                allocator.fail(0);
+                true
+            };
+
+            let mut storage = RetryStorage::new(AllocStorage::new(allocator.clone()), policy);
+
+            let handle = storage.create(1u32).unwrap();
+
+            unsafe { storage.destroy(&handle) };
+        }
+
+        assert_eq!(vec![1], attempts);
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let mut policy = ExponentialBackoff {
+            retries: 3,
+            spins: 4,
+        };
+
+        assert!(policy.retry(1));
+        assert!(policy.retry(3));
+        assert!(!policy.retry(4));
+    }
+
+    #[test]
+    fn range_grow() {
+        let allocator = FlakyAllocator::default();
+
+        let storage = RetryStorage::new(AllocStorage::new(allocator.clone()), Immediate(1));
+        let mut vec = RawVec::new(storage);
+
+        vec.push(1u32);
+
+        allocator.fail(1);
+        vec.push(2u32);
+
+        allocator.fail(2);
+        let error = vec.try_push(3u32).unwrap_err();
+
+        //  The failure of the underlying storage is reported as is.
+        assert_eq!(Some("AllocStorage"), error.error().context());
+        assert_eq!(3, error.into_value());
+
+        assert_eq!([1, 2], *vec);
+    }
+} // mod tests
@@ -0,0 +1,1202 @@
+//! Statistics gathering wrapper, for both `ElementStorage` and `RangeStorage`.
+//!
+//! Optionally, the latency of the allocations, growths, and deallocations of the underlying storage is recorded into
+//! fixed-size histograms, see `LatencyTimer`, so as to verify the worst-case latency of a storage under a given
+//! workload.
+
+use core::{
+    alloc::Layout,
+    fmt::{self, Debug},
+    marker::Unsize,
+    mem::{self, MaybeUninit},
+    ptr::NonNull,
+};
+
+use rfc2580::Pointee;
+
+use crate::{
+    error::StorageError,
+    memory_usage::{MemUsage, MemoryUsage},
+    traits::{
+        AddressStability, BoundedRangeStorage, ElementStorage, IndexedStorage, PinSafeStorage,
+        RangeStorage,
+    },
+    utils,
+};
+
+#[cfg(feature = "std")]
+use std::{backtrace::Backtrace, collections::HashMap, io, time::Instant};
+
+/// Statistics gathered by a `StatsStorage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of bytes currently allocated.
+    pub live_bytes: usize,
+    /// Maximum number of bytes allocated at any one time.
+    pub peak_bytes: usize,
+    /// Number of successful allocations.
+    pub allocations: usize,
+    /// Number of deallocations.
+    pub deallocations: usize,
+    /// Number of failed allocations, including failed attempts at growing.
+    pub failures: usize,
+}
+
+/// A reporter of statistics, notified after each update.
+pub trait StatsReporter {
+    /// Invoked after each update of the statistics.
+    fn report(&mut self, stats: &Stats);
+
+    /// Invoked after each successful allocation, with the address and layout of the newly allocated memory.
+    ///
+    /// The address is only meaningful for storages whose elements do not move when the storage is moved.
+    fn on_allocate(&mut self, _address: NonNull<u8>, _layout: Layout) {}
+
+    /// Invoked prior to each deallocation, with the address and layout of the memory about to be deallocated.
+    fn on_deallocate(&mut self, _address: NonNull<u8>, _layout: Layout) {}
+}
+
+impl StatsReporter for () {
+    fn report(&mut self, _stats: &Stats) {}
+}
+
+/// The operations of the underlying storage timed by a `StatsStorage`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimedOperation {
+    /// The creation, or allocation, of an element or range.
+    Allocate,
+    /// The growth of a range, including its first growth from a dangling range.
+    Grow,
+    /// The deallocation of an element or range.
+    Deallocate,
+}
+
+/// A timer of the operations of the underlying storage of a `StatsStorage`.
+pub trait StatsTimer {
+    /// Invoked prior to each timed operation, returns the starting timestamp, or `None` if the operation is not timed.
+    fn start(&mut self) -> Option<u64>;
+
+    /// Invoked after each timed operation, successful or not, with the timestamp returned by `start`.
+    fn stop(&mut self, operation: TimedOperation, start: u64);
+}
+
+impl StatsTimer for () {
+    fn start(&mut self) -> Option<u64> {
+        None
+    }
+
+    fn stop(&mut self, _operation: TimedOperation, _start: u64) {}
+}
+
+/// A source of timestamps, such as a cycle counter, or a monotonic clock.
+pub trait StatsClock {
+    /// Returns the current timestamp, in arbitrary but consistent units.
+    fn now(&mut self) -> u64;
+}
+
+/// A histogram of latencies, in the units of the clock, with `B` buckets of exponentially increasing width.
+///
+/// The first bucket counts the latencies of 0, and the bucket at index `i` the latencies in `[2^(i-1), 2^i)`, except
+/// for the last bucket, which also counts all larger latencies.
+///
+/// `B` must be at least 1, and at most 65 buckets are useful.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencyHistogram<const B: usize> {
+    buckets: [usize; B],
+    count: usize,
+    max: u64,
+    total: u64,
+}
+
+impl<const B: usize> LatencyHistogram<B> {
+    /// Creates an empty instance.
+    pub const fn new() -> Self {
+        Self {
+            buckets: [0; B],
+            count: 0,
+            max: 0,
+            total: 0,
+        }
+    }
+
+    /// Records one occurrence of `latency`.
+    pub fn record(&mut self, latency: u64) {
+        let index = (u64::BITS - latency.leading_zeros()) as usize;
+
+        self.buckets[index.min(B - 1)] += 1;
+        self.count += 1;
+        self.max = self.max.max(latency);
+        self.total = self.total.saturating_add(latency);
+    }
+
+    /// Returns the number of latencies recorded, in each bucket.
+    pub fn buckets(&self) -> &[usize; B] {
+        &self.buckets
+    }
+
+    /// Returns the exclusive upper bound of the latencies counted in the bucket at `index`, or `None` if unbounded.
+    pub fn upper_bound(index: usize) -> Option<u64> {
+        if index + 1 < B {
+            1u64.checked_shl(index as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of latencies recorded.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the largest latency recorded, or 0 if none was.
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    /// Returns the sum of the latencies recorded, saturating on overflow.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+impl<const B: usize> Default for LatencyHistogram<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The latency histograms of each of the timed operations.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Latencies<const B: usize> {
+    /// Latencies of the creations, and allocations, of elements and ranges.
+    pub allocate: LatencyHistogram<B>,
+    /// Latencies of the growths of ranges.
+    pub grow: LatencyHistogram<B>,
+    /// Latencies of the deallocations of elements and ranges.
+    pub deallocate: LatencyHistogram<B>,
+}
+
+impl<const B: usize> Latencies<B> {
+    /// Returns the histogram of `operation`.
+    pub fn get(&self, operation: TimedOperation) -> &LatencyHistogram<B> {
+        match operation {
+            TimedOperation::Allocate => &self.allocate,
+            TimedOperation::Grow => &self.grow,
+            TimedOperation::Deallocate => &self.deallocate,
+        }
+    }
+}
+
+/// A timer recording the latency of each timed operation, as measured by `C`, into histograms of `B` buckets.
+///
+/// No allocation is performed, and the histograms are retrieved with `latencies`.
+#[derive(Clone, Debug, Default)]
+pub struct LatencyTimer<C, const B: usize = 16> {
+    clock: C,
+    latencies: Latencies<B>,
+}
+
+impl<C, const B: usize> LatencyTimer<C, B> {
+    /// Creates an instance, timing the operations with `clock`.
+    pub fn new(clock: C) -> Self {
+        let latencies = Latencies::default();
+
+        Self { clock, latencies }
+    }
+
+    /// Returns the latencies recorded so far.
+    pub fn latencies(&self) -> &Latencies<B> {
+        &self.latencies
+    }
+
+    /// Clears the latencies recorded so far.
+    pub fn reset(&mut self) {
+        self.latencies = Latencies::default();
+    }
+}
+
+impl<C: StatsClock, const B: usize> StatsTimer for LatencyTimer<C, B> {
+    fn start(&mut self) -> Option<u64> {
+        Some(self.clock.now())
+    }
+
+    fn stop(&mut self, operation: TimedOperation, start: u64) {
+        //  Wrapping, to accomodate cycle counters.
+        let latency = self.clock.now().wrapping_sub(start);
+
+        let histogram = match operation {
+            TimedOperation::Allocate => &mut self.latencies.allocate,
+            TimedOperation::Grow => &mut self.latencies.grow,
+            TimedOperation::Deallocate => &mut self.latencies.deallocate,
+        };
+
+        histogram.record(latency);
+    }
+}
+
+/// A clock measuring the nanoseconds elapsed since its creation, based on `Instant`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub struct InstantClock {
+    origin: Instant,
+}
+
+#[cfg(feature = "std")]
+impl InstantClock {
+    /// Creates an instance, starting now.
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for InstantClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl StatsClock for InstantClock {
+    fn now(&mut self) -> u64 {
+        self.origin.elapsed().as_nanos() as u64
+    }
+}
+
+/// StatsStorage gathers statistics about the usage of the underlying storage.
+///
+/// Wrapping the second storage of a composite, such as `FallbackElement`, counts the number of times the composite
+/// spilled over to its second storage.
+///
+/// Empty ranges occupy no memory, and are therefore not counted as allocations.
+///
+/// The operations of the underlying storage are timed by `M`, which does nothing by default, see `with_timer`.
+pub struct StatsStorage<S, R = (), M = ()> {
+    storage: S,
+    stats: Stats,
+    reporter: R,
+    timer: M,
+}
+
+impl<S> StatsStorage<S> {
+    /// Creates an instance wrapping `storage`.
+    pub fn new(storage: S) -> Self {
+        Self::with_reporter(storage, ())
+    }
+}
+
+impl<S, R> StatsStorage<S, R> {
+    /// Creates an instance wrapping `storage`, notifying `reporter` of each update.
+    pub fn with_reporter(storage: S, reporter: R) -> Self {
+        let stats = Stats::default();
+
+        Self {
+            storage,
+            stats,
+            reporter,
+            timer: (),
+        }
+    }
+
+    /// Times the operations of the underlying storage with `timer`, such as a `LatencyTimer`.
+    pub fn with_timer<M>(self, timer: M) -> StatsStorage<S, R, M> {
+        let Self {
+            storage,
+            stats,
+            reporter,
+            ..
+        } = self;
+
+        StatsStorage {
+            storage,
+            stats,
+            reporter,
+            timer,
+        }
+    }
+}
+
+impl<S, R, M> StatsStorage<S, R, M> {
+    /// Returns the statistics gathered so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Returns the underlying storage.
+    pub fn inner(&self) -> &S {
+        &self.storage
+    }
+
+    /// Returns the reporter.
+    pub fn reporter(&self) -> &R {
+        &self.reporter
+    }
+
+    /// Returns the timer.
+    pub fn timer(&self) -> &M {
+        &self.timer
+    }
+}
+
+impl<S: ElementStorage, R: StatsReporter, M: StatsTimer> ElementStorage for StatsStorage<S, R, M> {
+    type Handle<T: ?Sized + Pointee> = S::Handle<T>;
+
+    const ADDRESS_STABILITY: AddressStability = S::ADDRESS_STABILITY;
+
+    unsafe fn deallocate<T: ?Sized + Pointee>(&mut self, handle: &Self::Handle<T>) {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let pointer = self.storage.get(handle);
+        let layout = Layout::for_value_raw(pointer.as_ptr());
+
+        self.reporter.on_deallocate(pointer.cast(), layout);
+
+        let start = self.timer.start();
+        self.storage.deallocate(handle);
+        self.stop(TimedOperation::Deallocate, start);
+
+        self.on_deallocate(layout);
+    }
+
+    unsafe fn get<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> NonNull<T> {
+        self.storage.get(handle)
+    }
+
+    unsafe fn coerce<U: ?Sized + Pointee, T: ?Sized + Pointee + Unsize<U>>(
+        &self,
+        handle: &Self::Handle<T>,
+    ) -> Self::Handle<U> {
+        self.storage.coerce(handle)
+    }
+
+    unsafe fn uncoerce<T: Pointee, U: ?Sized + Pointee>(
+        &self,
+        handle: &Self::Handle<U>,
+    ) -> Self::Handle<T> {
+        self.storage.uncoerce(handle)
+    }
+
+    unsafe fn recycle<T: ?Sized + Pointee, U: Pointee>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        //  Safety:
+        //  -   `handle` is assumed to be valid.
+        let pointer = self.storage.get(handle);
+        let old = Layout::for_value_raw(pointer.as_ptr());
+
+        let result = self.storage.recycle(handle);
+
+        if result.is_ok() {
+            self.on_recycle(pointer.cast(), old, Layout::new::<U>());
+        }
+
+        result
+    }
+
+    fn slot_layout<T: Pointee>(&self) -> Option<Layout> {
+        self.storage.slot_layout::<T>()
+    }
+
+    fn create<T: Pointee>(&mut self, value: T) -> Result<Self::Handle<T>, T> {
+        let layout = Layout::new::<T>();
+
+        let start = self.timer.start();
+        let result = self.storage.create(value);
+        self.stop(TimedOperation::Allocate, start);
+
+        match &result {
+            Ok(handle) => {
+                //  Safety:
+                //  -   `handle` is valid, fresh off the press.
+                let pointer = unsafe { self.storage.get(handle) };
+                self.reporter.on_allocate(pointer.cast(), layout);
+                self.on_allocate(layout);
+            }
+            Err(_) => self.on_failure(),
+        }
+
+        result
+    }
+
+    fn allocate<T: ?Sized + Pointee>(
+        &mut self,
+        meta: T::MetaData,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let layout = utils::layout_of::<T>(meta);
+
+        let start = self.timer.start();
+        let result = self.storage.allocate(meta);
+        self.stop(TimedOperation::Allocate, start);
+
+        match &result {
+            Ok(handle) => {
+                //  Safety:
+                //  -   `handle` is valid, fresh off the press.
+                let pointer = unsafe { self.storage.get(handle) };
+                self.reporter.on_allocate(pointer.cast(), layout);
+                self.on_allocate(layout);
+            }
+            Err(_) => self.on_failure(),
+        }
+
+        result
+    }
+}
+
+impl<S: IndexedStorage, R: StatsReporter, M: StatsTimer> IndexedStorage for StatsStorage<S, R, M> {
+    const SLOT_COUNT: usize = S::SLOT_COUNT;
+
+    unsafe fn index<T: ?Sized + Pointee>(&self, handle: &Self::Handle<T>) -> usize {
+        self.storage.index(handle)
+    }
+}
+
+//  Safety:
+//  -   Elements are those of the underlying storage, which never moves them.
+unsafe impl<S: PinSafeStorage, R: StatsReporter, M: StatsTimer> PinSafeStorage
+    for StatsStorage<S, R, M>
+{
+}
+
+impl<T, S: BoundedRangeStorage<T>, R: StatsReporter, M: StatsTimer> BoundedRangeStorage<T>
+    for StatsStorage<S, R, M>
+{
+    const MAX_CAPACITY: usize = S::MAX_CAPACITY;
+}
+
+impl<S: RangeStorage, R: StatsReporter, M: StatsTimer> RangeStorage for StatsStorage<S, R, M> {
+    type Handle<T> = S::Handle<T>;
+
+    type Capacity = S::Capacity;
+
+    fn maximum_capacity<T>(&self) -> Self::Capacity {
+        self.storage.maximum_capacity::<T>()
+    }
+
+    unsafe fn deallocate<T>(&mut self, handle: &Self::Handle<T>) {
+        let (pointer, layout) = Self::counted_range_of(&self.storage, handle);
+
+        let start = self.timer.start();
+        self.storage.deallocate(handle);
+        self.stop(TimedOperation::Deallocate, start);
+
+        if layout.size() > 0 {
+            self.reporter.on_deallocate(pointer, layout);
+            self.on_deallocate(layout);
+        }
+    }
+
+    unsafe fn get<T>(&self, handle: &Self::Handle<T>) -> NonNull<[MaybeUninit<T>]> {
+        self.storage.get(handle)
+    }
+
+    fn dangling<T>(&self) -> Self::Handle<T> {
+        self.storage.dangling()
+    }
+
+    unsafe fn try_grow<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let old = Self::counted_range_of(&self.storage, handle);
+
+        let start = self.timer.start();
+        let result = self.storage.try_grow(handle, new_capacity);
+        self.stop(TimedOperation::Grow, start);
+
+        match &result {
+            Ok(new_handle) => {
+                let new = Self::counted_range_of(&self.storage, new_handle);
+                self.on_resize(old, new);
+            }
+            Err(_) => self.on_failure(),
+        }
+
+        result
+    }
+
+    unsafe fn try_grow_with<T, F>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        init: F,
+    ) -> Result<Self::Handle<T>, StorageError>
+    where
+        F: FnOnce(NonNull<[MaybeUninit<T>]>) -> Result<(), StorageError>,
+    {
+        let old = Self::counted_range_of(&self.storage, handle);
+
+        let start = self.timer.start();
+        let result = self.storage.try_grow_with(handle, new_capacity, init);
+        self.stop(TimedOperation::Grow, start);
+
+        match &result {
+            Ok(new_handle) => {
+                let new = Self::counted_range_of(&self.storage, new_handle);
+                self.on_resize(old, new);
+            }
+            Err(_) => self.on_failure(),
+        }
+
+        result
+    }
+
+    unsafe fn try_grow_with_gap<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+        gap_at: usize,
+        gap_len: usize,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let old = Self::counted_range_of(&self.storage, handle);
+
+        let start = self.timer.start();
+        let result = self
+            .storage
+            .try_grow_with_gap(handle, new_capacity, gap_at, gap_len);
+        self.stop(TimedOperation::Grow, start);
+
+        match &result {
+            Ok(new_handle) => {
+                let new = Self::counted_range_of(&self.storage, new_handle);
+                self.on_resize(old, new);
+            }
+            Err(_) => self.on_failure(),
+        }
+
+        result
+    }
+
+    unsafe fn try_shrink<T>(
+        &mut self,
+        handle: &Self::Handle<T>,
+        new_capacity: Self::Capacity,
+    ) -> Result<Self::Handle<T>, StorageError> {
+        let old = Self::counted_range_of(&self.storage, handle);
+
+        let result = self.storage.try_shrink(handle, new_capacity);
+
+        if let Ok(new_handle) = &result {
+            let new = Self::counted_range_of(&self.storage, new_handle);
+            self.on_resize(old, new);
+        }
+
+        result
+    }
+
+    unsafe fn cast<T, U>(
+        &mut self,
+        handle: &Self::Handle<T>,
+    ) -> Result<Self::Handle<U>, StorageError> {
+        let old = Self::counted_range_of(&self.storage, handle);
+
+        let result = self.storage.cast(handle);
+
+        if let Ok(new_handle) = &result {
+            let new = Self::counted_range_of(&self.storage, new_handle);
+            self.on_resize(old, new);
+        }
+
+        result
+    }
+
+    fn allocate<T>(&mut self, capacity: Self::Capacity) -> Result<Self::Handle<T>, StorageError> {
+        let start = self.timer.start();
+        let result = self.storage.allocate(capacity);
+        self.stop(TimedOperation::Allocate, start);
+
+        match &result {
+            Ok(handle) => {
+                //  Safety:
+                //  -   `handle` is valid, fresh off the press.
+                let (pointer, layout) = unsafe { Self::counted_range_of(&self.storage, handle) };
+
+                if layout.size() > 0 {
+                    self.reporter.on_allocate(pointer, layout);
+                    self.on_allocate(layout);
+                }
+            }
+            Err(_) => self.on_failure(),
+        }
+
+        result
+    }
+}
+
+/// Reports the memory used by the underlying storage, and the size of the wrapper itself.
+///
+/// If the underlying storage does not track its allocations, such as `AllocStorage`, the live bytes are reported as
+/// allocated out of line in its stead.
+impl<S: MemoryUsage, R, M> MemoryUsage for StatsStorage<S, R, M> {
+    fn mem_usage(&self) -> MemUsage {
+        let own = mem::size_of::<Self>() - mem::size_of::<S>();
+
+        let heap_bytes = if S::TRACKS_ALLOCATIONS {
+            0
+        } else {
+            self.stats.live_bytes
+        };
+
+        self.storage.mem_usage() + MemUsage::with_payload(own, heap_bytes, heap_bytes)
+    }
+}
+
+impl<S: Default> Default for StatsStorage<S> {
+    fn default() -> Self {
+        Self::new(S::default())
+    }
+}
+
+impl<S, R, M> Debug for StatsStorage<S, R, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        write!(f, "StatsStorage{{ {:?} }}", self.stats)
+    }
+}
+
+/// A reporter forwarding the statistics to the `metrics` facade.
+///
+/// The metrics are labelled with `storage = name`, so as to distinguish several storages.
+///
+/// The spill count of a fallback composite, such as `FallbackElement` or `FallbackRange`, is the number of allocations
+/// which spilled over to its second storage; it is exported as the `storage_spills` counter by wrapping the second
+/// storage with a reporter created by `spill`:
+///
+/// ```ignore
+/// let storage = StatsStorage::with_reporter(
+///     FallbackElement::new(
+///         Inline::default(),
+///         StatsStorage::with_reporter(AllocStorage::default(), MetricsReporter::spill("sensors.spill")),
+///     ),
+///     MetricsReporter::new("sensors"),
+/// );
+/// ```
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug)]
+pub struct MetricsReporter {
+    name: &'static str,
+    spill: bool,
+}
+
+#[cfg(feature = "metrics")]
+impl MetricsReporter {
+    /// Creates an instance, labelling the metrics with `name`.
+    pub fn new(name: &'static str) -> Self {
+        Self { name, spill: false }
+    }
+
+    /// Creates an instance for the second storage of a fallback composite, labelling the metrics with `name`.
+    ///
+    /// Any allocation from the second storage spilled over from the first, hence the allocations are further exported
+    /// as the `storage_spills` counter.
+    pub fn spill(name: &'static str) -> Self {
+        Self { name, spill: true }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl StatsReporter for MetricsReporter {
+    fn report(&mut self, stats: &Stats) {
+        let name = self.name;
+
+        metrics::gauge!("storage_live_bytes", "storage" => name).set(stats.live_bytes as f64);
+        metrics::gauge!("storage_peak_bytes", "storage" => name).set(stats.peak_bytes as f64);
+        metrics::counter!("storage_allocations", "storage" => name)
+            .absolute(stats.allocations as u64);
+        metrics::counter!("storage_deallocations", "storage" => name)
+            .absolute(stats.deallocations as u64);
+        metrics::counter!("storage_failures", "storage" => name).absolute(stats.failures as u64);
+
+        if self.spill {
+            metrics::counter!("storage_spills", "storage" => name)
+                .absolute(stats.allocations as u64);
+        }
+    }
+}
+
+/// A reporter capturing a backtrace for each live allocation.
+///
+/// The outstanding allocations can be dumped on demand, and are dumped to the standard error output on drop if any
+/// remain and `dump_on_drop` was requested.
+///
+/// Allocations are tracked by address, and therefore the storage should not move its elements when moved.
+///
+/// Zero-sized allocations are not tracked, as they need not have a distinct address, nor leak anything.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct BacktraceReporter {
+    live: HashMap<NonNull<u8>, (Layout, Backtrace)>,
+    dump_on_drop: bool,
+}
+
+#[cfg(feature = "std")]
+impl BacktraceReporter {
+    /// Creates an instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests that the outstanding allocations, if any, be dumped on drop.
+    pub fn dump_on_drop(mut self) -> Self {
+        self.dump_on_drop = true;
+        self
+    }
+
+    /// Returns the number of outstanding allocations.
+    pub fn outstanding(&self) -> usize {
+        self.live.len()
+    }
+
+    /// Dumps the outstanding allocations, with the backtrace captured when they were allocated.
+    pub fn dump<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(writer, "{} outstanding allocation(s)", self.live.len())?;
+
+        for (address, (layout, backtrace)) in &self.live {
+            writeln!(
+                writer,
+                "-   {:?}, {} byte(s) aligned on {}, allocated at:\n{}",
+                address,
+                layout.size(),
+                layout.align(),
+                backtrace
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl StatsReporter for BacktraceReporter {
+    fn report(&mut self, _stats: &Stats) {}
+
+    fn on_allocate(&mut self, address: NonNull<u8>, layout: Layout) {
+        if layout.size() > 0 {
+            self.live
+                .insert(address, (layout, Backtrace::force_capture()));
+        }
+    }
+
+    fn on_deallocate(&mut self, address: NonNull<u8>, layout: Layout) {
+        if layout.size() > 0 {
+            self.live.remove(&address);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for BacktraceReporter {
+    fn drop(&mut self) {
+        if self.dump_on_drop && !self.live.is_empty() {
+            let _ = self.dump(io::stderr());
+        }
+    }
+}
+
+//
+//  Implementation
+//
+
+impl<S, R: StatsReporter, M: StatsTimer> StatsStorage<S, R, M> {
+    fn stop(&mut self, operation: TimedOperation, start: Option<u64>) {
+        if let Some(start) = start {
+            self.timer.stop(operation, start);
+        }
+    }
+
+    fn on_allocate(&mut self, layout: Layout) {
+        self.stats.allocations += 1;
+        self.stats.live_bytes += layout.size();
+        self.update_peak();
+        self.reporter.report(&self.stats);
+    }
+
+    fn on_deallocate(&mut self, layout: Layout) {
+        self.stats.deallocations += 1;
+        self.stats.live_bytes -= layout.size();
+        self.reporter.report(&self.stats);
+    }
+
+    fn on_resize(&mut self, old: (NonNull<u8>, Layout), new: (NonNull<u8>, Layout)) {
+        //  Empty ranges, such as dangling ones, are not counted as allocations.
+        match (old.1.size(), new.1.size()) {
+            (0, 0) => (),
+            (0, _) => {
+                self.reporter.on_allocate(new.0, new.1);
+                self.on_allocate(new.1);
+            }
+            (_, 0) => {
+                self.reporter.on_deallocate(old.0, old.1);
+                self.on_deallocate(old.1);
+            }
+            _ => {
+                self.reporter.on_deallocate(old.0, old.1);
+                self.reporter.on_allocate(new.0, new.1);
+
+                self.stats.live_bytes -= old.1.size();
+                self.stats.live_bytes += new.1.size();
+                self.update_peak();
+                self.reporter.report(&self.stats);
+            }
+        }
+    }
+
+    fn on_recycle(&mut self, address: NonNull<u8>, old: Layout, new: Layout) {
+        self.reporter.on_deallocate(address, old);
+        self.reporter.on_allocate(address, new);
+
+        self.stats.live_bytes -= old.size();
+        self.stats.live_bytes += new.size();
+        self.update_peak();
+        self.reporter.report(&self.stats);
+    }
+
+    fn on_failure(&mut self) {
+        self.stats.failures += 1;
+        self.reporter.report(&self.stats);
+    }
+
+    fn update_peak(&mut self) {
+        if self.stats.live_bytes > self.stats.peak_bytes {
+            self.stats.peak_bytes = self.stats.live_bytes;
+        }
+    }
+}
+
+impl<S: RangeStorage, R, M> StatsStorage<S, R, M> {
+    //  Returns the address and layout of the range of `handle`, as counted.
+    //
+    //  A range no larger than the dangling range of the underlying storage requires no allocation, and is counted as
+    //  empty. Notably, the dangling ranges of inline storages are not empty, yet are never counted when issued, and
+    //  must not be subtracted when deallocated, or grown.
+    //
+    //  #   Safety
+    //
+    //  -   Assumes that `handle` is valid.
+    unsafe fn counted_range_of<T>(storage: &S, handle: &S::Handle<T>) -> (NonNull<u8>, Layout) {
+        let pointer = storage.get(handle);
+        let layout = Layout::for_value_raw(pointer.as_ptr());
+
+        let dangling = storage.dangling::<T>();
+        let dangling = Layout::for_value_raw(storage.get(&dangling).as_ptr());
+
+        if layout.size() <= dangling.size() {
+            return (
+                pointer.cast(),
+                Layout::from_size_align_unchecked(0, layout.align()),
+            );
+        }
+
+        (pointer.cast(), layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::allocator::AllocStorage;
+    use crate::collections::RawVec;
+    use crate::inline::{NonTrackingRange, TrackingElement};
+    use crate::small::SingleRange;
+    use crate::utils::{NonAllocator, SpyAllocator};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder(Vec<Stats>);
+
+    impl StatsReporter for &mut Recorder {
+        fn report(&mut self, stats: &Stats) {
+            self.0.push(*stats);
+        }
+    }
+
+    #[test]
+    fn create_destroy() {
+        let mut storage = StatsStorage::new(TrackingElement::<u32, 2>::new());
+
+        let h1 = storage.create(1u32).unwrap();
+        let h2 = storage.create(2u16).unwrap();
+
+        storage.create(3u8).unwrap_err();
+
+        unsafe { storage.destroy(&h1) };
+
+        let expected = Stats {
+            live_bytes: 2,
+            peak_bytes: 6,
+            allocations: 2,
+            deallocations: 1,
+            failures: 1,
+        };
+
+        assert_eq!(&expected, storage.stats());
+
+        unsafe { storage.destroy(&h2) };
+
+        assert_eq!(0, storage.stats().live_bytes);
+    }
+
+    #[test]
+    fn coerce() {
+        let mut storage = StatsStorage::new(AllocStorage::new(SpyAllocator::default()));
+
+        let handle = storage.create([1u8, 2, 3]).unwrap();
+        let handle = unsafe { storage.coerce::<[u8], _>(&handle) };
+
+        assert_eq!(3, storage.stats().live_bytes);
+
+        unsafe { storage.destroy(&handle) };
+
+        assert_eq!(0, storage.stats().live_bytes);
+        assert_eq!(3, storage.stats().peak_bytes);
+    }
+
+    #[test]
+    fn range_grow() {
+        let mut storage = StatsStorage::new(AllocStorage::new(SpyAllocator::default()));
+
+        let handle = RangeStorage::allocate::<u32>(&mut storage, 2).unwrap();
+
+        assert_eq!(8, storage.stats().live_bytes);
+
+        let handle = unsafe { storage.try_grow(&handle, 4) }.unwrap();
+
+        assert_eq!(16, storage.stats().live_bytes);
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        let expected = Stats {
+            live_bytes: 0,
+            peak_bytes: 16,
+            allocations: 1,
+            deallocations: 1,
+            failures: 0,
+        };
+
+        assert_eq!(&expected, storage.stats());
+    }
+
+    #[test]
+    fn range_dangling() {
+        let mut storage = StatsStorage::new(AllocStorage::new(SpyAllocator::default()));
+
+        let handle = storage.dangling::<u32>();
+        let handle = unsafe { storage.try_grow(&handle, 2) }.unwrap();
+
+        assert_eq!(8, storage.stats().live_bytes);
+
+        let handle = unsafe { storage.try_shrink(&handle, 0) }.unwrap();
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        let expected = Stats {
+            live_bytes: 0,
+            peak_bytes: 8,
+            allocations: 1,
+            deallocations: 1,
+            failures: 0,
+        };
+
+        assert_eq!(&expected, storage.stats());
+    }
+
+    #[test]
+    fn range_dangling_inline() {
+        type Inline = NonTrackingRange<usize, u32, 4>;
+
+        //  The dangling range of an inline storage is not empty, yet is never counted.
+        drop(RawVec::<u32, _>::new(StatsStorage::new(Inline::default())));
+
+        let mut vec = RawVec::<u32, _>::new(StatsStorage::new(Inline::default()));
+
+        vec.try_extend_from_slice(&[1, 2, 3]).unwrap();
+        vec.try_shrink_to_fit().unwrap_or(());
+
+        let mut storage = vec.into_storage();
+
+        let handle = RangeStorage::allocate::<u32>(&mut storage, 4).unwrap();
+        let handle = unsafe { storage.cast::<u32, i32>(&handle) }.unwrap();
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        assert_eq!(&Stats::default(), storage.stats());
+    }
+
+    #[test]
+    fn range_grow_with_switch() {
+        let allocator = SpyAllocator::default();
+        let mut storage = StatsStorage::new(SingleRange::<[u32; 4], _>::new(allocator.clone()));
+
+        //  Inline, hence not counted.
+        let handle = RangeStorage::allocate::<u32>(&mut storage, 4).unwrap();
+
+        let handle = unsafe { storage.try_grow_with(&handle, 8, |_| Ok(())) }.unwrap();
+
+        assert_eq!(1, allocator.allocated());
+        assert_eq!(1, storage.stats().allocations);
+        assert_eq!(32, storage.stats().live_bytes);
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        assert_eq!(1, allocator.deallocated());
+        assert_eq!(1, storage.stats().deallocations);
+        assert_eq!(0, storage.stats().live_bytes);
+    }
+
+    #[test]
+    fn range_failure() {
+        let mut storage = StatsStorage::new(AllocStorage::new(NonAllocator));
+
+        RangeStorage::allocate::<u32>(&mut storage, 2).unwrap_err();
+
+        assert_eq!(1, storage.stats().failures);
+    }
+
+    #[test]
+    fn reporter() {
+        let mut recorder = Recorder::default();
+
+        {
+            let mut storage =
+                StatsStorage::with_reporter(TrackingElement::<u32, 1>::new(), &mut recorder);
+
+            let handle = storage.create(1u32).unwrap();
+            unsafe { storage.destroy(&handle) };
+        }
+
+        let live: Vec<_> = recorder.0.iter().map(|stats| stats.live_bytes).collect();
+
+        assert_eq!(vec![4, 0], live);
+    }
+
+    //  A clock ticking once per reading.
+    #[derive(Default)]
+    struct Ticks(u64);
+
+    impl StatsClock for Ticks {
+        fn now(&mut self) -> u64 {
+            self.0 += 1;
+            self.0
+        }
+    }
+
+    #[test]
+    fn latency_histogram() {
+        let mut histogram = LatencyHistogram::<4>::new();
+
+        for latency in [0, 1, 2, 3, 4, 100] {
+            histogram.record(latency);
+        }
+
+        assert_eq!(&[1, 1, 2, 2], histogram.buckets());
+        assert_eq!(
+            (6, 100, 110),
+            (histogram.count(), histogram.max(), histogram.total())
+        );
+
+        let bounds: Vec<_> = (0..4).map(LatencyHistogram::<4>::upper_bound).collect();
+
+        assert_eq!(vec![Some(1), Some(2), Some(4), None], bounds);
+    }
+
+    #[test]
+    fn latency_timer() {
+        let storage = StatsStorage::new(AllocStorage::new(SpyAllocator::default()));
+        let mut storage = storage.with_timer(LatencyTimer::<_, 8>::new(Ticks::default()));
+
+        let element = storage.create(1u32).unwrap();
+        let range = RangeStorage::allocate::<u32>(&mut storage, 2).unwrap();
+        let range = unsafe { storage.try_grow(&range, 4) }.unwrap();
+
+        unsafe {
+            storage.destroy(&element);
+            RangeStorage::deallocate(&mut storage, &range);
+        }
+
+        let latencies = storage.timer().latencies();
+
+        assert_eq!(2, latencies.get(TimedOperation::Allocate).count());
+        assert_eq!(1, latencies.grow.count());
+        assert_eq!(2, latencies.deallocate.count());
+        assert_eq!(1, latencies.deallocate.max());
+        assert_eq!(2, storage.stats().deallocations);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn backtrace_outstanding() {
+        let allocator = SpyAllocator::default();
+        let reporter = BacktraceReporter::new();
+
+        let mut storage = StatsStorage::with_reporter(AllocStorage::new(allocator), reporter);
+
+        let h1 = storage.create(1u32).unwrap();
+        let h2 = storage.create(2u64).unwrap();
+
+        assert_eq!(2, storage.reporter().outstanding());
+
+        unsafe { storage.destroy(&h1) };
+
+        assert_eq!(1, storage.reporter().outstanding());
+
+        let mut dump = Vec::new();
+        storage.reporter().dump(&mut dump).unwrap();
+
+        let dump = String::from_utf8(dump).unwrap();
+
+        assert!(dump.starts_with("1 outstanding allocation(s)\n"));
+        assert!(dump.contains("8 byte(s) aligned on 8"));
+
+        unsafe { storage.destroy(&h2) };
+
+        assert_eq!(0, storage.reporter().outstanding());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn backtrace_zero_sized() {
+        let allocator = SpyAllocator::default();
+        let reporter = BacktraceReporter::new();
+
+        let mut storage = StatsStorage::with_reporter(AllocStorage::new(allocator), reporter);
+
+        let h1 = storage.create(()).unwrap();
+        let h2 = storage.create(()).unwrap();
+        let h3 = storage.create(3u32).unwrap();
+
+        assert_eq!(1, storage.reporter().outstanding());
+
+        unsafe {
+            storage.destroy(&h1);
+            storage.destroy(&h2);
+        }
+
+        assert_eq!(1, storage.reporter().outstanding());
+
+        unsafe { storage.destroy(&h3) };
+
+        assert_eq!(0, storage.reporter().outstanding());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn backtrace_range_grow() {
+        let allocator = SpyAllocator::default();
+        let reporter = BacktraceReporter::new();
+
+        let mut storage = StatsStorage::with_reporter(AllocStorage::new(allocator), reporter);
+
+        let handle = RangeStorage::allocate::<u32>(&mut storage, 2).unwrap();
+        let handle = unsafe { storage.try_grow(&handle, 64) }.unwrap();
+
+        assert_eq!(1, storage.reporter().outstanding());
+
+        unsafe { RangeStorage::deallocate(&mut storage, &handle) };
+
+        assert_eq!(0, storage.reporter().outstanding());
+    }
+} // mod tests
@@ -0,0 +1,280 @@
+//! Derive macros delegating the storage traits of `storage-poc` to a field.
+//!
+//! Writing a wrapper storage -- for statistics, logging, zeroizing, checking... -- otherwise requires re-implementing
+//! every single trait method as a passthrough, even though only one or two differ.
+//!
+//! The storage is the single field of the struct, or the field marked `#[storage]`:
+//!
+//! ```ignore
+//! #[derive(ElementStorage, RangeStorage)]
+//! struct Named<S> {
+//!     #[storage]
+//!     inner: S,
+//!     name: &'static str,
+//! }
+//! ```
+//!
+//! The generated code uses the same nightly features as `storage-poc` itself, which the deriving crate must therefore
+//! enable: `allocator_api` and `unsize`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, Data, DeriveInput, Error, Fields, Index, Member, Type};
+
+/// Implements `ElementStorage` by delegating every method to the storage field.
+#[proc_macro_derive(ElementStorage, attributes(storage))]
+pub fn derive_element_storage(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    delegate(&input)
+        .map(|delegate| element_storage(&input, &delegate))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `RangeStorage` by delegating every method to the storage field.
+#[proc_macro_derive(RangeStorage, attributes(storage))]
+pub fn derive_range_storage(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    delegate(&input)
+        .map(|delegate| range_storage(&input, &delegate))
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+//
+//  Implementation
+//
+
+//  The field to delegate to.
+struct Delegate {
+    member: Member,
+    ty: Type,
+}
+
+fn delegate(input: &DeriveInput) -> Result<Delegate, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(Error::new_spanned(input, "expected a struct")),
+    };
+
+    let members: Vec<_> = match fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| (Member::Named(field.ident.clone().expect("Named")), field))
+            .collect(),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (Member::Unnamed(Index::from(index)), field))
+            .collect(),
+        Fields::Unit => Vec::new(),
+    };
+
+    let mut marked = members.iter().filter(|(_, field)| {
+        field
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("storage"))
+    });
+
+    let (member, field) = match (marked.next(), marked.next()) {
+        (Some(marked), None) => marked,
+        (Some(_), Some((_, field))) => {
+            return Err(Error::new_spanned(
+                field,
+                "expected a single field marked #[storage]",
+            ))
+        }
+        (None, _) if members.len() == 1 => &members[0],
+        (None, _) => {
+            return Err(Error::new_spanned(
+                input,
+                "expected a single field, or a field marked #[storage]",
+            ))
+        }
+    };
+
+    Ok(Delegate {
+        member: member.clone(),
+        ty: field.ty.clone(),
+    })
+}
+
+fn element_storage(input: &DeriveInput, delegate: &Delegate) -> TokenStream2 {
+    let Delegate { member, ty } = delegate;
+
+    let name = &input.ident;
+    let traits = quote!(::storage_poc::traits);
+    let error = quote!(::storage_poc::error);
+    let pointee = quote!(::storage_poc::__private::Pointee);
+
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#ty: #traits::ElementStorage));
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #traits::ElementStorage for #name #ty_generics #where_clause {
+            type Handle<__T: ?Sized + #pointee> = <#ty as #traits::ElementStorage>::Handle<__T>;
+
+            const ADDRESS_STABILITY: #traits::AddressStability =
+                <#ty as #traits::ElementStorage>::ADDRESS_STABILITY;
+
+            unsafe fn destroy<__T: ?Sized + #pointee>(&mut self, handle: &Self::Handle<__T>) {
+                #traits::ElementStorage::destroy(&mut self.#member, handle)
+            }
+
+            unsafe fn deallocate<__T: ?Sized + #pointee>(&mut self, handle: &Self::Handle<__T>) {
+                #traits::ElementStorage::deallocate(&mut self.#member, handle)
+            }
+
+            unsafe fn get<__T: ?Sized + #pointee>(
+                &self,
+                handle: &Self::Handle<__T>,
+            ) -> ::core::ptr::NonNull<__T> {
+                #traits::ElementStorage::get(&self.#member, handle)
+            }
+
+            unsafe fn coerce<__U: ?Sized + #pointee, __T: ?Sized + #pointee + ::core::marker::Unsize<__U>>(
+                &self,
+                handle: &Self::Handle<__T>,
+            ) -> Self::Handle<__U> {
+                #traits::ElementStorage::coerce(&self.#member, handle)
+            }
+
+            unsafe fn uncoerce<__T: #pointee, __U: ?Sized + #pointee>(
+                &self,
+                handle: &Self::Handle<__U>,
+            ) -> Self::Handle<__T> {
+                #traits::ElementStorage::uncoerce(&self.#member, handle)
+            }
+
+            unsafe fn recycle<__T: ?Sized + #pointee, __U: #pointee>(
+                &mut self,
+                handle: &Self::Handle<__T>,
+            ) -> ::core::result::Result<Self::Handle<__U>, #error::StorageError> {
+                #traits::ElementStorage::recycle(&mut self.#member, handle)
+            }
+
+            fn slot_layout<__T: #pointee>(&self) -> ::core::option::Option<::core::alloc::Layout> {
+                #traits::ElementStorage::slot_layout::<__T>(&self.#member)
+            }
+
+            fn create<__T: #pointee>(
+                &mut self,
+                value: __T,
+            ) -> ::core::result::Result<Self::Handle<__T>, __T> {
+                #traits::ElementStorage::create(&mut self.#member, value)
+            }
+
+            fn try_create<__T: #pointee>(
+                &mut self,
+                value: __T,
+            ) -> ::core::result::Result<Self::Handle<__T>, (__T, #error::StorageError)> {
+                #traits::ElementStorage::try_create(&mut self.#member, value)
+            }
+
+            fn allocate<__T: ?Sized + #pointee>(
+                &mut self,
+                meta: <__T as #pointee>::MetaData,
+            ) -> ::core::result::Result<Self::Handle<__T>, #error::StorageError> {
+                #traits::ElementStorage::allocate(&mut self.#member, meta)
+            }
+        }
+    }
+}
+
+fn range_storage(input: &DeriveInput, delegate: &Delegate) -> TokenStream2 {
+    let Delegate { member, ty } = delegate;
+
+    let name = &input.ident;
+    let traits = quote!(::storage_poc::traits);
+    let error = quote!(::storage_poc::error);
+
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#ty: #traits::RangeStorage));
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        impl #impl_generics #traits::RangeStorage for #name #ty_generics #where_clause {
+            type Handle<__T> = <#ty as #traits::RangeStorage>::Handle<__T>;
+
+            type Capacity = <#ty as #traits::RangeStorage>::Capacity;
+
+            fn maximum_capacity<__T>(&self) -> Self::Capacity {
+                #traits::RangeStorage::maximum_capacity::<__T>(&self.#member)
+            }
+
+            unsafe fn deallocate<__T>(&mut self, handle: &Self::Handle<__T>) {
+                #traits::RangeStorage::deallocate(&mut self.#member, handle)
+            }
+
+            unsafe fn get<__T>(
+                &self,
+                handle: &Self::Handle<__T>,
+            ) -> ::core::ptr::NonNull<[::core::mem::MaybeUninit<__T>]> {
+                #traits::RangeStorage::get(&self.#member, handle)
+            }
+
+            fn dangling<__T>(&self) -> Self::Handle<__T> {
+                #traits::RangeStorage::dangling(&self.#member)
+            }
+
+            unsafe fn try_grow<__T>(
+                &mut self,
+                handle: &Self::Handle<__T>,
+                new_capacity: Self::Capacity,
+            ) -> ::core::result::Result<Self::Handle<__T>, #error::StorageError> {
+                #traits::RangeStorage::try_grow(&mut self.#member, handle, new_capacity)
+            }
+
+            unsafe fn try_grow_with<__T, __F>(
+                &mut self,
+                handle: &Self::Handle<__T>,
+                new_capacity: Self::Capacity,
+                init: __F,
+            ) -> ::core::result::Result<Self::Handle<__T>, #error::StorageError>
+            where
+                __F: ::core::ops::FnOnce(
+                    ::core::ptr::NonNull<[::core::mem::MaybeUninit<__T>]>,
+                ) -> ::core::result::Result<(), #error::StorageError>,
+            {
+                #traits::RangeStorage::try_grow_with(&mut self.#member, handle, new_capacity, init)
+            }
+
+            unsafe fn try_shrink<__T>(
+                &mut self,
+                handle: &Self::Handle<__T>,
+                new_capacity: Self::Capacity,
+            ) -> ::core::result::Result<Self::Handle<__T>, #error::StorageError> {
+                #traits::RangeStorage::try_shrink(&mut self.#member, handle, new_capacity)
+            }
+
+            unsafe fn cast<__T, __U>(
+                &mut self,
+                handle: &Self::Handle<__T>,
+            ) -> ::core::result::Result<Self::Handle<__U>, #error::StorageError> {
+                #traits::RangeStorage::cast(&mut self.#member, handle)
+            }
+
+            fn allocate<__T>(
+                &mut self,
+                capacity: Self::Capacity,
+            ) -> ::core::result::Result<Self::Handle<__T>, #error::StorageError> {
+                #traits::RangeStorage::allocate(&mut self.#member, capacity)
+            }
+        }
+    }
+}
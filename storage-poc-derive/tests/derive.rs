@@ -0,0 +1,53 @@
+#![feature(allocator_api)]
+#![feature(generic_associated_types)]
+#![feature(unsize)]
+
+use std::alloc::Global;
+
+use storage_poc::{
+    allocator::AllocStorage,
+    collections::{RawBox, RawVec},
+    inline::TrackingElement,
+    traits::ElementStorage,
+    ElementStorage, RangeStorage,
+};
+
+#[derive(ElementStorage, RangeStorage)]
+struct Transparent<S>(S);
+
+#[derive(ElementStorage)]
+struct Named<S> {
+    #[storage]
+    inner: S,
+    name: &'static str,
+}
+
+#[test]
+fn element_storage() {
+    let storage = Named {
+        inner: TrackingElement::<u32, 1>::new(),
+        name: "inline",
+    };
+
+    assert_eq!("inline", storage.name);
+
+    let first = RawBox::new(1u32, storage).unwrap_or_else(|_| panic!("Sufficient capacity"));
+
+    assert_eq!(1, *first);
+
+    let mut storage = first.into_storage();
+
+    storage.create(2u32).unwrap();
+    storage.create(3u32).unwrap_err();
+}
+
+#[test]
+fn range_storage() {
+    let mut vec = RawVec::new(Transparent(AllocStorage::<Global>::default()));
+
+    for i in 0..10 {
+        vec.try_push(i).unwrap();
+    }
+
+    assert_eq!(45, vec.iter().sum::<i32>());
+}